@@ -0,0 +1,84 @@
+//! Compares the layouts of the subset trie used by the edge domination rule.
+//!
+//! For a range of node counts, this builds the trie the way edge domination
+//! does on random hypergraphs (querying each edge in order of increasing size
+//! and inserting it unless a subset was found) and reports the time taken
+//! with children stored in chunked arrays and in hash maps. The chunk
+//! directory of each trie node grows with the number of nodes, so hash maps
+//! eventually become faster. Both layouts take about the same time at 256
+//! nodes on our machines, which is the default for
+//! `subset_trie_chunked_max_nodes`.
+//!
+//! Run with `cargo run --release --example subset_trie`.
+
+use findminhs::{
+    data_structures::subset_trie::{SubsetTrie, SubsetTrieArena},
+    instance::NodeIdx,
+};
+use rand::{seq::index, Rng, SeedableRng};
+use rand_pcg::Pcg64;
+use std::time::{Duration, Instant};
+
+const NODE_COUNTS: [usize; 9] = [64, 128, 256, 512, 1024, 2048, 4096, 8192, 16384];
+const EDGES_PER_NODE: usize = 8;
+const MAX_EDGE_SIZE: usize = 8;
+const RUNS: usize = 5;
+
+fn random_edges(num_nodes: usize, rng: &mut impl Rng) -> Vec<Vec<NodeIdx>> {
+    let mut edges: Vec<Vec<_>> = (0..num_nodes * EDGES_PER_NODE)
+        .map(|_| {
+            let size = rng.gen_range(2..=MAX_EDGE_SIZE);
+            let mut edge: Vec<_> = index::sample(rng, num_nodes, size)
+                .into_iter()
+                .map(NodeIdx::from)
+                .collect();
+            edge.sort_unstable();
+            edge
+        })
+        .collect();
+    edges.sort_by_key(Vec::len);
+    edges
+}
+
+/// Runs edge domination on the edges, returning the fastest of several runs
+/// and the number of dominated edges.
+fn time_domination(
+    num_nodes: usize,
+    edges: &[Vec<NodeIdx>],
+    chunked_max_val_range: usize,
+) -> (Duration, usize) {
+    let mut arena = SubsetTrieArena::default();
+    let mut best = Duration::MAX;
+    let mut dominated = 0;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        let mut trie = SubsetTrie::new(num_nodes, chunked_max_val_range, &mut arena);
+        dominated = 0;
+        for edge in edges {
+            if trie.find_subset(edge.iter().copied()) {
+                dominated += 1;
+            } else {
+                trie.insert(true, edge.iter().copied());
+            }
+        }
+        best = best.min(start.elapsed());
+    }
+    (best, dominated)
+}
+
+fn main() {
+    let mut rng = Pcg64::seed_from_u64(0);
+    println!("{:>6} {:>12} {:>12}", "nodes", "chunked (ms)", "map (ms)");
+    for num_nodes in NODE_COUNTS {
+        let edges = random_edges(num_nodes, &mut rng);
+        let (chunked_time, chunked_dominated) = time_domination(num_nodes, &edges, usize::MAX);
+        let (map_time, map_dominated) = time_domination(num_nodes, &edges, 0);
+        assert_eq!(chunked_dominated, map_dominated);
+        println!(
+            "{:>6} {:>12.3} {:>12.3}",
+            num_nodes,
+            chunked_time.as_secs_f64() * 1000.0,
+            map_time.as_secs_f64() * 1000.0
+        );
+    }
+}
@@ -0,0 +1,39 @@
+/*
+ * C interface of findminhs, available when building the library with the
+ * `ffi` cargo feature. See src/ffi.rs for details.
+ */
+#ifndef FINDMINHS_H
+#define FINDMINHS_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct FindminhsInstance FindminhsInstance;
+typedef struct FindminhsSolution FindminhsSolution;
+
+/* Loads a hypergraph in the json format. Returns NULL on failure. */
+FindminhsInstance *findminhs_load_json(const char *json);
+
+/* Computes a minimum hitting set using settings given as json. The instance
+ * is not modified. Returns NULL on failure. */
+FindminhsSolution *findminhs_solve(const FindminhsInstance *instance,
+                                   const char *settings_json);
+
+size_t findminhs_solution_size(const FindminhsSolution *solution);
+const size_t *findminhs_solution_nodes(const FindminhsSolution *solution);
+const char *findminhs_solution_report(const FindminhsSolution *solution);
+
+/* Message describing the last failure on the calling thread, or NULL. */
+const char *findminhs_last_error(void);
+
+void findminhs_free_instance(FindminhsInstance *instance);
+void findminhs_free_solution(FindminhsSolution *solution);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* FINDMINHS_H */
@@ -0,0 +1,67 @@
+use crate::{
+    instance::{EdgeIdx, Instance, NodeIdx},
+    small_indices::SmallIdx,
+};
+
+/// Factor by which the activity of all edges decays after each bump
+const ACTIVITY_DECAY: f64 = 0.95;
+
+/// Activities are rescaled once the increment exceeds this value to avoid
+/// overflows
+const RESCALE_LIMIT: f64 = 1e100;
+
+/// Activities of the edges, bumped whenever an edge takes part in pruning a
+/// subtree and decaying over time, similar to VSIDS in SAT solvers. The score
+/// of a node is the sum of the activities of its incident edges plus its
+/// degree, so branching on the node of maximum score starts out as branching
+/// on the node of maximum degree.
+#[derive(Debug, Clone)]
+pub struct EdgeActivity {
+    activities: Vec<f64>,
+    increment: f64,
+}
+
+impl EdgeActivity {
+    pub fn new(instance: &Instance) -> Self {
+        Self::reusing(instance, Vec::new())
+    }
+
+    /// Like `new`, but stores the activities in the given, previously used
+    /// allocation.
+    pub fn reusing(instance: &Instance, mut activities: Vec<f64>) -> Self {
+        activities.clear();
+        activities.resize(instance.num_edges_total(), 0.0);
+        Self {
+            activities,
+            increment: 1.0,
+        }
+    }
+
+    /// Returns the allocation of the activities for reuse.
+    pub fn into_activities(self) -> Vec<f64> {
+        self.activities
+    }
+
+    /// Bumps the activity of the edges and decays all activities.
+    pub fn bump(&mut self, edges: &[EdgeIdx]) {
+        for edge in edges {
+            self.activities[edge.idx()] += self.increment;
+        }
+
+        // Instead of decaying all activities, future bumps are scaled up
+        self.increment /= ACTIVITY_DECAY;
+        if self.increment > RESCALE_LIMIT {
+            for activity in &mut self.activities {
+                *activity /= RESCALE_LIMIT;
+            }
+            self.increment /= RESCALE_LIMIT;
+        }
+    }
+
+    pub fn node_score(&self, instance: &Instance, node: NodeIdx) -> f64 {
+        instance
+            .node(node)
+            .map(|edge| 1.0 + self.activities[edge.idx()])
+            .sum()
+    }
+}
@@ -0,0 +1,284 @@
+use crate::{batch::write_csv_field, report::Report};
+use anyhow::{Context, Result};
+use log::{debug, info, warn};
+use serde_json::{Map, Value};
+use std::{
+    fs::{self, File},
+    io::{BufReader, Write},
+    path::{Path, PathBuf},
+};
+
+/// Table with named columns, written either as csv or as a json array of
+/// objects.
+#[derive(Debug, Default)]
+pub struct Table {
+    columns: Vec<String>,
+    rows: Vec<Map<String, Value>>,
+}
+
+impl Table {
+    fn push_row(&mut self, row: Vec<(String, Value)>) {
+        let mut map = Map::new();
+        for (column, value) in row {
+            if !self.columns.contains(&column) {
+                self.columns.push(column.clone());
+            }
+            map.insert(column, value);
+        }
+        self.rows.push(map);
+    }
+
+    pub fn write_csv(&self, mut writer: impl Write) -> Result<()> {
+        writeln!(writer, "{}", self.columns.join(","))?;
+        for row in &self.rows {
+            for (idx, column) in self.columns.iter().enumerate() {
+                if idx > 0 {
+                    write!(writer, ",")?;
+                }
+                match row.get(column) {
+                    None | Some(Value::Null) => {}
+                    Some(Value::String(text)) => write_csv_field(&mut writer, text)?,
+                    Some(value) => write_csv_field(&mut writer, &value.to_string())?,
+                }
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+
+    pub fn write_json(&self, writer: impl Write) -> Result<()> {
+        serde_json::to_writer(writer, &self.rows)?;
+        Ok(())
+    }
+}
+
+/// A report read from a file, kept as plain json so that reports written by
+/// older versions can be read as well.
+#[derive(Debug)]
+struct LoadedReport {
+    path: PathBuf,
+    report: Map<String, Value>,
+}
+
+impl LoadedReport {
+    fn file_name(&self) -> &str {
+        self.report
+            .get("file_name")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+    }
+
+    fn get(&self, key: &str) -> Value {
+        self.report.get(key).cloned().unwrap_or(Value::Null)
+    }
+
+    fn total_runtime(&self) -> Option<f64> {
+        self.report.get("runtimes")?.get("total")?.as_f64()
+    }
+}
+
+fn load_report(path: &Path) -> Result<Option<LoadedReport>> {
+    let reader = BufReader::new(File::open(path)?);
+    let value: Value = serde_json::from_reader(reader)
+        .with_context(|| format!("failed to parse report {}", path.display()))?;
+    match value {
+        Value::Object(report) if report.contains_key("file_name") && report.contains_key("opt") => {
+            Ok(Some(LoadedReport {
+                path: path.to_path_buf(),
+                report,
+            }))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Loads all reports from the given paths. Directories are searched
+/// (non-recursively) for json files containing reports, other json files in
+/// them are skipped.
+fn load_reports(paths: &[PathBuf]) -> Result<Vec<LoadedReport>> {
+    let mut reports = Vec::new();
+    for path in paths {
+        if !path.is_dir() {
+            let report = load_report(path)?
+                .with_context(|| format!("{} does not contain a solver report", path.display()))?;
+            reports.push(report);
+            continue;
+        }
+
+        let mut files = Vec::new();
+        for entry in fs::read_dir(path)? {
+            let file = entry?.path();
+            if file.is_file() && file.extension().is_some_and(|ext| ext == "json") {
+                files.push(file);
+            }
+        }
+        files.sort_unstable();
+        for file in files {
+            match load_report(&file) {
+                Ok(Some(report)) => reports.push(report),
+                Ok(None) => debug!("Skipping {}, it is not a solver report", file.display()),
+                Err(err) => warn!("Skipping {}: {:#}", file.display(), err),
+            }
+        }
+    }
+    Ok(reports)
+}
+
+/// Combines reports into one table with a row per report, containing the
+/// solution size, branching steps, runtimes, and bound breaks.
+pub fn merge_reports(paths: &[PathBuf]) -> Result<Table> {
+    let reports = load_reports(paths)?;
+    let mut table = Table::default();
+    for report in &reports {
+        let mut row = vec![
+            (
+                "report".to_string(),
+                Value::from(report.path.display().to_string()),
+            ),
+            ("file_name".to_string(), report.get("file_name")),
+            ("status".to_string(), report.get("status")),
+            ("opt".to_string(), report.get("opt")),
+            ("branching_steps".to_string(), report.get("branching_steps")),
+        ];
+        if let Some(Value::Object(runtimes)) = report.report.get("runtimes") {
+            for (name, runtime) in runtimes {
+                row.push((format!("runtime_{}", name), runtime.clone()));
+            }
+        }
+        if let Some(Value::Object(reductions)) = report.report.get("reductions") {
+            for (name, count) in reductions {
+                if name.ends_with("_breaks") {
+                    row.push((name.clone(), count.clone()));
+                }
+            }
+        }
+        table.push_row(row);
+    }
+    info!("Merged {} reports", reports.len());
+    Ok(table)
+}
+
+/// Flattens a report into a table with a single row. It contains the scalar
+/// entries of the report, the runtimes (prefixed with `runtime_`), the
+/// reduction counters, the root bounds (prefixed with `root_`), and the
+/// difficulty estimate (prefixed with `difficulty_`). Lists and
+/// the settings are omitted, so the columns are the same for every solve.
+pub fn flatten_report(report: &Report) -> Result<Table> {
+    let Value::Object(report) = serde_json::to_value(report)? else {
+        unreachable!("reports serialize to json objects");
+    };
+    let mut row = Vec::new();
+    for (name, value) in &report {
+        match (name.as_str(), value) {
+            (_, Value::Bool(_) | Value::Number(_) | Value::String(_)) => {
+                row.push((name.clone(), value.clone()));
+            }
+            ("runtimes", Value::Object(entries)) => {
+                push_scalars(&mut row, "runtime_", entries);
+            }
+            ("reductions", Value::Object(entries)) => push_scalars(&mut row, "", entries),
+            ("root_bounds", Value::Object(entries)) => push_scalars(&mut row, "root_", entries),
+            ("difficulty", Value::Object(entries)) => {
+                push_scalars(&mut row, "difficulty_", entries);
+            }
+            _ => {}
+        }
+    }
+    let mut table = Table::default();
+    table.push_row(row);
+    Ok(table)
+}
+
+fn push_scalars(row: &mut Vec<(String, Value)>, prefix: &str, entries: &Map<String, Value>) {
+    for (name, value) in entries {
+        if !matches!(value, Value::Array(_) | Value::Object(_)) {
+            row.push((format!("{}{}", prefix, name), value.clone()));
+        }
+    }
+}
+
+fn ratio(old: Option<f64>, new: Option<f64>) -> Value {
+    match (old, new) {
+        (Some(old), Some(new)) if old > 0.0 => Value::from(new / old),
+        _ => Value::Null,
+    }
+}
+
+/// Compares two sets of reports for the same hypergraphs, matched by their
+/// file names. The table contains the solution sizes, branching steps, and
+/// runtimes of both runs and their ratios (new / old).
+pub fn diff_reports(old_paths: &[PathBuf], new_paths: &[PathBuf]) -> Result<Table> {
+    let old_reports = load_reports(old_paths)?;
+    let new_reports = load_reports(new_paths)?;
+
+    let mut table = Table::default();
+    let mut num_opt_mismatches = 0;
+    let mut log_runtime_ratio_sum = 0.0;
+    let mut num_runtime_ratios = 0_u32;
+    for old in &old_reports {
+        let Some(new) = new_reports
+            .iter()
+            .find(|new| new.file_name() == old.file_name())
+        else {
+            warn!("No new report for {}", old.file_name());
+            continue;
+        };
+
+        let (old_opt, new_opt) = (old.get("opt"), new.get("opt"));
+        if old_opt != new_opt {
+            num_opt_mismatches += 1;
+            warn!(
+                "Solution sizes differ for {}: {} vs. {}",
+                old.file_name(),
+                old_opt,
+                new_opt
+            );
+        }
+        let (old_steps, new_steps) = (old.get("branching_steps"), new.get("branching_steps"));
+        let (old_runtime, new_runtime) = (old.total_runtime(), new.total_runtime());
+        let runtime_ratio = ratio(old_runtime, new_runtime);
+        if let Some(runtime_ratio) = runtime_ratio.as_f64().filter(|&ratio| ratio > 0.0) {
+            log_runtime_ratio_sum += runtime_ratio.ln();
+            num_runtime_ratios += 1;
+        }
+        let steps_ratio = ratio(old_steps.as_f64(), new_steps.as_f64());
+        table.push_row(vec![
+            ("file_name".to_string(), Value::from(old.file_name())),
+            ("old_opt".to_string(), old_opt),
+            ("new_opt".to_string(), new_opt),
+            ("old_branching_steps".to_string(), old_steps),
+            ("new_branching_steps".to_string(), new_steps),
+            ("branching_steps_ratio".to_string(), steps_ratio),
+            (
+                "old_runtime".to_string(),
+                old_runtime.map_or(Value::Null, Value::from),
+            ),
+            (
+                "new_runtime".to_string(),
+                new_runtime.map_or(Value::Null, Value::from),
+            ),
+            ("runtime_ratio".to_string(), runtime_ratio),
+        ]);
+    }
+    for new in &new_reports {
+        if !old_reports
+            .iter()
+            .any(|old| old.file_name() == new.file_name())
+        {
+            warn!("No old report for {}", new.file_name());
+        }
+    }
+
+    info!(
+        "Compared {} hypergraphs, {} with differing solution sizes",
+        table.rows.len(),
+        num_opt_mismatches
+    );
+    if num_runtime_ratios > 0 {
+        info!(
+            "Geometric mean of runtime ratios (new / old): {:.3}",
+            (log_runtime_ratio_sum / f64::from(num_runtime_ratios)).exp()
+        );
+    }
+    Ok(table)
+}
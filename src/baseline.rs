@@ -0,0 +1,139 @@
+use crate::{
+    report::{
+        Baseline, BaselineCheck, BaselineDeviation, BaselineEntry, BaselineMismatch, Settings,
+    },
+    summary::SolveSummary,
+};
+use rustc_hash::FxHashMap;
+use std::path::Path;
+
+/// How much worse than the baseline a new run may be
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BaselineTolerances {
+    /// Allowed relative increase of the branching steps, e.g. 0.1 for 10%
+    pub branching_steps: f64,
+
+    /// Allowed relative increase of the runtime. Runtimes are not compared if
+    /// this is `None`, since they vary between machines and runs.
+    pub runtime: Option<f64>,
+}
+
+fn hypergraph_name(hypergraph: &str) -> String {
+    Path::new(hypergraph).file_name().map_or_else(
+        || hypergraph.to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    )
+}
+
+/// Records the results of a batch of solver runs as a baseline.
+pub fn record_baseline(settings: Settings, summaries: &[SolveSummary]) -> Baseline {
+    let hypergraphs = summaries
+        .iter()
+        .map(|summary| BaselineEntry {
+            hypergraph: hypergraph_name(&summary.hypergraph),
+            status: summary.status.to_string(),
+            size: summary.size,
+            branching_steps: summary.branching_steps,
+            runtime: summary.runtime,
+        })
+        .collect();
+    Baseline {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        settings,
+        hypergraphs,
+    }
+}
+
+fn exceeds(old: f64, new: f64, tolerance: f64) -> bool {
+    new > old * (1.0 + tolerance)
+}
+
+/// Finds the deviation of a new run from its baseline entry, if any.
+#[allow(clippy::cast_precision_loss)]
+fn compare_entry(
+    old: &BaselineEntry,
+    new: &SolveSummary,
+    tolerances: BaselineTolerances,
+) -> Option<BaselineDeviation> {
+    if old.status != new.status {
+        return Some(BaselineDeviation::StatusChanged {
+            old: old.status.clone(),
+            new: new.status.to_string(),
+        });
+    }
+    // Sizes are only comparable for optimal solutions, which must not change
+    if new.is_optimal() {
+        if let (Some(old_size), Some(new_size)) = (old.size, new.size) {
+            if old_size != new_size {
+                return Some(BaselineDeviation::SizeChanged {
+                    old: old_size,
+                    new: new_size,
+                });
+            }
+        }
+    }
+    if let (Some(old_steps), Some(new_steps)) = (old.branching_steps, new.branching_steps) {
+        if exceeds(
+            old_steps as f64,
+            new_steps as f64,
+            tolerances.branching_steps,
+        ) {
+            return Some(BaselineDeviation::MoreBranchingSteps {
+                old: old_steps,
+                new: new_steps,
+            });
+        }
+    }
+    if let (Some(tolerance), Some(old_runtime), Some(new_runtime)) =
+        (tolerances.runtime, old.runtime, new.runtime)
+    {
+        if exceeds(old_runtime, new_runtime, tolerance) {
+            return Some(BaselineDeviation::SlowerRuntime {
+                old: old_runtime,
+                new: new_runtime,
+            });
+        }
+    }
+    None
+}
+
+/// Compares new solver runs against a baseline. Hypergraphs are matched by
+/// their file names, those without a baseline entry are ignored.
+pub fn check_baseline(
+    baseline: &Baseline,
+    summaries: &[SolveSummary],
+    tolerances: BaselineTolerances,
+) -> BaselineCheck {
+    let new_by_name: FxHashMap<_, _> = summaries
+        .iter()
+        .map(|summary| (hypergraph_name(&summary.hypergraph), summary))
+        .collect();
+    let mut check = BaselineCheck {
+        checked: 0,
+        fewer_branching_steps: 0,
+        mismatches: Vec::new(),
+    };
+    for old in &baseline.hypergraphs {
+        let deviation = match new_by_name.get(&old.hypergraph) {
+            Some(new) => {
+                check.checked += 1;
+                if let (Some(old_steps), Some(new_steps)) =
+                    (old.branching_steps, new.branching_steps)
+                {
+                    if new_steps < old_steps && new.status == old.status {
+                        check.fewer_branching_steps += 1;
+                    }
+                }
+                compare_entry(old, new, tolerances)
+            }
+            None => Some(BaselineDeviation::Missing),
+        };
+        if let Some(deviation) = deviation {
+            check.mismatches.push(BaselineMismatch {
+                hypergraph: old.hypergraph.clone(),
+                deviation,
+            });
+        }
+    }
+    check
+}
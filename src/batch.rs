@@ -0,0 +1,112 @@
+use crate::summary::SolveSummary;
+use anyhow::{Context, Result};
+use std::{
+    fs::{self, File},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+/// Collects the hypergraphs of a batch: all files in a directory (sorted by
+/// name), or the paths listed in a file, one per line. Blank lines and lines
+/// starting with `#` in a list file are skipped, and relative paths are
+/// resolved relative to the list file.
+pub fn collect_instances(path: &Path) -> Result<Vec<PathBuf>> {
+    if path.is_dir() {
+        let mut instances = Vec::new();
+        for entry in fs::read_dir(path)? {
+            let entry = entry?;
+            if entry.file_type()?.is_file() {
+                instances.push(entry.path());
+            }
+        }
+        instances.sort_unstable();
+        return Ok(instances);
+    }
+
+    let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let reader = BufReader::new(
+        File::open(path).with_context(|| format!("failed to open {}", path.display()))?,
+    );
+    let mut instances = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        instances.push(base_dir.join(line));
+    }
+    Ok(instances)
+}
+
+/// Runs `solve_instance` on all instances using `jobs` threads, which take the
+/// next unsolved instance whenever they are idle. The summaries are returned
+/// in the order of the instances.
+pub fn run_batch(
+    instances: &[PathBuf],
+    jobs: usize,
+    solve_instance: impl Fn(&Path) -> SolveSummary + Sync,
+) -> Vec<SolveSummary> {
+    let next_instance = AtomicUsize::new(0);
+    let worker = || {
+        let mut summaries = Vec::new();
+        loop {
+            let idx = next_instance.fetch_add(1, Ordering::Relaxed);
+            let Some(instance) = instances.get(idx) else {
+                break summaries;
+            };
+            summaries.push((idx, solve_instance(instance)));
+        }
+    };
+
+    let mut summaries: Vec<_> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..jobs.max(1)).map(|_| scope.spawn(worker)).collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("Batch worker thread panicked"))
+            .collect()
+    });
+    summaries.sort_unstable_by_key(|&(idx, _)| idx);
+    summaries.into_iter().map(|(_, summary)| summary).collect()
+}
+
+/// Writes a single csv field, quoting it if necessary.
+pub fn write_csv_field(mut writer: impl Write, field: &str) -> Result<()> {
+    if field.contains([',', '"', '\n', '\r']) {
+        write!(writer, "\"{}\"", field.replace('"', "\"\""))?;
+    } else {
+        write!(writer, "{}", field)?;
+    }
+    Ok(())
+}
+
+/// Writes the summaries as csv, with one row per instance.
+pub fn write_csv_summary(mut writer: impl Write, summaries: &[SolveSummary]) -> Result<()> {
+    writeln!(
+        writer,
+        "hypergraph,status,exit_code,size,branching_steps,runtime,error"
+    )?;
+    for summary in summaries {
+        write_csv_field(&mut writer, &summary.hypergraph)?;
+        write!(writer, ",{},{},", summary.status, summary.exit_code)?;
+        if let Some(size) = summary.size {
+            write!(writer, "{}", size)?;
+        }
+        write!(writer, ",")?;
+        if let Some(branching_steps) = summary.branching_steps {
+            write!(writer, "{}", branching_steps)?;
+        }
+        write!(writer, ",")?;
+        if let Some(runtime) = summary.runtime {
+            write!(writer, "{}", runtime)?;
+        }
+        write!(writer, ",")?;
+        if let Some(error) = &summary.error {
+            write_csv_field(&mut writer, error)?;
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use crate::{
+    instance::Instance,
+    report::{BenchReport, SampleStats, Settings},
+    solve::{self, SolveHooks},
+};
+use anyhow::Result;
+use log::{info, warn};
+
+#[allow(clippy::cast_precision_loss)]
+fn calc_sample_stats(samples: &[f64]) -> SampleStats {
+    if samples.is_empty() {
+        return SampleStats::default();
+    }
+
+    let mut sorted = samples.to_vec();
+    sorted.sort_unstable_by(f64::total_cmp);
+    let len = sorted.len();
+    let median = if len.is_multiple_of(2) {
+        f64::midpoint(sorted[len / 2 - 1], sorted[len / 2])
+    } else {
+        sorted[len / 2]
+    };
+    let mean = sorted.iter().sum::<f64>() / len as f64;
+    let stddev = if len > 1 {
+        let squared_deviations: f64 = sorted.iter().map(|sample| (sample - mean).powi(2)).sum();
+        (squared_deviations / (len - 1) as f64).sqrt()
+    } else {
+        0.0
+    };
+    SampleStats {
+        mean,
+        median,
+        stddev,
+        min: sorted[0],
+        max: sorted[len - 1],
+    }
+}
+
+/// Solves the instance `runs` times after `warmup_runs` unmeasured runs and
+/// collects statistics about the runtimes and branching steps. If `vary_seed`
+/// is set, run `i` uses the seed from the settings plus `i`.
+#[allow(clippy::cast_precision_loss)]
+pub fn run_bench(
+    instance: &Instance,
+    file_name: &str,
+    settings: &Settings,
+    runs: usize,
+    warmup_runs: usize,
+    vary_seed: bool,
+) -> Result<BenchReport> {
+    for run in 0..warmup_runs {
+        info!("Warmup run {}/{}", run + 1, warmup_runs);
+        solve::solve(
+            instance.clone(),
+            file_name.to_string(),
+            settings.clone(),
+            SolveHooks::default(),
+        )?;
+    }
+
+    let mut opts = Vec::with_capacity(runs);
+    let mut runtimes = Vec::with_capacity(runs);
+    let mut branching_steps = Vec::with_capacity(runs);
+    for run in 0..runs {
+        let mut run_settings = settings.clone();
+        if vary_seed {
+            run_settings.seed = settings.seed.wrapping_add(run as u64);
+        }
+        let (_, report) = solve::solve(
+            instance.clone(),
+            file_name.to_string(),
+            run_settings,
+            SolveHooks::default(),
+        )?;
+        info!(
+            "Run {}/{}: size {}, {} branching steps in {:.2?}",
+            run + 1,
+            runs,
+            report.opt,
+            report.branching_steps,
+            report.runtimes.total
+        );
+        opts.push(report.opt);
+        runtimes.push(report.runtimes.total.as_secs_f64());
+        branching_steps.push(report.branching_steps as f64);
+    }
+    if opts.windows(2).any(|pair| pair[0] != pair[1]) {
+        warn!("Runs found hitting sets of different sizes: {:?}", opts);
+    }
+
+    Ok(BenchReport {
+        file_name: file_name.to_string(),
+        runs,
+        opts,
+        runtime: calc_sample_stats(&runtimes),
+        branching_steps: calc_sample_stats(&branching_steps),
+        settings: settings.clone(),
+    })
+}
@@ -0,0 +1,98 @@
+use crate::{
+    lower_bound::{BuiltinBound, LowerBound},
+    report::{AdaptiveBoundSettings, BoundBandStats},
+};
+
+/// Bounds that may be skipped. The efficiency and packing bounds are always
+/// computed, as the costly discard rules reuse their results.
+const SELECTABLE_BOUNDS: [BuiltinBound; 5] = [
+    BuiltinBound::MaxDegree,
+    BuiltinBound::SumDegree,
+    BuiltinBound::SumOverPacking,
+    BuiltinBound::Flow,
+    BuiltinBound::CliqueCover,
+];
+
+#[derive(Debug, Clone, Copy, Default)]
+struct BandCounts {
+    evaluations: usize,
+    breaks: usize,
+    skips: usize,
+}
+
+/// Learns in which bands of depths each lower bound prunes subproblems, and
+/// skips the bounds that never did in a band after enough evaluations. Every
+/// `reprobe_interval`-th skipped evaluation is still computed, so that a bound
+/// starting to prune later on is enabled again.
+#[derive(Debug, Clone)]
+pub struct BoundSelection {
+    settings: AdaptiveBoundSettings,
+    /// Counts of each band, indexed like `SELECTABLE_BOUNDS`
+    bands: Vec<[BandCounts; SELECTABLE_BOUNDS.len()]>,
+}
+
+impl BoundSelection {
+    pub fn new(settings: AdaptiveBoundSettings) -> Self {
+        Self {
+            settings,
+            bands: Vec::new(),
+        }
+    }
+
+    fn counts(&mut self, bound: BuiltinBound, depth: usize) -> Option<&mut BandCounts> {
+        let idx = SELECTABLE_BOUNDS.iter().position(|&other| other == bound)?;
+        let band = depth / self.settings.depth_band;
+        if band >= self.bands.len() {
+            self.bands.resize_with(band + 1, Default::default);
+        }
+        Some(&mut self.bands[band][idx])
+    }
+
+    /// Whether to skip computing the bound for a subproblem at the given
+    /// depth.
+    pub fn skip(&mut self, bound: BuiltinBound, depth: usize) -> bool {
+        let AdaptiveBoundSettings {
+            min_evaluations,
+            reprobe_interval,
+            ..
+        } = self.settings;
+        let Some(counts) = self.counts(bound, depth) else {
+            return false;
+        };
+        if counts.breaks > 0 || counts.evaluations < min_evaluations {
+            return false;
+        }
+        counts.skips += 1;
+        !counts.skips.is_multiple_of(reprobe_interval)
+    }
+
+    /// Records that the bound was computed for a subproblem at the given
+    /// depth, and whether it pruned it.
+    pub fn record(&mut self, bound: BuiltinBound, depth: usize, pruned: bool) {
+        if let Some(counts) = self.counts(bound, depth) {
+            counts.evaluations += 1;
+            counts.breaks += usize::from(pruned);
+        }
+    }
+
+    /// Counts of each bound and band in which the bound was computed at
+    /// least once.
+    pub fn stats(&self) -> Vec<BoundBandStats> {
+        let mut stats = Vec::new();
+        for (band, counts) in self.bands.iter().enumerate() {
+            for (bound, counts) in SELECTABLE_BOUNDS.iter().zip(counts) {
+                if counts.evaluations == 0 {
+                    continue;
+                }
+                stats.push(BoundBandStats {
+                    bound: bound.name().to_string(),
+                    min_depth: band * self.settings.depth_band,
+                    evaluations: counts.evaluations,
+                    breaks: counts.breaks,
+                    skips: counts.skips - counts.skips / self.settings.reprobe_interval,
+                });
+            }
+        }
+        stats
+    }
+}
@@ -0,0 +1,285 @@
+//! Numbering of the nodes and edges of an instance that only depends on its
+//! structure.
+//!
+//! With `deterministic` set, the solver works on a copy of the instance
+//! numbered this way, so that every renumbering of the input, such as one
+//! written by `findminhs anonymize`, is solved along the same path. The
+//! numbering is found by color refinement on the graph connecting each edge
+//! to its nodes and each node to the nodes it conflicts with: nodes and edges
+//! are grouped into cells, which are split by the number of neighbors each
+//! element has in another cell until no cell can be split further. Cells left
+//! with several elements are broken up by moving one element into a cell of
+//! its own and refining again.
+//!
+//! All decisions only depend on the positions of cells and the numbers of
+//! neighbors, except for the element singled out of a cell. This choice does
+//! not matter if some automorphism of the instance maps the elements of the
+//! cell onto each other, as for the nodes of identical components. Refinement
+//! can not tell apart all elements that are not symmetric in this way, so the
+//! numbering may still depend on the input order for some highly regular
+//! instances.
+use crate::{
+    instance::{EdgeIdx, Instance, NodeIdx},
+    report::{SecondaryObjective, Settings},
+    small_indices::SmallIdx,
+};
+use std::collections::BTreeSet;
+
+/// Ordered partition of the nodes and edges. Node `v` is the element `v`, and
+/// edge `e` is the element `num_nodes + e`.
+struct Partition {
+    num_nodes: usize,
+
+    /// Elements ordered by their cells
+    elements: Vec<usize>,
+
+    /// Position of each element in `elements`
+    positions: Vec<usize>,
+
+    /// Position of the first element of the cell of each element
+    cell_starts: Vec<usize>,
+
+    /// End of the cell starting at each position, only valid at the first
+    /// position of each cell
+    cell_ends: Vec<usize>,
+
+    /// Cells whose elements still have to split the other cells, by position
+    splitters: BTreeSet<usize>,
+
+    /// Number of neighbors in the current splitter of each element
+    counts: Vec<usize>,
+}
+
+impl Partition {
+    /// Cells of the elements with equal keys, ordered by their keys
+    fn new<K: Ord>(num_nodes: usize, keys: &[K]) -> Self {
+        let mut elements: Vec<_> = (0..keys.len()).collect();
+        elements.sort_by(|&first, &second| keys[first].cmp(&keys[second]));
+        let mut positions = vec![0; keys.len()];
+        let mut cell_starts = vec![0; keys.len()];
+        let mut cell_ends = vec![0; keys.len()];
+        let mut splitters = BTreeSet::new();
+        let mut start = 0;
+        for (pos, &element) in elements.iter().enumerate() {
+            if keys[element] != keys[elements[start]] {
+                cell_ends[start] = pos;
+                splitters.insert(start);
+                start = pos;
+            }
+            positions[element] = pos;
+            cell_starts[element] = start;
+        }
+        if !elements.is_empty() {
+            cell_ends[start] = elements.len();
+            splitters.insert(start);
+        }
+
+        Self {
+            num_nodes,
+            elements,
+            positions,
+            cell_starts,
+            cell_ends,
+            splitters,
+            counts: vec![0; keys.len()],
+        }
+    }
+
+    fn move_to(&mut self, element: usize, pos: usize) {
+        let other = self.elements[pos];
+        let old_pos = self.positions[element];
+        self.elements.swap(old_pos, pos);
+        self.positions[other] = old_pos;
+        self.positions[element] = pos;
+    }
+
+    /// Splits a cell by the counts of its elements in `touched`, which are
+    /// ordered by their counts. Elements not in `touched` have count zero.
+    ///
+    /// The elements of each new cell are moved behind those with smaller
+    /// counts. Only the elements of the new cells starting after the first
+    /// one are updated, so that the work is proportional to `touched`.
+    fn split(&mut self, cell: usize, touched: &[usize]) {
+        let end = self.cell_ends[cell];
+        let num_untouched = end - cell - touched.len();
+        for (offset, &element) in touched.iter().enumerate() {
+            self.move_to(element, cell + num_untouched + offset);
+        }
+
+        let mut pieces = Vec::new();
+        if num_untouched > 0 {
+            pieces.push(cell..cell + num_untouched);
+        }
+        let mut start = cell + num_untouched;
+        for pos in start + 1..=end {
+            if pos == end || self.counts[self.elements[pos]] != self.counts[self.elements[start]] {
+                pieces.push(start..pos);
+                start = pos;
+            }
+        }
+        if pieces.len() == 1 {
+            return;
+        }
+
+        for piece in &pieces {
+            self.cell_ends[piece.start] = piece.end;
+            if piece.start != cell {
+                for pos in piece.clone() {
+                    self.cell_starts[self.elements[pos]] = piece.start;
+                }
+            }
+        }
+
+        // If the cell already split the others, splitting by all but one of
+        // the new cells has the same effect, so the largest one is skipped
+        let was_splitter = self.splitters.contains(&cell);
+        let mut largest = 0;
+        for (idx, piece) in pieces.iter().enumerate() {
+            if piece.len() > pieces[largest].len() {
+                largest = idx;
+            }
+        }
+        for (idx, piece) in pieces.iter().enumerate() {
+            if was_splitter || idx != largest {
+                self.splitters.insert(piece.start);
+            }
+        }
+    }
+
+    /// Splits cells until no cell has elements with different numbers of
+    /// neighbors in another cell.
+    fn refine(&mut self, instance: &Instance) {
+        let mut touched = Vec::new();
+        while let Some(splitter) = self.splitters.pop_first() {
+            let members = self.elements[splitter..self.cell_ends[splitter]].to_vec();
+            touched.clear();
+            for member in members {
+                for_each_neighbor(instance, self.num_nodes, member, |neighbor| {
+                    if self.counts[neighbor] == 0 {
+                        touched.push(neighbor);
+                    }
+                    self.counts[neighbor] += 1;
+                });
+            }
+
+            touched
+                .sort_unstable_by_key(|&element| (self.cell_starts[element], self.counts[element]));
+            let mut first = 0;
+            while first < touched.len() {
+                let cell = self.cell_starts[touched[first]];
+                let len = touched[first..]
+                    .iter()
+                    .take_while(|&&element| self.cell_starts[element] == cell)
+                    .count();
+                self.split(cell, &touched[first..first + len]);
+                first += len;
+            }
+            for &element in &touched {
+                self.counts[element] = 0;
+            }
+        }
+    }
+
+    /// Refines the partition until every element has a cell of its own.
+    fn discretize(&mut self, instance: &Instance) {
+        self.refine(instance);
+        let mut pos = 0;
+        while pos < self.elements.len() {
+            if self.cell_ends[pos] == pos + 1 {
+                pos += 1;
+                continue;
+            }
+
+            // Single out the last element of the cell
+            let element = self.elements[self.cell_ends[pos] - 1];
+            self.counts[element] = 1;
+            self.split(pos, &[element]);
+            self.counts[element] = 0;
+            self.refine(instance);
+        }
+    }
+}
+
+/// Calls `callback` with the edges of a node and the nodes it conflicts with,
+/// or the nodes of an edge.
+fn for_each_neighbor(
+    instance: &Instance,
+    num_nodes: usize,
+    element: usize,
+    mut callback: impl FnMut(usize),
+) {
+    if element < num_nodes {
+        let node = NodeIdx::from(element);
+        for edge in instance.node(node) {
+            callback(num_nodes + edge.idx());
+        }
+        for &other in instance.conflicts(node) {
+            callback(other.idx());
+        }
+    } else {
+        for node in instance.edge(EdgeIdx::from(element - num_nodes)) {
+            callback(node.idx());
+        }
+    }
+}
+
+/// Canonical numbering of the nodes and edges of an instance without deleted
+/// nodes or edges. Returns the index of each node and edge of the numbering in
+/// the instance.
+///
+/// Besides the structure of the instance, the numbering takes the node indices
+/// and costs of the settings into account, as well as the multiplicities and
+/// coverage requirements of the edges.
+pub fn canonical_numbering(
+    instance: &Instance,
+    settings: &Settings,
+) -> (Vec<NodeIdx>, Vec<EdgeIdx>) {
+    let num_nodes = instance.num_nodes_total();
+    let mut flags = vec![0_u8; num_nodes];
+    let initial_hitting_set = settings.initial_hitting_set.as_deref().unwrap_or_default();
+    for (bit, nodes) in [
+        settings.forced_nodes.as_slice(),
+        &settings.partial_hitting_set,
+        &settings.forbidden_nodes,
+        initial_hitting_set,
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        for node in nodes {
+            if let Some(node_flags) = flags.get_mut(node.idx()) {
+                *node_flags |= 1 << bit;
+            }
+        }
+    }
+    let costs = match &settings.secondary_objective {
+        Some(SecondaryObjective::MinCostSum { costs }) if costs.len() == num_nodes => {
+            costs.as_slice()
+        }
+        _ => &[],
+    };
+
+    // Nodes come before edges, so the numbering stays a numbering of each
+    let mut keys = Vec::with_capacity(num_nodes + instance.num_edges_total());
+    for (idx, &node_flags) in flags.iter().enumerate() {
+        let cost = costs.get(idx).map_or(0, |cost| cost.to_bits());
+        keys.push((0, node_flags, 0, cost));
+    }
+    for idx in 0..instance.num_edges_total() {
+        let edge = EdgeIdx::from(idx);
+        let requirement = instance.edge_requirement(edge) as u64;
+        keys.push((1, 0, instance.edge_multiplicity(edge), requirement));
+    }
+
+    let mut partition = Partition::new(num_nodes, &keys);
+    partition.discretize(instance);
+    let nodes = partition.elements[..num_nodes]
+        .iter()
+        .map(|&element| NodeIdx::from(element))
+        .collect();
+    let edges = partition.elements[num_nodes..]
+        .iter()
+        .map(|&element| EdgeIdx::from(element - num_nodes))
+        .collect();
+    (nodes, edges)
+}
@@ -0,0 +1,9 @@
+//! Monotonic clock working on all supported targets.
+//!
+//! `std::time::Instant` panics on `wasm32-unknown-unknown`, where the clock of
+//! the browser is used instead.
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+pub use web_time::Instant;
@@ -0,0 +1,172 @@
+//! Fast upper bounds on large hypergraphs by solving a coarsened surrogate.
+//!
+//! Nodes sharing many small edges are grouped into clusters, and each edge is
+//! replaced by the set of clusters of its nodes. Many edges become identical
+//! this way, so the surrogate has far fewer nodes and edges. Any hitting set
+//! of the surrogate expands to a hitting set of the original hypergraph by
+//! taking all nodes of each chosen cluster, after which redundant nodes are
+//! dropped again.
+use crate::{
+    instance::{Instance, LoadOptions, NodeIdx},
+    reductions,
+    small_indices::SmallIdx,
+};
+use log::debug;
+use rustc_hash::FxHashSet;
+
+/// Maximum number of nodes per cluster
+const MAX_CLUSTER_SIZE: usize = 8;
+
+/// Edges larger than this are ignored when rating node pairs, since they say
+/// little about the similarity of their nodes but are expensive to scan
+const MAX_RATED_EDGE_SIZE: usize = 16;
+
+/// Number of incident edges of a node scanned when rating node pairs
+const MAX_RATED_EDGES: usize = 16;
+
+/// Surrogates with fewer edges are solved directly instead of being
+/// coarsened further
+const MIN_COARSENED_EDGES: usize = 1000;
+
+/// Assigns each node to a cluster, returning the number of clusters and the
+/// cluster of each node. Nodes join the cluster of the node they share the
+/// most small edges with, unless it is full.
+#[allow(clippy::cast_precision_loss)]
+fn cluster_nodes(instance: &Instance) -> (usize, Vec<usize>) {
+    let mut cluster = vec![usize::MAX; instance.num_nodes_total()];
+    let mut cluster_sizes = Vec::new();
+    let mut rating = vec![0.0; instance.num_nodes_total()];
+    let mut rated = Vec::new();
+    for &node in instance.nodes() {
+        if cluster[node.idx()] != usize::MAX {
+            continue;
+        }
+
+        let rated_edges = instance
+            .node(node)
+            .filter(|&edge| instance.edge_size(edge) <= MAX_RATED_EDGE_SIZE)
+            .take(MAX_RATED_EDGES);
+        for edge in rated_edges {
+            let size = instance.edge_size(edge);
+            let weight = 1.0 / (size as f64 - 1.0).max(1.0);
+            for other in instance.edge(edge) {
+                let joinable = cluster[other.idx()] == usize::MAX
+                    || cluster_sizes[cluster[other.idx()]] < MAX_CLUSTER_SIZE;
+                if other != node && joinable {
+                    if rating[other.idx()] == 0.0 {
+                        rated.push(other);
+                    }
+                    rating[other.idx()] += weight;
+                }
+            }
+        }
+        let best = rated
+            .iter()
+            .copied()
+            .max_by(|a: &NodeIdx, b: &NodeIdx| rating[a.idx()].total_cmp(&rating[b.idx()]));
+        for other in rated.drain(..) {
+            rating[other.idx()] = 0.0;
+        }
+
+        match best {
+            Some(other) if cluster[other.idx()] != usize::MAX => {
+                cluster[node.idx()] = cluster[other.idx()];
+                cluster_sizes[cluster[other.idx()]] += 1;
+            }
+            Some(other) => {
+                cluster[node.idx()] = cluster_sizes.len();
+                cluster[other.idx()] = cluster_sizes.len();
+                cluster_sizes.push(2);
+            }
+            None => {
+                cluster[node.idx()] = cluster_sizes.len();
+                cluster_sizes.push(1);
+            }
+        }
+    }
+    (cluster_sizes.len(), cluster)
+}
+
+/// Replaces each edge by the clusters of its nodes, dropping duplicates.
+fn contract_edges(instance: &Instance, cluster: &[usize]) -> Vec<Vec<usize>> {
+    let mut contracted = FxHashSet::default();
+    let mut buffer = Vec::new();
+    for &edge in instance.edges() {
+        buffer.clear();
+        buffer.extend(instance.edge(edge).map(|node| cluster[node.idx()]));
+        buffer.sort_unstable();
+        buffer.dedup();
+        if !contracted.contains(buffer.as_slice()) {
+            contracted.insert(buffer.clone());
+        }
+    }
+    let mut edges: Vec<_> = contracted.into_iter().collect();
+    edges.sort_unstable();
+    edges
+}
+
+/// Drops nodes whose edges are all hit by other nodes, trying those hitting
+/// the fewest edges first.
+fn remove_redundant_nodes(instance: &Instance, hs: &mut Vec<NodeIdx>) {
+    let mut hit_count = vec![0_usize; instance.num_edges_total()];
+    for &node in hs.iter() {
+        for edge in instance.node(node) {
+            hit_count[edge.idx()] += 1;
+        }
+    }
+    hs.sort_by_key(|&node| instance.node_degree(node));
+    hs.retain(|&node| {
+        let redundant = instance.node(node).all(|edge| hit_count[edge.idx()] > 1);
+        if redundant {
+            for edge in instance.node(node) {
+                hit_count[edge.idx()] -= 1;
+            }
+        }
+        !redundant
+    });
+}
+
+/// Computes a hitting set by running the max-degree greedy algorithm on a
+/// coarsened surrogate of the instance, coarsening the surrogate again as long
+/// as this shrinks it considerably. Conflicts between nodes are not supported.
+pub fn calc_coarsened_greedy(instance: &Instance) -> Vec<NodeIdx> {
+    let (num_clusters, cluster) = cluster_nodes(instance);
+    let edges = contract_edges(instance, &cluster);
+    debug!(
+        "Coarsened {} nodes and {} edges to {} nodes and {} edges",
+        instance.nodes().len(),
+        instance.num_edges(),
+        num_clusters,
+        edges.len()
+    );
+    if 4 * edges.len() > 3 * instance.num_edges() {
+        // Solving the surrogate would take almost as long as solving the
+        // instance itself
+        debug!("Coarsening did not shrink the instance enough, running greedy directly");
+        return reductions::calc_greedy_approximation(instance)
+            .expect("Greedy strategies do not support conflicts");
+    }
+    let surrogate = Instance::from_edges(num_clusters, &edges, LoadOptions::default())
+        .expect("Contracted edges are valid");
+    let coarsen_further =
+        edges.len() >= MIN_COARSENED_EDGES && 2 * edges.len() <= instance.num_edges();
+    let surrogate_hs = if coarsen_further {
+        calc_coarsened_greedy(&surrogate)
+    } else {
+        reductions::calc_greedy_approximation(&surrogate)
+            .expect("Surrogate instances have no conflicts")
+    };
+
+    let mut chosen = vec![false; num_clusters];
+    for cluster_node in surrogate_hs {
+        chosen[cluster_node.idx()] = true;
+    }
+    let mut hs: Vec<_> = instance
+        .nodes()
+        .iter()
+        .copied()
+        .filter(|node| chosen[cluster[node.idx()]])
+        .collect();
+    remove_redundant_nodes(instance, &mut hs);
+    hs
+}
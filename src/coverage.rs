@@ -0,0 +1,163 @@
+use crate::{
+    clock::Instant,
+    instance::{Instance, NodeIdx},
+    small_indices::SmallIdx,
+};
+
+/// Branch and bound search for at most `budget` nodes hitting as many edges as
+/// possible. Edges merged from duplicates count with their multiplicity.
+///
+/// Branches on the node hitting the most unhit edges, first including and then
+/// excluding it. A subproblem is pruned if even the nodes with the largest
+/// gains together cannot hit more edges than the best selection found so far.
+/// After the deadline, only the first selection, which takes the node with the
+/// largest gain each time, is completed.
+struct CoverageSearch<'a> {
+    instance: &'a Instance,
+    budget: usize,
+    deadline: Option<Instant>,
+    hit_count: Vec<usize>,
+    /// Number of reasons a node can not be chosen: being chosen or excluded
+    /// already, or conflicting with a chosen node
+    num_blockers: Vec<usize>,
+    chosen: Vec<NodeIdx>,
+    num_hit: usize,
+    /// Number of remaining edges, counted with their multiplicity
+    num_total: usize,
+    best: Vec<NodeIdx>,
+    best_num_hit: usize,
+    timed_out: bool,
+}
+
+impl CoverageSearch<'_> {
+    fn gain(&self, node: NodeIdx) -> usize {
+        self.instance
+            .node(node)
+            .filter(|edge| self.hit_count[edge.idx()] == 0)
+            .map(|edge| self.instance.edge_multiplicity(edge))
+            .sum()
+    }
+
+    fn add(&mut self, node: NodeIdx) {
+        self.chosen.push(node);
+        self.num_blockers[node.idx()] += 1;
+        for other in self.instance.conflicts(node) {
+            self.num_blockers[other.idx()] += 1;
+        }
+        for edge in self.instance.node(node) {
+            self.hit_count[edge.idx()] += 1;
+            if self.hit_count[edge.idx()] == 1 {
+                self.num_hit += self.instance.edge_multiplicity(edge);
+            }
+        }
+    }
+
+    fn remove(&mut self, node: NodeIdx) {
+        debug_assert_eq!(self.chosen.last().copied(), Some(node));
+        self.chosen.pop();
+        self.num_blockers[node.idx()] -= 1;
+        for other in self.instance.conflicts(node) {
+            self.num_blockers[other.idx()] -= 1;
+        }
+        for edge in self.instance.node(node) {
+            self.hit_count[edge.idx()] -= 1;
+            if self.hit_count[edge.idx()] == 0 {
+                self.num_hit -= self.instance.edge_multiplicity(edge);
+            }
+        }
+    }
+
+    fn search(&mut self) {
+        if self.num_hit > self.best_num_hit {
+            self.best_num_hit = self.num_hit;
+            self.best.clear();
+            self.best.extend(self.chosen.iter().copied());
+        }
+        if self.chosen.len() >= self.budget || self.num_hit == self.num_total {
+            return;
+        }
+
+        let mut gains: Vec<_> = self
+            .instance
+            .nodes()
+            .iter()
+            .copied()
+            .filter(|node| self.num_blockers[node.idx()] == 0)
+            .map(|node| (self.gain(node), node))
+            .filter(|&(gain, _)| gain > 0)
+            .collect();
+        gains.sort_unstable_by(|a, b| b.cmp(a));
+        let remaining_budget = self.budget - self.chosen.len();
+        let upper_bound = self.num_hit
+            + gains
+                .iter()
+                .take(remaining_budget)
+                .map(|&(gain, _)| gain)
+                .sum::<usize>();
+        if upper_bound <= self.best_num_hit {
+            return;
+        }
+
+        let (_, node) = gains[0];
+        self.add(node);
+        self.search();
+        self.remove(node);
+
+        if self.timed_out
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.timed_out = true;
+            return;
+        }
+        self.num_blockers[node.idx()] += 1;
+        self.search();
+        self.num_blockers[node.idx()] -= 1;
+    }
+}
+
+/// Result of maximizing the number of hit edges within a budget
+#[derive(Debug, Clone)]
+pub struct CoverageResult {
+    pub nodes: Vec<NodeIdx>,
+
+    /// Number of edges hit by `nodes`, counting merged duplicate edges with
+    /// their multiplicity
+    pub num_hit: usize,
+
+    /// Whether the search completed before the deadline
+    pub complete: bool,
+}
+
+/// Finds at most `budget` nodes that hit the maximum number of edges, or the
+/// best nodes found before the deadline.
+pub fn maximize_coverage(
+    instance: &Instance,
+    budget: usize,
+    deadline: Option<Instant>,
+) -> CoverageResult {
+    let mut search = CoverageSearch {
+        instance,
+        budget,
+        deadline,
+        hit_count: vec![0; instance.num_edges_total()],
+        num_blockers: vec![0; instance.num_nodes_total()],
+        chosen: Vec::new(),
+        num_hit: 0,
+        num_total: instance
+            .edges()
+            .iter()
+            .map(|&edge| instance.edge_multiplicity(edge))
+            .sum(),
+        best: Vec::new(),
+        best_num_hit: 0,
+        timed_out: false,
+    };
+    search.search();
+    CoverageResult {
+        nodes: search.best,
+        num_hit: search.best_num_hit,
+        complete: !search.timed_out,
+    }
+}
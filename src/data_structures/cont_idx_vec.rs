@@ -1,4 +1,5 @@
 use crate::{create_idx_struct, small_indices::SmallIdx};
+use anyhow::{ensure, Result};
 use std::{iter::FromIterator, ops::Deref};
 
 create_idx_struct!(DataIdx);
@@ -38,6 +39,27 @@ impl<T: Into<usize> + Copy> ContiguousIdxVec<T> {
         self.len -= 1;
     }
 
+    /// Checks that the indirection table matches the stored indices.
+    pub fn check_invariants(&self) -> Result<()> {
+        ensure!(
+            self.data.len() == self.indices.len() && self.len <= self.data.len(),
+            "inconsistent lengths: {} indices, {} positions, {} not deleted",
+            self.data.len(),
+            self.indices.len(),
+            self.len
+        );
+        for (pos, &id) in self.data.iter().enumerate() {
+            let id = id.into();
+            ensure!(
+                id < self.indices.len() && self.indices[id].idx() == pos,
+                "index {} is stored at position {}, but the indirection table disagrees",
+                id,
+                pos
+            );
+        }
+        Ok(())
+    }
+
     pub fn restore(&mut self, id: usize) {
         debug_assert!(self.is_deleted(id), "Item with id {} is not deleted", id);
         let idx = self.indices[id].idx();
@@ -0,0 +1,274 @@
+use crate::{
+    instance::{EdgeIdx, NodeIdx},
+    small_indices::SmallIdx,
+};
+use anyhow::{ensure, Result};
+use std::{iter::FusedIterator, slice};
+
+/// Deletion time of nodes and edges that are not deleted
+const ALIVE: usize = usize::MAX;
+
+/// Incidences of a hypergraph stored in flat arrays (compressed sparse rows).
+///
+/// Instead of unlinking entries, deleted nodes and edges are stamped with the
+/// time of their deletion. An entry is visible from a node (or edge) if the
+/// edge (or node) on the other side is alive or was deleted later. This
+/// matches the linked incidence lists: deleting a node hides it from the edges
+/// alive at that time, but its own list keeps the edges deleted after it, so
+/// that they can be restored. Iterating skips hidden entries, trading the
+/// pointer chasing of linked lists for scanning contiguous rows.
+///
+/// As with the linked lists, restorations must be done in reverse order of
+/// the corresponding deletions.
+#[derive(Debug, Clone)]
+pub struct CsrIncidences {
+    node_starts: Vec<usize>,
+    node_edges: Vec<EdgeIdx>,
+    edge_starts: Vec<usize>,
+    edge_nodes: Vec<NodeIdx>,
+    node_deleted_at: Vec<usize>,
+    edge_deleted_at: Vec<usize>,
+    node_degrees: Vec<usize>,
+    edge_sizes: Vec<usize>,
+    time: usize,
+}
+
+impl CsrIncidences {
+    /// Builds the incidences from the nodes of all edges, stored contiguously
+    /// with the nodes of edge `i` starting at `edge_starts[i]`. The edge
+    /// arrays are used as they are, only the node rows are computed.
+    pub fn new(num_nodes: usize, edge_starts: Vec<usize>, edge_nodes: Vec<NodeIdx>) -> Self {
+        let num_edges = edge_starts.len() - 1;
+        let mut node_degrees = vec![0; num_nodes];
+        for &node in &edge_nodes {
+            node_degrees[node.idx()] += 1;
+        }
+        let mut node_starts = Vec::with_capacity(num_nodes + 1);
+        node_starts.push(0);
+        for &degree in &node_degrees {
+            node_starts.push(node_starts.last().unwrap() + degree);
+        }
+
+        // Filling the rows in order of the edges keeps them sorted
+        let mut next_entry = node_starts[..num_nodes].to_vec();
+        let mut node_edges = vec![EdgeIdx::INVALID; edge_nodes.len()];
+        for (edge, bounds) in edge_starts.windows(2).enumerate() {
+            for &node in &edge_nodes[bounds[0]..bounds[1]] {
+                node_edges[next_entry[node.idx()]] = EdgeIdx::from(edge);
+                next_entry[node.idx()] += 1;
+            }
+        }
+
+        let edge_sizes = edge_starts
+            .windows(2)
+            .map(|bounds| bounds[1] - bounds[0])
+            .collect();
+        Self {
+            node_starts,
+            node_edges,
+            edge_starts,
+            edge_nodes,
+            node_deleted_at: vec![ALIVE; num_nodes],
+            edge_deleted_at: vec![ALIVE; num_edges],
+            node_degrees,
+            edge_sizes,
+            time: 0,
+        }
+    }
+
+    fn node_row(&self, node: NodeIdx) -> &[EdgeIdx] {
+        &self.node_edges[self.node_starts[node.idx()]..self.node_starts[node.idx() + 1]]
+    }
+
+    fn edge_row(&self, edge: EdgeIdx) -> &[NodeIdx] {
+        &self.edge_nodes[self.edge_starts[edge.idx()]..self.edge_starts[edge.idx() + 1]]
+    }
+
+    fn next_time(&mut self) -> usize {
+        self.time += 1;
+        self.time
+    }
+
+    pub fn num_nodes(&self) -> usize {
+        self.node_degrees.len()
+    }
+
+    pub fn num_edges(&self) -> usize {
+        self.edge_sizes.len()
+    }
+
+    pub fn node(&self, node: NodeIdx) -> Iter<'_, EdgeIdx> {
+        Iter {
+            entries: self.node_row(node).iter(),
+            deleted_at: &self.edge_deleted_at,
+            own_deleted_at: self.node_deleted_at[node.idx()],
+            remaining: self.node_degrees[node.idx()],
+        }
+    }
+
+    pub fn edge(&self, edge: EdgeIdx) -> Iter<'_, NodeIdx> {
+        Iter {
+            entries: self.edge_row(edge).iter(),
+            deleted_at: &self.node_deleted_at,
+            own_deleted_at: self.edge_deleted_at[edge.idx()],
+            remaining: self.edge_sizes[edge.idx()],
+        }
+    }
+
+    pub fn node_degree(&self, node: NodeIdx) -> usize {
+        self.node_degrees[node.idx()]
+    }
+
+    pub fn edge_size(&self, edge: EdgeIdx) -> usize {
+        self.edge_sizes[edge.idx()]
+    }
+
+    /// Checks that the degree and size counters of alive nodes and edges
+    /// match their visible entries.
+    pub fn check_invariants(&self) -> Result<()> {
+        for node in (0..self.num_nodes()).map(NodeIdx::from) {
+            let own_deleted_at = self.node_deleted_at[node.idx()];
+            let visible = self
+                .node_row(node)
+                .iter()
+                .filter(|edge| self.edge_deleted_at[edge.idx()] >= own_deleted_at)
+                .count();
+            ensure!(
+                own_deleted_at != ALIVE || visible == self.node_degrees[node.idx()],
+                "node {} has {} visible edges, but a degree of {}",
+                node,
+                visible,
+                self.node_degrees[node.idx()]
+            );
+        }
+        for edge in (0..self.num_edges()).map(EdgeIdx::from) {
+            let own_deleted_at = self.edge_deleted_at[edge.idx()];
+            let visible = self
+                .edge_row(edge)
+                .iter()
+                .filter(|node| self.node_deleted_at[node.idx()] >= own_deleted_at)
+                .count();
+            ensure!(
+                own_deleted_at != ALIVE || visible == self.edge_sizes[edge.idx()],
+                "edge {} has {} visible nodes, but a size of {}",
+                edge,
+                visible,
+                self.edge_sizes[edge.idx()]
+            );
+        }
+        Ok(())
+    }
+
+    /// Deletes a node, calling `on_resized` with each alive edge containing it
+    /// and the new size of the edge.
+    pub fn delete_node(&mut self, node: NodeIdx, mut on_resized: impl FnMut(EdgeIdx, usize)) {
+        self.node_deleted_at[node.idx()] = self.next_time();
+        let row = self.node_starts[node.idx()]..self.node_starts[node.idx() + 1];
+        for &edge in &self.node_edges[row] {
+            if self.edge_deleted_at[edge.idx()] == ALIVE {
+                self.edge_sizes[edge.idx()] -= 1;
+                on_resized(edge, self.edge_sizes[edge.idx()]);
+            }
+        }
+    }
+
+    pub fn restore_node(&mut self, node: NodeIdx, mut on_resized: impl FnMut(EdgeIdx, usize)) {
+        let row = self.node_starts[node.idx()]..self.node_starts[node.idx() + 1];
+        for &edge in self.node_edges[row].iter().rev() {
+            if self.edge_deleted_at[edge.idx()] == ALIVE {
+                self.edge_sizes[edge.idx()] += 1;
+                on_resized(edge, self.edge_sizes[edge.idx()]);
+            }
+        }
+        self.node_deleted_at[node.idx()] = ALIVE;
+    }
+
+    pub fn delete_edge(&mut self, edge: EdgeIdx) {
+        self.edge_deleted_at[edge.idx()] = self.next_time();
+        let row = self.edge_starts[edge.idx()]..self.edge_starts[edge.idx() + 1];
+        for &node in &self.edge_nodes[row] {
+            if self.node_deleted_at[node.idx()] == ALIVE {
+                self.node_degrees[node.idx()] -= 1;
+            }
+        }
+    }
+
+    pub fn restore_edge(&mut self, edge: EdgeIdx) {
+        let row = self.edge_starts[edge.idx()]..self.edge_starts[edge.idx() + 1];
+        for &node in self.edge_nodes[row].iter().rev() {
+            if self.node_deleted_at[node.idx()] == ALIVE {
+                self.node_degrees[node.idx()] += 1;
+            }
+        }
+        self.edge_deleted_at[edge.idx()] = ALIVE;
+    }
+
+    /// Deletes all alive edges of a deleted node, calling `on_deleted` with
+    /// each of them and its size.
+    pub fn delete_incident_edges(
+        &mut self,
+        node: NodeIdx,
+        mut on_deleted: impl FnMut(EdgeIdx, usize),
+    ) {
+        for entry in self.node_starts[node.idx()]..self.node_starts[node.idx() + 1] {
+            let edge = self.node_edges[entry];
+            if self.edge_deleted_at[edge.idx()] == ALIVE {
+                self.delete_edge(edge);
+                on_deleted(edge, self.edge_sizes[edge.idx()]);
+            }
+        }
+    }
+
+    /// Restores the edges deleted by `delete_incident_edges`, calling
+    /// `on_restored` with each of them and its size.
+    pub fn restore_incident_edges(
+        &mut self,
+        node: NodeIdx,
+        mut on_restored: impl FnMut(EdgeIdx, usize),
+    ) {
+        let node_deleted_at = self.node_deleted_at[node.idx()];
+        for entry in (self.node_starts[node.idx()]..self.node_starts[node.idx() + 1]).rev() {
+            let edge = self.node_edges[entry];
+            let edge_deleted_at = self.edge_deleted_at[edge.idx()];
+            if edge_deleted_at != ALIVE && edge_deleted_at > node_deleted_at {
+                self.restore_edge(edge);
+                on_restored(edge, self.edge_sizes[edge.idx()]);
+            }
+        }
+    }
+}
+
+/// Iterator over the visible entries of a row
+#[derive(Debug, Clone)]
+pub struct Iter<'a, T> {
+    entries: slice::Iter<'a, T>,
+    deleted_at: &'a [usize],
+    own_deleted_at: usize,
+    remaining: usize,
+}
+
+impl<T: SmallIdx> Iterator for Iter<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        // Items are alive or deleted after the owner of the row, see above
+        let item = self
+            .entries
+            .by_ref()
+            .copied()
+            .find(|item| self.deleted_at[item.idx()] >= self.own_deleted_at)?;
+        self.remaining -= 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T: SmallIdx> FusedIterator for Iter<'_, T> {}
+
+impl<T: SmallIdx> ExactSizeIterator for Iter<'_, T> {}
@@ -1,4 +1,6 @@
 pub mod cont_idx_vec;
+pub mod csr;
+pub mod signature;
 pub mod skipvec;
 pub mod subset_trie;
 pub mod superset_trie;
@@ -0,0 +1,15 @@
+use crate::small_indices::SmallIdx;
+
+/// 64-bit Bloom-filter style fingerprint of a set of indices.
+///
+/// If a set is a subset of another, the same holds for their signatures.
+/// Thus, signatures can cheaply rule out containment between two sets.
+pub fn signature<V: SmallIdx>(set: impl IntoIterator<Item = V>) -> u64 {
+    set.into_iter().fold(0, |sig, val| sig | element_bit(val))
+}
+
+fn element_bit<V: SmallIdx>(val: V) -> u64 {
+    // Fibonacci hashing to spread consecutive indices over all bits
+    const MULTIPLIER: u64 = 0x9E37_79B9_7F4A_7C15;
+    1 << ((val.idx() as u64).wrapping_mul(MULTIPLIER) >> 58)
+}
@@ -1,4 +1,8 @@
-use crate::{create_idx_struct, small_indices::SmallIdx};
+use crate::{
+    create_idx_struct,
+    small_indices::{RawIdx, SmallIdx},
+};
+use anyhow::{ensure, Result};
 use std::{
     convert::TryFrom,
     fmt::{self, Debug, Formatter},
@@ -15,13 +19,13 @@ use std::{
 /// accessed, but will be skipped while iterating, and are not accounted for
 /// in `len()`.
 ///
-/// To conserve additional space, all indices are kept as `u32`'s internally.
+/// To conserve additional space, all indices are kept as `RawIdx`'s internally.
 #[derive(Clone)]
 pub struct SkipVec<T> {
     entries: Box<[Entry<T>]>,
     first: EntryIdx,
     last: EntryIdx,
-    len: u32,
+    len: RawIdx,
     #[cfg(feature = "debug-skipvec")]
     deletions: Vec<EntryIdx>,
 }
@@ -41,7 +45,7 @@ pub struct Iter<'a, T> {
     list: &'a SkipVec<T>,
     front: EntryIdx,
     back: EntryIdx,
-    rem_len: u32,
+    rem_len: RawIdx,
 }
 
 /// Mutable iterator over an `SkipVec<T>`.
@@ -50,7 +54,7 @@ pub struct IterMut<'a, T> {
     list: &'a mut SkipVec<T>,
     front: EntryIdx,
     back: EntryIdx,
-    rem_len: u32,
+    rem_len: RawIdx,
 }
 
 create_idx_struct!(EntryIdx);
@@ -68,34 +72,49 @@ impl<T> Entry<T> {
 }
 
 impl<T> SkipVec<T> {
-    #[cfg(feature = "debug-skipvec")]
-    fn check_invariants(&self) {
+    /// Checks that the non-deleted entries form a consistent doubly-linked
+    /// list of length `len()`.
+    pub fn check_invariants(&self) -> Result<()> {
+        let mut prev = EntryIdx::INVALID;
         let mut idx = self.first;
+        let mut count = 0;
         while idx.valid() {
-            let next = self.entries[idx.idx()].next;
-            if next.valid() {
-                let prev_of_next = self.entries[next.idx()].prev;
-                debug_assert_eq!(
-                    idx, prev_of_next,
-                    "Invariant violated: next of {} is {}, but prev of {} is {}",
-                    idx, next, next, prev_of_next
-                );
-            }
-            idx = next;
-        }
-        if self.first.valid() {
-            debug_assert_eq!(
-                self.entries[self.first.idx()].prev,
-                EntryIdx::INVALID,
-                "Invariant violated: prev of first is not invalid",
+            ensure!(
+                count < self.len(),
+                "more than {} entries are linked",
+                self.len()
             );
-        }
-        if self.last.valid() {
-            debug_assert_eq!(
-                self.entries[self.last.idx()].next,
-                EntryIdx::INVALID,
-                "Invariant violated: next of last is not invalid",
+            let entry = &self.entries[idx.idx()];
+            ensure!(
+                entry.prev == prev,
+                "prev of {} is {}, but it follows {}",
+                idx,
+                entry.prev,
+                prev
             );
+            prev = idx;
+            idx = entry.next;
+            count += 1;
+        }
+        ensure!(
+            prev == self.last,
+            "last is {}, but the list ends at {}",
+            self.last,
+            prev
+        );
+        ensure!(
+            count == self.len(),
+            "{} entries are linked, but the length is {}",
+            count,
+            self.len()
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "debug-skipvec")]
+    fn assert_invariants(&self) {
+        if let Err(err) = self.check_invariants() {
+            panic!("Invariant violated: {:#}", err);
         }
     }
 
@@ -108,10 +127,10 @@ impl<T> SkipVec<T> {
             entry.next = EntryIdx::INVALID;
         }
         debug_assert!(
-            u32::try_from(vec.len()).is_ok(),
-            "SkipVec size must fit a u32"
+            RawIdx::try_from(vec.len()).is_ok(),
+            "SkipVec size must fit a RawIdx"
         );
-        let len = vec.len() as u32;
+        let len = vec.len() as RawIdx;
         let (first, last) = if vec.is_empty() {
             (EntryIdx::INVALID, EntryIdx::INVALID)
         } else {
@@ -127,7 +146,7 @@ impl<T> SkipVec<T> {
             deletions: vec![],
         };
         #[cfg(feature = "debug-skipvec")]
-        instance.check_invariants();
+        instance.assert_invariants();
         instance
     }
 
@@ -163,6 +182,10 @@ impl<T> SkipVec<T> {
         self.len as usize
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
     pub fn first(&self) -> Option<usize> {
         self.first.idx_if_valid()
     }
@@ -207,7 +230,7 @@ impl<T> SkipVec<T> {
         #[cfg(feature = "debug-skipvec")]
         {
             self.deletions.push(EntryIdx::from(index));
-            self.check_invariants();
+            self.assert_invariants();
         }
     }
 
@@ -252,7 +275,7 @@ impl<T> SkipVec<T> {
         }
         #[cfg(feature = "debug-skipvec")]
         {
-            self.check_invariants();
+            self.assert_invariants();
         }
     }
 }
@@ -350,7 +373,7 @@ impl<'a, T> Iterator for Iter<'a, T> {
     }
 }
 
-impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+impl<T> DoubleEndedIterator for Iter<'_, T> {
     fn next_back(&mut self) -> Option<Self::Item> {
         if !self.front.valid() {
             return None;
@@ -367,9 +390,9 @@ impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
     }
 }
 
-impl<'a, T> FusedIterator for Iter<'a, T> {}
+impl<T> FusedIterator for Iter<'_, T> {}
 
-impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+impl<T> ExactSizeIterator for Iter<'_, T> {}
 
 impl<'a, T> Iterator for IterMut<'a, T> {
     type Item = (usize, &'a mut T);
@@ -395,7 +418,7 @@ impl<'a, T> Iterator for IterMut<'a, T> {
     }
 }
 
-impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
     fn next_back(&mut self) -> Option<Self::Item> {
         if !self.front.valid() {
             return None;
@@ -406,13 +429,13 @@ impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
             self.front = EntryIdx::INVALID;
         } else {
             self.back = entry.prev;
-        };
+        }
         self.rem_len -= 1;
         // Unsafe reborrow to get 'a lifetime
         Some((index, unsafe { &mut *ptr::addr_of_mut!(entry.value) }))
     }
 }
 
-impl<'a, T> FusedIterator for IterMut<'a, T> {}
+impl<T> FusedIterator for IterMut<'_, T> {}
 
-impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
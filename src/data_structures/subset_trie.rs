@@ -1,105 +1,279 @@
 use crate::{
     create_idx_struct,
+    data_structures::signature::signature,
     small_indices::{IdxHashMap, SmallIdx},
 };
 use std::collections::hash_map::Entry;
 
-create_idx_struct!(TrieNodeIdx);
+create_idx_struct!(pub TrieNodeIdx);
+create_idx_struct!(ChunkIdx);
 
-#[derive(Debug)]
-enum SubsetTrieChildren<I> {
-    Small(usize, Vec<TrieNodeIdx>),
-    Large(Vec<IdxHashMap<I, TrieNodeIdx>>),
+/// Number of consecutive values whose children are stored together, which is
+/// also the largest value range for which children are stored as flat arrays.
+const CHUNK_SIZE: usize = 32;
+
+/// Default for the largest value range for which children are stored in
+/// chunks, see `examples/subset_trie.rs` for how it was chosen.
+pub const DEFAULT_CHUNKED_MAX_VAL_RANGE: usize = 256;
+
+/// How the children of trie nodes are stored, depending on the value range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Layout {
+    /// An array of `val_range` children per trie node, indexed by the value
+    Flat(usize),
+    /// For each of the given number of chunks of `CHUNK_SIZE` consecutive
+    /// values, either no chunk or a flat array of children per trie node
+    Chunked(usize),
+    /// A hash map per trie node
+    Map,
 }
 
-impl<V: SmallIdx> SubsetTrieChildren<V> {
-    fn new(val_range: usize) -> Self {
-        if val_range <= 32 {
-            Self::Small(val_range, vec![TrieNodeIdx::INVALID; val_range])
+impl Layout {
+    fn new(val_range: usize, chunked_max_val_range: usize) -> Self {
+        if val_range <= CHUNK_SIZE {
+            Self::Flat(val_range)
+        } else if val_range <= chunked_max_val_range {
+            Self::Chunked(val_range.div_ceil(CHUNK_SIZE))
         } else {
-            Self::Large(vec![IdxHashMap::default()])
+            Self::Map
         }
     }
+}
+
+/// Storage of a `SubsetTrie`, kept between uses of the trie.
+///
+/// Building a trie allocates per trie node. Resetting the arena instead of
+/// dropping it keeps all these allocations around for the next trie, which
+/// matters for the domination rules building a fresh trie on every `reduce`
+/// call.
+#[derive(Debug)]
+pub struct SubsetTrieArena<V, M> {
+    /// Flat arrays of children, or chunks of them
+    flat: Vec<TrieNodeIdx>,
+    chunks: Vec<ChunkIdx>,
+    /// Children of each trie node. Maps past `num_maps` are cleared spares
+    /// from previous uses.
+    maps: Vec<IdxHashMap<V, TrieNodeIdx>>,
+    num_maps: usize,
+    markers: Vec<M>,
+    /// Intersection of the signatures of all sets below each trie node.
+    /// Clearing the marker of a set does not widen these, which only makes the
+    /// pruning less effective.
+    signatures: Vec<u64>,
+    /// Layout of the trie last created on this arena
+    layout: Option<Layout>,
+}
 
-    fn get(&self, node: TrieNodeIdx, edge_val: V) -> TrieNodeIdx {
-        match *self {
-            Self::Small(val_range, ref flat) => flat[node.idx() * val_range + edge_val.idx()],
-            Self::Large(ref maps) => maps[node.idx()].get(&edge_val).copied().unwrap_or_default(),
+impl<V, M> Default for SubsetTrieArena<V, M> {
+    fn default() -> Self {
+        Self {
+            flat: Vec::new(),
+            chunks: Vec::new(),
+            maps: Vec::new(),
+            num_maps: 0,
+            markers: Vec::new(),
+            signatures: Vec::new(),
+            layout: None,
         }
     }
+}
 
-    fn get_or_insert(&mut self, node: TrieNodeIdx, edge_val: V) -> (TrieNodeIdx, bool) {
-        match *self {
-            Self::Small(val_range, ref mut flat) => {
-                let idx = node.idx() * val_range + edge_val.idx();
-                if flat[idx].valid() {
-                    (flat[idx], false)
-                } else {
-                    flat[idx] = TrieNodeIdx::from(flat.len() / val_range);
-                    flat.resize(flat.len() + val_range, TrieNodeIdx::INVALID);
-                    (flat[idx], true)
-                }
+impl<V: SmallIdx, M: Copy + Default> SubsetTrieArena<V, M> {
+    /// Removes all trie nodes except for an empty root.
+    fn reset(&mut self, layout: Layout) {
+        self.flat.clear();
+        self.chunks.clear();
+        for map in &mut self.maps[..self.num_maps] {
+            map.clear();
+        }
+        self.num_maps = 0;
+        self.markers.clear();
+        self.signatures.clear();
+        self.layout = Some(layout);
+        self.push_node(layout);
+    }
+
+    fn push_node(&mut self, layout: Layout) -> TrieNodeIdx {
+        let idx = TrieNodeIdx::from(self.markers.len());
+        match layout {
+            Layout::Flat(val_range) => {
+                self.flat
+                    .resize(self.flat.len() + val_range, TrieNodeIdx::INVALID);
+            }
+            Layout::Chunked(num_chunks) => {
+                self.chunks
+                    .resize(self.chunks.len() + num_chunks, ChunkIdx::INVALID);
             }
-            Self::Large(ref mut maps) => {
-                let new_node_idx = TrieNodeIdx::from(maps.len());
-                match maps[node.idx()].entry(edge_val) {
-                    Entry::Occupied(occupied) => (*occupied.get(), false),
-                    Entry::Vacant(vacant) => {
-                        vacant.insert(new_node_idx);
-                        maps.push(IdxHashMap::default());
-                        (new_node_idx, true)
-                    }
+            Layout::Map => {
+                if self.num_maps == self.maps.len() {
+                    self.maps.push(IdxHashMap::default());
                 }
+                self.num_maps += 1;
             }
         }
+        self.markers.push(M::default());
+        self.signatures.push(u64::MAX);
+        idx
     }
 }
 
 #[derive(Debug)]
-pub struct SubsetTrie<V, M, I> {
-    children: SubsetTrieChildren<V>,
-    markers: Vec<M>,
+pub struct SubsetTrie<'a, V, M, I> {
+    arena: &'a mut SubsetTrieArena<V, M>,
+    layout: Layout,
     stack: Vec<(TrieNodeIdx, I)>,
 }
 
-impl<V, M, I> SubsetTrie<V, M, I>
+impl<'a, V, M, I> SubsetTrie<'a, V, M, I>
 where
     V: SmallIdx,
     M: Copy + Default + Eq,
     I: Iterator<Item = V> + Clone,
 {
-    pub fn new(val_range: usize) -> Self {
+    /// Creates an empty trie, discarding any sets previously stored in the
+    /// arena.
+    ///
+    /// Children are stored in flat arrays for tiny value ranges, in chunks of
+    /// flat arrays for value ranges up to `chunked_max_val_range`, and in hash
+    /// maps beyond that.
+    pub fn new(
+        val_range: usize,
+        chunked_max_val_range: usize,
+        arena: &'a mut SubsetTrieArena<V, M>,
+    ) -> Self {
+        let layout = Layout::new(val_range, chunked_max_val_range);
+        arena.reset(layout);
         Self {
-            children: SubsetTrieChildren::new(val_range),
-            markers: vec![M::default(); 1],
+            arena,
+            layout,
             stack: Vec::with_capacity(val_range),
         }
     }
 
-    pub fn insert(&mut self, marker: M, set: impl IntoIterator<Item = V>) {
+    /// Continues with the sets stored in the arena by the last trie using it.
+    ///
+    /// The arena must have been used by a trie created with `new` before.
+    pub fn open(val_range: usize, arena: &'a mut SubsetTrieArena<V, M>) -> Self {
+        let layout = arena.layout.expect("Opening an unused arena");
+        Self {
+            arena,
+            layout,
+            stack: Vec::with_capacity(val_range),
+        }
+    }
+
+    fn child(&self, node: TrieNodeIdx, edge_val: V) -> TrieNodeIdx {
+        match self.layout {
+            Layout::Flat(val_range) => self.arena.flat[node.idx() * val_range + edge_val.idx()],
+            Layout::Chunked(num_chunks) => {
+                let chunk =
+                    self.arena.chunks[node.idx() * num_chunks + edge_val.idx() / CHUNK_SIZE];
+                if chunk.valid() {
+                    self.arena.flat[chunk.idx() * CHUNK_SIZE + edge_val.idx() % CHUNK_SIZE]
+                } else {
+                    TrieNodeIdx::INVALID
+                }
+            }
+            Layout::Map => self.arena.maps[node.idx()]
+                .get(&edge_val)
+                .copied()
+                .unwrap_or_default(),
+        }
+    }
+
+    fn child_or_insert(&mut self, node: TrieNodeIdx, edge_val: V) -> TrieNodeIdx {
+        let layout = self.layout;
+        let arena = &mut *self.arena;
+        let new_node_idx = TrieNodeIdx::from(arena.markers.len());
+        match layout {
+            Layout::Flat(val_range) => {
+                let idx = node.idx() * val_range + edge_val.idx();
+                if arena.flat[idx].valid() {
+                    return arena.flat[idx];
+                }
+                arena.flat[idx] = new_node_idx;
+            }
+            Layout::Chunked(num_chunks) => {
+                let chunk =
+                    &mut arena.chunks[node.idx() * num_chunks + edge_val.idx() / CHUNK_SIZE];
+                if !chunk.valid() {
+                    *chunk = ChunkIdx::from(arena.flat.len() / CHUNK_SIZE);
+                    arena
+                        .flat
+                        .resize(arena.flat.len() + CHUNK_SIZE, TrieNodeIdx::INVALID);
+                }
+                let idx = chunk.idx() * CHUNK_SIZE + edge_val.idx() % CHUNK_SIZE;
+                if arena.flat[idx].valid() {
+                    return arena.flat[idx];
+                }
+                arena.flat[idx] = new_node_idx;
+            }
+            Layout::Map => match arena.maps[node.idx()].entry(edge_val) {
+                Entry::Occupied(occupied) => return *occupied.get(),
+                Entry::Vacant(vacant) => {
+                    vacant.insert(new_node_idx);
+                }
+            },
+        }
+        arena.push_node(layout)
+    }
+
+    pub fn insert<S>(&mut self, marker: M, set: S)
+    where
+        S: IntoIterator<Item = V>,
+        S::IntoIter: Clone,
+    {
+        let idx = self.insert_path(set);
+        self.arena.markers[idx.idx()] = marker;
+    }
+
+    /// Adds the trie nodes for a set without changing any marker and returns
+    /// the trie node the set ends at, see `marker_mut`.
+    pub fn insert_path<S>(&mut self, set: S) -> TrieNodeIdx
+    where
+        S: IntoIterator<Item = V>,
+        S::IntoIter: Clone,
+    {
+        let set = set.into_iter();
+        let sig = signature(set.clone());
         let mut idx = TrieNodeIdx(0);
+        self.arena.signatures[0] &= sig;
         for edge_val in set {
-            let (new_idx, inserted) = self.children.get_or_insert(idx, edge_val);
-            if inserted {
-                self.markers.push(M::default());
-            }
-            idx = new_idx;
+            idx = self.child_or_insert(idx, edge_val);
+            self.arena.signatures[idx.idx()] &= sig;
         }
-        self.markers[idx.idx()] = marker;
+        idx
+    }
+
+    /// Marker of the sets ending at a trie node. Setting it to the default
+    /// marker removes them from the trie.
+    pub fn marker_mut(&mut self, node: TrieNodeIdx) -> &mut M {
+        &mut self.arena.markers[node.idx()]
     }
 
     pub fn find_subset(&mut self, iter: impl IntoIterator<IntoIter = I>) -> M {
         debug_assert!(self.stack.is_empty());
-        self.stack.push((TrieNodeIdx(0), iter.into_iter()));
+        let iter = iter.into_iter();
+
+        // Any set in a subtree whose signature intersection contains a bit
+        // not in the signature of the query has an element not in the query
+        let query_sig = signature(iter.clone());
+        let signatures = &self.arena.signatures;
+        let can_contain_subset = |node: TrieNodeIdx| signatures[node.idx()] & !query_sig == 0;
+        if !can_contain_subset(TrieNodeIdx(0)) {
+            return M::default();
+        }
+
+        self.stack.push((TrieNodeIdx(0), iter));
         while let Some((node, mut iter)) = self.stack.pop() {
-            if self.markers[node.idx()] != M::default() {
+            if self.arena.markers[node.idx()] != M::default() {
                 self.stack.clear();
-                return self.markers[node.idx()];
+                return self.arena.markers[node.idx()];
             }
 
             while let Some(edge_val) = iter.next() {
-                let next_node = self.children.get(node, edge_val);
-                if next_node.valid() {
+                let next_node = self.child(node, edge_val);
+                if next_node.valid() && can_contain_subset(next_node) {
                     let iter_clone = iter.clone();
                     self.stack.push((node, iter));
                     self.stack.push((next_node, iter_clone));
@@ -1,65 +1,178 @@
-use crate::{create_idx_struct, small_indices::SmallIdx};
-use std::{
-    collections::{btree_map::Range as BTreeMapRange, BTreeMap},
-    iter::Peekable,
-    mem,
-    ops::Bound,
-};
-
-create_idx_struct!(TrieNodeIdx);
-
-pub struct SupersetTrie<V: 'static, I: Iterator> {
-    children: Vec<BTreeMap<V, TrieNodeIdx>>,
-    is_set: Vec<bool>,
-    stack: Vec<(
-        TrieNodeIdx,
-        Peekable<I>,
-        BTreeMapRange<'static, V, TrieNodeIdx>,
-    )>,
+use crate::{create_idx_struct, data_structures::signature::signature, small_indices::SmallIdx};
+use std::{iter::Peekable, mem, slice};
+
+create_idx_struct!(pub TrieNodeIdx);
+
+/// Storage of a `SupersetTrie`, kept between uses of the trie.
+///
+/// The children of each trie node are a sorted list. As with
+/// `SubsetTrieArena`, resetting the arena clears these lists but keeps their
+/// allocations for the next trie.
+#[derive(Debug)]
+pub struct SupersetTrieArena<V> {
+    /// Children of each trie node. Lists past `parents.len()` are cleared
+    /// spares from previous uses.
+    children: Vec<Vec<(V, TrieNodeIdx)>>,
+    parents: Vec<TrieNodeIdx>,
+    /// Number of sets ending at or below each trie node
+    num_sets: Vec<u32>,
+    /// Union of the signatures of all sets below each trie node. Removing a
+    /// set does not shrink these, which only makes the pruning less effective.
+    signatures: Vec<u64>,
 }
 
-impl<V, I> SupersetTrie<V, I>
+impl<V> Default for SupersetTrieArena<V> {
+    fn default() -> Self {
+        Self {
+            children: Vec::new(),
+            parents: Vec::new(),
+            num_sets: Vec::new(),
+            signatures: Vec::new(),
+        }
+    }
+}
+
+impl<V: SmallIdx> SupersetTrieArena<V> {
+    /// Removes all trie nodes except for an empty root.
+    fn reset(&mut self) {
+        for children in &mut self.children[..self.parents.len()] {
+            children.clear();
+        }
+        self.parents.clear();
+        self.num_sets.clear();
+        self.signatures.clear();
+        self.push_node(TrieNodeIdx::INVALID);
+    }
+
+    fn push_node(&mut self, parent: TrieNodeIdx) -> TrieNodeIdx {
+        let idx = TrieNodeIdx::from(self.parents.len());
+        if self.children.len() == idx.idx() {
+            self.children.push(Vec::new());
+        }
+        self.parents.push(parent);
+        self.num_sets.push(0);
+        self.signatures.push(0);
+        idx
+    }
+
+    /// Children of a trie node with values greater than `after` (if given) and
+    /// at most `to`.
+    fn range(
+        &self,
+        node: TrieNodeIdx,
+        after: Option<V>,
+        to: V,
+    ) -> slice::Iter<'_, (V, TrieNodeIdx)> {
+        let children = &self.children[node.idx()];
+        let start = after.map_or(0, |after| {
+            children.partition_point(|&(val, _)| val <= after)
+        });
+        let end = children.partition_point(|&(val, _)| val <= to);
+        children[start..end].iter()
+    }
+}
+
+/// Trie node, remaining query items, and children of the trie node yet to be
+/// visited
+type StackEntry<'a, V, I> = (TrieNodeIdx, Peekable<I>, slice::Iter<'a, (V, TrieNodeIdx)>);
+
+pub struct SupersetTrie<'a, V: 'static, I: Iterator> {
+    arena: &'a mut SupersetTrieArena<V>,
+    stack: Vec<StackEntry<'static, V, I>>,
+}
+
+impl<'a, V, I> SupersetTrie<'a, V, I>
 where
     V: SmallIdx,
     I: Iterator<Item = V> + Clone,
 {
-    pub fn new(val_range: usize) -> Self {
+    /// Creates an empty trie, discarding any sets previously stored in the
+    /// arena.
+    pub fn new(val_range: usize, arena: &'a mut SupersetTrieArena<V>) -> Self {
+        arena.reset();
+        Self {
+            arena,
+            stack: Vec::with_capacity(val_range),
+        }
+    }
+
+    /// Continues with the sets stored in the arena by the last trie using it.
+    ///
+    /// The arena must have been used by a trie created with `new` before.
+    pub fn open(val_range: usize, arena: &'a mut SupersetTrieArena<V>) -> Self {
+        debug_assert!(!arena.parents.is_empty(), "Opening an unused arena");
         Self {
-            children: vec![BTreeMap::new()],
-            is_set: vec![false],
+            arena,
             stack: Vec::with_capacity(val_range),
         }
     }
 
-    pub fn insert(&mut self, iter: impl IntoIterator<Item = V>) {
+    /// Adds a set to the trie and returns the trie node it ends at, which is
+    /// needed to remove it again.
+    pub fn insert<S>(&mut self, iter: S) -> TrieNodeIdx
+    where
+        S: IntoIterator<Item = V>,
+        S::IntoIter: Clone,
+    {
+        let iter = iter.into_iter();
+        let sig = signature(iter.clone());
+        let arena = &mut *self.arena;
         let mut idx = TrieNodeIdx(0);
+        arena.num_sets[0] += 1;
+        arena.signatures[0] |= sig;
         for item in iter {
-            let new_node_idx = TrieNodeIdx::from(self.children.len());
-            idx = *self.children[idx.idx()].entry(item).or_insert(new_node_idx);
-            if idx == new_node_idx {
-                self.children.push(BTreeMap::new());
-                self.is_set.push(false);
-            }
+            let children = &arena.children[idx.idx()];
+            let pos = children.partition_point(|&(val, _)| val < item);
+            idx = match children.get(pos) {
+                Some(&(val, child)) if val == item => child,
+                _ => {
+                    let child = arena.push_node(idx);
+                    arena.children[idx.idx()].insert(pos, (item, child));
+                    child
+                }
+            };
+            arena.num_sets[idx.idx()] += 1;
+            arena.signatures[idx.idx()] |= sig;
+        }
+        idx
+    }
+
+    /// Removes a set given by the trie node returned when inserting it.
+    ///
+    /// The trie nodes of the set are kept, even if no other set uses them.
+    pub fn remove(&mut self, mut idx: TrieNodeIdx) {
+        while idx.valid() {
+            self.arena.num_sets[idx.idx()] -= 1;
+            idx = self.arena.parents[idx.idx()];
         }
-        self.is_set[idx.idx()] = true;
     }
 
-    fn contains_superset_with_stack<'a>(
-        &'a self,
+    fn contains_superset_with_stack<'b>(
+        arena: &'b SupersetTrieArena<V>,
         set: I,
-        stack: &mut Vec<(TrieNodeIdx, Peekable<I>, BTreeMapRange<'a, V, TrieNodeIdx>)>,
+        stack: &mut Vec<StackEntry<'b, V, I>>,
     ) -> bool {
-        let edge_val_zero = V::from(0_u32);
+        // A subtree whose signature union misses a bit of the query signature
+        // cannot contain a superset of the query, and neither can one whose
+        // sets were all removed
+        let query_sig = signature(set.clone());
+        let can_contain_superset = |node: TrieNodeIdx| {
+            arena.num_sets[node.idx()] > 0 && query_sig & !arena.signatures[node.idx()] == 0
+        };
+        if !can_contain_superset(TrieNodeIdx(0)) {
+            return false;
+        }
+
         let mut iter = set.peekable();
         if let Some(&first_val) = iter.peek() {
             stack.push((
                 TrieNodeIdx(0),
                 iter,
-                self.children[0].range(edge_val_zero..=first_val),
+                arena.range(TrieNodeIdx(0), None, first_val),
             ));
         } else {
-            // Any non-empty trie contains a leaf.
-            return self.children.len() > 1;
+            // Any set is a superset of the empty set
+            return true;
         }
 
         while let Some((node, mut iter, mut range)) = stack.pop() {
@@ -69,24 +182,25 @@ where
 
             // Iterate the range backwards, so that if we have a match for the
             // next item from the set, we process it first.
-            if let Some((&edge_val, &next_node)) = range.next_back() {
+            if let Some(&(edge_val, next_node)) = range.next_back() {
                 stack.push((node, iter.clone(), range));
+                if !can_contain_superset(next_node) {
+                    continue;
+                }
+
                 if edge_val == val_to_match {
                     iter.next();
                     if let Some(&next_val_to_match) = iter.peek() {
-                        let next_range = self.children[next_node.idx()].range((
-                            Bound::Excluded(val_to_match),
-                            Bound::Included(next_val_to_match),
-                        ));
+                        let next_range =
+                            arena.range(next_node, Some(val_to_match), next_val_to_match);
                         stack.push((next_node, iter, next_range));
                     } else {
-                        // We would have moved below the root, so the trie is non-empty and there
-                        // is a leaf below
+                        // All items matched, and some set ends at or below
+                        // the node, which is a superset of the query
                         return true;
                     }
                 } else {
-                    let next_range = self.children[next_node.idx()]
-                        .range((Bound::Excluded(edge_val), Bound::Included(val_to_match)));
+                    let next_range = arena.range(next_node, Some(edge_val), val_to_match);
                     stack.push((next_node, iter, next_range));
                 }
             }
@@ -97,7 +211,7 @@ where
 
     pub fn contains_superset(&mut self, set: impl IntoIterator<IntoIter = I>) -> bool {
         let mut stack = mem::take(&mut self.stack);
-        let result = self.contains_superset_with_stack(set.into_iter(), &mut stack);
+        let result = Self::contains_superset_with_stack(self.arena, set.into_iter(), &mut stack);
 
         stack.clear();
         let ptr = stack.as_mut_ptr();
@@ -0,0 +1,106 @@
+//! Quick estimate of how hard an instance is to solve, computed from the root
+//! bounds and a few statistics of the hypergraph before the search starts.
+use crate::{
+    instance::Instance,
+    report::{DifficultyEstimate, RootBounds},
+    small_indices::SmallIdx,
+};
+
+/// Increase of the score per unit of root gap and per node of an edge beyond
+/// the first. Fitted to the branching steps needed by the default settings on
+/// random hypergraphs, which grow exponentially in the root gap, and the
+/// faster the larger the edges are.
+const GAP_EDGE_SIZE_FACTOR: f64 = 0.09;
+
+/// Increase of the score per unit of root gap, reduced by hubs of large
+/// degree, which make branching more effective
+const GAP_SKEW_FACTOR: f64 = 0.03;
+const GAP_SKEW_OFFSET: f64 = 3.0;
+
+/// Margin added to the score for the recommended time limit, covering about
+/// two standard deviations of the estimate
+const SCORE_MARGIN: f64 = 0.75;
+
+/// Runtime of a branching step in seconds is at most about this factor times the
+/// number of incidences to the power of `STEP_TIME_EXPONENT`
+const STEP_TIME_FACTOR: f64 = 2e-5;
+const STEP_TIME_EXPONENT: f64 = 0.4;
+
+/// Smallest recommended time limit in seconds
+const MIN_TIME_LIMIT: f64 = 1.0;
+
+/// Computes the difficulty estimate from the bounds computed at the root.
+#[allow(clippy::cast_precision_loss)]
+pub fn estimate_difficulty(instance: &Instance, root_bounds: &RootBounds) -> DifficultyEstimate {
+    let num_nodes = instance.nodes().len();
+    let num_edges = instance.num_edges();
+    let num_incidences: usize = instance
+        .nodes()
+        .iter()
+        .map(|&node| instance.node_degree(node))
+        .sum();
+    let max_degree = instance
+        .nodes()
+        .iter()
+        .map(|&node| instance.node_degree(node))
+        .max()
+        .unwrap_or(0);
+
+    let mut seen = vec![false; instance.num_nodes_total()];
+    let mut largest_component_nodes = 0;
+    let mut largest_component_edges = 0;
+    let components = instance.edge_components();
+    for edges in &components {
+        let mut component_nodes = 0;
+        for &edge in edges {
+            for node in instance.edge(edge) {
+                if !seen[node.idx()] {
+                    seen[node.idx()] = true;
+                    component_nodes += 1;
+                }
+            }
+        }
+        largest_component_nodes = largest_component_nodes.max(component_nodes);
+        largest_component_edges = largest_component_edges.max(edges.len());
+    }
+
+    let lower_bound = root_bounds.best_lower_bound();
+    let upper_bound = root_bounds.greedy_upper;
+    let root_gap = upper_bound.saturating_sub(lower_bound);
+    let density = if num_nodes == 0 || num_edges == 0 {
+        0.0
+    } else {
+        num_incidences as f64 / (num_nodes as f64 * num_edges as f64)
+    };
+    let degree_skew = if num_incidences == 0 {
+        0.0
+    } else {
+        max_degree as f64 * num_nodes as f64 / num_incidences as f64
+    };
+
+    let average_edge_size = if num_edges == 0 {
+        0.0
+    } else {
+        num_incidences as f64 / num_edges as f64
+    };
+
+    let gap_factor = GAP_EDGE_SIZE_FACTOR * (average_edge_size - 1.0).max(0.0)
+        + GAP_SKEW_FACTOR * (GAP_SKEW_OFFSET - degree_skew);
+    let score = (root_gap as f64 * gap_factor).max(0.0);
+    let step_time = STEP_TIME_FACTOR * (num_incidences as f64).powf(STEP_TIME_EXPONENT);
+    let recommended_time_limit =
+        (10_f64.powf(score + SCORE_MARGIN) * step_time).max(MIN_TIME_LIMIT);
+    DifficultyEstimate {
+        root_lower_bound: lower_bound,
+        root_upper_bound: upper_bound,
+        root_gap,
+        density,
+        average_edge_size,
+        components: components.len(),
+        largest_component_nodes,
+        largest_component_edges,
+        degree_skew,
+        score,
+        recommended_time_limit,
+    }
+}
@@ -0,0 +1,158 @@
+use crate::{
+    instance::{EdgeIdx, Instance, NodeIdx},
+    small_indices::SmallIdx,
+};
+use anyhow::{ensure, Result};
+
+/// Enumerates all inclusion-minimal hitting sets using the MMCS algorithm by
+/// Murakami and Uno.
+///
+/// The current set is extended by nodes of an unhit edge with the fewest
+/// candidate nodes. A node is only added if every node of the extended set
+/// still has a critical edge, i.e., an edge hit by no other node of the set.
+/// This guarantees that every set is only output once and is minimal.
+struct Enumerator<'a, F> {
+    instance: &'a Instance,
+    max_size: usize,
+    output: F,
+    num_found: usize,
+
+    hs: Vec<NodeIdx>,
+    in_hs: Vec<bool>,
+    is_candidate: Vec<bool>,
+    hit_count: Vec<usize>,
+    num_unhit: usize,
+
+    /// For nodes in the set, the number of edges hit by this node only
+    num_critical: Vec<usize>,
+}
+
+impl<F: FnMut(&[NodeIdx]) -> Result<()>> Enumerator<'_, F> {
+    fn choose_edge(&self) -> EdgeIdx {
+        self.instance
+            .edges()
+            .iter()
+            .copied()
+            .filter(|edge| self.hit_count[edge.idx()] == 0)
+            .min_by_key(|&edge| {
+                self.instance
+                    .edge(edge)
+                    .filter(|node| self.is_candidate[node.idx()])
+                    .count()
+            })
+            .expect("No unhit edge left")
+    }
+
+    fn other_node_in_hs(&self, edge: EdgeIdx, node: NodeIdx) -> NodeIdx {
+        self.instance
+            .edge(edge)
+            .find(|&other| other != node && self.in_hs[other.idx()])
+            .expect("Edge has no other node in the set")
+    }
+
+    /// Adds a node to the set and returns whether the set is still minimal.
+    fn add(&mut self, node: NodeIdx) -> bool {
+        self.in_hs[node.idx()] = true;
+        self.hs.push(node);
+
+        let mut minimal = true;
+        for edge in self.instance.node(node) {
+            self.hit_count[edge.idx()] += 1;
+            match self.hit_count[edge.idx()] {
+                1 => {
+                    self.num_unhit -= 1;
+                    self.num_critical[node.idx()] += 1;
+                }
+                2 => {
+                    let other = self.other_node_in_hs(edge, node);
+                    self.num_critical[other.idx()] -= 1;
+                    minimal &= self.num_critical[other.idx()] > 0;
+                }
+                _ => {}
+            }
+        }
+
+        minimal && self.num_critical[node.idx()] > 0
+    }
+
+    /// Removes the node that was added last.
+    fn remove(&mut self, node: NodeIdx) {
+        debug_assert_eq!(self.hs.last().copied(), Some(node));
+        self.in_hs[node.idx()] = false;
+        self.hs.pop();
+
+        for edge in self.instance.node(node) {
+            self.hit_count[edge.idx()] -= 1;
+            match self.hit_count[edge.idx()] {
+                0 => self.num_unhit += 1,
+                1 => {
+                    let other = self.other_node_in_hs(edge, node);
+                    self.num_critical[other.idx()] += 1;
+                }
+                _ => {}
+            }
+        }
+        self.num_critical[node.idx()] = 0;
+    }
+
+    fn enumerate(&mut self) -> Result<()> {
+        if self.num_unhit == 0 {
+            self.num_found += 1;
+            return (self.output)(&self.hs);
+        }
+        if self.hs.len() >= self.max_size {
+            return Ok(());
+        }
+
+        let edge = self.choose_edge();
+        let candidates: Vec<_> = self
+            .instance
+            .edge(edge)
+            .filter(|node| self.is_candidate[node.idx()])
+            .collect();
+        for &node in &candidates {
+            self.is_candidate[node.idx()] = false;
+        }
+        for node in candidates {
+            if self.add(node) {
+                self.enumerate()?;
+            }
+            self.remove(node);
+            self.is_candidate[node.idx()] = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Calls `output` for every inclusion-minimal hitting set with at most
+/// `max_size` nodes and returns their number. Coverage requirements are not
+/// supported.
+pub fn enumerate_minimal_hitting_sets(
+    instance: &Instance,
+    max_size: Option<usize>,
+    output: impl FnMut(&[NodeIdx]) -> Result<()>,
+) -> Result<usize> {
+    ensure!(
+        !instance.has_requirements(),
+        "enumerating minimal hitting sets does not support coverage requirements"
+    );
+    let mut is_candidate = vec![false; instance.num_nodes_total()];
+    for &node in instance.nodes() {
+        is_candidate[node.idx()] = true;
+    }
+    let mut enumerator = Enumerator {
+        instance,
+        max_size: max_size.unwrap_or(usize::MAX),
+        output,
+        num_found: 0,
+        hs: Vec::new(),
+        in_hs: vec![false; instance.num_nodes_total()],
+        is_candidate,
+        hit_count: vec![0; instance.num_edges_total()],
+        num_unhit: instance.num_edges(),
+        num_critical: vec![0; instance.num_nodes_total()],
+    };
+    enumerator.enumerate()?;
+    Ok(enumerator.num_found)
+}
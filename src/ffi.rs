@@ -0,0 +1,176 @@
+//! C ABI for embedding the solver into other programs.
+//!
+//! Instances are loaded from the json hypergraph format and solved with
+//! settings given as json, just like the settings file of the `solve`
+//! subcommand. All functions returning a pointer return null on failure, in
+//! which case `findminhs_last_error` describes the problem. Panics are caught
+//! at the boundary and reported the same way.
+//!
+//! All pointers passed to these functions must either be null or be valid:
+//! strings must be nul-terminated, and instances and solutions must have been
+//! returned by this module and not freed yet.
+#![allow(clippy::missing_safety_doc)]
+
+use crate::{
+    instance::{Instance, LoadOptions},
+    report::Settings,
+    small_indices::SmallIdx,
+    solve::{self, SolveHooks},
+};
+use anyhow::{anyhow, Context, Result};
+use std::{
+    cell::RefCell,
+    ffi::{c_char, CStr, CString},
+    io::Cursor,
+    panic::{self, AssertUnwindSafe},
+    ptr,
+};
+
+/// File name reported for instances passed through the C ABI
+const FFI_FILE_NAME: &str = "<ffi>";
+
+/// Hypergraph loaded through `findminhs_load_json`
+#[derive(Debug)]
+pub struct FindminhsInstance(Instance);
+
+/// Minimum hitting set and solver report returned by `findminhs_solve`
+#[derive(Debug)]
+pub struct FindminhsSolution {
+    nodes: Vec<usize>,
+    report: CString,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: &str) {
+    // Interior nul bytes can't be represented, so they are dropped
+    let message = CString::new(message.replace('\0', "")).unwrap_or_default();
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = Some(message));
+}
+
+/// Runs `f`, converting errors and panics into the last error and a null
+/// pointer.
+fn run<T>(f: impl FnOnce() -> Result<T>) -> *mut T {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => Box::into_raw(Box::new(value)),
+        Ok(Err(err)) => {
+            set_last_error(&format!("{:#}", err));
+            ptr::null_mut()
+        }
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|msg| (*msg).to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            set_last_error(&format!("solver panicked: {}", message));
+            ptr::null_mut()
+        }
+    }
+}
+
+unsafe fn str_arg<'a>(ptr: *const c_char, name: &str) -> Result<&'a str> {
+    if ptr.is_null() {
+        return Err(anyhow!("{} is null", name));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .with_context(|| format!("{} is not valid UTF-8", name))
+}
+
+/// Loads a hypergraph from a nul-terminated string in the json format.
+///
+/// The returned instance must be freed with `findminhs_free_instance`.
+#[no_mangle]
+pub unsafe extern "C" fn findminhs_load_json(json: *const c_char) -> *mut FindminhsInstance {
+    run(|| {
+        let json = str_arg(json, "json")?;
+        let instance = Instance::load_from_json(Cursor::new(json), LoadOptions::default())
+            .context("failed to load hypergraph")?;
+        Ok(FindminhsInstance(instance))
+    })
+}
+
+/// Computes a minimum hitting set of `instance` using the settings given as a
+/// nul-terminated json string. The instance is not modified and can be solved
+/// again.
+///
+/// The returned solution must be freed with `findminhs_free_solution`.
+#[no_mangle]
+pub unsafe extern "C" fn findminhs_solve(
+    instance: *const FindminhsInstance,
+    settings_json: *const c_char,
+) -> *mut FindminhsSolution {
+    run(|| {
+        let instance = instance
+            .as_ref()
+            .ok_or_else(|| anyhow!("instance is null"))?;
+        let settings: Settings = serde_json::from_str(str_arg(settings_json, "settings")?)
+            .context("failed to parse settings")?;
+        let (final_hs, report) = solve::solve(
+            instance.0.clone(),
+            FFI_FILE_NAME.to_string(),
+            settings,
+            SolveHooks::default(),
+        )?;
+        Ok(FindminhsSolution {
+            nodes: final_hs.iter().map(SmallIdx::idx).collect(),
+            report: CString::new(serde_json::to_string(&report)?)?,
+        })
+    })
+}
+
+/// Number of nodes in the hitting set.
+#[no_mangle]
+pub unsafe extern "C" fn findminhs_solution_size(solution: *const FindminhsSolution) -> usize {
+    solution.as_ref().map_or(0, |solution| solution.nodes.len())
+}
+
+/// Nodes of the hitting set, valid until the solution is freed.
+#[no_mangle]
+pub unsafe extern "C" fn findminhs_solution_nodes(
+    solution: *const FindminhsSolution,
+) -> *const usize {
+    solution
+        .as_ref()
+        .map_or(ptr::null(), |solution| solution.nodes.as_ptr())
+}
+
+/// Solver report as a nul-terminated json string, valid until the solution is
+/// freed.
+#[no_mangle]
+pub unsafe extern "C" fn findminhs_solution_report(
+    solution: *const FindminhsSolution,
+) -> *const c_char {
+    solution
+        .as_ref()
+        .map_or(ptr::null(), |solution| solution.report.as_ptr())
+}
+
+/// Message describing the last failure on the calling thread, or null if
+/// nothing failed yet. Valid until the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn findminhs_last_error() -> *const c_char {
+    LAST_ERROR.with(|last_error| {
+        last_error
+            .borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn findminhs_free_instance(instance: *mut FindminhsInstance) {
+    if !instance.is_null() {
+        drop(Box::from_raw(instance));
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn findminhs_free_solution(solution: *mut FindminhsSolution) {
+    if !solution.is_null() {
+        drop(Box::from_raw(solution));
+    }
+}
@@ -0,0 +1,167 @@
+use crate::{
+    clock::Instant,
+    incremental::IncrementalSolver,
+    instance::NodeIdx,
+    report::{ImplicitReport, Settings, SolveStatus},
+    small_indices::SmallIdx,
+};
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use log::{debug, info};
+use std::{
+    io::{BufRead, BufReader, BufWriter, Write},
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+    time::Duration,
+};
+
+/// Source of the edges of a hypergraph that is too large to enumerate.
+pub trait Oracle {
+    /// Returns an edge that is not hit by `hitting_set`, or `None` if it hits
+    /// all edges.
+    fn find_unhit_edge(&mut self, hitting_set: &[NodeIdx]) -> Result<Option<Vec<usize>>>;
+}
+
+impl<F> Oracle for F
+where
+    F: FnMut(&[NodeIdx]) -> Result<Option<Vec<usize>>>,
+{
+    fn find_unhit_edge(&mut self, hitting_set: &[NodeIdx]) -> Result<Option<Vec<usize>>> {
+        self(hitting_set)
+    }
+}
+
+/// Computes a minimum hitting set of the hypergraph described by `oracle`
+/// using the implicit hitting set approach.
+///
+/// Starting without edges, a minimum hitting set of the edges known so far is
+/// computed and passed to the oracle. Each unhit edge returned by the oracle is
+/// added, and the hitting set re-optimized, until the oracle reports that all
+/// edges are hit. As every hitting set of all edges also hits the known ones,
+/// the final hitting set is minimum if the last solve was optimal.
+pub fn solve_implicit(
+    num_nodes: usize,
+    settings: Settings,
+    mut oracle: impl Oracle,
+    max_iterations: Option<usize>,
+) -> Result<(Vec<NodeIdx>, ImplicitReport)> {
+    let mut solver = IncrementalSolver::new(num_nodes, settings.clone())?;
+    let mut report = ImplicitReport {
+        iterations: 0,
+        num_edges: 0,
+        opt: 0,
+        status: SolveStatus::Optimal,
+        finished: false,
+        branching_steps: 0,
+        solve_runtime: Duration::ZERO,
+        oracle_runtime: Duration::ZERO,
+        settings,
+    };
+
+    loop {
+        if max_iterations.is_some_and(|max| report.iterations >= max) {
+            info!("Stopping after {} iterations", report.iterations);
+            break;
+        }
+        report.iterations += 1;
+
+        let solve_start = Instant::now();
+        let solve_report = solver.solve()?;
+        report.solve_runtime += solve_start.elapsed();
+        report.branching_steps += solve_report.branching_steps;
+        report.status = solve_report.status;
+        report.opt = solver.hitting_set().len();
+        debug!(
+            "Iteration {}: hitting set of size {} for {} edges",
+            report.iterations,
+            report.opt,
+            solver.num_edges()
+        );
+
+        let oracle_start = Instant::now();
+        let edge = oracle
+            .find_unhit_edge(solver.hitting_set())
+            .context("oracle failed")?;
+        report.oracle_runtime += oracle_start.elapsed();
+        let Some(edge) = edge else {
+            report.finished = true;
+            break;
+        };
+
+        let hs = solver.hitting_set();
+        ensure!(
+            !edge
+                .iter()
+                .any(|&node| hs.iter().any(|hs_node| hs_node.idx() == node)),
+            "oracle returned an edge that is hit by the hitting set"
+        );
+        solver
+            .add_edge(&edge)
+            .context("oracle returned an invalid edge")?;
+    }
+
+    report.num_edges = solver.num_edges();
+    if report.finished {
+        info!(
+            "Found hitting set of size {} after {} iterations with {} edges",
+            report.opt, report.iterations, report.num_edges
+        );
+    }
+    Ok((solver.hitting_set().to_vec(), report))
+}
+
+/// Oracle running as a separate process.
+///
+/// Each candidate hitting set is written to the standard input of the process
+/// as a json array on a single line. The process answers with a single line
+/// containing either an unhit edge as a json array of node indices or `null`
+/// if all edges are hit. Its standard input is closed once the solver is done,
+/// after which it should exit.
+#[derive(Debug)]
+pub struct CommandOracle {
+    child: Child,
+    stdin: Option<BufWriter<ChildStdin>>,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl CommandOracle {
+    pub fn spawn(program: &str, args: &[String]) -> Result<Self> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("failed to start oracle {}", program))?;
+        let stdin = child.stdin.take().map(BufWriter::new);
+        let stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+        Ok(Self {
+            child,
+            stdin,
+            stdout,
+        })
+    }
+}
+
+impl Oracle for CommandOracle {
+    fn find_unhit_edge(&mut self, hitting_set: &[NodeIdx]) -> Result<Option<Vec<usize>>> {
+        let stdin = self
+            .stdin
+            .as_mut()
+            .ok_or_else(|| anyhow!("oracle input is closed"))?;
+        serde_json::to_writer(&mut *stdin, hitting_set)?;
+        stdin.write_all(b"\n")?;
+        stdin.flush()?;
+
+        let mut line = String::new();
+        if self.stdout.read_line(&mut line)? == 0 {
+            bail!("oracle exited without answering");
+        }
+        serde_json::from_str(&line).with_context(|| format!("invalid oracle answer {:?}", line))
+    }
+}
+
+impl Drop for CommandOracle {
+    fn drop(&mut self) {
+        // Closing the input signals the process to exit
+        drop(self.stdin.take());
+        let _ = self.child.wait();
+    }
+}
@@ -0,0 +1,125 @@
+use crate::{
+    instance::{Instance, LoadOptions, NodeIdx},
+    report::{Report, Settings, SolveStatus},
+    small_indices::{IdxHashSet, SmallIdx},
+    solve::{self, SolveHooks},
+};
+use anyhow::{bail, ensure, Result};
+use log::debug;
+
+/// File name reported for instances solved incrementally
+const INCREMENTAL_FILE_NAME: &str = "<incremental>";
+
+/// Solver for hypergraphs that grow by adding edges between solves, as in
+/// implicit hitting set algorithms.
+///
+/// Adding edges never decreases the size of a minimum hitting set. Hence, the
+/// previous optimum remains a lower bound, and the previous hitting set,
+/// extended by a node of each new edge it misses, is used as the initial
+/// hitting set of the next solve. If that extension is not larger than the
+/// previous optimum, it is optimal without any branching. Learned nogoods are
+/// not carried over, as they are only valid relative to the upper bound of the
+/// search that learned them.
+#[derive(Debug, Clone)]
+pub struct IncrementalSolver {
+    num_nodes: usize,
+    edges: Vec<Vec<usize>>,
+    seen_edges: IdxHashSet<Vec<usize>>,
+    settings: Settings,
+    hitting_set: Vec<NodeIdx>,
+    lower_bound: usize,
+}
+
+impl IncrementalSolver {
+    pub fn new(num_nodes: usize, settings: Settings) -> Result<Self> {
+        ensure!(
+            settings.budget.is_none(),
+            "budgets are not supported when solving incrementally"
+        );
+        Ok(Self {
+            num_nodes,
+            edges: Vec::new(),
+            seen_edges: IdxHashSet::default(),
+            settings,
+            hitting_set: Vec::new(),
+            lower_bound: 0,
+        })
+    }
+
+    /// Adds an edge, returning `false` if the same edge was added before.
+    /// Duplicate nodes within the edge are ignored.
+    pub fn add_edge(&mut self, nodes: &[usize]) -> Result<bool> {
+        ensure!(!nodes.is_empty(), "edges may not be empty");
+        if let Some(&node) = nodes.iter().find(|&&node| node >= self.num_nodes) {
+            bail!("invalid node idx in edge: {}", node);
+        }
+        let mut edge = nodes.to_vec();
+        edge.sort_unstable();
+        edge.dedup();
+        if !self.seen_edges.insert(edge.clone()) {
+            return Ok(false);
+        }
+        self.edges.push(edge);
+        Ok(true)
+    }
+
+    pub fn num_edges(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Hitting set found by the last solve
+    pub fn hitting_set(&self) -> &[NodeIdx] {
+        &self.hitting_set
+    }
+
+    /// Size of the last hitting set proven to be optimal, which is a lower
+    /// bound for all later solves
+    pub fn lower_bound(&self) -> usize {
+        self.lower_bound
+    }
+
+    /// Extends the previous hitting set by a node of each edge it misses.
+    fn warm_start(&self) -> Vec<NodeIdx> {
+        let mut in_hs = vec![false; self.num_nodes];
+        for node in &self.hitting_set {
+            in_hs[node.idx()] = true;
+        }
+        let mut hitting_set = self.hitting_set.clone();
+        for edge in &self.edges {
+            if !edge.iter().any(|&node| in_hs[node]) {
+                in_hs[edge[0]] = true;
+                hitting_set.push(NodeIdx::from(edge[0]));
+            }
+        }
+        hitting_set
+    }
+
+    /// Computes a minimum hitting set of all edges added so far.
+    pub fn solve(&mut self) -> Result<Report> {
+        let instance = Instance::from_edges(self.num_nodes, &self.edges, LoadOptions::default())?;
+        let mut settings = self.settings.clone();
+        settings.initial_hitting_set = Some(self.warm_start());
+        settings.stop_at = settings.stop_at.max(self.lower_bound);
+        debug!(
+            "Solving incrementally with {} edges, lower bound {}",
+            self.edges.len(),
+            self.lower_bound
+        );
+
+        let (hitting_set, mut report) = solve::solve(
+            instance,
+            INCREMENTAL_FILE_NAME.to_string(),
+            settings,
+            SolveHooks::default(),
+        )?;
+        if report.status == SolveStatus::StoppedAt && hitting_set.len() <= self.lower_bound {
+            // Stopped at the lower bound, so the hitting set is optimal
+            report.status = SolveStatus::Optimal;
+        }
+        if report.status == SolveStatus::Optimal {
+            self.lower_bound = hitting_set.len();
+        }
+        self.hitting_set = hitting_set;
+        Ok(report)
+    }
+}
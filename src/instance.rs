@@ -1,216 +1,1669 @@
 use crate::{
+    clock::Instant,
     create_idx_struct,
-    data_structures::{cont_idx_vec::ContiguousIdxVec, skipvec::SkipVec},
-    small_indices::SmallIdx,
+    data_structures::{
+        cont_idx_vec::ContiguousIdxVec,
+        csr::{self, CsrIncidences},
+        skipvec::{self, SkipVec},
+    },
+    relabel::NodeRelabeling,
+    small_indices::{fits_raw_idx, IdxHashMap, SmallIdx},
 };
-use anyhow::{anyhow, ensure, Error, Result};
-use log::{info, trace};
-use serde::Deserialize;
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use log::{info, trace, warn};
+use serde::de::{self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
 use std::{
+    error::Error as StdError,
     fmt::{self, Display, Write as _},
-    io::{BufRead, Write},
+    io::{BufRead, Seek, SeekFrom, Write},
+    iter::{self, Peekable},
     mem,
-    time::Instant,
+    str::{FromStr, SplitAsciiWhitespace},
 };
 
 create_idx_struct!(pub NodeIdx);
 create_idx_struct!(pub EdgeIdx);
 create_idx_struct!(pub EntryIdx);
 
+/// Error in a text formatted hypergraph, pointing to the offending token.
 #[derive(Debug)]
-struct CompressedIlpName<T>(T);
+pub struct TextParseError {
+    line: usize,
+    column: usize,
+    token: Option<String>,
+    message: String,
+}
+
+impl TextParseError {
+    fn new(line: usize, column: usize, token: Option<String>, message: String) -> Self {
+        Self {
+            line,
+            column,
+            token,
+            message,
+        }
+    }
+}
+
+impl Display for TextParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )?;
+        if let Some(token) = &self.token {
+            write!(f, " (found '{}')", token)?;
+        }
+        Ok(())
+    }
+}
+
+impl StdError for TextParseError {}
+
+/// Whitespace separated tokens of a single line together with their (1-based) columns.
+struct TextLineTokens<'a> {
+    line_number: usize,
+    line: &'a str,
+    tokens: Peekable<SplitAsciiWhitespace<'a>>,
+}
+
+impl<'a> TextLineTokens<'a> {
+    fn new(line_number: usize, line: &'a str) -> Self {
+        Self {
+            line_number,
+            line,
+            tokens: line.split_ascii_whitespace().peekable(),
+        }
+    }
+
+    fn column_of(&self, token: &str) -> usize {
+        token.as_ptr() as usize - self.line.as_ptr() as usize + 1
+    }
+
+    /// Column and text of the next token, or the end of the line if there is none.
+    fn peek_token(&mut self) -> (usize, Option<&'a str>) {
+        match self.tokens.peek().copied() {
+            Some(token) => (self.column_of(token), Some(token)),
+            None => (self.line.trim_end().len() + 1, None),
+        }
+    }
+
+    fn parse(&self, token: &'a str, what: &str) -> Result<usize, TextParseError> {
+        token.parse().map_err(|err| {
+            TextParseError::new(
+                self.line_number,
+                self.column_of(token),
+                Some(token.to_string()),
+                format!("invalid {}: {}", what, err),
+            )
+        })
+    }
+
+    fn next_number(&mut self, what: &str) -> Result<usize, TextParseError> {
+        match self.peek_token() {
+            (_, Some(token)) => {
+                self.tokens.next();
+                self.parse(token, what)
+            }
+            (column, None) => Err(TextParseError::new(
+                self.line_number,
+                column,
+                None,
+                format!("missing {}", what),
+            )),
+        }
+    }
+
+    /// Parses all remaining tokens, yielding their column, text, and value.
+    fn remaining_numbers<'b>(
+        &'b self,
+        what: &'b str,
+    ) -> impl Iterator<Item = Result<(usize, &'a str, usize)>> + 'b {
+        self.tokens.clone().map(move |token| {
+            let value = self.parse(token, what)?;
+            Ok((self.column_of(token), token, value))
+        })
+    }
+
+    fn skip(&mut self, num_tokens: usize) {
+        for _ in 0..num_tokens {
+            self.tokens.next();
+        }
+    }
+
+    fn expect_end(&mut self, what: &str) -> Result<(), TextParseError> {
+        match self.peek_token() {
+            (_, None) => Ok(()),
+            (column, Some(token)) => Err(TextParseError::new(
+                self.line_number,
+                column,
+                Some(token.to_string()),
+                format!("too many numbers in {}", what),
+            )),
+        }
+    }
+}
+
+/// Reads the next line that is neither blank nor a comment into `line`.
+///
+/// Comment lines start with `#` or `c`. Returns `false` once the end of the
+/// input is reached.
+fn read_text_content_line(
+    reader: &mut impl BufRead,
+    line: &mut String,
+    line_number: &mut usize,
+) -> Result<bool> {
+    loop {
+        line.clear();
+        if reader.read_line(line)? == 0 {
+            return Ok(false);
+        }
+        *line_number += 1;
+
+        let trimmed = line.trim_start();
+        if !trimmed.is_empty() && !trimmed.starts_with('#') && !trimmed.starts_with('c') {
+            return Ok(true);
+        }
+    }
+}
+
+/// Maximum total number of nodes for which `Instance::edge_bitsets` represents
+/// edges as bitsets.
+pub const MAX_BITSET_NODES: usize = u128::BITS as usize;
+
+/// Digits of compressed ILP names, in the order of their values
+const ILP_NAME_DIGITS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+/// Short name of a node or edge in ILPs written by `Instance::export_as_ilp`.
+/// The index is written in base 62 using the digits `a-z`, `A-Z`, and `0-9`,
+/// least significant digit first and without trailing zeros, so index 0 is
+/// the empty string. Nodes become the variables `v<name>`, edges the
+/// constraints `e<name>`, and conflicts between two nodes the constraints
+/// `c<name>_<name>`.
+///
+/// Displaying encodes the name, parsing decodes it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressedIlpName<T>(pub T);
 
 impl<T: SmallIdx> Display for CompressedIlpName<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        const CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
         let mut val = self.0.idx();
         while val != 0 {
-            f.write_char(char::from(CHARS[val % CHARS.len()]))?;
-            val /= CHARS.len();
+            f.write_char(char::from(ILP_NAME_DIGITS[val % ILP_NAME_DIGITS.len()]))?;
+            val /= ILP_NAME_DIGITS.len();
         }
         Ok(())
     }
 }
 
+impl<T: SmallIdx> FromStr for CompressedIlpName<T> {
+    type Err = anyhow::Error;
+
+    /// Decodes a name, rejecting names that are not written exactly like the
+    /// encoding of their index.
+    fn from_str(name: &str) -> Result<Self> {
+        ensure!(
+            !name.ends_with(char::from(ILP_NAME_DIGITS[0])),
+            "ILP name {:?} has a trailing zero digit",
+            name
+        );
+        let mut val = 0_usize;
+        for ch in name.bytes().rev() {
+            let digit = ILP_NAME_DIGITS
+                .iter()
+                .position(|&other| other == ch)
+                .with_context(|| format!("invalid character in ILP name {:?}", name))?;
+            val = val
+                .checked_mul(ILP_NAME_DIGITS.len())
+                .and_then(|val| val.checked_add(digit))
+                .filter(|&val| fits_raw_idx(val))
+                .with_context(|| format!("index of ILP name {:?} is too large", name))?;
+        }
+        Ok(Self(T::from(val)))
+    }
+}
+
+impl CompressedIlpName<NodeIdx> {
+    /// Name of the variable of the node in exported ILPs
+    pub fn node_variable(node: NodeIdx) -> String {
+        format!("v{}", Self(node))
+    }
+
+    /// Node of a variable name in exported ILPs, e.g., from the solution of
+    /// an ILP solver
+    pub fn parse_node_variable(variable: &str) -> Result<NodeIdx> {
+        let name = variable
+            .strip_prefix('v')
+            .with_context(|| format!("ILP variable {:?} does not start with 'v'", variable))?;
+        Ok(name.parse::<Self>()?.0)
+    }
+}
+
+/// Options controlling how hypergraphs are loaded.
+#[derive(Debug, Clone, Copy, Default)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct LoadOptions {
+    /// Fix malformed input (duplicate nodes within edges, empty edges)
+    /// instead of rejecting it.
+    pub normalize: bool,
+
+    /// Keep duplicate edges as separate edges instead of merging them into
+    /// one edge with a multiplicity.
+    pub keep_duplicate_edges: bool,
+
+    /// Drop nodes not contained in any edge and renumber the remaining ones,
+    /// see `NodeRelabeling`.
+    pub compact_nodes: bool,
+
+    /// Edge lines of the text format only contain node indices, without the
+    /// leading edge size.
+    pub no_edge_sizes: bool,
+
+    /// Node indices in the input start at 1 rather than 0.
+    pub one_based: bool,
+
+    /// Store incidences in flat arrays instead of linked lists per node and
+    /// edge, see `CsrIncidences`.
+    pub csr: bool,
+
+    /// Check that restorations happen in reverse order of the deletions, see
+    /// `Instance::enable_restore_checks`.
+    pub check_restores: bool,
+
+    /// Check the consistency of the instance after every change made by the
+    /// reductions, see `Instance::set_paranoid`.
+    pub paranoid: bool,
+}
+
+/// Builds an instance from edges held in memory, validating them the same way
+/// as edges read from a file.
+///
+/// Unless set explicitly, the number of nodes is inferred from the largest
+/// node index used by any edge.
+#[derive(Debug, Clone, Default)]
+pub struct InstanceBuilder {
+    num_nodes: Option<usize>,
+    edges: Vec<Vec<usize>>,
+    options: LoadOptions,
+}
+
+impl InstanceBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the number of nodes, which is needed if the largest nodes are not
+    /// part of any edge.
+    pub fn num_nodes(&mut self, num_nodes: usize) -> &mut Self {
+        self.num_nodes = Some(num_nodes);
+        self
+    }
+
+    /// Sets the options applied when building the instance, such as
+    /// `normalize` or `one_based`.
+    pub fn options(&mut self, options: LoadOptions) -> &mut Self {
+        self.options = options;
+        self
+    }
+
+    /// Adds an edge given by its node indices in arbitrary order.
+    pub fn add_edge(&mut self, nodes: &[usize]) -> &mut Self {
+        self.edges.push(nodes.to_vec());
+        self
+    }
+
+    pub fn num_edges(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Builds the instance, failing with the index of the first invalid edge
+    /// if an edge is empty, contains a node twice, or contains a node index
+    /// not below the number of nodes.
+    pub fn build(&self) -> Result<Instance> {
+        let num_nodes = self.num_nodes.unwrap_or_else(|| {
+            let max_node = self.edges.iter().flatten().copied().max();
+            max_node.map_or(0, |max_node| {
+                if self.options.one_based {
+                    max_node
+                } else {
+                    max_node.saturating_add(1)
+                }
+            })
+        });
+        Instance::from_edges(num_nodes, &self.edges, self.options)
+    }
+}
+
+/// Counts of the input problems fixed while normalizing.
+#[derive(Debug, Clone, Copy, Default)]
+struct NormalizeStats {
+    duplicate_nodes: usize,
+    duplicate_edges: usize,
+    empty_edges: usize,
+}
+
+/// Pass of the two-pass load, see `ParsedEdgeHandler`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LoadPass {
+    /// Counts the size of each edge
+    Count,
+    /// Copies the nodes of the edges into the preallocated `edge_nodes` and
+    /// counts the node degrees
+    Fill,
+}
+
+/// Collects parsed edges in a flat, CSR-like layout.
+///
+/// The input is read twice: the first pass only counts the size of each edge,
+/// so that the second pass can store the nodes of all edges in a single
+/// allocation of exactly the right size. As the node count of json input is
+/// only known after the first pass, node indices are checked and counted in
+/// the second one. The incidence lists of the final instance are then built
+/// from these arrays.
 #[derive(Debug)]
 struct ParsedEdgeHandler {
-    edge_incidences: Vec<SkipVec<(NodeIdx, EntryIdx)>>,
+    pass: LoadPass,
+    /// Nodes of the edge currently being read
+    edge_buffer: Vec<NodeIdx>,
+    /// Node indices of all edges, each edge sorted and stored contiguously
+    edge_nodes: Vec<NodeIdx>,
+    /// Start of each edge in `edge_nodes`, followed by the total length
+    edge_starts: Vec<usize>,
+    /// Number of edges copied to `edge_nodes` in the fill pass
+    num_filled_edges: usize,
     node_degrees: Vec<usize>,
+    /// Coverage requirement of each edge, empty unless the input has any
+    requirements: Vec<usize>,
+    options: LoadOptions,
+    normalize_stats: NormalizeStats,
 }
 
 impl ParsedEdgeHandler {
+    fn new(num_edges: usize, options: LoadOptions) -> Self {
+        let mut edge_starts = Vec::with_capacity(num_edges + 1);
+        edge_starts.push(0);
+        Self {
+            pass: LoadPass::Count,
+            edge_buffer: Vec::new(),
+            edge_nodes: Vec::new(),
+            edge_starts,
+            num_filled_edges: 0,
+            node_degrees: Vec::new(),
+            requirements: Vec::new(),
+            options,
+            normalize_stats: NormalizeStats::default(),
+        }
+    }
+
+    /// Allocates `edge_nodes` for the edges counted so far and switches to
+    /// the fill pass.
+    fn start_fill_pass(&mut self, num_nodes: usize) {
+        self.pass = LoadPass::Fill;
+        self.edge_nodes = Vec::with_capacity(*self.edge_starts.last().unwrap());
+        self.node_degrees = vec![0; num_nodes];
+    }
+
     fn handle_edge(&mut self, node_indices: impl IntoIterator<Item = Result<usize>>) -> Result<()> {
-        let incidences = SkipVec::try_sorted_from(node_indices.into_iter().map(|idx_result| {
-            idx_result.and_then(|node_idx| {
+        let num_nodes = self.node_degrees.len();
+        let pass = self.pass;
+        let one_based = self.options.one_based;
+        let nodes = &mut self.edge_buffer;
+        nodes.clear();
+        for idx_result in node_indices {
+            let node_idx = idx_result?;
+            let zero_based_idx = if one_based {
+                node_idx.checked_sub(1)
+            } else {
+                Some(node_idx)
+            };
+            match zero_based_idx {
+                Some(idx) if pass == LoadPass::Count || idx < num_nodes => {
+                    nodes.push(NodeIdx::from(idx));
+                }
+                _ => bail!("invalid node idx in edge: {}", node_idx),
+            }
+        }
+
+        nodes.sort_unstable();
+        let mut len = nodes.len().min(1);
+        for idx in 1..nodes.len() {
+            if nodes[idx] != nodes[len - 1] {
+                nodes[len] = nodes[idx];
+                len += 1;
+            } else if !self.options.normalize {
+                bail!("node {} occurs multiple times in the same edge", nodes[idx]);
+            }
+        }
+        let num_duplicates = nodes.len() - len;
+        nodes.truncate(len);
+
+        if nodes.is_empty() {
+            ensure!(self.options.normalize, "edges may not be empty");
+            if pass == LoadPass::Count {
+                self.normalize_stats.empty_edges += 1;
+            }
+            return Ok(());
+        }
+
+        match pass {
+            LoadPass::Count => {
+                self.normalize_stats.duplicate_nodes += num_duplicates;
+                let start = *self.edge_starts.last().unwrap();
+                self.edge_starts.push(start + nodes.len());
+            }
+            LoadPass::Fill => {
+                let edge_idx = self.num_filled_edges;
                 ensure!(
-                    node_idx < self.node_degrees.len(),
-                    "invalid node idx in edge: {}",
-                    node_idx
+                    edge_idx + 1 < self.edge_starts.len()
+                        && self.edge_starts[edge_idx + 1] - self.edge_starts[edge_idx]
+                            == nodes.len(),
+                    "input changed while loading, edge {} differs between the two passes",
+                    edge_idx
                 );
-                Ok((NodeIdx::from(node_idx), EntryIdx::INVALID))
-            })
-        }))?;
-        ensure!(incidences.len() > 0, "edges may not be empty");
-        for (_, (node, _)) in &incidences {
-            self.node_degrees[node.idx()] += 1;
+                for node in nodes.iter() {
+                    self.node_degrees[node.idx()] += 1;
+                }
+                self.edge_nodes.extend_from_slice(nodes);
+                self.num_filled_edges += 1;
+            }
         }
-        self.edge_incidences.push(incidences);
         Ok(())
     }
+
+    /// Handles an edge that must be hit by at least `requirement` distinct
+    /// nodes, which may not exceed the size of the edge. The requirement is
+    /// only checked and stored in the fill pass.
+    fn handle_edge_with_requirement(
+        &mut self,
+        node_indices: impl IntoIterator<Item = Result<usize>>,
+        requirement: usize,
+    ) -> Result<()> {
+        if self.pass == LoadPass::Count {
+            return self.handle_edge(node_indices);
+        }
+
+        ensure!(requirement > 0, "coverage requirements must be at least 1");
+        let edge_idx = self.num_filled_edges;
+        self.handle_edge(node_indices)?;
+        if self.num_filled_edges == edge_idx {
+            // Dropped as an empty edge while normalizing
+            ensure!(
+                requirement == 1,
+                "coverage requirement {} exceeds edge size 0",
+                requirement
+            );
+            return Ok(());
+        }
+
+        let size = self.edge_starts[edge_idx + 1] - self.edge_starts[edge_idx];
+        ensure!(
+            requirement <= size,
+            "coverage requirement {} exceeds edge size {}",
+            requirement,
+            size
+        );
+        self.requirements.push(requirement);
+        Ok(())
+    }
+
+    fn edge_nodes(&self, edge_idx: usize) -> &[NodeIdx] {
+        &self.edge_nodes[self.edge_starts[edge_idx]..self.edge_starts[edge_idx + 1]]
+    }
+
+    /// Merges edges with identical nodes into their first occurrence and
+    /// returns the multiplicity of each remaining edge, or an empty vector if
+    /// there were no duplicates. A merged edge keeps the largest coverage
+    /// requirement of its duplicates.
+    ///
+    /// Duplicates are found by sorting the edge indices by their nodes, which
+    /// avoids storing a second copy of all edges in a hash set.
+    fn merge_duplicate_edges(&mut self) -> Vec<usize> {
+        let num_edges = self.edge_starts.len() - 1;
+        let mut order: Vec<_> = (0..num_edges).collect();
+        order.sort_unstable_by(|&edge1, &edge2| {
+            self.edge_nodes(edge1)
+                .cmp(self.edge_nodes(edge2))
+                .then(edge1.cmp(&edge2))
+        });
+
+        let mut multiplicities = vec![1; num_edges];
+        let mut num_duplicates = 0;
+        let mut first = 0;
+        for idx in 1..order.len() {
+            if self.edge_nodes(order[idx]) == self.edge_nodes(order[first]) {
+                multiplicities[order[first]] += 1;
+                if !self.requirements.is_empty() {
+                    let requirement = self.requirements[order[idx]];
+                    let first_requirement = &mut self.requirements[order[first]];
+                    *first_requirement = (*first_requirement).max(requirement);
+                }
+                multiplicities[order[idx]] = 0;
+                num_duplicates += 1;
+            } else {
+                first = idx;
+            }
+        }
+        if num_duplicates == 0 {
+            return Vec::new();
+        }
+
+        // Edges only move towards the front, so they can be compacted in place
+        let mut write_pos = 0;
+        let mut num_kept = 0;
+        for edge_idx in 0..num_edges {
+            let (start, end) = (self.edge_starts[edge_idx], self.edge_starts[edge_idx + 1]);
+            if multiplicities[edge_idx] == 0 {
+                for node_idx in start..end {
+                    self.node_degrees[self.edge_nodes[node_idx].idx()] -= 1;
+                }
+                continue;
+            }
+            self.edge_nodes.copy_within(start..end, write_pos);
+            self.edge_starts[num_kept] = write_pos;
+            write_pos += end - start;
+            multiplicities[num_kept] = multiplicities[edge_idx];
+            if !self.requirements.is_empty() {
+                self.requirements[num_kept] = self.requirements[edge_idx];
+            }
+            num_kept += 1;
+        }
+        self.edge_nodes.truncate(write_pos);
+        self.edge_starts.truncate(num_kept);
+        self.edge_starts.push(write_pos);
+        multiplicities.truncate(num_kept);
+        self.requirements.truncate(num_kept);
+        self.normalize_stats.duplicate_edges = num_duplicates;
+        multiplicities
+    }
+
+    /// Drops the nodes not contained in any edge and renumbers the remaining
+    /// ones in increasing order. Returns the mapping if any node was dropped.
+    fn compact_nodes(&mut self) -> Option<NodeRelabeling> {
+        let num_nodes = self.node_degrees.len();
+        let mut new_index = vec![NodeIdx::INVALID; num_nodes];
+        let mut original_nodes = Vec::new();
+        for (idx, &degree) in self.node_degrees.iter().enumerate() {
+            if degree > 0 {
+                new_index[idx] = NodeIdx::from(original_nodes.len());
+                original_nodes.push(NodeIdx::from(idx));
+            }
+        }
+        if original_nodes.len() == num_nodes {
+            return None;
+        }
+
+        // The mapping is monotone, so the nodes of each edge stay sorted
+        for node in &mut self.edge_nodes {
+            *node = new_index[node.idx()];
+        }
+        self.node_degrees.retain(|&degree| degree > 0);
+        Some(NodeRelabeling::new(original_nodes, num_nodes))
+    }
 }
 
-#[derive(Debug, Deserialize)]
+/// Entries of a hypergraph in the json format, except for the nodes of the
+/// edges, which are passed to the `ParsedEdgeHandler` while reading.
+#[derive(Debug, Default)]
 struct JsonInstance {
     num_nodes: usize,
-    edges: Vec<Vec<usize>>,
+    num_edges: usize,
+
+    /// Number of times each edge must be hit (defaults to once)
+    requirements: Option<Vec<usize>>,
+
+    /// Groups of nodes of which at most one may be part of the hitting set
+    conflicts: Vec<Vec<usize>>,
+}
+
+/// Reads a hypergraph in the json format, passing its edges to the handler one
+/// at a time instead of collecting them.
+///
+/// In the fill pass, only the edges are read, using the coverage requirements
+/// read in the count pass, and the other entries are left empty.
+struct JsonInstanceStream<'a> {
+    handler: &'a mut ParsedEdgeHandler,
+    requirements: Option<&'a [usize]>,
+}
+
+impl<'de> DeserializeSeed<'de> for JsonInstanceStream<'_> {
+    type Value = JsonInstance;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<JsonInstance, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for JsonInstanceStream<'_> {
+    type Value = JsonInstance;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a hypergraph")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<JsonInstance, A::Error> {
+        let count_pass = self.handler.pass == LoadPass::Count;
+        let mut num_nodes = None;
+        let mut num_edges = None;
+        let mut instance = JsonInstance::default();
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "edges" => {
+                    num_edges = Some(map.next_value_seed(JsonEdges {
+                        handler: &mut *self.handler,
+                        requirements: self.requirements,
+                    })?);
+                }
+                "num_nodes" if count_pass => num_nodes = Some(map.next_value()?),
+                "requirements" if count_pass => instance.requirements = map.next_value()?,
+                "conflicts" if count_pass => instance.conflicts = map.next_value()?,
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        instance.num_edges = num_edges.ok_or_else(|| de::Error::missing_field("edges"))?;
+        if count_pass {
+            instance.num_nodes = num_nodes.ok_or_else(|| de::Error::missing_field("num_nodes"))?;
+        }
+        Ok(instance)
+    }
+}
+
+struct JsonEdges<'a> {
+    handler: &'a mut ParsedEdgeHandler,
+    requirements: Option<&'a [usize]>,
+}
+
+impl<'de> DeserializeSeed<'de> for JsonEdges<'_> {
+    type Value = usize;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<usize, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for JsonEdges<'_> {
+    type Value = usize;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a list of edges")
+    }
+
+    /// Passes each edge to the handler, returning the number of edges.
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<usize, A::Error> {
+        let mut edge_idx = 0;
+        loop {
+            let edge = JsonEdge {
+                handler: &mut *self.handler,
+                requirements: self.requirements,
+                edge_idx,
+            };
+            if seq.next_element_seed(edge)?.is_none() {
+                return Ok(edge_idx);
+            }
+            edge_idx += 1;
+        }
+    }
+}
+
+struct JsonEdge<'a> {
+    handler: &'a mut ParsedEdgeHandler,
+    requirements: Option<&'a [usize]>,
+    edge_idx: usize,
+}
+
+impl<'de> DeserializeSeed<'de> for JsonEdge<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for JsonEdge<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a list of node indices")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        // Errors of the json itself are passed on as they are, with their position
+        let mut json_error = None;
+        let nodes = iter::from_fn(|| match seq.next_element() {
+            Ok(node_idx) => node_idx.map(Ok),
+            Err(err) => {
+                json_error = Some(err);
+                None
+            }
+        });
+        let result = match self.requirements {
+            Some(requirements) => match requirements.get(self.edge_idx) {
+                Some(&requirement) => self
+                    .handler
+                    .handle_edge_with_requirement(nodes, requirement),
+                None => Err(anyhow!(
+                    "input changed while loading, found additional edges"
+                )),
+            },
+            None => self.handler.handle_edge(nodes),
+        };
+        if let Some(err) = json_error {
+            return Err(err);
+        }
+        result
+            .map_err(|err| de::Error::custom(format!("invalid edge {}: {:#}", self.edge_idx, err)))
+    }
+}
+
+/// Incidence lists of all nodes and edges, in one of two layouts chosen when
+/// loading the instance.
+#[derive(Clone, Debug)]
+enum Incidences {
+    /// A linked list per node and edge, with each entry storing the position of
+    /// its counterpart in the list on the other side.
+    Linked {
+        nodes: Vec<SkipVec<(EdgeIdx, EntryIdx)>>,
+        edges: Vec<SkipVec<(NodeIdx, EntryIdx)>>,
+    },
+    Csr(CsrIncidences),
+}
+
+impl Incidences {
+    /// Builds the incidences from the nodes of all edges, stored contiguously
+    /// with the nodes of edge `i` starting at `edge_starts[i]`.
+    fn new(csr: bool, num_nodes: usize, edge_starts: Vec<usize>, edge_nodes: Vec<NodeIdx>) -> Self {
+        if csr {
+            Self::Csr(CsrIncidences::new(num_nodes, edge_starts, edge_nodes))
+        } else {
+            Self::linked(num_nodes, edge_starts, edge_nodes)
+        }
+    }
+
+    /// Builds the lists of the edges first and frees the flat arrays before
+    /// allocating the lists of the nodes, so that loading never needs more
+    /// memory than the finished incidences.
+    fn linked(num_nodes: usize, edge_starts: Vec<usize>, edge_nodes: Vec<NodeIdx>) -> Self {
+        // Entries of each node list are numbered in order of the edges
+        let mut node_degrees = vec![0; num_nodes];
+        let edges: Vec<SkipVec<_>> = edge_starts
+            .windows(2)
+            .map(|bounds| {
+                edge_nodes[bounds[0]..bounds[1]]
+                    .iter()
+                    .map(|&node| {
+                        let node_entry_idx = EntryIdx::from(node_degrees[node.idx()]);
+                        node_degrees[node.idx()] += 1;
+                        (node, node_entry_idx)
+                    })
+                    .collect()
+            })
+            .collect();
+        drop(edge_starts);
+        drop(edge_nodes);
+
+        let mut nodes: Vec<_> = node_degrees
+            .iter()
+            .map(|&len| SkipVec::with_len(len))
+            .collect();
+        for (edge, incidences) in edges.iter().enumerate() {
+            for (edge_entry_idx, &(node, node_entry_idx)) in incidences {
+                nodes[node.idx()][node_entry_idx.idx()] =
+                    (EdgeIdx::from(edge), EntryIdx::from(edge_entry_idx));
+            }
+        }
+        Self::Linked { nodes, edges }
+    }
+
+    fn num_nodes(&self) -> usize {
+        match self {
+            Self::Linked { nodes, .. } => nodes.len(),
+            Self::Csr(csr) => csr.num_nodes(),
+        }
+    }
+
+    fn num_edges(&self) -> usize {
+        match self {
+            Self::Linked { edges, .. } => edges.len(),
+            Self::Csr(csr) => csr.num_edges(),
+        }
+    }
+
+    fn node(&self, node: NodeIdx) -> IncidenceIter<'_, EdgeIdx> {
+        match self {
+            Self::Linked { nodes, .. } => IncidenceIter::Linked(nodes[node.idx()].iter()),
+            Self::Csr(csr) => IncidenceIter::Csr(csr.node(node)),
+        }
+    }
+
+    fn edge(&self, edge: EdgeIdx) -> IncidenceIter<'_, NodeIdx> {
+        match self {
+            Self::Linked { edges, .. } => IncidenceIter::Linked(edges[edge.idx()].iter()),
+            Self::Csr(csr) => IncidenceIter::Csr(csr.edge(edge)),
+        }
+    }
+
+    fn node_degree(&self, node: NodeIdx) -> usize {
+        match self {
+            Self::Linked { nodes, .. } => nodes[node.idx()].len(),
+            Self::Csr(csr) => csr.node_degree(node),
+        }
+    }
+
+    fn edge_size(&self, edge: EdgeIdx) -> usize {
+        match self {
+            Self::Linked { edges, .. } => edges[edge.idx()].len(),
+            Self::Csr(csr) => csr.edge_size(edge),
+        }
+    }
+
+    /// Deletes a node, calling `on_resized` with each alive edge containing it
+    /// and the new size of the edge.
+    fn delete_node(&mut self, node: NodeIdx, mut on_resized: impl FnMut(EdgeIdx, usize)) {
+        match self {
+            Self::Linked { nodes, edges } => {
+                for (_idx, (edge, entry_idx)) in &nodes[node.idx()] {
+                    let edge_incidence = &mut edges[edge.idx()];
+                    edge_incidence.delete(entry_idx.idx());
+                    on_resized(*edge, edge_incidence.len());
+                }
+            }
+            Self::Csr(csr) => csr.delete_node(node, on_resized),
+        }
+    }
+
+    fn restore_node(&mut self, node: NodeIdx, mut on_resized: impl FnMut(EdgeIdx, usize)) {
+        match self {
+            Self::Linked { nodes, edges } => {
+                for (_idx, (edge, entry_idx)) in nodes[node.idx()].iter().rev() {
+                    let edge_incidence = &mut edges[edge.idx()];
+                    edge_incidence.restore(entry_idx.idx());
+                    on_resized(*edge, edge_incidence.len());
+                }
+            }
+            Self::Csr(csr) => csr.restore_node(node, on_resized),
+        }
+    }
+
+    fn delete_edge(&mut self, edge: EdgeIdx) {
+        match self {
+            Self::Linked { nodes, edges } => Self::delete_linked_edge(nodes, edges, edge),
+            Self::Csr(csr) => csr.delete_edge(edge),
+        }
+    }
+
+    fn restore_edge(&mut self, edge: EdgeIdx) {
+        match self {
+            Self::Linked { nodes, edges } => Self::restore_linked_edge(nodes, edges, edge),
+            Self::Csr(csr) => csr.restore_edge(edge),
+        }
+    }
+
+    fn delete_linked_edge(
+        nodes: &mut [SkipVec<(EdgeIdx, EntryIdx)>],
+        edges: &[SkipVec<(NodeIdx, EntryIdx)>],
+        edge: EdgeIdx,
+    ) {
+        for (_idx, (node, entry_idx)) in &edges[edge.idx()] {
+            nodes[node.idx()].delete(entry_idx.idx());
+        }
+    }
+
+    fn restore_linked_edge(
+        nodes: &mut [SkipVec<(EdgeIdx, EntryIdx)>],
+        edges: &[SkipVec<(NodeIdx, EntryIdx)>],
+        edge: EdgeIdx,
+    ) {
+        for (_idx, (node, entry_idx)) in edges[edge.idx()].iter().rev() {
+            nodes[node.idx()].restore(entry_idx.idx());
+        }
+    }
+
+    /// Deletes all alive edges of a deleted node, calling `on_deleted` with
+    /// each of them and its size.
+    fn delete_incident_edges(&mut self, node: NodeIdx, mut on_deleted: impl FnMut(EdgeIdx, usize)) {
+        match self {
+            Self::Linked { nodes, edges } => {
+                // We want to iterate over the incidence of `node` while
+                // deleting edges, which in turn changes node incidences. This
+                // is safe, since `node` itself was already deleted. To make the
+                // borrow checker accept this, we temporarily move `node`
+                // incidence to a local variable, replacing it with an empty
+                // list. This should not be much slower than unsafe
+                // alternatives, since an incidence list is only 28 bytes large.
+                let incidence = mem::take(&mut nodes[node.idx()]);
+                for (_, (edge, _)) in &incidence {
+                    Self::delete_linked_edge(nodes, edges, *edge);
+                    on_deleted(*edge, edges[edge.idx()].len());
+                }
+                nodes[node.idx()] = incidence;
+            }
+            Self::Csr(csr) => csr.delete_incident_edges(node, on_deleted),
+        }
+    }
+
+    /// Restores the edges deleted by `delete_incident_edges`, calling
+    /// `on_restored` with each of them and its size.
+    fn restore_incident_edges(
+        &mut self,
+        node: NodeIdx,
+        mut on_restored: impl FnMut(EdgeIdx, usize),
+    ) {
+        match self {
+            Self::Linked { nodes, edges } => {
+                // See `delete_incident_edges` for an explanation of this
+                // swapping around
+                let incidence = mem::take(&mut nodes[node.idx()]);
+
+                // It is important that we restore the edges in reverse order
+                for (_, (edge, _)) in incidence.iter().rev() {
+                    Self::restore_linked_edge(nodes, edges, *edge);
+                    on_restored(*edge, edges[edge.idx()].len());
+                }
+                nodes[node.idx()] = incidence;
+            }
+            Self::Csr(csr) => csr.restore_incident_edges(node, on_restored),
+        }
+    }
+}
+
+impl Incidences {
+    /// Checks the internal consistency of the layout: for linked lists, that
+    /// the lists are linked properly and each entry points to its mirror entry
+    /// on the other side.
+    fn check_invariants(&self) -> Result<()> {
+        match self {
+            Self::Linked { nodes, edges } => {
+                for (node_idx, incidence) in nodes.iter().enumerate() {
+                    incidence
+                        .check_invariants()
+                        .with_context(|| format!("invalid incidence of node {}", node_idx))?;
+                    for (entry_idx, &(edge, mirror_idx)) in incidence {
+                        let mirror = edges[edge.idx()][mirror_idx.idx()];
+                        ensure!(
+                            mirror == (NodeIdx::from(node_idx), EntryIdx::from(entry_idx)),
+                            "entry {} of node {} points to entry {} of edge {}, which points \
+                             to entry {} of node {}",
+                            entry_idx,
+                            node_idx,
+                            mirror_idx,
+                            edge,
+                            mirror.1,
+                            mirror.0
+                        );
+                    }
+                }
+                for (edge_idx, incidence) in edges.iter().enumerate() {
+                    incidence
+                        .check_invariants()
+                        .with_context(|| format!("invalid incidence of edge {}", edge_idx))?;
+                    for (entry_idx, &(node, mirror_idx)) in incidence {
+                        let mirror = nodes[node.idx()][mirror_idx.idx()];
+                        ensure!(
+                            mirror == (EdgeIdx::from(edge_idx), EntryIdx::from(entry_idx)),
+                            "entry {} of edge {} points to entry {} of node {}, which points \
+                             to entry {} of edge {}",
+                            entry_idx,
+                            edge_idx,
+                            mirror_idx,
+                            node,
+                            mirror.1,
+                            mirror.0
+                        );
+                    }
+                }
+                Ok(())
+            }
+            Self::Csr(csr) => csr.check_invariants(),
+        }
+    }
 }
 
+/// Iterator over an incidence list of either layout
+#[derive(Clone, Debug)]
+enum IncidenceIter<'a, T> {
+    Linked(skipvec::Iter<'a, (T, EntryIdx)>),
+    Csr(csr::Iter<'a, T>),
+}
+
+impl<T: SmallIdx> Iterator for IncidenceIter<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::Linked(iter) => iter.next().map(|(_, (item, _))| *item),
+            Self::Csr(iter) => iter.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            Self::Linked(iter) => iter.size_hint(),
+            Self::Csr(iter) => iter.size_hint(),
+        }
+    }
+}
+
+impl<T: SmallIdx> ExactSizeIterator for IncidenceIter<'_, T> {}
+
 #[derive(Clone, Debug)]
 pub struct Instance {
     nodes: ContiguousIdxVec<NodeIdx>,
     edges: ContiguousIdxVec<EdgeIdx>,
-    node_incidences: Vec<SkipVec<(EdgeIdx, EntryIdx)>>,
-    edge_incidences: Vec<SkipVec<(NodeIdx, EntryIdx)>>,
+    incidences: Incidences,
+
+    /// Alive edges containing only as many nodes as they need to be hit by,
+    /// usually a single node. Updated whenever an edge, one of its nodes, or
+    /// its requirement changes.
+    unit_edges: ContiguousIdxVec<EdgeIdx>,
+
+    /// Number of input edges merged into each edge. Empty if the input had no
+    /// duplicate edges.
+    edge_multiplicities: Vec<usize>,
+
+    /// Number of distinct nodes each edge still needs to be hit by, decreased
+    /// whenever a node of the edge is added to the hitting set. Empty if all
+    /// edges only need to be hit once.
+    requirements: Vec<usize>,
+
+    /// Original indices of the nodes, if nodes without edges were dropped
+    /// while loading
+    relabeling: Option<NodeRelabeling>,
+
+    /// For each node, the nodes it may not be chosen together with. Empty if
+    /// the instance has no conflicts.
+    conflicts: Vec<Vec<NodeIdx>>,
+
+    /// Deletions not restored yet, in order. Only recorded if restore checks
+    /// are enabled.
+    deletions: Option<Vec<Deletion>>,
+
+    /// Number of deletions and restorations so far
+    num_changes: usize,
+
+    paranoid: bool,
+}
+
+/// Deletion recorded to check the order of restorations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Deletion {
+    Node(NodeIdx),
+    Edge(EdgeIdx),
+    IncidentEdges(NodeIdx),
+}
+
+impl Display for Deletion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Node(node) => write!(f, "node {}", node),
+            Self::Edge(edge) => write!(f, "edge {}", edge),
+            Self::IncidentEdges(node) => write!(f, "edges incident to {}", node),
+        }
+    }
+}
+
+/// Requirement of an edge given the requirements stored in an instance
+fn requirement(requirements: &[usize], edge: EdgeIdx) -> usize {
+    requirements.get(edge.idx()).copied().unwrap_or(1)
+}
+
+/// Adds or removes an alive edge from the unit edges after its size or its
+/// requirement changed.
+fn update_unit_edge(
+    unit_edges: &mut ContiguousIdxVec<EdgeIdx>,
+    edge: EdgeIdx,
+    was_unit: bool,
+    is_unit: bool,
+) {
+    match (was_unit, is_unit) {
+        (true, false) => unit_edges.delete(edge.idx()),
+        (false, true) => unit_edges.restore(edge.idx()),
+        _ => {}
+    }
 }
 
 impl Instance {
+    /// Loads an instance with a known number of nodes and edges. `read_edges`
+    /// passes all edges of the input to the handler, and is called once for
+    /// each pass of the handler, see `ParsedEdgeHandler`.
     fn load(
         num_nodes: usize,
         num_edges: usize,
-        read_edges: impl FnOnce(&mut ParsedEdgeHandler) -> Result<()>,
+        options: LoadOptions,
+        mut read_edges: impl FnMut(&mut ParsedEdgeHandler) -> Result<()>,
     ) -> Result<Self> {
-        let mut handler = ParsedEdgeHandler {
-            edge_incidences: Vec::with_capacity(num_edges),
-            node_degrees: vec![0; num_nodes],
-        };
+        let mut handler = ParsedEdgeHandler::new(num_edges, options);
+        read_edges(&mut handler)?;
+        handler.start_fill_pass(num_nodes);
         read_edges(&mut handler)?;
+        Self::from_parsed_edges(handler)
+    }
+
+    /// Builds the instance from the edges collected by both passes of the
+    /// handler.
+    fn from_parsed_edges(mut handler: ParsedEdgeHandler) -> Result<Self> {
+        let num_counted_edges = handler.edge_starts.len() - 1;
+        ensure!(
+            handler.num_filled_edges == num_counted_edges,
+            "input changed while loading, found {} edges in the first pass but {} in the second",
+            num_counted_edges,
+            handler.num_filled_edges
+        );
+
+        let options = handler.options;
+        let edge_multiplicities = if options.keep_duplicate_edges {
+            Vec::new()
+        } else {
+            handler.merge_duplicate_edges()
+        };
+        let relabeling = if options.compact_nodes {
+            handler.compact_nodes()
+        } else {
+            None
+        };
         let ParsedEdgeHandler {
-            mut edge_incidences,
+            edge_nodes,
+            edge_starts,
             node_degrees,
+            requirements,
+            normalize_stats,
+            ..
         } = handler;
 
-        let mut node_incidences: Vec<_> = node_degrees
-            .iter()
-            .map(|&len| SkipVec::with_len(len))
-            .collect();
-        let mut rem_node_degrees = node_degrees;
-        for (edge, incidences) in edge_incidences.iter_mut().enumerate() {
-            let edge = EdgeIdx::from(edge);
-            for (edge_entry_idx, edge_entry) in incidences.iter_mut() {
-                let node = edge_entry.0.idx();
-                let node_entry_idx = node_incidences[node].len() - rem_node_degrees[node];
-                rem_node_degrees[node] -= 1;
-                edge_entry.1 = EntryIdx::from(node_entry_idx);
-                node_incidences[node][node_entry_idx] = (edge, EntryIdx::from(edge_entry_idx));
+        // Edge sizes and node degrees are bounded by these two counts
+        let num_nodes = node_degrees.len();
+        let num_edges = edge_starts.len() - 1;
+        ensure!(
+            fits_raw_idx(num_nodes) && fits_raw_idx(num_edges),
+            "instance with {} nodes and {} edges exceeds the index size, rebuild with the \
+             wide-indices feature enabled",
+            num_nodes,
+            num_edges
+        );
+
+        if options.normalize {
+            let NormalizeStats {
+                duplicate_nodes,
+                empty_edges,
+                ..
+            } = normalize_stats;
+            info!(
+                "Normalized input: removed {} duplicate nodes within edges, {} empty edges",
+                duplicate_nodes, empty_edges
+            );
+        }
+        if let Some(relabeling) = &relabeling {
+            info!(
+                "Compacted {} nodes to the {} nodes contained in edges",
+                relabeling.num_original_nodes(),
+                num_nodes
+            );
+        }
+        if normalize_stats.duplicate_edges > 0 {
+            info!(
+                "Merged {} duplicate edges into {} edges",
+                normalize_stats.duplicate_edges,
+                edge_multiplicities
+                    .iter()
+                    .filter(|&&multiplicity| multiplicity > 1)
+                    .count()
+            );
+        }
+
+        let incidences = Incidences::new(options.csr, num_nodes, edge_starts, edge_nodes);
+        let mut instance = Self::with_incidences(incidences, options);
+        instance.edge_multiplicities = edge_multiplicities;
+        instance.set_requirements(requirements);
+        instance.relabeling = relabeling;
+        Ok(instance)
+    }
+
+    /// Instance without deletions, multiplicities, requirements, or conflicts
+    /// using the given incidences.
+    fn with_incidences(incidences: Incidences, options: LoadOptions) -> Self {
+        let num_nodes = incidences.num_nodes();
+        let num_edges = incidences.num_edges();
+        let mut unit_edges: ContiguousIdxVec<_> = (0..num_edges).map(EdgeIdx::from).collect();
+        for edge_idx in 0..num_edges {
+            if incidences.edge_size(EdgeIdx::from(edge_idx)) != 1 {
+                unit_edges.delete(edge_idx);
             }
         }
 
-        Ok(Self {
+        Self {
             nodes: (0..num_nodes).map(NodeIdx::from).collect(),
             edges: (0..num_edges).map(EdgeIdx::from).collect(),
-            node_incidences,
-            edge_incidences,
-        })
+            incidences,
+            unit_edges,
+            edge_multiplicities: Vec::new(),
+            requirements: Vec::new(),
+            relabeling: None,
+            conflicts: Vec::new(),
+            deletions: options.check_restores.then(Vec::new),
+            num_changes: 0,
+            paranoid: options.paranoid,
+        }
     }
 
-    pub fn load_from_text(mut reader: impl BufRead) -> Result<Self> {
+    /// Copy of the instance in which node `i` is `nodes[i]` and edge `j` is
+    /// `edges[j]`, using the same layout of the incidences. The instance must
+    /// not have any deleted nodes or edges, and the copy has no relabeling.
+    pub(crate) fn renumbered(&self, nodes: &[NodeIdx], edges: &[EdgeIdx]) -> Self {
+        debug_assert!(
+            self.nodes.len() == self.num_nodes_total()
+                && self.edges.len() == self.num_edges_total()
+        );
+        let mut new_index = vec![NodeIdx::INVALID; self.num_nodes_total()];
+        for (idx, node) in nodes.iter().enumerate() {
+            new_index[node.idx()] = NodeIdx::from(idx);
+        }
+
+        let mut edge_starts = Vec::with_capacity(edges.len() + 1);
+        let mut edge_nodes = Vec::new();
+        edge_starts.push(0);
+        for &edge in edges {
+            let start = edge_nodes.len();
+            edge_nodes.extend(self.edge(edge).map(|node| new_index[node.idx()]));
+            edge_nodes[start..].sort_unstable();
+            edge_starts.push(edge_nodes.len());
+        }
+
+        let csr = matches!(self.incidences, Incidences::Csr(_));
+        let incidences = Incidences::new(csr, nodes.len(), edge_starts, edge_nodes);
+        let options = LoadOptions {
+            check_restores: self.deletions.is_some(),
+            paranoid: self.paranoid,
+            ..LoadOptions::default()
+        };
+        let mut instance = Self::with_incidences(incidences, options);
+        if !self.edge_multiplicities.is_empty() {
+            instance.edge_multiplicities = edges
+                .iter()
+                .map(|edge| self.edge_multiplicities[edge.idx()])
+                .collect();
+        }
+        if self.has_requirements() {
+            instance.set_requirements(
+                edges
+                    .iter()
+                    .map(|&edge| self.edge_requirement(edge))
+                    .collect(),
+            );
+        }
+        if self.has_conflicts() {
+            instance.conflicts = nodes
+                .iter()
+                .map(|&node| {
+                    let mut conflicts: Vec<_> = self
+                        .conflicts(node)
+                        .iter()
+                        .map(|other| new_index[other.idx()])
+                        .collect();
+                    conflicts.sort_unstable();
+                    conflicts
+                })
+                .collect();
+        }
+        instance
+    }
+
+    /// Sets the coverage requirement of each edge and updates the unit edges
+    /// accordingly. Requirements that are all 1 are not stored.
+    fn set_requirements(&mut self, requirements: Vec<usize>) {
+        if requirements.iter().all(|&requirement| requirement == 1) {
+            return;
+        }
+        for (edge_idx, &requirement) in requirements.iter().enumerate() {
+            let is_unit = self.edge_size(EdgeIdx::from(edge_idx)) == requirement;
+            if is_unit == self.unit_edges.is_deleted(edge_idx) {
+                if is_unit {
+                    self.unit_edges.restore(edge_idx);
+                } else {
+                    self.unit_edges.delete(edge_idx);
+                }
+            }
+        }
+        self.requirements = requirements;
+    }
+
+    /// Hints at input files using one-based indices without `--one-based`.
+    fn warn_if_first_node_unused(&self, options: LoadOptions) {
+        let first_node = NodeIdx::from(0_usize);
+        let unused = match &self.relabeling {
+            Some(relabeling) => relabeling.relabeled(first_node).is_none(),
+            None => self.num_nodes_total() > 0 && self.node_degree(first_node) == 0,
+        };
+        if !options.one_based && unused {
+            warn!("Node 0 is not part of any edge, the input might use one-based indices");
+        }
+    }
+
+    /// Adds groups of mutually exclusive nodes, of which at most one may be
+    /// part of a hitting set.
+    fn add_conflict_groups(&mut self, groups: Vec<Vec<usize>>, options: LoadOptions) -> Result<()> {
+        let num_nodes = self.num_nodes_total();
+        let num_input_nodes = self
+            .relabeling
+            .as_ref()
+            .map_or(num_nodes, NodeRelabeling::num_original_nodes);
+        let mut conflicts = vec![Vec::new(); num_nodes];
+        for (group_idx, group) in groups.into_iter().enumerate() {
+            let mut nodes = Vec::with_capacity(group.len());
+            for node_idx in group {
+                let zero_based_idx = if options.one_based {
+                    node_idx.checked_sub(1)
+                } else {
+                    Some(node_idx)
+                };
+                match zero_based_idx {
+                    // Dropped nodes are never part of a hitting set
+                    Some(idx) if idx < num_input_nodes => match &self.relabeling {
+                        Some(relabeling) => nodes.extend(relabeling.relabeled(NodeIdx::from(idx))),
+                        None => nodes.push(NodeIdx::from(idx)),
+                    },
+                    _ => bail!(
+                        "invalid node idx {} in conflict group {}",
+                        node_idx,
+                        group_idx
+                    ),
+                }
+            }
+            for &node in &nodes {
+                let others = nodes.iter().copied().filter(|&other| other != node);
+                conflicts[node.idx()].extend(others);
+            }
+        }
+
+        let mut num_pairs = 0;
+        for node_conflicts in &mut conflicts {
+            node_conflicts.sort_unstable();
+            node_conflicts.dedup();
+            num_pairs += node_conflicts.len();
+        }
+        if num_pairs > 0 {
+            info!("Loaded {} conflicting node pairs", num_pairs / 2);
+            self.conflicts = conflicts;
+        }
+        Ok(())
+    }
+
+    pub fn load_from_text(mut reader: impl BufRead + Seek, options: LoadOptions) -> Result<Self> {
         let time_before = Instant::now();
         let mut line = String::new();
 
-        reader.read_line(&mut line)?;
-        let mut numbers = line.split_ascii_whitespace().map(str::parse);
-        let num_nodes = numbers
-            .next()
-            .ok_or_else(|| anyhow!("Missing node count"))??;
-        let num_edges = numbers
-            .next()
-            .ok_or_else(|| anyhow!("Missing edge count"))??;
-        ensure!(
-            numbers.next().is_none(),
-            "Too many numbers in first input line"
-        );
+        let mut line_number = 0;
+        if !read_text_content_line(&mut reader, &mut line, &mut line_number)? {
+            return Err(TextParseError::new(
+                line_number + 1,
+                1,
+                None,
+                "missing header line".to_string(),
+            )
+            .into());
+        }
+        let mut tokens = TextLineTokens::new(line_number, &line);
+        if tokens.peek_token().1 == Some("p") {
+            // DIMACS-style header `p <format> <nodes> <edges>`, the format name is ignored
+            tokens.skip(2);
+        }
+        let num_nodes = tokens.next_number("node count")?;
+        let num_edges = tokens.next_number("edge count")?;
+        tokens.expect_end("header line")?;
+
+        let edges_position = reader.stream_position()?;
+        let header_line_number = line_number;
+        let instance = Self::load(num_nodes, num_edges, options, |handler| {
+            reader.seek(SeekFrom::Start(edges_position))?;
+            line_number = header_line_number;
+            let mut edges_read = 0;
+            while edges_read < num_edges {
+                if !read_text_content_line(&mut reader, &mut line, &mut line_number)? {
+                    return Err(TextParseError::new(
+                        line_number + 1,
+                        1,
+                        None,
+                        format!(
+                            "unexpected end of input, expected {} edges but found {}",
+                            num_edges, edges_read
+                        ),
+                    )
+                    .into());
+                }
+
+                edges_read += 1;
+                let mut tokens = TextLineTokens::new(line_number, &line);
+                let (degree_column, degree_token) = tokens.peek_token();
+                let degree = if options.no_edge_sizes {
+                    None
+                } else {
+                    Some(tokens.next_number("edge size")?)
+                };
+                let nodes: Vec<_> = tokens
+                    .remaining_numbers("node index")
+                    .collect::<Result<_>>()?;
+                if !options.normalize && degree.is_some_and(|degree| degree != nodes.len()) {
+                    return Err(TextParseError::new(
+                        line_number,
+                        degree_column,
+                        degree_token.map(str::to_string),
+                        format!(
+                            "edge size does not match the {} node indices in the line",
+                            nodes.len()
+                        ),
+                    )
+                    .into());
+                }
+                let (min_idx, max_idx) = if options.one_based {
+                    (1, num_nodes)
+                } else {
+                    (0, num_nodes.wrapping_sub(1))
+                };
+                if let Some(&(column, token, idx)) = nodes
+                    .iter()
+                    .find(|&&(_, _, idx)| idx < min_idx || idx > max_idx || num_nodes == 0)
+                {
+                    let hint = if !options.one_based && idx == num_nodes {
+                        " (use --one-based for inputs with one-based indices)"
+                    } else {
+                        ""
+                    };
+                    return Err(TextParseError::new(
+                        line_number,
+                        column,
+                        Some(token.to_string()),
+                        format!(
+                            "node index out of range, must be between {} and {}{}",
+                            min_idx, max_idx, hint
+                        ),
+                    )
+                    .into());
+                }
 
-        let instance = Self::load(num_nodes, num_edges, |handler| {
-            for _ in 0..num_edges {
-                line.clear();
-                reader.read_line(&mut line)?;
-                let mut numbers = line
-                    .split_ascii_whitespace()
-                    .map(|s| s.parse::<usize>().map_err(Error::from));
-                // Skip degree
-                numbers
-                    .next()
-                    .ok_or_else(|| anyhow!("empty edge line in input, expected degree"))??;
-                handler.handle_edge(numbers)?;
+                handler
+                    .handle_edge(nodes.into_iter().map(|(_, _, idx)| Ok(idx)))
+                    .with_context(|| format!("invalid edge in line {}", line_number))?;
             }
 
             Ok(())
         })?;
 
+        instance.warn_if_first_node_unused(options);
         info!(
             "Loaded text instance with {} nodes, {} edges in {:.2?}",
             num_nodes,
-            num_edges,
+            instance.num_edges_total(),
             time_before.elapsed(),
         );
         Ok(instance)
     }
 
-    pub fn load_from_json(mut reader: impl BufRead) -> Result<Self> {
+    pub fn load_from_json(mut reader: impl BufRead + Seek, options: LoadOptions) -> Result<Self> {
         let time_before = Instant::now();
 
-        // Usually faster for large inputs, see https://github.com/serde-rs/json/issues/160
-        let mut text = String::new();
-        reader.read_to_string(&mut text)?;
-        let JsonInstance { num_nodes, edges } = serde_json::from_str(&text)?;
+        // The input is streamed twice, reading everything besides the edges in the first pass
+        let instance_position = reader.stream_position()?;
+        let mut handler = ParsedEdgeHandler::new(0, options);
+        let mut deserializer = serde_json::Deserializer::from_reader(&mut reader);
+        let JsonInstance {
+            num_nodes,
+            num_edges,
+            requirements,
+            conflicts,
+        } = JsonInstanceStream {
+            handler: &mut handler,
+            requirements: None,
+        }
+        .deserialize(&mut deserializer)?;
+        deserializer.end()?;
 
-        let num_edges = edges.len();
-        let instance = Self::load(num_nodes, num_edges, |handler| {
-            for edge in edges {
-                handler.handle_edge(edge.into_iter().map(Ok))?;
-            }
-            Ok(())
-        })?;
+        if let Some(requirements) = &requirements {
+            ensure!(
+                requirements.len() == num_edges,
+                "got {} coverage requirements for {} edges",
+                requirements.len(),
+                num_edges
+            );
+        }
+        handler.start_fill_pass(num_nodes);
+        reader.seek(SeekFrom::Start(instance_position))?;
+        JsonInstanceStream {
+            handler: &mut handler,
+            requirements: requirements.as_deref(),
+        }
+        .deserialize(&mut serde_json::Deserializer::from_reader(&mut reader))?;
+        let mut instance = Self::from_parsed_edges(handler)?;
+        instance.add_conflict_groups(conflicts, options)?;
+        instance.warn_if_first_node_unused(options);
 
         info!(
             "Loaded json instance with {} nodes, {} edges in {:.2?}",
             num_nodes,
-            num_edges,
+            instance.num_edges_total(),
             time_before.elapsed(),
         );
         Ok(instance)
     }
 
+    /// Builds an instance from edges given as lists of node indices, which are
+    /// validated the same way as those read from a file.
+    pub fn from_edges(
+        num_nodes: usize,
+        edges: &[Vec<usize>],
+        options: LoadOptions,
+    ) -> Result<Self> {
+        Self::load(num_nodes, edges.len(), options, |handler| {
+            for (edge_idx, edge) in edges.iter().enumerate() {
+                handler
+                    .handle_edge(edge.iter().copied().map(Ok))
+                    .with_context(|| format!("invalid edge {}", edge_idx))?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Mapping to the original node indices, if nodes without edges were
+    /// dropped while loading
+    pub fn node_relabeling(&self) -> Option<&NodeRelabeling> {
+        self.relabeling.as_ref()
+    }
+
+    /// Number of identical edges in the input that were merged into this edge
+    pub fn edge_multiplicity(&self, edge: EdgeIdx) -> usize {
+        self.edge_multiplicities
+            .get(edge.idx())
+            .copied()
+            .unwrap_or(1)
+    }
+
+    /// Number of distinct nodes the edge still needs to be hit by. Nodes added
+    /// to the hitting set (by deleting their incident edges) count towards
+    /// this, so it only exceeds 1 for edges with coverage requirements.
+    pub fn edge_requirement(&self, edge: EdgeIdx) -> usize {
+        requirement(&self.requirements, edge)
+    }
+
+    /// Whether some edge of the input has to be hit more than once
+    pub fn has_requirements(&self) -> bool {
+        !self.requirements.is_empty()
+    }
+
+    /// Whether a set of nodes hits each alive edge as often as required
+    pub fn is_hitting_set(&self, nodes: &[NodeIdx]) -> bool {
+        let mut contained = vec![false; self.num_nodes_total()];
+        for &node in nodes {
+            contained[node.idx()] = true;
+        }
+        self.edges().iter().all(|&edge| {
+            self.edge(edge).filter(|node| contained[node.idx()]).count()
+                >= self.edge_requirement(edge)
+        })
+    }
+
+    /// Number of duplicate edges removed while loading, not counting the edges
+    /// they were merged into
+    pub fn num_merged_duplicate_edges(&self) -> usize {
+        self.edge_multiplicities
+            .iter()
+            .map(|&multiplicity| multiplicity - 1)
+            .sum()
+    }
+
+    /// Nodes that may not be part of a hitting set together with the given node
+    pub fn conflicts(&self, node: NodeIdx) -> &[NodeIdx] {
+        self.conflicts.get(node.idx()).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn has_conflicts(&self) -> bool {
+        !self.conflicts.is_empty()
+    }
+
+    /// Whether a set of nodes contains no two conflicting nodes
+    pub fn is_conflict_free(&self, nodes: &[NodeIdx]) -> bool {
+        if !self.has_conflicts() {
+            return true;
+        }
+
+        let mut contained = vec![false; self.num_nodes_total()];
+        for &node in nodes {
+            contained[node.idx()] = true;
+        }
+        nodes.iter().all(|&node| {
+            self.conflicts(node)
+                .iter()
+                .all(|other| !contained[other.idx()])
+        })
+    }
+
+    pub fn is_node_deleted(&self, node: NodeIdx) -> bool {
+        self.nodes.is_deleted(node.idx())
+    }
+
+    pub fn is_edge_deleted(&self, edge: EdgeIdx) -> bool {
+        self.edges.is_deleted(edge.idx())
+    }
+
     pub fn num_edges(&self) -> usize {
         self.edges.len()
     }
 
     pub fn num_nodes_total(&self) -> usize {
-        self.node_incidences.len()
+        self.incidences.num_nodes()
     }
 
     pub fn num_edges_total(&self) -> usize {
-        self.edge_incidences.len()
+        self.incidences.num_edges()
     }
 
     /// Edges incident to a node, sorted by increasing indices.
-    pub fn node(
-        &self,
-        node: NodeIdx,
-    ) -> impl Iterator<Item = EdgeIdx> + ExactSizeIterator + Clone + '_ {
-        self.node_incidences[node.idx()]
-            .iter()
-            .map(|(_, (edge, _))| *edge)
+    pub fn node(&self, node: NodeIdx) -> impl ExactSizeIterator<Item = EdgeIdx> + Clone + '_ {
+        self.incidences.node(node)
     }
 
-    /// Nodes incident to an edge, sorted by increasing indices.
-    pub fn edge(
-        &self,
-        edge: EdgeIdx,
-    ) -> impl Iterator<Item = NodeIdx> + ExactSizeIterator + Clone + '_ {
-        self.edge_incidences[edge.idx()]
-            .iter()
-            .map(|(_, (node, _))| *node)
+    /// Nodes incident to an edge, sorted by increasing indices.
+    pub fn edge(&self, edge: EdgeIdx) -> impl ExactSizeIterator<Item = NodeIdx> + Clone + '_ {
+        self.incidences.edge(edge)
     }
 
     /// Alive nodes in the instance, in arbitrary order.
@@ -224,76 +1677,287 @@ impl Instance {
     }
 
     pub fn node_degree(&self, node: NodeIdx) -> usize {
-        self.node_incidences[node.idx()].len()
+        self.incidences.node_degree(node)
     }
 
     pub fn edge_size(&self, edge: EdgeIdx) -> usize {
-        self.edge_incidences[edge.idx()].len()
+        self.incidences.edge_size(edge)
+    }
+
+    /// Alive nodes of each alive edge as a bitset, indexed by edge, or `None`
+    /// if the instance has more than `MAX_BITSET_NODES` nodes in total. Small
+    /// instances use these for word-parallel domination and packing checks.
+    pub fn edge_bitsets(&self) -> Option<Vec<u128>> {
+        if self.num_nodes_total() > MAX_BITSET_NODES {
+            return None;
+        }
+
+        let mut bitsets = vec![0; self.num_edges_total()];
+        for &edge in self.edges() {
+            bitsets[edge.idx()] = self
+                .edge(edge)
+                .fold(0, |bits, node| bits | (1 << node.idx()));
+        }
+        Some(bitsets)
+    }
+
+    /// Alive edges containing only as many nodes as they need to be hit by
+    /// (usually a single node), in arbitrary order.
+    pub fn unit_edges(&self) -> &[EdgeIdx] {
+        &self.unit_edges
+    }
+
+    /// Size of the largest alive edge, or 0 if there are no edges.
+    pub fn max_edge_size(&self) -> usize {
+        self.edges
+            .iter()
+            .map(|&edge| self.edge_size(edge))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Groups the alive edges into connected components, where two edges are
+    /// connected if they share a node.
+    pub fn edge_components(&self) -> Vec<Vec<EdgeIdx>> {
+        fn find(parents: &mut [usize], mut node: usize) -> usize {
+            while parents[node] != node {
+                parents[node] = parents[parents[node]];
+                node = parents[node];
+            }
+            node
+        }
+
+        let mut parents: Vec<_> = (0..self.num_nodes_total()).collect();
+        for &edge in self.edges() {
+            let mut nodes = self.edge(edge);
+            let Some(first) = nodes.next() else {
+                continue;
+            };
+            let first_root = find(&mut parents, first.idx());
+            for node in nodes {
+                let root = find(&mut parents, node.idx());
+                parents[root] = first_root;
+            }
+        }
+
+        let mut component_of_root = vec![usize::MAX; self.num_nodes_total()];
+        let mut components: Vec<Vec<EdgeIdx>> = Vec::new();
+        for &edge in self.edges() {
+            let Some(node) = self.edge(edge).next() else {
+                continue;
+            };
+            let root = find(&mut parents, node.idx());
+            if component_of_root[root] == usize::MAX {
+                component_of_root[root] = components.len();
+                components.push(Vec::new());
+            }
+            components[component_of_root[root]].push(edge);
+        }
+        components
+    }
+
+    /// Builds an instance containing only the given alive edges and their
+    /// alive nodes. Nodes are renumbered in order of appearance, and edge `i`
+    /// of the new instance is `edges[i]`.
+    pub fn restrict_to_edges(&self, edges: &[EdgeIdx]) -> Result<Self> {
+        let mut local_nodes = IdxHashMap::default();
+        let local_edges: Vec<Vec<usize>> = edges
+            .iter()
+            .map(|&edge| {
+                self.edge(edge)
+                    .map(|node| {
+                        let num_nodes = local_nodes.len();
+                        *local_nodes.entry(node).or_insert(num_nodes)
+                    })
+                    .collect()
+            })
+            .collect();
+        // Edges may have become identical by deleting nodes, but must keep their indices
+        let options = LoadOptions {
+            keep_duplicate_edges: true,
+            ..LoadOptions::default()
+        };
+        let mut instance = Self::from_edges(local_nodes.len(), &local_edges, options)?;
+        if self.has_requirements() {
+            instance.set_requirements(
+                edges
+                    .iter()
+                    .map(|&edge| self.edge_requirement(edge))
+                    .collect(),
+            );
+        }
+        Ok(instance)
     }
 
     /// Deletes a node from the instance.
     pub fn delete_node(&mut self, node: NodeIdx) {
         trace!("Deleting node {}", node);
-        for (_idx, (edge, entry_idx)) in &self.node_incidences[node.idx()] {
-            self.edge_incidences[edge.idx()].delete(entry_idx.idx());
-        }
+        self.record_deletion(Deletion::Node(node));
+        let (unit_edges, requirements) = (&mut self.unit_edges, &self.requirements);
+        self.incidences.delete_node(node, |edge, size| {
+            let requirement = requirement(requirements, edge);
+            update_unit_edge(
+                unit_edges,
+                edge,
+                size + 1 == requirement,
+                size == requirement,
+            );
+        });
         self.nodes.delete(node.idx());
     }
 
     /// Deletes an edge from the instance.
     pub fn delete_edge(&mut self, edge: EdgeIdx) {
         trace!("Deleting edge {}", edge);
-        for (_idx, (node, entry_idx)) in &self.edge_incidences[edge.idx()] {
-            self.node_incidences[node.idx()].delete(entry_idx.idx());
+        self.record_deletion(Deletion::Edge(edge));
+        self.unlink_edge(edge);
+    }
+
+    /// Deletes an edge without recording the deletion.
+    fn unlink_edge(&mut self, edge: EdgeIdx) {
+        self.incidences.delete_edge(edge);
+        if self.edge_size(edge) == self.edge_requirement(edge) {
+            self.unit_edges.delete(edge.idx());
         }
         self.edges.delete(edge.idx());
     }
 
+    /// Deletes all nodes conflicting with a node chosen for the hitting set and
+    /// returns them in order of deletion.
+    ///
+    /// The second return value is `false` if some edge has fewer nodes left
+    /// than it needs to be hit by afterwards, in which case the choice can
+    /// not be completed to a hitting set.
+    pub fn delete_conflicting_nodes(&mut self, node: NodeIdx) -> (Vec<NodeIdx>, bool) {
+        let mut deleted = Vec::new();
+        let mut feasible = true;
+        for conflict_idx in 0..self.conflicts(node).len() {
+            let other = self.conflicts[node.idx()][conflict_idx];
+            if self.is_node_deleted(other) {
+                continue;
+            }
+            self.delete_node(other);
+            deleted.push(other);
+            feasible &= self.is_coverable_without(other);
+        }
+        (deleted, feasible)
+    }
+
+    /// Whether all edges of a deleted node still have as many nodes as they
+    /// need to be hit by.
+    pub fn is_coverable_without(&self, node: NodeIdx) -> bool {
+        self.node(node)
+            .all(|edge| self.edge_size(edge) >= self.edge_requirement(edge))
+    }
+
     /// Restores a previously deleted node.
     ///
     /// All restore operations (node or edge) must be done in reverse order of
-    /// the corresponding deletions to produce sensible results.
+    /// the corresponding deletions to produce sensible results. If restore
+    /// checks are enabled, violating this panics, see `try_restore_node` for
+    /// a variant returning an error instead.
     pub fn restore_node(&mut self, node: NodeIdx) {
-        trace!("Restoring node {}", node);
-        for (_idx, (edge, entry_idx)) in self.node_incidences[node.idx()].iter().rev() {
-            self.edge_incidences[edge.idx()].restore(entry_idx.idx());
+        if let Err(err) = self.try_restore_node(node) {
+            panic!("{:#}", err);
         }
+    }
+
+    /// Restores a previously deleted node, failing without changing the
+    /// instance if restore checks are enabled and the node was not the last
+    /// deletion left to restore.
+    pub fn try_restore_node(&mut self, node: NodeIdx) -> Result<()> {
+        trace!("Restoring node {}", node);
+        self.check_restore(Deletion::Node(node))?;
+        let (unit_edges, requirements) = (&mut self.unit_edges, &self.requirements);
+        self.incidences.restore_node(node, |edge, size| {
+            let requirement = requirement(requirements, edge);
+            update_unit_edge(
+                unit_edges,
+                edge,
+                size - 1 == requirement,
+                size == requirement,
+            );
+        });
         self.nodes.restore(node.idx());
+        Ok(())
     }
 
     /// Restores a previously deleted edge.
     ///
     /// All restore operations (node or edge) must be done in reverse order of
-    /// the corresponding deletions to produce sensible results.
+    /// the corresponding deletions to produce sensible results. If restore
+    /// checks are enabled, violating this panics, see `try_restore_edge` for
+    /// a variant returning an error instead.
     pub fn restore_edge(&mut self, edge: EdgeIdx) {
+        if let Err(err) = self.try_restore_edge(edge) {
+            panic!("{:#}", err);
+        }
+    }
+
+    /// Restores a previously deleted edge, failing without changing the
+    /// instance if restore checks are enabled and the edge was not the last
+    /// deletion left to restore.
+    pub fn try_restore_edge(&mut self, edge: EdgeIdx) -> Result<()> {
         trace!("Restoring edge {}", edge);
-        for (_idx, (node, entry_idx)) in self.edge_incidences[edge.idx()].iter().rev() {
-            self.node_incidences[node.idx()].restore(entry_idx.idx());
+        self.check_restore(Deletion::Edge(edge))?;
+        self.relink_edge(edge);
+        Ok(())
+    }
+
+    /// Restores an edge without checking the order of restorations.
+    fn relink_edge(&mut self, edge: EdgeIdx) {
+        self.incidences.restore_edge(edge);
+        if self.edge_size(edge) == self.edge_requirement(edge) {
+            self.unit_edges.restore(edge.idx());
         }
         self.edges.restore(edge.idx());
     }
 
-    /// Deletes all edges incident to a node.
+    /// Changes the requirement of an alive edge.
+    fn set_requirement(&mut self, edge: EdgeIdx, requirement: usize) {
+        let size = self.edge_size(edge);
+        let old_requirement = mem::replace(&mut self.requirements[edge.idx()], requirement);
+        update_unit_edge(
+            &mut self.unit_edges,
+            edge,
+            size == old_requirement,
+            size == requirement,
+        );
+    }
+
+    /// Deletes all edges incident to a node, as done when adding the node to
+    /// the hitting set. Edges that need to be hit by more nodes are kept with
+    /// their requirement decreased by one instead.
     ///
     /// The node itself must have already been deleted.
     pub fn delete_incident_edges(&mut self, node: NodeIdx) {
-        // We want to iterate over the incidence of `node` while deleting
-        // edges, which in turn changes node incidences. This is safe, since
-        // `node` itself was already deleted. To make the borrow checker
-        // accept this, we temporarily move `node` incidence to a local
-        // variable, replacing it with an empty list. This should not be much
-        // slower than unsafe alternatives, since an incidence list is only
-        // 28 bytes large.
         trace!("Deleting all edges incident to {}", node);
         debug_assert!(
             self.nodes.is_deleted(node.idx()),
             "Node passed to delete_incident_edges must be deleted"
         );
-        let incidence = mem::take(&mut self.node_incidences[node.idx()]);
-        for (_, (edge, _)) in &incidence {
-            self.delete_edge(*edge);
+        self.record_deletion(Deletion::IncidentEdges(node));
+        if self.has_requirements() {
+            let incident_edges: Vec<_> = self.node(node).collect();
+            for edge in incident_edges {
+                let requirement = self.edge_requirement(edge);
+                if requirement > 1 {
+                    self.set_requirement(edge, requirement - 1);
+                } else {
+                    trace!("Deleting edge {}", edge);
+                    self.unlink_edge(edge);
+                }
+            }
+            return;
         }
-        self.node_incidences[node.idx()] = incidence;
+        let (unit_edges, edges) = (&mut self.unit_edges, &mut self.edges);
+        self.incidences.delete_incident_edges(node, |edge, size| {
+            trace!("Deleting edge {}", edge);
+            if size == 1 {
+                unit_edges.delete(edge.idx());
+            }
+            edges.delete(edge.idx());
+        });
     }
 
     /// Restores all incident edges to a node.
@@ -302,29 +1966,264 @@ impl Instance {
     /// `restore_*` methods, this must be done in reverse order of deletions.
     /// In particular, the node itself must still be deleted.
     pub fn restore_incident_edges(&mut self, node: NodeIdx) {
+        if let Err(err) = self.try_restore_incident_edges(node) {
+            panic!("{:#}", err);
+        }
+    }
+
+    /// Checked variant of `restore_incident_edges`, see `try_restore_node`.
+    pub fn try_restore_incident_edges(&mut self, node: NodeIdx) -> Result<()> {
         trace!("Restoring all edges incident to {}", node);
         debug_assert!(
             self.nodes.is_deleted(node.idx()),
             "Node passed to restore_incident_edges must be deleted"
         );
+        self.check_restore(Deletion::IncidentEdges(node))?;
+        if self.has_requirements() {
+            // The node still lists the edges it was incident to, both those
+            // deleted and those kept with a decreased requirement
+            let incident_edges: Vec<_> = self.node(node).collect();
+            for &edge in incident_edges.iter().rev() {
+                if self.is_edge_deleted(edge) {
+                    trace!("Restoring edge {}", edge);
+                    self.relink_edge(edge);
+                } else {
+                    self.set_requirement(edge, self.edge_requirement(edge) + 1);
+                }
+            }
+            return Ok(());
+        }
+        let (unit_edges, edges) = (&mut self.unit_edges, &mut self.edges);
+        self.incidences.restore_incident_edges(node, |edge, size| {
+            trace!("Restoring edge {}", edge);
+            if size == 1 {
+                unit_edges.restore(edge.idx());
+            }
+            edges.restore(edge.idx());
+        });
+        Ok(())
+    }
+
+    /// Checks that the instance is internally consistent, returning an error
+    /// describing the first problem found.
+    ///
+    /// This cross-checks the incidences of all alive nodes and edges against
+    /// each other and against the alive nodes and edges, as well as the
+    /// tracked unit edges. It takes time linear in the size of the instance
+    /// (times the size of the largest edge), and is meant for debugging.
+    pub fn check_invariants(&self) -> Result<()> {
+        self.incidences.check_invariants()?;
+        self.nodes
+            .check_invariants()
+            .context("invalid list of alive nodes")?;
+        self.edges
+            .check_invariants()
+            .context("invalid list of alive edges")?;
+        self.unit_edges
+            .check_invariants()
+            .context("invalid list of unit edges")?;
+        ensure!(
+            self.nodes.len() <= self.num_nodes_total()
+                && self.num_edges() <= self.num_edges_total(),
+            "more alive nodes or edges than exist in total"
+        );
 
-        // See `delete_incident_edges` for an explanation of this swapping around
-        let incidence = mem::take(&mut self.node_incidences[node.idx()]);
+        for &node in self.nodes() {
+            self.check_node_incidence(node)?;
+        }
+        for &edge in self.edges() {
+            self.check_edge_incidence(edge)?;
+        }
+        for &edge in self.unit_edges() {
+            ensure!(
+                !self.edges.is_deleted(edge.idx()),
+                "deleted edge {} is tracked as a unit edge",
+                edge
+            );
+        }
+        Ok(())
+    }
+
+    fn check_node_incidence(&self, node: NodeIdx) -> Result<()> {
+        ensure!(
+            !self.is_node_deleted(node),
+            "alive node {} is marked as deleted",
+            node
+        );
+        let mut prev = None;
+        let mut degree = 0;
+        for edge in self.node(node) {
+            ensure!(
+                prev < Some(edge),
+                "edges of node {} are not sorted, {} follows {:?}",
+                node,
+                edge,
+                prev
+            );
+            ensure!(
+                !self.edges.is_deleted(edge.idx()),
+                "node {} is incident to deleted edge {}",
+                node,
+                edge
+            );
+            ensure!(
+                self.edge(edge).any(|other| other == node),
+                "node {} is incident to edge {}, but not the other way around",
+                node,
+                edge
+            );
+            prev = Some(edge);
+            degree += 1;
+        }
+        ensure!(
+            degree == self.node_degree(node),
+            "node {} has {} edges, but a degree of {}",
+            node,
+            degree,
+            self.node_degree(node)
+        );
+        Ok(())
+    }
 
-        // It is important that we restore the edges in reverse order
-        for (_, (edge, _)) in incidence.iter().rev() {
-            self.restore_edge(*edge);
+    fn check_edge_incidence(&self, edge: EdgeIdx) -> Result<()> {
+        let mut prev = None;
+        let mut size = 0;
+        for node in self.edge(edge) {
+            ensure!(
+                prev < Some(node),
+                "nodes of edge {} are not sorted, {} follows {:?}",
+                edge,
+                node,
+                prev
+            );
+            ensure!(
+                !self.is_node_deleted(node),
+                "edge {} contains deleted node {}",
+                edge,
+                node
+            );
+            ensure!(
+                self.node(node).any(|other| other == edge),
+                "edge {} contains node {}, but not the other way around",
+                edge,
+                node
+            );
+            prev = Some(node);
+            size += 1;
         }
-        self.node_incidences[node.idx()] = incidence;
+        ensure!(
+            size == self.edge_size(edge),
+            "edge {} has {} nodes, but a size of {}",
+            edge,
+            size,
+            self.edge_size(edge)
+        );
+        let requirement = self.edge_requirement(edge);
+        ensure!(
+            (size == requirement) != self.unit_edges.is_deleted(edge.idx()),
+            "edge {} of size {} with requirement {} is {}tracked as a unit edge",
+            edge,
+            size,
+            requirement,
+            if size == requirement { "not " } else { "" }
+        );
+        Ok(())
     }
 
-    pub fn export_as_ilp(&self, mut writer: impl Write) -> Result<()> {
-        writeln!(writer, "Minimize")?;
-        write!(writer, "  v{}", CompressedIlpName(self.nodes()[0]))?;
+    /// Makes the reductions run `check_invariants` after every node or edge
+    /// they delete or restore, panicking on the first inconsistency. This
+    /// makes solving drastically slower.
+    pub fn set_paranoid(&mut self, paranoid: bool) {
+        self.paranoid = paranoid;
+    }
+
+    pub fn is_paranoid(&self) -> bool {
+        self.paranoid
+    }
+
+    /// Records all following deletions, so that restorations happening out of
+    /// order are detected instead of silently corrupting the instance.
+    ///
+    /// Restorations of deletions made before enabling the checks are reported
+    /// as errors. The checks cost some time and memory, so they are meant for
+    /// debugging, e.g. when adding new reductions.
+    pub fn enable_restore_checks(&mut self) {
+        self.deletions.get_or_insert_with(Vec::new);
+    }
+
+    /// Number of deletions and restorations so far, which tells whether the
+    /// instance changed since a data structure derived from it was updated.
+    pub(crate) fn num_changes(&self) -> usize {
+        self.num_changes
+    }
+
+    fn record_deletion(&mut self, deletion: Deletion) {
+        self.num_changes += 1;
+        if let Some(deletions) = &mut self.deletions {
+            deletions.push(deletion);
+        }
+    }
+
+    fn check_restore(&mut self, restoration: Deletion) -> Result<()> {
+        self.num_changes += 1;
+        let Some(deletions) = &mut self.deletions else {
+            return Ok(());
+        };
+        match deletions.last() {
+            Some(&last) if last == restoration => {
+                deletions.pop();
+                Ok(())
+            }
+            Some(&last) => bail!(
+                "restoring {} out of order, the last deletion not restored yet is {}",
+                restoration,
+                last
+            ),
+            None => bail!(
+                "restoring {}, but there are no deletions left to restore",
+                restoration
+            ),
+        }
+    }
+
+    /// Writes an ILP in the CPLEX LP format whose optimal solutions are the
+    /// minimum hitting sets.
+    pub fn export_as_ilp(&self, writer: impl Write) -> Result<()> {
+        self.write_ilp(writer, None)
+    }
+
+    /// Writes an ILP in the CPLEX LP format without an objective, which is
+    /// feasible if and only if there is a hitting set of at most `max_size`
+    /// nodes. The cardinality constraint is named `size`.
+    pub fn export_as_decision_ilp(&self, writer: impl Write, max_size: usize) -> Result<()> {
+        self.write_ilp(writer, Some(max_size))
+    }
+
+    /// Pairs of conflicting nodes that are both alive, each listed once
+    fn conflict_pairs(&self) -> impl Iterator<Item = (NodeIdx, NodeIdx)> + '_ {
+        self.nodes().iter().flat_map(move |&node| {
+            self.conflicts(node)
+                .iter()
+                .filter(move |&&other| other > node && !self.is_node_deleted(other))
+                .map(move |&other| (node, other))
+        })
+    }
+
+    fn write_node_sum(&self, writer: &mut impl Write) -> Result<()> {
+        write!(writer, "v{}", CompressedIlpName(self.nodes()[0]))?;
         for &node in &self.nodes()[1..] {
             write!(writer, " + v{}", CompressedIlpName(node))?;
         }
-        writeln!(writer)?;
+        Ok(())
+    }
+
+    fn write_ilp(&self, mut writer: impl Write, max_size: Option<usize>) -> Result<()> {
+        writeln!(writer, "Minimize")?;
+        if max_size.is_none() {
+            write!(writer, "  ")?;
+            self.write_node_sum(&mut writer)?;
+            writeln!(writer)?;
+        }
 
         writeln!(writer, "Subject To")?;
         for &edge in self.edges() {
@@ -335,7 +2234,23 @@ impl Instance {
                 }
                 write!(writer, "v{}", CompressedIlpName(node))?;
             }
-            writeln!(writer, " >= 1")?;
+            writeln!(writer, " >= {}", self.edge_requirement(edge))?;
+        }
+        for (node, other) in self.conflict_pairs() {
+            writeln!(
+                writer,
+                "  c{}_{}: v{} + v{} <= 1",
+                CompressedIlpName(node),
+                CompressedIlpName(other),
+                CompressedIlpName(node),
+                CompressedIlpName(other)
+            )?;
+        }
+
+        if let Some(max_size) = max_size {
+            write!(writer, "  size: ")?;
+            self.write_node_sum(&mut writer)?;
+            writeln!(writer, " <= {}", max_size)?;
         }
 
         writeln!(writer, "Binaries")?;
@@ -348,4 +2263,542 @@ impl Instance {
         writeln!(writer, "End")?;
         Ok(())
     }
+
+    /// Writes an SMT-LIB 2 problem in the `QF_LIA` logic with a boolean
+    /// variable for each node, named like in the ILP, and minimizes the size
+    /// of the hitting set using the `minimize` command of Z3's optimization
+    /// extension.
+    pub fn export_as_smtlib(&self, writer: impl Write) -> Result<()> {
+        self.write_smtlib(writer, None)
+    }
+
+    /// Writes a standard SMT-LIB 2 problem in the `QF_LIA` logic, which is
+    /// satisfiable if and only if there is a hitting set of at most
+    /// `max_size` nodes.
+    pub fn export_as_decision_smtlib(&self, writer: impl Write, max_size: usize) -> Result<()> {
+        self.write_smtlib(writer, Some(max_size))
+    }
+
+    fn write_smtlib(&self, mut writer: impl Write, max_size: Option<usize>) -> Result<()> {
+        writeln!(writer, "(set-option :produce-models true)")?;
+        writeln!(writer, "(set-logic QF_LIA)")?;
+        for &node in self.nodes() {
+            writeln!(writer, "(declare-const v{} Bool)", CompressedIlpName(node))?;
+        }
+
+        // `or` and `+` require at least two arguments
+        for &edge in self.edges() {
+            write!(writer, "(assert ")?;
+            let requirement = self.edge_requirement(edge);
+            match self.edge(edge).len() {
+                0 => write!(writer, "false")?,
+                size if requirement > 1 => {
+                    write!(writer, "(>= (+")?;
+                    for node in self.edge(edge) {
+                        write!(writer, " (ite v{} 1 0)", CompressedIlpName(node))?;
+                    }
+                    if size == 1 {
+                        write!(writer, " 0")?;
+                    }
+                    write!(writer, ") {})", requirement)?;
+                }
+                1 => write!(
+                    writer,
+                    "v{}",
+                    CompressedIlpName(self.edge(edge).next().unwrap())
+                )?,
+                _ => {
+                    write!(writer, "(or")?;
+                    for node in self.edge(edge) {
+                        write!(writer, " v{}", CompressedIlpName(node))?;
+                    }
+                    write!(writer, ")")?;
+                }
+            }
+            writeln!(writer, ")")?;
+        }
+        for (node, other) in self.conflict_pairs() {
+            writeln!(
+                writer,
+                "(assert (not (and v{} v{})))",
+                CompressedIlpName(node),
+                CompressedIlpName(other)
+            )?;
+        }
+
+        write!(writer, "(define-fun size () Int ")?;
+        match self.nodes() {
+            [] => write!(writer, "0")?,
+            &[node] => write!(writer, "(ite v{} 1 0)", CompressedIlpName(node))?,
+            nodes => {
+                write!(writer, "(+")?;
+                for &node in nodes {
+                    write!(writer, " (ite v{} 1 0)", CompressedIlpName(node))?;
+                }
+                write!(writer, ")")?;
+            }
+        }
+        writeln!(writer, ")")?;
+        match max_size {
+            Some(max_size) => writeln!(writer, "(assert (<= size {}))", max_size)?,
+            None => writeln!(writer, "(minimize size)")?,
+        }
+
+        writeln!(writer, "(check-sat)")?;
+        writeln!(writer, "(get-model)")?;
+        writeln!(writer, "(exit)")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CompressedIlpName, EdgeIdx, Instance, InstanceBuilder, LoadOptions, NodeIdx, TextParseError,
+    };
+    use crate::{
+        sample::SubInstance,
+        small_indices::SmallIdx,
+        test_utils::{load_text, write_to_string, BRANCHING_HYPERGRAPH},
+    };
+    use std::io::Cursor;
+
+    /// Nodes of all edges of the instance, in order
+    fn edge_lists(instance: &Instance) -> Vec<Vec<usize>> {
+        instance
+            .edges()
+            .iter()
+            .map(|&edge| instance.edge(edge).map(|node| node.idx()).collect())
+            .collect()
+    }
+
+    fn text_parse_error(text: &str, options: LoadOptions) -> TextParseError {
+        Instance::load_from_text(Cursor::new(text), options)
+            .unwrap_err()
+            .downcast()
+            .expect("not a text parse error")
+    }
+
+    /// Asserts the position and token of the error, and that its message
+    /// contains `message`.
+    fn assert_text_parse_error(
+        text: &str,
+        (line, column, token): (usize, usize, Option<&str>),
+        message: &str,
+    ) {
+        let err = text_parse_error(text, LoadOptions::default());
+        assert_eq!((err.line, err.column), (line, column), "{}", err);
+        assert_eq!(err.token.as_deref(), token, "{}", err);
+        assert!(err.message.contains(message), "{}", err);
+    }
+
+    #[test]
+    fn text_header_errors() {
+        assert_text_parse_error("", (1, 1, None), "missing header line");
+        assert_text_parse_error("# comment\n\n", (3, 1, None), "missing header line");
+        assert_text_parse_error("4\n", (1, 2, None), "missing edge count");
+        assert_text_parse_error("x 3\n", (1, 1, Some("x")), "invalid node count");
+        assert_text_parse_error("4 -3\n", (1, 3, Some("-3")), "invalid edge count");
+        assert_text_parse_error(
+            "4 3 1\n",
+            (1, 5, Some("1")),
+            "too many numbers in header line",
+        );
+    }
+
+    #[test]
+    fn text_edge_errors() {
+        assert_text_parse_error("4 2\n2 0 1\n", (3, 1, None), "expected 2 edges but found 1");
+        assert_text_parse_error("4 1\n2. 0 1\n", (2, 1, Some("2.")), "invalid edge size");
+        assert_text_parse_error("4 1\n2 0 1x\n", (2, 5, Some("1x")), "invalid node index");
+        assert_text_parse_error(
+            "4 1\n2 0 4\n",
+            (2, 5, Some("4")),
+            "must be between 0 and 3 (use --one-based",
+        );
+        assert_text_parse_error(
+            "4 1\n3 0 1\n",
+            (2, 1, Some("3")),
+            "edge size does not match the 2 node indices",
+        );
+    }
+
+    #[test]
+    fn text_parse_error_display() {
+        let err = text_parse_error("4 1\n2 0 4\n", LoadOptions::default());
+        assert!(err
+            .to_string()
+            .starts_with("line 2, column 5: node index out of range"));
+        assert!(err.to_string().ends_with(" (found '4')"));
+
+        let err = text_parse_error("4\n", LoadOptions::default());
+        assert_eq!(err.to_string(), "line 1, column 2: missing edge count");
+
+        // The hint about one-based indices is only given for the index one past the end
+        let err = text_parse_error("4 1\n2  0 9\n", LoadOptions::default());
+        assert_eq!(
+            err.to_string(),
+            "line 2, column 6: node index out of range, must be between 0 and 3 (found '9')"
+        );
+    }
+
+    #[test]
+    fn text_edge_size_mismatch_is_accepted_when_normalizing() {
+        let options = LoadOptions {
+            normalize: true,
+            ..LoadOptions::default()
+        };
+        let instance =
+            Instance::load_from_text(Cursor::new("4 2\n3 0 1\n1 2 3\n"), options).unwrap();
+        assert_eq!(edge_lists(&instance), [vec![0, 1], vec![2, 3]]);
+    }
+
+    #[test]
+    fn text_comments_and_header() {
+        let plain = load_text("4 2\n2 0 1\n1 3\n");
+        let commented =
+            load_text("c comment\n# comment\n\np hs 4 2\n  # indented\n2 0 1\n\nc 1 2\n1 3\n");
+        assert_eq!(commented.num_nodes_total(), 4);
+        assert_eq!(edge_lists(&commented), edge_lists(&plain));
+
+        // Line numbers count comments and blank lines
+        assert_text_parse_error(
+            "# comment\n4 1\n\n2 0 5\n",
+            (4, 5, Some("5")),
+            "out of range",
+        );
+        assert_text_parse_error("p hs 4\n", (1, 7, None), "missing edge count");
+    }
+
+    #[test]
+    fn text_without_edge_sizes_round_trips() {
+        let options = LoadOptions {
+            no_edge_sizes: true,
+            keep_duplicate_edges: true,
+            ..LoadOptions::default()
+        };
+        let text = "# sizes omitted\np hs 5 3\n0 1\n\n1 2 4\nc last edge\n3\n";
+        let instance = Instance::load_from_text(Cursor::new(text), options).unwrap();
+        assert_eq!(edge_lists(&instance), [vec![0, 1], vec![1, 2, 4], vec![3]]);
+
+        let all = SubInstance::anonymize(&instance, 0, true);
+        let written = write_to_string(|writer| all.write_text(&instance, writer));
+        assert_eq!(written, "5 3\n2 0 1\n3 1 2 4\n1 3\n");
+        let reloaded = load_text(&written);
+        assert_eq!(reloaded.num_nodes_total(), instance.num_nodes_total());
+        assert_eq!(edge_lists(&reloaded), edge_lists(&instance));
+    }
+
+    #[test]
+    fn malformed_flexible_text() {
+        assert_text_parse_error("p hs 4 2 1\n", (1, 10, Some("1")), "too many numbers");
+        assert_text_parse_error("p hs\n", (1, 5, None), "missing node count");
+        assert_text_parse_error(
+            "4 1\n2 0 1 # one\n",
+            (2, 7, Some("#")),
+            "invalid node index",
+        );
+
+        let options = LoadOptions {
+            no_edge_sizes: true,
+            ..LoadOptions::default()
+        };
+        let err = text_parse_error("4 1\n0 x\n", options);
+        assert_eq!(
+            (err.line, err.column, err.token.as_deref()),
+            (2, 3, Some("x"))
+        );
+        // Without edge sizes, the first number is a node index
+        let err = text_parse_error("4 1\n2 0 3 4\n", options);
+        assert_eq!(
+            (err.line, err.column, err.token.as_deref()),
+            (2, 7, Some("4"))
+        );
+    }
+
+    /// The hypergraph of `ZERO_BASED_TEXT` using one-based indices
+    const ONE_BASED_TEXT: &str = "4 3\n2 1 2\n3 2 3 4\n1 4\n";
+    const ONE_BASED_JSON: &str =
+        r#"{"num_nodes": 4, "edges": [[1, 2], [2, 3, 4], [4]], "conflicts": [[1, 3]]}"#;
+    const ZERO_BASED_TEXT: &str = "4 3\n2 0 1\n3 1 2 3\n1 3\n";
+
+    fn one_based() -> LoadOptions {
+        LoadOptions {
+            one_based: true,
+            ..LoadOptions::default()
+        }
+    }
+
+    #[test]
+    fn one_based_input() {
+        let expected = edge_lists(&load_text(ZERO_BASED_TEXT));
+        let text = Instance::load_from_text(Cursor::new(ONE_BASED_TEXT), one_based()).unwrap();
+        assert_eq!(text.num_nodes_total(), 4);
+        assert_eq!(edge_lists(&text), expected);
+
+        let json = Instance::load_from_json(Cursor::new(ONE_BASED_JSON), one_based()).unwrap();
+        assert_eq!(json.num_nodes_total(), 4);
+        assert_eq!(edge_lists(&json), expected);
+        assert_eq!(
+            json.conflicts(NodeIdx::from(0_usize)),
+            [NodeIdx::from(2_usize)]
+        );
+    }
+
+    #[test]
+    fn invalid_one_based_input() {
+        let err = text_parse_error("4 1\n2 0 1\n", one_based());
+        assert_eq!(
+            (err.line, err.column, err.token.as_deref()),
+            (2, 3, Some("0"))
+        );
+        assert!(err.message.contains("must be between 1 and 4"), "{}", err);
+        let err = text_parse_error("4 1\n2 4 5\n", one_based());
+        assert_eq!(
+            (err.line, err.column, err.token.as_deref()),
+            (2, 5, Some("5"))
+        );
+
+        // Without the option, the last index of one-based input is out of range
+        assert_text_parse_error(ONE_BASED_TEXT, (3, 7, Some("4")), "use --one-based");
+
+        let json = r#"{"num_nodes": 4, "edges": [[0, 1]]}"#;
+        assert!(Instance::load_from_json(Cursor::new(json), one_based()).is_err());
+        let json = r#"{"num_nodes": 4, "edges": [[1, 2]], "conflicts": [[0, 1]]}"#;
+        assert!(Instance::load_from_json(Cursor::new(json), one_based()).is_err());
+    }
+
+    #[test]
+    fn json_entries_in_any_order() {
+        let json = r#"{"conflicts": [[0, 2]], "requirements": [1, 2, 1], "comment": {"edges": []},
+            "edges": [[1, 0], [1, 2, 3], [3]], "num_nodes": 4}"#;
+        let instance = Instance::load_from_json(Cursor::new(json), LoadOptions::default()).unwrap();
+        assert_eq!(instance.num_nodes_total(), 4);
+        assert_eq!(
+            edge_lists(&instance),
+            edge_lists(&load_text(ZERO_BASED_TEXT))
+        );
+        let requirements: Vec<_> = instance
+            .edges()
+            .iter()
+            .map(|&edge| instance.edge_requirement(edge))
+            .collect();
+        assert_eq!(requirements, [1, 2, 1]);
+        assert_eq!(
+            instance.conflicts(NodeIdx::from(0_usize)),
+            [NodeIdx::from(2_usize)]
+        );
+    }
+
+    #[test]
+    fn invalid_json_instances() {
+        let load_error = |json: &str| {
+            let err = Instance::load_from_json(Cursor::new(json), LoadOptions::default())
+                .unwrap_err()
+                .to_string();
+            let (message, _) = err.split_once(" at line ").unwrap_or((&err, ""));
+            message.to_string()
+        };
+        assert_eq!(
+            load_error(r#"{"edges": [[0]]}"#),
+            "missing field `num_nodes`"
+        );
+        assert_eq!(load_error(r#"{"num_nodes": 2}"#), "missing field `edges`");
+        assert_eq!(
+            load_error(r#"{"num_nodes": 2, "edges": [[0]]} 1"#),
+            "trailing characters"
+        );
+        assert_eq!(
+            load_error(r#"{"num_nodes": 2, "edges": [[0], [1, "1"]]}"#),
+            "invalid type: string \"1\", expected usize"
+        );
+        assert_eq!(
+            load_error(r#"{"num_nodes": 2, "edges": [[0], [1, 2]]}"#),
+            "invalid edge 1: invalid node idx in edge: 2"
+        );
+        assert_eq!(
+            load_error(r#"{"num_nodes": 2, "edges": [[0, 1], [1, 0, 1]]}"#),
+            "invalid edge 1: node 1 occurs multiple times in the same edge"
+        );
+        assert_eq!(
+            load_error(r#"{"num_nodes": 2, "edges": [[0], []]}"#),
+            "invalid edge 1: edges may not be empty"
+        );
+        assert_eq!(
+            load_error(r#"{"num_nodes": 2, "edges": [[0], [1]], "requirements": [1]}"#),
+            "got 1 coverage requirements for 2 edges"
+        );
+        assert_eq!(
+            load_error(r#"{"num_nodes": 2, "edges": [[0], [1]], "requirements": [1, 2]}"#),
+            "invalid edge 1: coverage requirement 2 exceeds edge size 1"
+        );
+    }
+
+    #[test]
+    fn compressed_ilp_names_round_trip() {
+        assert_eq!(CompressedIlpName(NodeIdx::from(0_usize)).to_string(), "");
+        assert_eq!(CompressedIlpName(NodeIdx::from(1_usize)).to_string(), "b");
+        assert_eq!(CompressedIlpName(NodeIdx::from(61_usize)).to_string(), "9");
+        assert_eq!(CompressedIlpName(NodeIdx::from(62_usize)).to_string(), "ab");
+
+        let max_idx = NodeIdx::INVALID.idx() - 1;
+        for idx in (0..5000).chain([238_327, 238_328, max_idx]) {
+            let name = CompressedIlpName(NodeIdx::from(idx)).to_string();
+            let parsed: CompressedIlpName<NodeIdx> = name.parse().unwrap();
+            assert_eq!(parsed.0.idx(), idx, "{:?}", name);
+        }
+
+        let instance = load_text("4 3\n2 0 1\n3 1 2 3\n1 3\n");
+        let ilp = write_to_string(|writer| instance.export_as_ilp(writer));
+        let mut nodes: Vec<_> = ilp
+            .split(|ch: char| !ch.is_ascii_alphanumeric())
+            .filter(|word| word.starts_with('v'))
+            .map(|variable| CompressedIlpName::parse_node_variable(variable).unwrap())
+            .collect();
+        nodes.sort_unstable();
+        nodes.dedup();
+        assert_eq!(nodes, instance.nodes());
+    }
+
+    #[test]
+    fn invalid_compressed_ilp_names() {
+        let parse = |name: &str| name.parse::<CompressedIlpName<EdgeIdx>>();
+        assert!(parse("a").is_err());
+        assert!(parse("ba").is_err());
+        assert!(parse("b-").is_err());
+        assert!(parse("b c").is_err());
+        assert!(parse("999999999999").is_err());
+        // The largest raw index is reserved for invalid indices
+        assert!(parse(&CompressedIlpName(EdgeIdx::INVALID).to_string()).is_err());
+
+        assert_eq!(
+            CompressedIlpName::parse_node_variable("vb").unwrap(),
+            NodeIdx::from(1_usize)
+        );
+        assert!(CompressedIlpName::parse_node_variable("eb").is_err());
+        assert!(CompressedIlpName::parse_node_variable("b").is_err());
+        assert!(CompressedIlpName::parse_node_variable("vba").is_err());
+    }
+
+    #[test]
+    fn builder_matches_text_instance() {
+        let text_instance = load_text(BRANCHING_HYPERGRAPH);
+        let mut builder = InstanceBuilder::new();
+        for edge in BRANCHING_HYPERGRAPH.lines().skip(1) {
+            let nodes: Vec<usize> = edge
+                .split_ascii_whitespace()
+                .skip(1)
+                .map(|node| node.parse().unwrap())
+                .collect();
+            builder.add_edge(&nodes);
+        }
+        assert_eq!(builder.num_edges(), text_instance.num_edges_total());
+
+        let instance = builder.build().unwrap();
+        assert_eq!(instance.num_nodes_total(), text_instance.num_nodes_total());
+        assert_eq!(edge_lists(&instance), edge_lists(&text_instance));
+        assert_eq!(instance.unit_edges(), text_instance.unit_edges());
+        for &node in text_instance.nodes() {
+            assert!(instance.node(node).eq(text_instance.node(node)));
+        }
+    }
+
+    #[test]
+    fn builder_node_count() {
+        let mut builder = InstanceBuilder::new();
+        builder.add_edge(&[3, 1]).add_edge(&[2]);
+        assert_eq!(builder.build().unwrap().num_nodes_total(), 4);
+        assert_eq!(builder.num_nodes(6).build().unwrap().num_nodes_total(), 6);
+
+        let mut builder = InstanceBuilder::new();
+        builder
+            .options(one_based())
+            .add_edge(&[3, 1])
+            .add_edge(&[2]);
+        let instance = builder.build().unwrap();
+        assert_eq!(instance.num_nodes_total(), 3);
+        assert_eq!(edge_lists(&instance), [vec![0, 2], vec![1]]);
+
+        assert_eq!(InstanceBuilder::new().build().unwrap().num_nodes_total(), 0);
+    }
+
+    #[test]
+    fn builder_rejects_invalid_edges() {
+        let build = |edges: &[&[usize]], options| {
+            let mut builder = InstanceBuilder::new();
+            builder.num_nodes(3).options(options);
+            for edge in edges {
+                builder.add_edge(edge);
+            }
+            builder.build()
+        };
+        let normalize = LoadOptions {
+            normalize: true,
+            ..LoadOptions::default()
+        };
+
+        let duplicate_node: &[&[usize]] = &[&[0, 1], &[2, 1, 2]];
+        let err = build(duplicate_node, LoadOptions::default()).unwrap_err();
+        assert!(format!("{:#}", err).starts_with("invalid edge 1: node 2 occurs multiple times"));
+        let instance = build(duplicate_node, normalize).unwrap();
+        assert_eq!(edge_lists(&instance), [vec![0, 1], vec![1, 2]]);
+
+        let empty_edge: &[&[usize]] = &[&[0], &[], &[1, 2]];
+        let err = build(empty_edge, LoadOptions::default()).unwrap_err();
+        assert_eq!(
+            format!("{:#}", err),
+            "invalid edge 1: edges may not be empty"
+        );
+        let instance = build(empty_edge, normalize).unwrap();
+        assert_eq!(edge_lists(&instance), [vec![0], vec![1, 2]]);
+
+        let out_of_range: &[&[usize]] = &[&[0, 1], &[1, 3]];
+        for options in [LoadOptions::default(), normalize] {
+            let err = build(out_of_range, options).unwrap_err();
+            assert_eq!(
+                format!("{:#}", err),
+                "invalid edge 1: invalid node idx in edge: 3"
+            );
+        }
+        let err = build(&[&[1, 2], &[0]], one_based()).unwrap_err();
+        assert_eq!(
+            format!("{:#}", err),
+            "invalid edge 1: invalid node idx in edge: 0"
+        );
+    }
+
+    #[test]
+    fn decision_ilp_constrains_size() {
+        let instance = load_text("4 3\n2 0 1\n3 1 2 3\n1 3\n");
+        let ilp = write_to_string(|writer| instance.export_as_decision_ilp(writer, 2));
+        let size = ilp
+            .lines()
+            .find(|line| line.trim_start().starts_with("size: "))
+            .expect("missing size constraint");
+        assert!(size.ends_with(" <= 2"));
+        assert_eq!(size.matches('v').count(), 4);
+    }
+
+    #[test]
+    fn decision_ilp_omits_objective() {
+        let instance = load_text("4 3\n2 0 1\n3 1 2 3\n1 3\n");
+        let ilp = write_to_string(|writer| instance.export_as_decision_ilp(writer, 2));
+        assert!(ilp.starts_with("Minimize\nSubject To\n"));
+
+        let ilp = write_to_string(|writer| instance.export_as_ilp(writer));
+        assert!(!ilp.starts_with("Minimize\nSubject To\n"));
+        assert!(!ilp.contains("size: "));
+    }
+
+    #[test]
+    fn decision_smtlib_asserts_size() {
+        let instance = load_text("4 3\n2 0 1\n3 1 2 3\n1 3\n");
+        let smtlib = write_to_string(|writer| instance.export_as_decision_smtlib(writer, 2));
+        assert!(smtlib.lines().any(|line| line == "(assert (<= size 2))"));
+        assert!(!smtlib.contains("(minimize size)"));
+
+        let smtlib = write_to_string(|writer| instance.export_as_smtlib(writer));
+        assert!(smtlib.lines().any(|line| line == "(minimize size)"));
+        assert!(!smtlib.contains("(assert (<= size"));
+    }
 }
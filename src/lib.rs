@@ -0,0 +1,69 @@
+//! Branch-and-bound solver for the minimum hitting set problem.
+//!
+//! The `findminhs` binary is a thin command line interface over this
+//! library. Enabling the `ffi` feature additionally exposes a small C ABI in
+//! the `ffi` module, and the `wasm` feature a JavaScript API in the `wasm`
+//! module. Modules only needed by the command line are not available on wasm.
+#![warn(clippy::all, clippy::pedantic)]
+#![allow(
+    clippy::similar_names,
+    clippy::cast_possible_truncation,
+    clippy::uninlined_format_args,
+    clippy::missing_errors_doc,
+    clippy::missing_panics_doc,
+    clippy::must_use_candidate,
+    clippy::module_name_repetitions
+)]
+
+pub mod activity;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod analysis;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod baseline;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod batch;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod bench;
+pub mod bound_selection;
+pub mod canonical;
+pub mod clock;
+pub mod coarsening;
+pub mod coverage;
+pub mod data_structures;
+pub mod difficulty;
+pub mod enumerate;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod implicit;
+pub mod incremental;
+pub mod instance;
+pub mod lower_bound;
+pub mod nogoods;
+pub mod preprocessing;
+pub mod presets;
+pub mod profile;
+pub mod progress;
+pub mod reductions;
+pub mod relabel;
+pub mod replay;
+pub mod report;
+pub mod restart;
+pub mod sample;
+pub mod search_tree;
+pub mod secondary;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod serve;
+pub mod settings_doc;
+pub mod small_indices;
+pub mod solution;
+pub mod solve;
+pub mod summary;
+pub mod symmetry;
+#[cfg(test)]
+mod test_utils;
+pub mod tiny;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod tune;
+pub mod upper_bound;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -1,21 +1,158 @@
 use crate::{
     create_idx_struct,
-    data_structures::subset_trie::SubsetTrie,
+    data_structures::subset_trie::{self, SubsetTrie, SubsetTrieArena},
     instance::{EdgeIdx, Instance, NodeIdx},
-    report::Settings,
-    small_indices::{IdxHashSet, SmallIdx},
+    report::{PackingOrder, Settings},
+    small_indices::{IdxHashMap, IdxHashSet, SmallIdx},
+    solve::SolverRng,
+};
+use rand::{seq::SliceRandom, SeedableRng};
+use std::{
+    cmp::{Ordering, Reverse},
+    fmt,
+    iter::Peekable,
 };
-use std::iter::Peekable;
 
 create_idx_struct!(PackingIdx);
 
+/// A lower bound on the size of any hitting set of an instance.
+///
+/// The solver computes the enabled bounds in `BuiltinBound::ALL` in every
+/// reduction round, followed by the custom bounds passed in
+/// `SolveHooks::lower_bounds`. A subproblem is pruned as soon as one of them
+/// shows that it can not lead to a smaller hitting set.
+pub trait LowerBound: Send + Sync {
+    /// Identifies the bound in logs
+    fn name(&self) -> &'static str;
+
+    /// Whether the bound is computed when solving with the given settings
+    fn is_enabled(&self, _settings: &Settings) -> bool {
+        true
+    }
+
+    fn compute(&self, instance: &Instance, settings: &Settings) -> usize;
+
+    /// Lower bounds on the size of any hitting set not containing the
+    /// respective node. Nodes whose bound is too large are forced into the
+    /// hitting set. Nodes that are not listed fall back to `compute`.
+    fn discard_bounds(&self, _instance: &Instance, _settings: &Settings) -> Vec<(NodeIdx, usize)> {
+        Vec::new()
+    }
+}
+
+impl fmt::Debug for dyn LowerBound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The lower bounds implemented by the solver, each controlled by an
+/// `enable_*_bound` setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuiltinBound {
+    MaxDegree,
+    SumDegree,
+    Efficiency,
+    Packing,
+
+    /// Requires the packing bound to be enabled, and is computed from its
+    /// packing
+    SumOverPacking,
+    Flow,
+    CliqueCover,
+}
+
+impl BuiltinBound {
+    /// All built-in bounds, in the order the solver computes them. The cheap
+    /// bounds come first, so that they can prune before the expensive ones
+    /// are computed.
+    pub const ALL: [Self; 7] = [
+        Self::MaxDegree,
+        Self::SumDegree,
+        Self::Efficiency,
+        Self::Packing,
+        Self::SumOverPacking,
+        Self::Flow,
+        Self::CliqueCover,
+    ];
+}
+
+impl LowerBound for BuiltinBound {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::MaxDegree => "max_degree",
+            Self::SumDegree => "sum_degree",
+            Self::Efficiency => "efficiency",
+            Self::Packing => "packing",
+            Self::SumOverPacking => "sum_over_packing",
+            Self::Flow => "flow",
+            Self::CliqueCover => "clique_cover",
+        }
+    }
+
+    fn is_enabled(&self, settings: &Settings) -> bool {
+        match self {
+            Self::MaxDegree => settings.enable_max_degree_bound,
+            Self::SumDegree => settings.enable_sum_degree_bound,
+            Self::Efficiency => settings.enable_efficiency_bound,
+            Self::Packing => settings.enable_packing_bound,
+            Self::SumOverPacking => {
+                settings.enable_packing_bound && settings.enable_sum_over_packing_bound
+            }
+            Self::Flow => settings.enable_flow_bound,
+            Self::CliqueCover => settings.enable_clique_cover_bound,
+        }
+    }
+
+    fn compute(&self, instance: &Instance, settings: &Settings) -> usize {
+        match self {
+            Self::MaxDegree => calc_max_degree_bound(instance).unwrap_or(usize::MAX),
+            Self::SumDegree => calc_sum_degree_bound(instance),
+            Self::Efficiency => calc_efficiency_bound(instance)
+                .0
+                .round()
+                .unwrap_or(usize::MAX),
+            Self::Packing => PackingBound::new(instance, settings).bound(),
+            Self::SumOverPacking => {
+                PackingBound::new(instance, settings).calc_sum_over_packing_bound(instance)
+            }
+            Self::Flow => calc_flow_bound(instance),
+            Self::CliqueCover => calc_clique_cover_bound(instance),
+        }
+    }
+
+    fn discard_bounds(&self, instance: &Instance, settings: &Settings) -> Vec<(NodeIdx, usize)> {
+        match self {
+            Self::Efficiency => {
+                let (_, discard_bounds) = calc_efficiency_bound(instance);
+                instance
+                    .nodes()
+                    .iter()
+                    .map(|&node| {
+                        let bound = discard_bounds[node.idx()].round().unwrap_or(usize::MAX);
+                        (node, bound)
+                    })
+                    .collect()
+            }
+            Self::Packing => PackingBound::new(instance, settings)
+                .calc_discard_bounds(instance)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
 pub fn calc_max_degree_bound(instance: &Instance) -> Option<usize> {
+    if instance.num_edges() == 0 {
+        return Some(0);
+    }
+
     instance
         .nodes()
         .iter()
         .map(|&node| instance.node_degree(node))
         .max()
-        .map(|max_degree| (instance.num_edges() + max_degree - 1) / max_degree)
+        .map(|max_degree| instance.num_edges().div_ceil(max_degree))
 }
 
 pub fn calc_sum_degree_bound(instance: &Instance) -> usize {
@@ -98,44 +235,299 @@ pub fn calc_efficiency_bound(instance: &Instance) -> (EfficiencyBound, Vec<Effic
     (bound, discard_bounds)
 }
 
+/// Lower bound from a maximum fractional matching of the edges of size two.
+///
+/// Every hitting set contains a vertex cover of the graph formed by these
+/// edges, which is at least as large as any fractional matching of the graph.
+/// A maximum fractional matching has half the size of a maximum matching in
+/// the bipartite double cover, in which each edge `{u, v}` joins the left copy
+/// of `u` to the right copy of `v` and vice versa. Other edges not containing
+/// any node of the graph are packed greedily on top, as they need distinct
+/// nodes.
+pub fn calc_flow_bound(instance: &Instance) -> usize {
+    let mut neighbors = vec![Vec::new(); instance.num_nodes_total()];
+    for &edge in instance.edges() {
+        if instance.edge_size(edge) == 2 {
+            let mut nodes = instance.edge(edge);
+            let (u, v) = (nodes.next().unwrap(), nodes.next().unwrap());
+            neighbors[u.idx()].push(v);
+            neighbors[v.idx()].push(u);
+        }
+    }
+
+    // Augmenting path search from each left node (Kuhn's algorithm), using an
+    // explicit stack of left nodes and the index of their next neighbor
+    let mut matched_left = vec![NodeIdx::INVALID; instance.num_nodes_total()];
+    let mut visited = vec![NodeIdx::INVALID; instance.num_nodes_total()];
+    let mut matching_size: usize = 0;
+    let mut stack = Vec::new();
+    for &start in instance.nodes() {
+        if neighbors[start.idx()].is_empty() {
+            continue;
+        }
+        stack.push((start, 0));
+        while let Some((left, next)) = stack.last_mut() {
+            let Some(&right) = neighbors[left.idx()].get(*next) else {
+                stack.pop();
+                continue;
+            };
+            *next += 1;
+            if visited[right.idx()] == start {
+                continue;
+            }
+            visited[right.idx()] = start;
+            let partner = matched_left[right.idx()];
+            if partner.valid() {
+                stack.push((partner, 0));
+                continue;
+            }
+
+            // Each node on the stack takes the right node it explored last
+            for (left, next) in stack.drain(..) {
+                matched_left[neighbors[left.idx()][next - 1].idx()] = left;
+            }
+            matching_size += 1;
+        }
+    }
+
+    let mut used: Vec<_> = neighbors.iter().map(|nodes| !nodes.is_empty()).collect();
+    let mut packing_size = 0;
+    for &edge in instance.edges() {
+        if instance.edge(edge).any(|node| used[node.idx()]) {
+            continue;
+        }
+        for node in instance.edge(edge) {
+            used[node.idx()] = true;
+        }
+        packing_size += 1;
+    }
+
+    matching_size.div_ceil(2) + packing_size
+}
+
+/// Lower bound from covering the graph formed by the edges of size two with
+/// node-disjoint cliques.
+///
+/// Every hitting set contains all but at most one node of each clique, and
+/// the cliques share no nodes, so these counts add up. The packing bound only
+/// takes a single edge of each clique, so this is stronger on dense graphs
+/// with many triangles. Cliques are grown greedily, starting from the nodes
+/// of largest degree in the graph and repeatedly adding the common neighbor
+/// of largest degree. As for the flow bound, other edges avoiding all clique
+/// nodes are packed greedily on top.
+pub fn calc_clique_cover_bound(instance: &Instance) -> usize {
+    let mut neighbors = vec![Vec::new(); instance.num_nodes_total()];
+    for &edge in instance.edges() {
+        if instance.edge_size(edge) == 2 {
+            let mut nodes = instance.edge(edge);
+            let (u, v) = (nodes.next().unwrap(), nodes.next().unwrap());
+            neighbors[u.idx()].push(v);
+            neighbors[v.idx()].push(u);
+        }
+    }
+    let mut starts: Vec<_> = instance
+        .nodes()
+        .iter()
+        .copied()
+        .filter(|node| !neighbors[node.idx()].is_empty())
+        .collect();
+    starts.sort_unstable_by_key(|node| Reverse(neighbors[node.idx()].len()));
+
+    let mut used = vec![false; instance.num_nodes_total()];
+    let mut is_neighbor = vec![false; instance.num_nodes_total()];
+    let mut candidates = Vec::new();
+    let mut clique_bound = 0;
+    for start in starts {
+        if used[start.idx()] {
+            continue;
+        }
+        candidates.clear();
+        candidates.extend(
+            neighbors[start.idx()]
+                .iter()
+                .copied()
+                .filter(|node| !used[node.idx()]),
+        );
+        if candidates.is_empty() {
+            // A clique of a single node does not add to the bound, so the
+            // node is left for the packing
+            continue;
+        }
+
+        used[start.idx()] = true;
+        while let Some(pos) = (0..candidates.len())
+            .max_by_key(|&pos| (neighbors[candidates[pos].idx()].len(), Reverse(pos)))
+        {
+            let node = candidates.swap_remove(pos);
+            used[node.idx()] = true;
+            clique_bound += 1;
+            for neighbor in &neighbors[node.idx()] {
+                is_neighbor[neighbor.idx()] = true;
+            }
+            candidates.retain(|candidate| is_neighbor[candidate.idx()]);
+            for neighbor in &neighbors[node.idx()] {
+                is_neighbor[neighbor.idx()] = false;
+            }
+        }
+    }
+
+    let mut packing_size = 0;
+    for &edge in instance.edges() {
+        if instance.edge(edge).any(|node| used[node.idx()]) {
+            continue;
+        }
+        for node in instance.edge(edge) {
+            used[node.idx()] = true;
+        }
+        packing_size += 1;
+    }
+
+    clique_bound + packing_size
+}
+
+/// Number of improvements found by the packing local search.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LocalSearchStats {
+    pub two_opt_swaps: usize,
+    pub three_opt_swaps: usize,
+}
+
 #[derive(Debug, Default)]
 pub struct PackingBound {
     packing: Vec<EdgeIdx>,
+
+    /// Number of distinct nodes needed to hit the packed edges as often as
+    /// they need to be hit
+    bound: usize,
+    local_search_stats: LocalSearchStats,
 }
 
 impl PackingBound {
     pub fn new(instance: &Instance, settings: &Settings) -> Self {
         let mut packing: Vec<_> = instance.edges().to_vec();
-        packing.sort_by_cached_key(|&edge| {
-            instance.edge(edge).fold((0, 0), |(sum, max), node| {
-                let degree = instance.node_degree(node);
-                (sum + degree, max.max(degree))
-            })
-        });
+        let degree_key = |edge: EdgeIdx| degree_key(instance, edge);
+        match settings.packing_order {
+            PackingOrder::DegreeSum => packing.sort_by_cached_key(|&edge| degree_key(edge)),
+            PackingOrder::Random => {
+                packing.shuffle(&mut SolverRng::seed_from_u64(settings.seed));
+            }
+            PackingOrder::EdgeSizeFirst => {
+                packing.sort_by_cached_key(|&edge| (instance.edge_size(edge), degree_key(edge)));
+            }
+        }
 
-        let mut disjoint = vec![true; instance.num_edges_total()];
-        packing.retain(|&edge| {
-            if !disjoint[edge.idx()] {
-                return false;
+        Self::extend(instance, settings, Vec::new(), &packing)
+    }
+
+    /// Repairs a packing computed before some nodes or edges of the instance
+    /// were deleted. Deleted edges are dropped, while the others stay pairwise
+    /// disjoint, as edges only shrink. The packing is then extended by the
+    /// remaining edges in arbitrary order, and improved by local search if
+    /// enabled. Paranoid instances verify that the kept edges are disjoint.
+    pub fn repair(instance: &Instance, settings: &Settings, mut packing: Vec<EdgeIdx>) -> Self {
+        packing.retain(|&edge| !instance.is_edge_deleted(edge));
+        if instance.is_paranoid() {
+            let mut hit = vec![false; instance.num_nodes_total()];
+            for &edge in &packing {
+                for node in instance.edge(edge) {
+                    assert!(
+                        !hit[node.idx()],
+                        "Reused packing contains overlapping edges at node {}",
+                        node
+                    );
+                    hit[node.idx()] = true;
+                }
             }
+        }
+        Self::extend(instance, settings, packing, instance.edges())
+    }
 
-            for node in instance.edge(edge) {
-                for overlapping_edge in instance.node(node) {
-                    disjoint[overlapping_edge.idx()] = false;
+    /// Adds the candidates in order if they are disjoint from all edges added
+    /// before, starting from a valid packing. On instances with few nodes, the
+    /// edges are compared as bitsets.
+    fn extend(
+        instance: &Instance,
+        settings: &Settings,
+        mut packing: Vec<EdgeIdx>,
+        candidates: &[EdgeIdx],
+    ) -> Self {
+        let edge_bitsets = instance.edge_bitsets();
+        if let Some(edge_bitsets) = &edge_bitsets {
+            let mut hit = packing
+                .iter()
+                .fold(0, |hit, edge| hit | edge_bitsets[edge.idx()]);
+            for &edge in candidates {
+                if hit & edge_bitsets[edge.idx()] == 0 {
+                    hit |= edge_bitsets[edge.idx()];
+                    packing.push(edge);
                 }
             }
-            true
-        });
+        } else {
+            Self::extend_by_incidences(instance, &mut packing, candidates);
+        }
 
+        let mut local_search_stats = LocalSearchStats::default();
         if settings.enable_local_search {
-            packing = improve_packing_by_local_search(instance, packing);
+            // The local search maximizes the number of packed edges, which
+            // can lower the bound if edges need to be hit more than once
+            let unimproved = instance.has_requirements().then(|| packing.clone());
+            packing = improve_packing_by_local_search(
+                instance,
+                edge_bitsets.as_deref(),
+                packing,
+                settings.local_search_three_opt_budget,
+                &mut local_search_stats,
+            );
+            if let Some(unimproved) = unimproved {
+                if packing_weight(instance, &unimproved) > packing_weight(instance, &packing) {
+                    packing = unimproved;
+                }
+            }
         }
 
-        Self { packing }
+        Self {
+            bound: packing_weight(instance, &packing),
+            packing,
+            local_search_stats,
+        }
+    }
+
+    /// Variant of `extend` without bitsets, marking the edges overlapping each
+    /// added edge using the incidences.
+    fn extend_by_incidences(
+        instance: &Instance,
+        packing: &mut Vec<EdgeIdx>,
+        candidates: &[EdgeIdx],
+    ) {
+        let mut disjoint = vec![true; instance.num_edges_total()];
+        let block_overlapping = |edge: EdgeIdx, disjoint: &mut [bool]| {
+            for node in instance.edge(edge) {
+                for overlapping_edge in instance.node(node) {
+                    disjoint[overlapping_edge.idx()] = false;
+                }
+            }
+        };
+        for &edge in packing.iter() {
+            block_overlapping(edge, &mut disjoint);
+        }
+        for &edge in candidates {
+            if disjoint[edge.idx()] {
+                block_overlapping(edge, &mut disjoint);
+                packing.push(edge);
+            }
+        }
     }
 
     pub fn bound(&self) -> usize {
-        self.packing.len()
+        self.bound
+    }
+
+    pub fn packing(&self) -> &[EdgeIdx] {
+        &self.packing
+    }
+
+    pub fn local_search_stats(&self) -> LocalSearchStats {
+        self.local_search_stats
     }
 
     pub fn calc_sum_over_packing_bound(&self, instance: &Instance) -> usize {
@@ -145,18 +537,32 @@ impl PackingBound {
             adjusted_degrees[node.idx()] = instance.node_degree(node);
         }
 
+        let mut edge_nodes = Vec::new();
         for &packed_edge in &self.packing {
-            let max_degree_node = instance
-                .edge(packed_edge)
-                .max_by_key(|&node| instance.node_degree(node))
-                .expect("Empty edge in packing");
-            covered_edges += instance.node_degree(max_degree_node);
-
             for node in instance.edge(packed_edge) {
                 adjusted_degrees[node.idx()] -= 1;
             }
 
-            adjusted_degrees[max_degree_node.idx()] = 0;
+            let requirement = instance.edge_requirement(packed_edge);
+            if requirement == 1 {
+                let max_degree_node = instance
+                    .edge(packed_edge)
+                    .max_by_key(|&node| instance.node_degree(node))
+                    .expect("Empty edge in packing");
+                covered_edges += instance.node_degree(max_degree_node);
+                adjusted_degrees[max_degree_node.idx()] = 0;
+                continue;
+            }
+
+            // The packed edge needs as many of its nodes as its requirement,
+            // which hit at most as many edges as the ones of largest degree
+            edge_nodes.clear();
+            edge_nodes.extend(instance.edge(packed_edge));
+            edge_nodes.sort_unstable_by_key(|&node| Reverse(instance.node_degree(node)));
+            for &node in edge_nodes.iter().take(requirement) {
+                covered_edges += instance.node_degree(node);
+                adjusted_degrees[node.idx()] = 0;
+            }
         }
 
         adjusted_degrees.sort_unstable();
@@ -173,13 +579,20 @@ impl PackingBound {
             })
             .count();
 
-        self.packing.len() + sum_bound
+        self.bound + sum_bound
     }
 
     pub fn calc_discard_bounds<'a>(
         &'a self,
         instance: &'a Instance,
-    ) -> impl Iterator<Item = (NodeIdx, usize)> + 'a {
+    ) -> Box<dyn Iterator<Item = (NodeIdx, usize)> + 'a> {
+        if let Some(edge_bitsets) = instance.edge_bitsets() {
+            return Box::new(
+                self.calc_discard_bounds_bitset(instance, &edge_bitsets)
+                    .into_iter(),
+            );
+        }
+
         let mut hit = vec![false; instance.num_nodes_total()];
         for &edge in &self.packing {
             for node in instance.edge(edge) {
@@ -205,49 +618,122 @@ impl PackingBound {
             }
         }
 
+        Box::new(
+            blocked_by
+                .into_iter()
+                .enumerate()
+                .filter_map(move |(idx, mut blocked)| {
+                    let blocking_node = NodeIdx::from(idx);
+                    blocked.sort_by_cached_key(|&edge| degree_key(instance, edge));
+
+                    blocked.retain(|&edge| {
+                        let can_be_added = instance
+                            .edge(edge)
+                            .all(|node| node == blocking_node || !hit[node.idx()]);
+                        if can_be_added {
+                            for node in instance.edge(edge) {
+                                hit[node.idx()] = true;
+                            }
+                            true
+                        } else {
+                            false
+                        }
+                    });
+
+                    let result = if blocked.is_empty() {
+                        None
+                    } else {
+                        Some((
+                            blocking_node,
+                            self.bound + packing_weight(instance, &blocked),
+                        ))
+                    };
+
+                    for edge in blocked {
+                        for node in instance.edge(edge) {
+                            if node != blocking_node {
+                                hit[node.idx()] = false;
+                            }
+                        }
+                    }
+
+                    result
+                }),
+        )
+    }
+
+    /// Word-parallel variant of `calc_discard_bounds` for small instances.
+    fn calc_discard_bounds_bitset(
+        &self,
+        instance: &Instance,
+        edge_bitsets: &[u128],
+    ) -> Vec<(NodeIdx, usize)> {
+        let hit = self
+            .packing
+            .iter()
+            .fold(0, |hit, edge| hit | edge_bitsets[edge.idx()]);
+
+        let mut blocked_by = vec![Vec::new(); instance.num_nodes_total()];
+        let packing_set: IdxHashSet<_> = self.packing.iter().copied().collect();
+        for &remaining_edge in instance.edges() {
+            if packing_set.contains(&remaining_edge) {
+                continue;
+            }
+
+            let blocking = edge_bitsets[remaining_edge.idx()] & hit;
+            assert_ne!(blocking, 0, "Edge could have been added to packing");
+            if blocking.is_power_of_two() {
+                blocked_by[blocking.trailing_zeros() as usize].push(remaining_edge);
+            }
+        }
+
         blocked_by
             .into_iter()
             .enumerate()
-            .filter_map(move |(idx, mut blocked)| {
-                let blocking_node = NodeIdx::from(idx);
-                blocked.sort_by_cached_key(|&edge| {
-                    instance.edge(edge).fold((0, 0), |(sum, max), node| {
-                        let degree = instance.node_degree(node);
-                        (sum + degree, max.max(degree))
-                    })
-                });
+            .filter_map(|(idx, mut blocked)| {
+                let blocking_bit = 1 << idx;
+                blocked.sort_by_cached_key(|&edge| degree_key(instance, edge));
 
+                let mut extended_hit = hit;
                 blocked.retain(|&edge| {
-                    let can_be_added = instance
-                        .edge(edge)
-                        .all(|node| node == blocking_node || !hit[node.idx()]);
+                    let bits = edge_bitsets[edge.idx()];
+                    let can_be_added = bits & extended_hit & !blocking_bit == 0;
                     if can_be_added {
-                        for node in instance.edge(edge) {
-                            hit[node.idx()] = true;
-                        }
-                        true
-                    } else {
-                        false
+                        extended_hit |= bits;
                     }
+                    can_be_added
                 });
 
-                let result = if blocked.is_empty() {
-                    None
-                } else {
-                    Some((blocking_node, self.packing.len() + blocked.len()))
-                };
-
-                for edge in blocked {
-                    for node in instance.edge(edge) {
-                        if node != blocking_node {
-                            hit[node.idx()] = false;
-                        }
-                    }
-                }
-
-                result
+                (!blocked.is_empty()).then(|| {
+                    (
+                        NodeIdx::from(idx),
+                        self.bound + packing_weight(instance, &blocked),
+                    )
+                })
             })
+            .collect()
+    }
+}
+
+/// Number of distinct nodes needed to hit pairwise disjoint edges as often as
+/// they need to be hit.
+fn packing_weight(instance: &Instance, packing: &[EdgeIdx]) -> usize {
+    if !instance.has_requirements() {
+        return packing.len();
     }
+    packing
+        .iter()
+        .map(|&edge| instance.edge_requirement(edge))
+        .sum()
+}
+
+/// Sum and maximum of the degrees of the nodes of an edge, by which edges are
+/// added to packings.
+fn degree_key(instance: &Instance, edge: EdgeIdx) -> (usize, usize) {
+    instance.edge(edge).fold((0, 0), |(sum, max), node| {
+        let degree = instance.node_degree(node);
+        (sum + degree, max.max(degree))
+    })
 }
 
 #[derive(Clone)]
@@ -289,11 +775,9 @@ where
                 (Some(item1), Some(item2)) if *item1 == *item2 => {
                     self.0.next();
                     self.1.next();
-                    continue;
                 }
                 (Some(_), Some(_)) => {
                     self.1.next();
-                    continue;
                 }
             }
         }
@@ -303,6 +787,7 @@ where
 fn find_two_opt_swap(
     instance: &Instance,
     available_nodes: &mut Vec<NodeIdx>,
+    trie_arena: &mut SubsetTrieArena<NodeIdx, EdgeIdx>,
     packing: &[EdgeIdx],
     blocked_by: &[Vec<EdgeIdx>],
     hit_by: &[PackingIdx],
@@ -324,7 +809,11 @@ fn find_two_opt_swap(
         let blocking_edge = packing[blocking];
         available_nodes.extend(instance.edge(blocking_edge));
         available_nodes.sort_unstable();
-        let mut trie: SubsetTrie<_, EdgeIdx, _> = SubsetTrie::new(instance.num_nodes_total());
+        let mut trie = SubsetTrie::new(
+            instance.num_nodes_total(),
+            subset_trie::DEFAULT_CHUNKED_MAX_VAL_RANGE,
+            trie_arena,
+        );
 
         for &blocked_edge in blocked {
             let available_iter =
@@ -344,7 +833,121 @@ fn find_two_opt_swap(
     None
 }
 
-fn improve_packing_by_local_search(instance: &Instance, mut packing: Vec<EdgeIdx>) -> Vec<EdgeIdx> {
+fn are_disjoint(
+    instance: &Instance,
+    edge_bitsets: Option<&[u128]>,
+    edge1: EdgeIdx,
+    edge2: EdgeIdx,
+) -> bool {
+    if let Some(edge_bitsets) = edge_bitsets {
+        return edge_bitsets[edge1.idx()] & edge_bitsets[edge2.idx()] == 0;
+    }
+
+    let mut iter1 = instance.edge(edge1).peekable();
+    let mut iter2 = instance.edge(edge2).peekable();
+    while let (Some(&node1), Some(&node2)) = (iter1.peek(), iter2.peek()) {
+        match node1.cmp(&node2) {
+            Ordering::Less => {
+                iter1.next();
+            }
+            Ordering::Greater => {
+                iter2.next();
+            }
+            Ordering::Equal => return false,
+        }
+    }
+    true
+}
+
+/// Uses up `amount` units of the budget, or all of it and returns `None` if
+/// it does not suffice.
+fn use_budget(budget: &mut usize, amount: usize) -> Option<()> {
+    let Some(left) = budget.checked_sub(amount) else {
+        *budget = 0;
+        return None;
+    };
+    *budget = left;
+    Some(())
+}
+
+/// Searches for two packing edges that can be replaced by three remaining edges.
+///
+/// Only edges blocked by at most the two removed packing edges are considered
+/// as replacements. Each remaining edge grouped by its blocking packing edges,
+/// each candidate edge, and each disjointness check between two candidate
+/// edges uses up one unit of the budget.
+fn find_three_opt_swap(
+    instance: &Instance,
+    edge_bitsets: Option<&[u128]>,
+    remaining: &[EdgeIdx],
+    blocked_by: &[Vec<EdgeIdx>],
+    hit_by: &[PackingIdx],
+    budget: &mut usize,
+) -> Option<([PackingIdx; 2], [EdgeIdx; 3])> {
+    use_budget(budget, remaining.len().max(1))?;
+    let mut blocked_by_pair: IdxHashMap<(PackingIdx, PackingIdx), Vec<EdgeIdx>> =
+        IdxHashMap::default();
+    for &remaining_edge in remaining {
+        let mut blocking = [PackingIdx::INVALID; 2];
+        let mut num_blocking = 0;
+        for node in instance.edge(remaining_edge) {
+            let packing_idx = hit_by[node.idx()];
+            if !packing_idx.valid() || blocking[..num_blocking].contains(&packing_idx) {
+                continue;
+            }
+            if num_blocking == 2 {
+                num_blocking += 1;
+                break;
+            }
+            blocking[num_blocking] = packing_idx;
+            num_blocking += 1;
+        }
+
+        if num_blocking == 2 {
+            blocking.sort_unstable();
+            blocked_by_pair
+                .entry((blocking[0], blocking[1]))
+                .or_default()
+                .push(remaining_edge);
+        }
+    }
+
+    let mut candidates = Vec::new();
+    for (&(blocking1, blocking2), blocked) in &blocked_by_pair {
+        candidates.clear();
+        candidates.extend_from_slice(blocked);
+        candidates.extend_from_slice(&blocked_by[blocking1.idx()]);
+        candidates.extend_from_slice(&blocked_by[blocking2.idx()]);
+        use_budget(budget, candidates.len())?;
+        let mut disjoint = |edge1, edge2| {
+            use_budget(budget, 1)?;
+            Some(are_disjoint(instance, edge_bitsets, edge1, edge2))
+        };
+        for (idx1, &edge1) in candidates.iter().enumerate() {
+            for (idx2, &edge2) in candidates.iter().enumerate().skip(idx1 + 1) {
+                if !disjoint(edge1, edge2)? {
+                    continue;
+                }
+
+                for &edge3 in &candidates[idx2 + 1..] {
+                    if disjoint(edge1, edge3)? && disjoint(edge2, edge3)? {
+                        return Some(([blocking1, blocking2], [edge1, edge2, edge3]));
+                    }
+                }
+            }
+        }
+    }
+
+    None
+}
+
+fn improve_packing_by_local_search(
+    instance: &Instance,
+    edge_bitsets: Option<&[u128]>,
+    mut packing: Vec<EdgeIdx>,
+    mut three_opt_budget: usize,
+    stats: &mut LocalSearchStats,
+) -> Vec<EdgeIdx> {
     let packing_set: IdxHashSet<_> = packing.iter().copied().collect();
     let mut remaining: Vec<_> = instance
         .edges()
@@ -357,6 +960,7 @@ fn improve_packing_by_local_search(instance: &Instance, mut packing: Vec<EdgeIdx
     let mut hit_by = vec![PackingIdx::INVALID; instance.num_nodes_total()];
     let mut blocked_by: Vec<Vec<_>> = Vec::new();
     let mut available_nodes = Vec::new();
+    let mut trie_arena = SubsetTrieArena::default();
 
     loop {
         // For each node, calculate which packing edge is hitting it (if any)
@@ -398,14 +1002,36 @@ fn improve_packing_by_local_search(instance: &Instance, mut packing: Vec<EdgeIdx
         let two_opt_swap = find_two_opt_swap(
             instance,
             &mut available_nodes,
+            &mut trie_arena,
             &packing,
             &blocked_by,
             &hit_by,
         );
-        let (removed_edge_idx, (added_edge1, added_edge2)) = match two_opt_swap {
-            Some(tuple) => tuple,
-            None => return packing,
+        let Some((removed_edge_idx, (added_edge1, added_edge2))) = two_opt_swap else {
+            let three_opt_swap = find_three_opt_swap(
+                instance,
+                edge_bitsets,
+                &remaining,
+                &blocked_by,
+                &hit_by,
+                &mut three_opt_budget,
+            );
+            let Some((removed_edge_indices, added_edges)) = three_opt_swap else {
+                return packing;
+            };
+
+            stats.three_opt_swaps += 1;
+            apply_three_opt_swap(
+                instance,
+                &mut packing,
+                &mut remaining,
+                &mut hit_by,
+                removed_edge_indices,
+                added_edges,
+            );
+            continue;
         };
+        stats.two_opt_swaps += 1;
 
         let removed_edge = packing[removed_edge_idx.idx()];
         packing.retain(|&edge| edge != removed_edge);
@@ -442,3 +1068,48 @@ fn improve_packing_by_local_search(instance: &Instance, mut packing: Vec<EdgeIdx
         }
     }
 }
+
+fn apply_three_opt_swap(
+    instance: &Instance,
+    packing: &mut Vec<EdgeIdx>,
+    remaining: &mut Vec<EdgeIdx>,
+    hit_by: &mut [PackingIdx],
+    removed_edge_indices: [PackingIdx; 2],
+    added_edges: [EdgeIdx; 3],
+) {
+    let removed_edges = removed_edge_indices.map(|idx| packing[idx.idx()]);
+    packing.retain(|edge| !removed_edges.contains(edge));
+    remaining.retain(|edge| !added_edges.contains(edge));
+    packing.extend_from_slice(&added_edges);
+    remaining.extend_from_slice(&removed_edges);
+
+    // As with 2-opt swaps, add any edges that became addable after the swap. These can only
+    // overlap the removed edges.
+    for &removed_edge in &removed_edges {
+        for node in instance.edge(removed_edge) {
+            hit_by[node.idx()] = PackingIdx::INVALID;
+        }
+    }
+    let dummy_idx = PackingIdx(0);
+    for &added_edge in &added_edges {
+        for node in instance.edge(added_edge) {
+            hit_by[node.idx()] = dummy_idx;
+        }
+    }
+    for &removed_edge in &removed_edges {
+        for removed_edge_node in instance.edge(removed_edge) {
+            for candidate_edge in instance.node(removed_edge_node) {
+                if instance
+                    .edge(candidate_edge)
+                    .all(|node| !hit_by[node.idx()].valid())
+                {
+                    packing.push(candidate_edge);
+                    remaining.retain(|&edge| edge != candidate_edge);
+                    for node in instance.edge(candidate_edge) {
+                        hit_by[node.idx()] = dummy_idx;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,25 +1,47 @@
 #![warn(clippy::all, clippy::pedantic)]
-#![allow(clippy::similar_names, clippy::cast_possible_truncation)]
-use crate::{instance::Instance, report::IlpReductionReport};
-use anyhow::{anyhow, Result};
-use log::{debug, info};
+#![allow(
+    clippy::similar_names,
+    clippy::cast_possible_truncation,
+    clippy::uninlined_format_args,
+    clippy::unnecessary_debug_formatting
+)]
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use findminhs::{
+    analysis,
+    analysis::Table,
+    baseline::{self, BaselineTolerances},
+    batch, bench, enumerate,
+    implicit::{self, CommandOracle},
+    instance::{Instance, LoadOptions, NodeIdx},
+    presets, profile,
+    progress::{IncumbentCallback, ProgressWriter, ReportStream},
+    reductions,
+    replay::{BranchingDecision, DecisionRecorder, Replay},
+    report::{Baseline, IlpReductionReport, InstanceProfile, Report, Settings},
+    sample::{SampleMethod, SubInstance},
+    serve,
+    serve::ServeConfig,
+    settings_doc,
+    small_indices::IdxHashSet,
+    solution::{self, SolutionFormat, SolutionMetadata},
+    solve,
+    solve::{Panicked, SolveHooks},
+    summary::{InputError, SolveSummary},
+    tune,
+    tune::TuneSpec,
+};
+use log::{debug, info, warn};
 use std::{
+    env,
     ffi::OsStr,
-    fs::File,
-    io::{self, BufReader, BufWriter},
-    path::PathBuf,
-    time::Instant,
+    fs::{self, File, OpenOptions},
+    io::{self, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    process, slice,
+    time::{Duration, Instant},
 };
 use structopt::{clap::AppSettings, StructOpt};
 
-mod data_structures;
-mod instance;
-mod lower_bound;
-mod reductions;
-mod report;
-mod small_indices;
-mod solve;
-
 const APP_SETTINGS: &[AppSettings] = &[
     AppSettings::DisableHelpSubcommand,
     AppSettings::SubcommandRequiredElseHelp,
@@ -30,12 +52,62 @@ const GLOBAL_APP_SETTINGS: &[AppSettings] =
 
 #[derive(Debug, StructOpt)]
 #[structopt(settings = APP_SETTINGS, global_settings = GLOBAL_APP_SETTINGS)]
+#[allow(clippy::large_enum_variant)]
 enum CliOpts {
     /// Run the solver on a given hypergraph
     Solve(SolveOpts),
 
-    /// Convert a hypergraph into an equivalent ILP
+    /// Convert a hypergraph into an equivalent ILP, as an LP or SMT-LIB file
     Ilp(IlpOpts),
+
+    /// Enumerate all inclusion-minimal hitting sets (minimal transversals) of a hypergraph
+    Enumerate(EnumerateOpts),
+
+    /// Run the solver on many hypergraphs, writing their solutions, reports, and a summary
+    Batch(BatchOpts),
+
+    /// Combine solver reports into a single table
+    ReportMerge(ReportMergeOpts),
+
+    /// Compare the solver reports of two runs on the same hypergraphs
+    ReportDiff(ReportDiffOpts),
+
+    /// Run the solver repeatedly and report statistics about runtimes and branching steps
+    Bench(BenchOpts),
+
+    /// Search for the settings solving a set of hypergraphs fastest
+    Tune(TuneOpts),
+
+    /// Solve hypergraphs sent as json lines over a local tcp socket
+    Serve(ServeOpts),
+
+    /// Solve a hypergraph whose edges are produced on demand by an oracle command
+    Implicit(ImplicitOpts),
+
+    /// Estimate how hard a hypergraph is to solve, without solving it
+    Estimate(EstimateOpts),
+
+    /// Print the json schema of the report or solution files
+    Schema(SchemaOpts),
+
+    /// Print the resolved settings, or describe all settings fields
+    Settings(PrintSettingsOpts),
+
+    /// Suggest settings based on the profile recorded in a report
+    RecommendSettings(RecommendSettingsOpts),
+
+    /// Extract a smaller hypergraph from a hypergraph, e.g., to reproduce a problem
+    Sample(SampleOpts),
+
+    /// Renumber the nodes and edges of a hypergraph randomly and drop its metadata, e.g., to share
+    /// it in a bug report
+    Anonymize(AnonymizeOpts),
+
+    /// Solve many hypergraphs and store their results as a baseline for later builds
+    RecordBaseline(RecordBaselineOpts),
+
+    /// Solve the hypergraphs of a baseline again and fail if the results got worse
+    CheckBaseline(CheckBaselineOpts),
 }
 
 #[derive(Debug, StructOpt)]
@@ -44,18 +116,143 @@ struct CommonOpts {
     #[structopt(parse(from_os_str), value_name = "hypergraph-file")]
     hypergraph: PathBuf,
 
+    #[structopt(flatten)]
+    format: FormatOpts,
+}
+
+impl CommonOpts {
+    fn load_instance(&self) -> Result<Instance> {
+        self.format.load_instance(&self.hypergraph)
+    }
+
+    fn load_instance_for_solving(&self, solver: &SolverOpts) -> Result<Instance> {
+        self.format
+            .load_instance_with(&self.hypergraph, solver.load_options(&self.format))
+    }
+}
+
+#[derive(Debug, StructOpt)]
+struct SettingsOpts {
+    /// Solver settings. Together with `--preset`, its entries replace the ones of the preset
+    #[structopt(parse(from_os_str), value_name = "settings-file")]
+    settings: Option<PathBuf>,
+
+    /// Use the built-in settings with this name instead of a settings file
+    #[structopt(long, possible_values = presets::PRESET_NAMES, value_name = "name")]
+    preset: Option<String>,
+}
+
+impl SettingsOpts {
+    fn load(&self) -> Result<Settings> {
+        match (&self.preset, &self.settings) {
+            (None, Some(path)) => read_settings(path),
+            (Some(preset), path) => {
+                info!("Using the {} preset", preset);
+                let overrides = path.as_deref().map(read_settings_entries).transpose()?;
+                presets::preset_settings(preset, overrides)
+                    .with_context(|| InputError(format!("failed to apply the {} preset", preset)))
+            }
+            (None, None) => Err(anyhow!(InputError(
+                "either a settings file or --preset is required".to_string()
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, StructOpt)]
+#[allow(clippy::struct_excessive_bools)]
+struct FormatOpts {
     /// Use the json format for the input hypergraph rather than the text-based one.
     #[structopt(short, long)]
     json: bool,
+
+    /// Fix duplicate nodes within edges and empty edges in the input hypergraph instead of
+    /// rejecting it.
+    #[structopt(long)]
+    normalize: bool,
+
+    /// Keep duplicate edges of the input hypergraph as separate edges instead of merging them.
+    #[structopt(long)]
+    keep_duplicate_edges: bool,
+
+    /// Lines of the text-based input format contain only the node indices of an edge, without the
+    /// leading edge size.
+    #[structopt(long, conflicts_with("json"))]
+    no_edge_sizes: bool,
+
+    /// Node indices in the input hypergraph start at 1 instead of 0. All other node indices (e.g.,
+    /// in the settings or the written solution) remain zero-based.
+    #[structopt(long)]
+    one_based: bool,
 }
 
-impl CommonOpts {
-    fn load_instance(&self) -> Result<Instance> {
-        let reader = BufReader::new(File::open(&self.hypergraph)?);
-        if self.json {
-            Instance::load_from_json(reader)
-        } else {
-            Instance::load_from_text(reader)
+impl FormatOpts {
+    fn load_options(&self) -> LoadOptions {
+        LoadOptions {
+            normalize: self.normalize,
+            keep_duplicate_edges: self.keep_duplicate_edges,
+            no_edge_sizes: self.no_edge_sizes,
+            one_based: self.one_based,
+            ..LoadOptions::default()
+        }
+    }
+
+    fn load_instance(&self, hypergraph: &Path) -> Result<Instance> {
+        self.load_instance_with(hypergraph, self.load_options())
+    }
+
+    fn load_instance_with(&self, hypergraph: &Path, options: LoadOptions) -> Result<Instance> {
+        let load = || {
+            let reader = BufReader::new(File::open(hypergraph)?);
+            if self.json {
+                Instance::load_from_json(reader, options)
+            } else {
+                Instance::load_from_text(reader, options)
+            }
+        };
+        load().with_context(|| {
+            InputError(format!(
+                "failed to load hypergraph from {}",
+                hypergraph.display()
+            ))
+        })
+    }
+}
+
+/// Options of the commands that solve hypergraphs, which change how the solver stores and checks
+/// the hypergraph
+#[derive(Debug, StructOpt)]
+#[allow(clippy::struct_excessive_bools)]
+struct SolverOpts {
+    /// Drop nodes not contained in any edge and renumber the remaining ones while solving. Hitting
+    /// sets and reports still use the original node indices.
+    #[structopt(long)]
+    compact_nodes: bool,
+
+    /// Store the incidences of the hypergraph in flat arrays instead of linked lists, which
+    /// improves cache locality on large hypergraphs.
+    #[structopt(long)]
+    csr: bool,
+
+    /// Check that the solver restores deleted nodes and edges in reverse order of their deletion,
+    /// aborting on violations instead of continuing with a corrupted hypergraph (slower).
+    #[structopt(long)]
+    check_restores: bool,
+
+    /// Check the consistency of the hypergraph after every node or edge deleted or restored by the
+    /// reductions, aborting on the first inconsistency (much slower).
+    #[structopt(long)]
+    paranoid: bool,
+}
+
+impl SolverOpts {
+    fn load_options(&self, format: &FormatOpts) -> LoadOptions {
+        LoadOptions {
+            compact_nodes: self.compact_nodes,
+            csr: self.csr,
+            check_restores: self.check_restores,
+            paranoid: self.paranoid,
+            ..format.load_options()
         }
     }
 }
@@ -78,14 +275,105 @@ struct IlpOpts {
         value_name = "file"
     )]
     report: Option<PathBuf>,
+
+    /// Export the decision version without an objective, which is feasible if and only if there
+    /// is a hitting set of at most k nodes
+    #[structopt(long, value_name = "k")]
+    decision: Option<usize>,
+
+    /// Format of the model: CPLEX LP, or SMT-LIB 2 (minimizing with Z3's optimization extension
+    /// unless `--decision` is given)
+    #[structopt(long, possible_values = &["lp", "smtlib"], default_value = "lp", value_name = "format")]
+    format: String,
 }
 
 #[derive(Debug, StructOpt)]
-struct SolveOpts {
+struct EnumerateOpts {
     #[structopt(flatten)]
     common: CommonOpts,
 
-    /// Solver settings
+    /// Only enumerate minimal hitting sets with at most this many nodes
+    #[structopt(long, value_name = "size")]
+    max_size: Option<usize>,
+
+    /// Write the hitting sets to this file instead of stdout, one json array per line
+    #[structopt(short, long, parse(from_os_str), value_name = "file")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, StructOpt)]
+struct BatchOpts {
+    /// Directory containing the input hypergraphs, or a file listing their paths, one per line
+    #[structopt(parse(from_os_str), value_name = "dir|list-file")]
+    instances: PathBuf,
+
+    #[structopt(flatten)]
+    settings: SettingsOpts,
+
+    #[structopt(flatten)]
+    format: FormatOpts,
+
+    #[structopt(flatten)]
+    solver: SolverOpts,
+
+    /// Write the solutions and reports of all hypergraphs as well as the summary.json and
+    /// summary.csv files to this directory
+    #[structopt(short, long, parse(from_os_str), value_name = "dir")]
+    output_dir: PathBuf,
+
+    #[structopt(flatten)]
+    solution_output: SolutionOutputOpts,
+
+    /// Number of hypergraphs solved in parallel
+    #[structopt(long, default_value = "1", value_name = "jobs")]
+    jobs: usize,
+
+    /// Stop solving each hypergraph after this many seconds (overrides the time limit from the
+    /// settings file)
+    #[structopt(long, value_name = "seconds")]
+    time_limit: Option<f64>,
+}
+
+#[derive(Debug, StructOpt)]
+struct SolutionOutputOpts {
+    /// Format of written solutions: a json array, or one node index per line
+    #[structopt(
+        long,
+        possible_values = &["json", "text"],
+        default_value = "json",
+        value_name = "format"
+    )]
+    solution_format: SolutionFormat,
+
+    /// Include the file name, size, optimality, solver version, and settings hash in written
+    /// solutions
+    #[structopt(long)]
+    solution_metadata: bool,
+}
+
+#[derive(Debug, StructOpt)]
+struct ServeOpts {
+    /// Address to listen on
+    #[structopt(long, default_value = "127.0.0.1:7878", value_name = "addr")]
+    listen: String,
+
+    /// Solver settings used for requests that don't contain settings
+    #[structopt(long, parse(from_os_str), value_name = "settings-file")]
+    settings: Option<PathBuf>,
+
+    /// Fix duplicate nodes within edges and empty edges in the received
+    /// hypergraphs instead of rejecting them.
+    #[structopt(long)]
+    normalize: bool,
+}
+
+#[derive(Debug, StructOpt)]
+struct ImplicitOpts {
+    /// Number of nodes of the hypergraph
+    #[structopt(value_name = "num-nodes")]
+    num_nodes: usize,
+
+    /// Solver settings used for each solve
     #[structopt(parse(from_os_str), value_name = "settings-file")]
     settings: PathBuf,
 
@@ -93,38 +381,1119 @@ struct SolveOpts {
     #[structopt(short, long, parse(from_os_str), value_name = "file")]
     solution: Option<PathBuf>,
 
+    /// Write a summary report to this file formatted as json
+    #[structopt(short, long, parse(from_os_str), value_name = "file")]
+    report: Option<PathBuf>,
+
+    /// Stop after passing this many hitting sets to the oracle
+    #[structopt(long, value_name = "n")]
+    max_iterations: Option<usize>,
+
+    /// Oracle command and its arguments. It receives each hitting set as a json array on a line
+    /// of its stdin and answers with a line containing an unhit edge as a json array, or `null` if
+    /// all edges are hit.
+    #[structopt(required = true, last = true, value_name = "oracle")]
+    oracle: Vec<String>,
+}
+
+#[derive(Debug, StructOpt)]
+struct BenchOpts {
+    #[structopt(flatten)]
+    common: CommonOpts,
+
+    #[structopt(flatten)]
+    solver: SolverOpts,
+
+    #[structopt(flatten)]
+    settings: SettingsOpts,
+
+    /// Number of measured runs
+    #[structopt(long, default_value = "10", value_name = "runs")]
+    runs: usize,
+
+    /// Number of unmeasured runs before the measured ones
+    #[structopt(long, default_value = "0", value_name = "runs")]
+    warmup: usize,
+
+    /// Use a different seed for each run (the seed from the settings plus the run index)
+    #[structopt(long)]
+    vary_seed: bool,
+
+    /// Pin the solver to this CPU core to reduce variance between runs
+    #[structopt(long, value_name = "core")]
+    pin_core: Option<usize>,
+
+    /// Write the statistics to this file instead of stdout, formatted as json
+    #[structopt(short, long, parse(from_os_str), value_name = "file")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, StructOpt)]
+struct TuneOpts {
+    /// Directory containing the input hypergraphs, or a file listing their paths, one per line
+    #[structopt(parse(from_os_str), value_name = "dir|list-file")]
+    instances: PathBuf,
+
+    /// Settings used for all parameters that are not tuned
+    #[structopt(parse(from_os_str), value_name = "base-settings-file")]
+    settings: PathBuf,
+
+    /// Json file describing the tuned parameters, their values, and the search strategy
+    #[structopt(parse(from_os_str), value_name = "spec-file")]
+    spec: PathBuf,
+
+    #[structopt(flatten)]
+    format: FormatOpts,
+
+    #[structopt(flatten)]
+    solver: SolverOpts,
+
+    /// Number of hypergraphs solved in parallel
+    #[structopt(long, default_value = "1", value_name = "jobs")]
+    jobs: usize,
+
+    /// Write the tuning report to this file instead of stdout, formatted as json
+    #[structopt(short, long, parse(from_os_str), value_name = "file")]
+    output: Option<PathBuf>,
+
+    /// Write the best settings found to this file
+    #[structopt(long, parse(from_os_str), value_name = "file")]
+    best_settings: Option<PathBuf>,
+}
+
+#[derive(Debug, StructOpt)]
+struct SampleOpts {
+    #[structopt(flatten)]
+    common: CommonOpts,
+
+    /// Keep this many edges, chosen uniformly at random
+    #[structopt(long, value_name = "count")]
+    random_edges: Option<usize>,
+
+    /// Keep the edges within `--radius` steps of this node
+    #[structopt(long, value_name = "node")]
+    ball: Option<usize>,
+
+    /// Number of steps from the node given by `--ball`
+    #[structopt(long, default_value = "1", value_name = "steps")]
+    radius: usize,
+
+    /// Keep the edges within a dense part of this many nodes
+    #[structopt(long, value_name = "nodes")]
+    densest: Option<usize>,
+
+    /// Seed for choosing random edges
+    #[structopt(long, default_value = "0", value_name = "seed")]
+    seed: u64,
+
+    /// Write the sub-hypergraph in the json format instead of the text-based one, which also
+    /// keeps the conflicts between its nodes
+    #[structopt(long)]
+    json_output: bool,
+
+    /// Write the original indices of the nodes and edges of the sub-hypergraph to this file as
+    /// json
+    #[structopt(long, parse(from_os_str), value_name = "file")]
+    mapping: Option<PathBuf>,
+
+    /// Write the sub-hypergraph to this file instead of stdout
+    #[structopt(short, long, parse(from_os_str), value_name = "file")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, StructOpt)]
+struct AnonymizeOpts {
+    #[structopt(flatten)]
+    common: CommonOpts,
+
+    /// Seed for the random permutations of the nodes and edges
+    #[structopt(long, default_value = "0", value_name = "seed")]
+    seed: u64,
+
+    /// Keep the numbering of the nodes and edges instead of permuting them. The written hypergraph
+    /// is then not anonymized, but the solver behaves identically on it
+    #[structopt(long)]
+    keep_numbering: bool,
+
+    /// Write the hypergraph in the json format instead of the text-based one, which also keeps
+    /// the conflicts between its nodes
+    #[structopt(long)]
+    json_output: bool,
+
+    /// Write the original indices of the nodes and edges to this file as json. Keep it private, as
+    /// it undoes the anonymization
+    #[structopt(long, parse(from_os_str), value_name = "file")]
+    mapping: Option<PathBuf>,
+
+    /// Write the anonymized hypergraph to this file instead of stdout
+    #[structopt(short, long, parse(from_os_str), value_name = "file")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, StructOpt)]
+struct RecordBaselineOpts {
+    /// Directory containing the input hypergraphs, or a file listing their paths, one per line
+    #[structopt(parse(from_os_str), value_name = "dir|list-file")]
+    instances: PathBuf,
+
+    #[structopt(flatten)]
+    settings: SettingsOpts,
+
+    #[structopt(flatten)]
+    format: FormatOpts,
+
+    #[structopt(flatten)]
+    solver: SolverOpts,
+
+    /// Number of hypergraphs solved in parallel
+    #[structopt(long, default_value = "1", value_name = "jobs")]
+    jobs: usize,
+
+    /// Write the baseline to this file
+    #[structopt(short, long, parse(from_os_str), value_name = "file")]
+    output: PathBuf,
+}
+
+#[derive(Debug, StructOpt)]
+struct CheckBaselineOpts {
+    /// Directory containing the input hypergraphs, or a file listing their paths, one per line
+    #[structopt(parse(from_os_str), value_name = "dir|list-file")]
+    instances: PathBuf,
+
+    /// Baseline written by `record-baseline`, whose settings are used for solving
+    #[structopt(parse(from_os_str), value_name = "baseline-file")]
+    baseline: PathBuf,
+
+    #[structopt(flatten)]
+    format: FormatOpts,
+
+    #[structopt(flatten)]
+    solver: SolverOpts,
+
+    /// Number of hypergraphs solved in parallel
+    #[structopt(long, default_value = "1", value_name = "jobs")]
+    jobs: usize,
+
+    /// Allowed relative increase of the branching steps, e.g. 0.1 for 10%
+    #[structopt(long, default_value = "0", value_name = "fraction")]
+    steps_tolerance: f64,
+
+    /// Allowed relative increase of the runtimes. Runtimes are not compared if omitted
+    #[structopt(long, value_name = "fraction")]
+    runtime_tolerance: Option<f64>,
+
+    /// Write the result of the check to this file instead of stdout, formatted as json
+    #[structopt(short, long, parse(from_os_str), value_name = "file")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, StructOpt)]
+struct TableOutputOpts {
+    /// Write the table as a json array of objects instead of csv
+    #[structopt(long)]
+    json_table: bool,
+
+    /// Write the table to this file instead of stdout
+    #[structopt(short, long, parse(from_os_str), value_name = "file")]
+    output: Option<PathBuf>,
+}
+
+impl TableOutputOpts {
+    fn write(&self, table: &Table) -> Result<()> {
+        let mut writer: Box<dyn Write> = match &self.output {
+            Some(output_file) => Box::new(BufWriter::new(File::create(output_file)?)),
+            None => Box::new(io::stdout().lock()),
+        };
+        if self.json_table {
+            table.write_json(&mut writer)?;
+            writeln!(writer)?;
+        } else {
+            table.write_csv(&mut writer)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, StructOpt)]
+struct EstimateOpts {
+    #[structopt(flatten)]
+    common: CommonOpts,
+
+    #[structopt(flatten)]
+    settings: SettingsOpts,
+
+    /// Write the estimate to this file instead of stdout
+    #[structopt(short, long, parse(from_os_str), value_name = "file")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, StructOpt)]
+struct SchemaOpts {
+    /// File whose schema is printed
+    #[structopt(possible_values = &["report", "solution"], value_name = "kind")]
+    kind: String,
+
+    /// Write the schema to this file instead of stdout
+    #[structopt(short, long, parse(from_os_str), value_name = "file")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, StructOpt)]
+struct PrintSettingsOpts {
+    #[structopt(flatten)]
+    settings: SettingsOpts,
+
+    /// Describe the type, default, allowed values, and pipeline stage of every settings field,
+    /// including the values of the given settings if any
+    #[structopt(long)]
+    describe: bool,
+
+    /// Write to this file instead of stdout
+    #[structopt(short, long, parse(from_os_str), value_name = "file")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, StructOpt)]
+struct RecommendSettingsOpts {
+    /// Report written by `solve`, whose profile the recommendation is based on
+    #[structopt(parse(from_os_str), value_name = "report")]
+    report: PathBuf,
+
+    /// Settings to adapt, instead of the ones recorded in the report
+    #[structopt(short, long, parse(from_os_str), value_name = "file")]
+    settings: Option<PathBuf>,
+
+    /// Write the settings to this file instead of stdout
+    #[structopt(short, long, parse(from_os_str), value_name = "file")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, StructOpt)]
+struct ReportMergeOpts {
+    /// Report files, or directories containing report files
+    #[structopt(parse(from_os_str), required = true, value_name = "report|dir")]
+    reports: Vec<PathBuf>,
+
+    #[structopt(flatten)]
+    output: TableOutputOpts,
+}
+
+#[derive(Debug, StructOpt)]
+struct ReportDiffOpts {
+    /// Report file, or directory containing the report files, of the first run
+    #[structopt(parse(from_os_str), value_name = "old-reports")]
+    old: PathBuf,
+
+    /// Report file, or directory containing the report files, of the second run
+    #[structopt(parse(from_os_str), value_name = "new-reports")]
+    new: PathBuf,
+
+    #[structopt(flatten)]
+    output: TableOutputOpts,
+}
+
+#[derive(Debug, StructOpt)]
+struct SolveOpts {
+    #[structopt(flatten)]
+    common: CommonOpts,
+
+    #[structopt(flatten)]
+    solver: SolverOpts,
+
+    #[structopt(flatten)]
+    settings: SettingsOpts,
+
+    /// Write the final hitting set to this file as a json array
+    #[structopt(short, long, parse(from_os_str), value_name = "file")]
+    solution: Option<PathBuf>,
+
     /// Write a detailed statistics report to this file formatted as json
     #[structopt(short, long, parse(from_os_str), value_name = "file")]
     report: Option<PathBuf>,
+
+    #[structopt(flatten)]
+    solution_output: SolutionOutputOpts,
+
+    /// Format of the report: nested json, or a single csv row with the runtimes and counters
+    #[structopt(long, possible_values = &["json", "csv"], default_value = "json", value_name = "format")]
+    report_format: String,
+
+    /// Select at most this many nodes, maximizing the number of hit edges if no hitting set of
+    /// this size exists (overrides the budget from the settings file)
+    #[structopt(long, value_name = "k")]
+    budget: Option<usize>,
+
+    /// Stop solving after this many seconds and keep the best hitting set found so far (overrides
+    /// the time limit from the settings file)
+    #[structopt(long, value_name = "seconds")]
+    time_limit: Option<f64>,
+
+    /// Stop solving once the best hitting set is within this many percent of the lower bound
+    /// (overrides the gap from the settings file)
+    #[structopt(long, value_name = "percent")]
+    gap: Option<f64>,
+
+    /// Seed for randomized components, overriding the seed from the settings file
+    #[structopt(long, value_name = "seed")]
+    seed: Option<u64>,
+
+    /// Omit wall-clock measurements from the report so that identical runs produce identical
+    /// reports, and solve the hypergraph numbered canonically so that renumbering it does not
+    /// change the search
+    #[structopt(long)]
+    deterministic: bool,
+
+    /// Return the lexicographically smallest minimum hitting set, using additional solver runs
+    #[structopt(long)]
+    canonical_solution: bool,
+
+    /// Initialize the solver with the hitting set from this file (a json array, e.g., written by
+    /// `--solution`), replacing the initial hitting set from the settings file
+    #[structopt(long, parse(from_os_str), value_name = "file")]
+    initial_solution: Option<PathBuf>,
+
+    /// Find an optimal completion of the partial hitting set in this file (a json array), in
+    /// addition to the one from the settings file
+    #[structopt(long, parse(from_os_str), value_name = "file")]
+    complete: Option<PathBuf>,
+
+    /// Json array of nodes that must be part of the hitting set, in addition to the ones from the
+    /// settings file
+    #[structopt(long, parse(from_os_str), value_name = "file")]
+    forced: Option<PathBuf>,
+
+    /// Json array of nodes that must not be part of the hitting set, in addition to the ones from
+    /// the settings file
+    #[structopt(long, parse(from_os_str), value_name = "file")]
+    forbidden: Option<PathBuf>,
+
+    /// Periodically write progress events as json lines to this file (or to stderr if given as
+    /// "stderr")
+    #[structopt(long, parse(from_os_str), value_name = "file|stderr")]
+    progress: Option<PathBuf>,
+
+    /// Minimum number of seconds between two progress events
+    #[structopt(long, default_value = "1", value_name = "seconds")]
+    progress_interval: f64,
+
+    /// Append each improved hitting set found during the search to this file as a json line
+    #[structopt(long, parse(from_os_str), value_name = "file")]
+    incumbent_log: Option<PathBuf>,
+
+    /// Write the upper bound improvements to this file as json lines instead of keeping them in
+    /// the report, which then refers to the file
+    #[structopt(long, parse(from_os_str), value_name = "file")]
+    report_stream: Option<PathBuf>,
+
+    /// Record the branch-and-bound tree and write it to this file, formatted as graphviz if the
+    /// file ends in ".dot" and as json otherwise
+    #[structopt(long, parse(from_os_str), value_name = "file")]
+    search_tree: Option<PathBuf>,
+
+    /// Write the nodes and edges removed or forced before the first branching step, together with
+    /// the responsible rules, to this file formatted as json
+    #[structopt(long, parse(from_os_str), value_name = "file")]
+    preprocess_report: Option<PathBuf>,
+
+    /// Maximum number of search nodes recorded with `--search-tree`
+    #[structopt(long, default_value = "10000", value_name = "n")]
+    search_tree_max_nodes: usize,
+
+    /// Record the branching decisions to this file as json lines, for use with `--replay`
+    #[structopt(long, parse(from_os_str), value_name = "file")]
+    record_decisions: Option<PathBuf>,
+
+    /// Branch on the nodes recorded in this file (written by `--record-decisions`) instead of
+    /// choosing them by the branching heuristic
+    #[structopt(long, parse(from_os_str), value_name = "file")]
+    replay: Option<PathBuf>,
+
+    /// Stop the replay after this many branching steps
+    #[structopt(long, requires = "replay", value_name = "step")]
+    replay_until: Option<usize>,
 }
 
-fn solve(opts: SolveOpts) -> Result<()> {
-    let file_name = opts
-        .common
-        .hypergraph
-        .file_name()
+fn read_node_list(path: &Path) -> Result<Vec<NodeIdx>> {
+    let read = || solution::read_solution(BufReader::new(File::open(path)?));
+    read().with_context(|| InputError(format!("failed to read node list from {}", path.display())))
+}
+
+fn read_settings(path: &Path) -> Result<Settings> {
+    let read = || -> Result<_> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(reader)?)
+    };
+    read().with_context(|| InputError(format!("failed to read settings from {}", path.display())))
+}
+
+fn read_settings_entries(path: &Path) -> Result<serde_json::Map<String, serde_json::Value>> {
+    let read = || -> Result<_> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(reader)?)
+    };
+    read().with_context(|| InputError(format!("failed to read settings from {}", path.display())))
+}
+
+fn file_name(path: &Path) -> Result<&str> {
+    path.file_name()
         .and_then(OsStr::to_str)
-        .ok_or_else(|| anyhow!("File name can't be extracted"))?
-        .to_string();
+        .ok_or_else(|| anyhow!("File name can't be extracted"))
+}
+
+fn solve_hypergraph(
+    hypergraph: &Path,
+    format: &FormatOpts,
+    solver: &SolverOpts,
+    settings: Settings,
+    hooks: SolveHooks,
+) -> Result<(Vec<NodeIdx>, Report)> {
+    let file_name = file_name(hypergraph)?.to_string();
+    let instance = format.load_instance_with(hypergraph, solver.load_options(format))?;
+    info!("Solving {:?}", hypergraph);
+    solve::solve(instance, file_name, settings, hooks)
+}
+
+fn read_decisions(path: &Path) -> Result<Vec<BranchingDecision>> {
+    let read = || -> Result<_> {
+        let reader = BufReader::new(File::open(path)?);
+        Replay::read_decisions(reader)
+    };
+    read().with_context(|| {
+        InputError(format!(
+            "failed to read branching decisions from {}",
+            path.display()
+        ))
+    })
+}
+
+/// Hitting set and report to write after solving. If the solver panicked,
+/// these are the best hitting set found before the panic and the partial
+/// report, so that the incumbent is not lost.
+fn results_to_write(result: &Result<(Vec<NodeIdx>, Report)>) -> Option<(&[NodeIdx], &Report)> {
+    match result {
+        Ok((final_hs, report)) => Some((final_hs, report)),
+        Err(err) => err
+            .downcast_ref::<Panicked>()
+            .map(|panicked| (&*panicked.hitting_set, &*panicked.report)),
+    }
+}
+
+fn write_solution_and_report(
+    final_hs: &[NodeIdx],
+    report: &Report,
+    solution_file: Option<&Path>,
+    solution_output: &SolutionOutputOpts,
+    report_file: Option<&Path>,
+    csv_report: bool,
+) -> Result<()> {
+    if let Some(solution_file) = solution_file {
+        debug!("Writing solution to {}", solution_file.display());
+        let writer = BufWriter::new(File::create(solution_file)?);
+        let metadata = if solution_output.solution_metadata {
+            Some(SolutionMetadata::new(report)?)
+        } else {
+            None
+        };
+        solution::write_solution(writer, final_hs, solution_output.solution_format, metadata)?;
+    }
+    if let Some(report_file) = report_file {
+        debug!("Writing report to {}", report_file.display());
+        let writer = BufWriter::new(File::create(report_file)?);
+        if csv_report {
+            analysis::flatten_report(report)?.write_csv(writer)?;
+        } else {
+            serde_json::to_writer(writer, report)?;
+        }
+    }
+    Ok(())
+}
+
+fn solve(opts: &SolveOpts) -> Result<Report> {
+    let mut settings = opts.settings.load()?;
+    if opts.budget.is_some() {
+        settings.budget = opts.budget;
+    }
+    if opts.time_limit.is_some() {
+        settings.time_limit = opts.time_limit;
+    }
+    if opts.gap.is_some() {
+        settings.gap = opts.gap;
+    }
+    if let Some(seed) = opts.seed {
+        settings.seed = seed;
+    }
+    settings.deterministic |= opts.deterministic;
+    settings.canonical_solution |= opts.canonical_solution;
+    if opts.search_tree.is_some() {
+        settings.search_tree_max_nodes = Some(opts.search_tree_max_nodes);
+    }
+    settings.record_preprocessing |= opts.preprocess_report.is_some();
+    if let Some(initial_solution_file) = &opts.initial_solution {
+        settings.initial_hitting_set = Some(read_node_list(initial_solution_file)?);
+    }
+    if let Some(partial_hs_file) = &opts.complete {
+        settings
+            .partial_hitting_set
+            .extend(read_node_list(partial_hs_file)?);
+    }
+    if let Some(forced_file) = &opts.forced {
+        settings.forced_nodes.extend(read_node_list(forced_file)?);
+    }
+    if let Some(forbidden_file) = &opts.forbidden {
+        settings
+            .forbidden_nodes
+            .extend(read_node_list(forbidden_file)?);
+    }
+
+    let progress = match &opts.progress {
+        Some(path) => {
+            let writer: Box<dyn Write> = if path.as_os_str() == "stderr" {
+                Box::new(io::stderr())
+            } else {
+                Box::new(BufWriter::new(File::create(path)?))
+            };
+            let interval = Duration::try_from_secs_f64(opts.progress_interval)
+                .map_err(|_| anyhow!("Invalid progress interval"))?;
+            Some(ProgressWriter::new(writer, interval))
+        }
+        None => None,
+    };
+    let decision_recorder = match &opts.record_decisions {
+        Some(path) => Some(DecisionRecorder::new(Box::new(BufWriter::new(
+            File::create(path)?,
+        )))),
+        None => None,
+    };
+    let replay = match &opts.replay {
+        Some(path) => Some(Replay::new(read_decisions(path)?, opts.replay_until)),
+        None => None,
+    };
+    let incumbent_callback = match &opts.incumbent_log {
+        Some(path) => Some(incumbent_log_callback(path)?),
+        None => None,
+    };
+    let report_stream = match &opts.report_stream {
+        Some(path) => Some(ReportStream::new(
+            Box::new(BufWriter::new(File::create(path)?)),
+            path.display().to_string(),
+        )),
+        None => None,
+    };
+    let hooks = SolveHooks {
+        progress,
+        decision_recorder,
+        replay,
+        incumbent_callback,
+        report_stream,
+        ..SolveHooks::default()
+    };
+
+    let result = solve_hypergraph(
+        &opts.common.hypergraph,
+        &opts.common.format,
+        &opts.solver,
+        settings,
+        hooks,
+    );
+    if let Some((final_hs, report)) = results_to_write(&result) {
+        write_solution_and_report(
+            final_hs,
+            report,
+            opts.solution.as_deref(),
+            &opts.solution_output,
+            opts.report.as_deref(),
+            opts.report_format == "csv",
+        )?;
+    }
+    let (_, report) = result?;
+    write_recorded_search(opts, &report)?;
+    Ok(report)
+}
+
+/// Writes the preprocessing log and search tree recorded in the report.
+fn write_recorded_search(opts: &SolveOpts, report: &Report) -> Result<()> {
+    if let (Some(path), Some(preprocessing)) = (&opts.preprocess_report, &report.preprocessing) {
+        debug!("Writing preprocessing report to {}", path.display());
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(writer, preprocessing)?;
+    }
+    if let (Some(path), Some(search_tree)) = (&opts.search_tree, &report.search_tree) {
+        debug!("Writing search tree to {}", path.display());
+        let writer = BufWriter::new(File::create(path)?);
+        if path.extension().is_some_and(|ext| ext == "dot") {
+            search_tree.write_dot(writer)?;
+        } else {
+            search_tree.write_json(writer)?;
+        }
+    }
+    Ok(())
+}
+
+/// Appends each incumbent as a json line to the given file. Write errors
+/// disable the log instead of aborting the search.
+fn incumbent_log_callback(path: &Path) -> Result<IncumbentCallback> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let mut writer = Some(BufWriter::new(file));
+    Ok(IncumbentCallback::new(move |event| {
+        let Some(log) = &mut writer else {
+            return;
+        };
+        let result = serde_json::to_writer(&mut *log, event)
+            .map_err(io::Error::from)
+            .and_then(|()| log.write_all(b"\n"))
+            .and_then(|()| log.flush());
+        if let Err(err) = result {
+            warn!(
+                "Failed to write incumbent, disabling incumbent log: {}",
+                err
+            );
+            writer = None;
+        }
+    }))
+}
+
+/// Runs the `solve` subcommand and prints a single json summary line to
+/// stdout, regardless of whether solving succeeded. Returns the exit code.
+fn solve_and_summarize(opts: &SolveOpts) -> i32 {
+    let result = solve(opts);
+    if let Err(err) = &result {
+        eprintln!("Error: {:?}", err);
+    }
+    let summary = SolveSummary::new(&opts.common.hypergraph, &result);
+    match serde_json::to_string(&summary) {
+        Ok(line) => println!("{}", line),
+        Err(err) => eprintln!("Failed to serialize summary: {}", err),
+    }
+    summary.exit_code
+}
+
+fn batch(opts: &BatchOpts) -> Result<()> {
+    let instances = batch::collect_instances(&opts.instances)?;
+    let mut settings = opts.settings.load()?;
+    if opts.time_limit.is_some() {
+        settings.time_limit = opts.time_limit;
+    }
+
+    let mut output_names = IdxHashSet::default();
+    for hypergraph in &instances {
+        let name = file_name(hypergraph)?;
+        ensure!(
+            output_names.insert(name),
+            "multiple hypergraphs named {} in batch",
+            name
+        );
+    }
+    fs::create_dir_all(&opts.output_dir)?;
+
+    info!(
+        "Solving {} hypergraphs using {} jobs",
+        instances.len(),
+        opts.jobs
+    );
+    let time_before = Instant::now();
+    let summaries = batch::run_batch(&instances, opts.jobs, |hypergraph| {
+        let result = file_name(hypergraph).and_then(|name| {
+            let result = solve_hypergraph(
+                hypergraph,
+                &opts.format,
+                &opts.solver,
+                settings.clone(),
+                SolveHooks::default(),
+            );
+            let Some((final_hs, report)) = results_to_write(&result) else {
+                return result.map(|(_, report)| report);
+            };
+            let solution_extension = match opts.solution_output.solution_format {
+                SolutionFormat::Json => "json",
+                SolutionFormat::Text => "txt",
+            };
+            let solution_file = opts
+                .output_dir
+                .join(format!("{}.solution.{}", name, solution_extension));
+            let report_file = opts.output_dir.join(format!("{}.report.json", name));
+            write_solution_and_report(
+                final_hs,
+                report,
+                Some(&solution_file),
+                &opts.solution_output,
+                Some(&report_file),
+                false,
+            )?;
+            result.map(|(_, report)| report)
+        });
+        if let Err(err) = &result {
+            warn!("Failed to solve {}: {:#}", hypergraph.display(), err);
+        }
+        SolveSummary::new(hypergraph, &result)
+    });
+
+    let json_writer = BufWriter::new(File::create(opts.output_dir.join("summary.json"))?);
+    serde_json::to_writer(json_writer, &summaries)?;
+    let mut csv_writer = BufWriter::new(File::create(opts.output_dir.join("summary.csv"))?);
+    batch::write_csv_summary(&mut csv_writer, &summaries)?;
+    csv_writer.flush()?;
+
+    let num_failed = summaries
+        .iter()
+        .filter(|summary| summary.size.is_none())
+        .count();
+    info!(
+        "Solved {} of {} hypergraphs in {:.2?}",
+        summaries.len() - num_failed,
+        summaries.len(),
+        time_before.elapsed()
+    );
+    // Only fails after writing the summaries, which record why each run failed
+    ensure!(
+        num_failed == 0,
+        "failed to solve {} of {} hypergraphs",
+        num_failed,
+        summaries.len()
+    );
+    Ok(())
+}
+
+fn bench(opts: &BenchOpts) -> Result<()> {
+    let file_name = file_name(&opts.common.hypergraph)?;
+    let instance = opts.common.load_instance_for_solving(&opts.solver)?;
+    let mut settings = opts.settings.load()?;
+    if settings.deterministic {
+        warn!("Ignoring the deterministic setting, runtimes are needed for benchmarking");
+        settings.deterministic = false;
+    }
+    if let Some(core) = opts.pin_core {
+        ensure!(
+            core_affinity::set_for_current(core_affinity::CoreId { id: core }),
+            "failed to pin the solver to core {}",
+            core
+        );
+        info!("Pinned solver to core {}", core);
+    }
+
+    let report = bench::run_bench(
+        &instance,
+        file_name,
+        &settings,
+        opts.runs,
+        opts.warmup,
+        opts.vary_seed,
+    )?;
+    info!(
+        "Runtime: mean {:.3}s, median {:.3}s, stddev {:.3}s",
+        report.runtime.mean, report.runtime.median, report.runtime.stddev
+    );
+    info!(
+        "Branching steps: mean {:.1}, median {:.1}, stddev {:.1}",
+        report.branching_steps.mean, report.branching_steps.median, report.branching_steps.stddev
+    );
+
+    let mut writer: Box<dyn Write> = match &opts.output {
+        Some(output_file) => Box::new(BufWriter::new(File::create(output_file)?)),
+        None => Box::new(io::stdout().lock()),
+    };
+    serde_json::to_writer(&mut writer, &report)?;
+    writeln!(writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn tune(opts: &TuneOpts) -> Result<()> {
+    let instances = batch::collect_instances(&opts.instances)?;
+    let settings = read_settings(&opts.settings)?;
+    let spec: TuneSpec = {
+        let reader = BufReader::new(File::open(&opts.spec)?);
+        serde_json::from_reader(reader).with_context(|| {
+            InputError(format!(
+                "failed to read tuning spec from {}",
+                opts.spec.display()
+            ))
+        })?
+    };
+
+    let report = tune::run_tuning(
+        &instances,
+        &settings,
+        &spec,
+        opts.jobs,
+        |hypergraph, settings| {
+            let result = solve_hypergraph(
+                hypergraph,
+                &opts.format,
+                &opts.solver,
+                settings,
+                SolveHooks::default(),
+            )
+            .map(|(_, report)| report);
+            SolveSummary::new(hypergraph, &result)
+        },
+    )?;
+    if let Some(best) = report.configurations.first() {
+        info!(
+            "Best configuration {} solved {} of {} hypergraphs in {:.2}s",
+            serde_json::Value::from(best.parameters.clone()),
+            best.solved,
+            report.num_hypergraphs,
+            best.total_runtime
+        );
+    }
+
+    if let (Some(best_settings_file), Some(best_settings)) =
+        (&opts.best_settings, &report.best_settings)
+    {
+        let writer = BufWriter::new(File::create(best_settings_file)?);
+        serde_json::to_writer_pretty(writer, best_settings)?;
+    }
+    let mut writer: Box<dyn Write> = match &opts.output {
+        Some(output_file) => Box::new(BufWriter::new(File::create(output_file)?)),
+        None => Box::new(io::stdout().lock()),
+    };
+    serde_json::to_writer(&mut writer, &report)?;
+    writeln!(writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn sample(opts: &SampleOpts) -> Result<()> {
+    let method = match (opts.random_edges, opts.ball, opts.densest) {
+        (Some(count), None, None) => SampleMethod::RandomEdges {
+            count,
+            seed: opts.seed,
+        },
+        (None, Some(center), None) => SampleMethod::Ball {
+            center: NodeIdx::from(center),
+            radius: opts.radius,
+        },
+        (None, None, Some(num_nodes)) => SampleMethod::Densest { num_nodes },
+        _ => bail!("exactly one of --random-edges, --ball, and --densest is required"),
+    };
     let instance = opts.common.load_instance()?;
-    let settings = {
-        let reader = BufReader::new(File::open(&opts.settings)?);
-        serde_json::from_reader(reader)?
+    let sub_instance = SubInstance::sample(&instance, method)?;
+    info!(
+        "Sampled {} nodes and {} edges",
+        sub_instance.nodes.len(),
+        sub_instance.edges.len()
+    );
+    if instance.has_conflicts() && !opts.json_output {
+        warn!("Dropping the conflicts between nodes, use --json-output to keep them");
+    }
+    if instance.has_requirements() && !opts.json_output {
+        warn!("Dropping the coverage requirements of edges, use --json-output to keep them");
+    }
+
+    let mut writer: Box<dyn Write> = match &opts.output {
+        Some(output_file) => Box::new(BufWriter::new(File::create(output_file)?)),
+        None => Box::new(io::stdout().lock()),
+    };
+    if opts.json_output {
+        sub_instance.write_json(&instance, &mut writer)?;
+        writeln!(writer)?;
+    } else {
+        sub_instance.write_text(&instance, &mut writer)?;
+    }
+    writer.flush()?;
+    if let Some(mapping_file) = &opts.mapping {
+        let writer = BufWriter::new(File::create(mapping_file)?);
+        serde_json::to_writer(writer, &sub_instance)?;
+    }
+    Ok(())
+}
+
+fn anonymize(opts: &AnonymizeOpts) -> Result<()> {
+    // Duplicate edges are written as separate edges, so that the mapping
+    // refers to the edges of the input
+    let options = LoadOptions {
+        keep_duplicate_edges: true,
+        ..opts.common.format.load_options()
+    };
+    let instance = opts
+        .common
+        .format
+        .load_instance_with(&opts.common.hypergraph, options)?;
+    let anonymized = SubInstance::anonymize(&instance, opts.seed, opts.keep_numbering);
+    if instance.has_conflicts() && !opts.json_output {
+        warn!("Dropping the conflicts between nodes, use --json-output to keep them");
+    }
+    if instance.has_requirements() && !opts.json_output {
+        warn!("Dropping the coverage requirements of edges, use --json-output to keep them");
+    }
+
+    let mut writer: Box<dyn Write> = match &opts.output {
+        Some(output_file) => Box::new(BufWriter::new(File::create(output_file)?)),
+        None => Box::new(io::stdout().lock()),
+    };
+    if opts.json_output {
+        anonymized.write_json(&instance, &mut writer)?;
+        writeln!(writer)?;
+    } else {
+        anonymized.write_text(&instance, &mut writer)?;
+    }
+    writer.flush()?;
+    if let Some(mapping_file) = &opts.mapping {
+        let writer = BufWriter::new(File::create(mapping_file)?);
+        serde_json::to_writer(writer, &anonymized)?;
+    }
+    Ok(())
+}
+
+fn solve_for_baseline(
+    instances: &[PathBuf],
+    settings: &Settings,
+    format: &FormatOpts,
+    solver: &SolverOpts,
+    jobs: usize,
+) -> Vec<SolveSummary> {
+    batch::run_batch(instances, jobs, |hypergraph| {
+        let result = solve_hypergraph(
+            hypergraph,
+            format,
+            solver,
+            settings.clone(),
+            SolveHooks::default(),
+        )
+        .map(|(_, report)| report);
+        if let Err(err) = &result {
+            warn!("Failed to solve {}: {:#}", hypergraph.display(), err);
+        }
+        SolveSummary::new(hypergraph, &result)
+    })
+}
+
+fn record_baseline(opts: &RecordBaselineOpts) -> Result<()> {
+    let instances = batch::collect_instances(&opts.instances)?;
+    let settings = opts.settings.load()?;
+    let summaries =
+        solve_for_baseline(&instances, &settings, &opts.format, &opts.solver, opts.jobs);
+    let baseline = baseline::record_baseline(settings, &summaries);
+    let writer = BufWriter::new(File::create(&opts.output)?);
+    serde_json::to_writer_pretty(writer, &baseline)?;
+    info!(
+        "Recorded baseline of {} hypergraphs to {}",
+        baseline.hypergraphs.len(),
+        opts.output.display()
+    );
+    Ok(())
+}
+
+fn check_baseline(opts: &CheckBaselineOpts) -> Result<()> {
+    let instances = batch::collect_instances(&opts.instances)?;
+    let baseline: Baseline = {
+        let reader = BufReader::new(File::open(&opts.baseline)?);
+        serde_json::from_reader(reader).with_context(|| {
+            InputError(format!(
+                "failed to read baseline from {}",
+                opts.baseline.display()
+            ))
+        })?
     };
+    ensure!(
+        opts.steps_tolerance >= 0.0 && opts.runtime_tolerance.is_none_or(|tol| tol >= 0.0),
+        "tolerances must not be negative"
+    );
+    if baseline.version != env!("CARGO_PKG_VERSION") {
+        warn!(
+            "Baseline was recorded by version {} of the solver",
+            baseline.version
+        );
+    }
+
+    let summaries = solve_for_baseline(
+        &instances,
+        &baseline.settings,
+        &opts.format,
+        &opts.solver,
+        opts.jobs,
+    );
+    let tolerances = BaselineTolerances {
+        branching_steps: opts.steps_tolerance,
+        runtime: opts.runtime_tolerance,
+    };
+    let check = baseline::check_baseline(&baseline, &summaries, tolerances);
+    for mismatch in &check.mismatches {
+        warn!("{}: {:?}", mismatch.hypergraph, mismatch.deviation);
+    }
 
-    info!("Solving {:?}", &opts.common.hypergraph);
-    let (final_hs, report) = solve::solve(instance, file_name, settings)?;
+    let mut writer: Box<dyn Write> = match &opts.output {
+        Some(output_file) => Box::new(BufWriter::new(File::create(output_file)?)),
+        None => Box::new(io::stdout().lock()),
+    };
+    serde_json::to_writer(&mut writer, &check)?;
+    writeln!(writer)?;
+    writer.flush()?;
+
+    ensure!(
+        check.mismatches.is_empty(),
+        "{} of {} hypergraphs deviate from the baseline",
+        check.mismatches.len(),
+        baseline.hypergraphs.len()
+    );
+    info!(
+        "All {} hypergraphs match the baseline, {} with fewer branching steps",
+        check.checked, check.fewer_branching_steps
+    );
+    Ok(())
+}
 
-    if let Some(solution_file) = opts.solution {
+fn serve(opts: &ServeOpts) -> Result<()> {
+    let settings = opts.settings.as_deref().map(read_settings).transpose()?;
+    let config = ServeConfig {
+        settings,
+        load_options: LoadOptions {
+            normalize: opts.normalize,
+            ..LoadOptions::default()
+        },
+    };
+    serve::serve(opts.listen.as_str(), config)
+}
+
+fn solve_implicit(opts: &ImplicitOpts) -> Result<()> {
+    let settings = read_settings(&opts.settings)?;
+    let oracle = CommandOracle::spawn(&opts.oracle[0], &opts.oracle[1..])?;
+    let (final_hs, report) =
+        implicit::solve_implicit(opts.num_nodes, settings, oracle, opts.max_iterations)?;
+    if !report.finished {
+        warn!("The hitting set might miss edges the oracle did not return yet");
+    }
+    if let Some(solution_file) = &opts.solution {
         debug!("Writing solution to {}", solution_file.display());
-        let writer = BufWriter::new(File::create(&solution_file)?);
+        let writer = BufWriter::new(File::create(solution_file)?);
         serde_json::to_writer(writer, &final_hs)?;
     }
-    if let Some(report_file) = opts.report {
+    if let Some(report_file) = &opts.report {
         debug!("Writing report to {}", report_file.display());
-        let writer = BufWriter::new(File::create(&report_file)?);
+        let writer = BufWriter::new(File::create(report_file)?);
         serde_json::to_writer(writer, &report)?;
     }
+    Ok(())
+}
+
+fn enumerate(opts: EnumerateOpts) -> Result<()> {
+    let instance = opts.common.load_instance()?;
+    let mut writer: Box<dyn Write> = match opts.output {
+        Some(output_file) => Box::new(BufWriter::new(File::create(output_file)?)),
+        None => Box::new(io::stdout().lock()),
+    };
+
+    info!(
+        "Enumerating minimal hitting sets of {:?}",
+        &opts.common.hypergraph
+    );
+    let time_before = Instant::now();
+    let mut sorted_hs = Vec::new();
+    let num_found = enumerate::enumerate_minimal_hitting_sets(&instance, opts.max_size, |hs| {
+        sorted_hs.clear();
+        sorted_hs.extend_from_slice(hs);
+        sorted_hs.sort_unstable();
+        serde_json::to_writer(&mut writer, &sorted_hs)?;
+        writer.write_all(b"\n")?;
+        Ok(())
+    })?;
+    writer.flush()?;
+    info!(
+        "Found {} minimal hitting sets in {:.2?}",
+        num_found,
+        time_before.elapsed()
+    );
 
     Ok(())
 }
@@ -147,17 +1516,198 @@ fn convert_to_ilp(opts: IlpOpts) -> Result<()> {
     }
 
     let stdout = io::stdout();
-    instance.export_as_ilp(stdout.lock())
+    match (opts.format.as_str(), opts.decision) {
+        ("lp", Some(max_size)) => instance.export_as_decision_ilp(stdout.lock(), max_size),
+        ("lp", None) => instance.export_as_ilp(stdout.lock()),
+        ("smtlib", Some(max_size)) => instance.export_as_decision_smtlib(stdout.lock(), max_size),
+        ("smtlib", None) => instance.export_as_smtlib(stdout.lock()),
+        (format, _) => bail!("Unknown model format {}", format),
+    }
+}
+
+/// Collects the key-value pairs of a log record as json fields, keeping
+/// numbers and booleans as such
+struct JsonLogFields<'a>(&'a mut serde_json::Map<String, serde_json::Value>);
+
+impl<'kvs> log::kv::VisitSource<'kvs> for JsonLogFields<'_> {
+    fn visit_pair(
+        &mut self,
+        key: log::kv::Key<'kvs>,
+        value: log::kv::Value<'kvs>,
+    ) -> Result<(), log::kv::Error> {
+        let value = if let Some(value) = value.to_u64() {
+            value.into()
+        } else if let Some(value) = value.to_i64() {
+            value.into()
+        } else if let Some(value) = value.to_f64() {
+            value.into()
+        } else if let Some(value) = value.to_bool() {
+            value.into()
+        } else {
+            value.to_string().into()
+        };
+        self.0.insert(key.to_string(), value);
+        Ok(())
+    }
+}
+
+fn init_logger() -> Result<()> {
+    let mut builder =
+        env_logger::Builder::from_env(env_logger::Env::new().filter_or("FINDMINHS_LOG", "info"));
+    match env::var("FINDMINHS_LOG_FORMAT") {
+        Ok(format) if format == "json" => {
+            let start_time = Instant::now();
+            builder.format(move |buf, record| {
+                let mut line = serde_json::Map::new();
+                line.insert(
+                    "timestamp".into(),
+                    buf.timestamp_millis().to_string().into(),
+                );
+                line.insert("elapsed".into(), start_time.elapsed().as_secs_f64().into());
+                line.insert("level".into(), record.level().as_str().into());
+                line.insert("target".into(), record.target().into());
+                line.insert("event".into(), serde_json::Value::Null);
+                // Fields like `event`, `node`, and `size` attached by the solver
+                let _ = record.key_values().visit(&mut JsonLogFields(&mut line));
+                line.insert("message".into(), record.args().to_string().into());
+                writeln!(buf, "{}", serde_json::Value::Object(line))
+            });
+        }
+        Ok(format) if format != "text" => {
+            bail!(
+                "Unknown log format {:?}, expected \"text\" or \"json\"",
+                format
+            )
+        }
+        _ => {
+            builder.format_timestamp_millis();
+        }
+    }
+    builder.init();
+    Ok(())
+}
+
+fn estimate(opts: &EstimateOpts) -> Result<()> {
+    let instance = opts.common.load_instance()?;
+    let settings = opts.settings.load()?;
+    let estimate = solve::estimate_difficulty(&instance, &settings)?;
+    let mut writer: Box<dyn Write> = match &opts.output {
+        Some(output_file) => Box::new(BufWriter::new(File::create(output_file)?)),
+        None => Box::new(io::stdout().lock()),
+    };
+    serde_json::to_writer_pretty(&mut writer, &estimate)?;
+    writeln!(writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn print_schema(opts: &SchemaOpts) -> Result<()> {
+    let schema = match opts.kind.as_str() {
+        "report" => schemars::schema_for!(Report),
+        "solution" => schemars::schema_for!(solution::SolutionFile),
+        kind => bail!("Unknown schema kind {}", kind),
+    };
+    let mut writer: Box<dyn Write> = match &opts.output {
+        Some(output_file) => Box::new(BufWriter::new(File::create(output_file)?)),
+        None => Box::new(io::stdout().lock()),
+    };
+    serde_json::to_writer_pretty(&mut writer, &schema)?;
+    writeln!(writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn print_settings(opts: &PrintSettingsOpts) -> Result<()> {
+    let given = opts.settings.settings.is_some() || opts.settings.preset.is_some();
+    let settings = if opts.describe && !given {
+        None
+    } else {
+        Some(opts.settings.load()?)
+    };
+    let mut writer: Box<dyn Write> = match &opts.output {
+        Some(output_file) => Box::new(BufWriter::new(File::create(output_file)?)),
+        None => Box::new(io::stdout().lock()),
+    };
+    if opts.describe {
+        write!(
+            writer,
+            "{}",
+            settings_doc::describe_settings(settings.as_ref())
+        )?;
+    } else {
+        serde_json::to_writer_pretty(&mut writer, &settings)?;
+        writeln!(writer)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn recommend_settings(opts: &RecommendSettingsOpts) -> Result<()> {
+    let read_report = || -> Result<(InstanceProfile, Settings)> {
+        let reader = BufReader::new(File::open(&opts.report)?);
+        let mut report: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_reader(reader)?;
+        let mut field = |name| {
+            report
+                .remove(name)
+                .ok_or_else(|| anyhow!("report has no {} entry", name))
+        };
+        let profile = serde_json::from_value(field("profile")?)?;
+        let settings = serde_json::from_value(field("settings")?)?;
+        Ok((profile, settings))
+    };
+    let (profile, report_settings) = read_report()
+        .with_context(|| format!("failed to read profile from {}", opts.report.display()))?;
+    let settings = match &opts.settings {
+        Some(path) => read_settings(path)?,
+        None => report_settings,
+    };
+
+    let (settings, reasons) = profile::recommend_settings(&profile, settings);
+    for reason in &reasons {
+        info!("{}", reason);
+    }
+    let mut writer: Box<dyn Write> = match &opts.output {
+        Some(output_file) => Box::new(BufWriter::new(File::create(output_file)?)),
+        None => Box::new(io::stdout().lock()),
+    };
+    serde_json::to_writer_pretty(&mut writer, &settings)?;
+    writeln!(writer)?;
+    writer.flush()?;
+    Ok(())
 }
 
 fn main() -> Result<()> {
-    env_logger::Builder::from_env(env_logger::Env::new().filter_or("FINDMINHS_LOG", "info"))
-        .format_timestamp_millis()
-        .init();
+    init_logger()?;
 
     let opts = CliOpts::from_args();
     match opts {
-        CliOpts::Solve(solve_opts) => solve(solve_opts),
+        CliOpts::Solve(solve_opts) => process::exit(solve_and_summarize(&solve_opts)),
         CliOpts::Ilp(ilp_opts) => convert_to_ilp(ilp_opts),
+        CliOpts::Enumerate(enumerate_opts) => enumerate(enumerate_opts),
+        CliOpts::Batch(batch_opts) => batch(&batch_opts),
+        CliOpts::Bench(bench_opts) => bench(&bench_opts),
+        CliOpts::Tune(tune_opts) => tune(&tune_opts),
+        CliOpts::Serve(serve_opts) => serve(&serve_opts),
+        CliOpts::Implicit(implicit_opts) => solve_implicit(&implicit_opts),
+        CliOpts::Estimate(estimate_opts) => estimate(&estimate_opts),
+        CliOpts::Schema(schema_opts) => print_schema(&schema_opts),
+        CliOpts::Settings(settings_opts) => print_settings(&settings_opts),
+        CliOpts::RecommendSettings(recommend_opts) => recommend_settings(&recommend_opts),
+        CliOpts::Sample(sample_opts) => sample(&sample_opts),
+        CliOpts::Anonymize(anonymize_opts) => anonymize(&anonymize_opts),
+        CliOpts::RecordBaseline(record_opts) => record_baseline(&record_opts),
+        CliOpts::CheckBaseline(check_opts) => check_baseline(&check_opts),
+        CliOpts::ReportMerge(merge_opts) => {
+            let table = analysis::merge_reports(&merge_opts.reports)?;
+            merge_opts.output.write(&table)
+        }
+        CliOpts::ReportDiff(diff_opts) => {
+            let table = analysis::diff_reports(
+                slice::from_ref(&diff_opts.old),
+                slice::from_ref(&diff_opts.new),
+            )?;
+            diff_opts.output.write(&table)
+        }
     }
 }
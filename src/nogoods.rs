@@ -0,0 +1,112 @@
+use crate::{
+    instance::{EdgeIdx, Instance, NodeIdx},
+    report::ReductionStats,
+    small_indices::SmallIdx,
+};
+use std::collections::VecDeque;
+
+/// Combination of branching outcomes that cannot lead to a hitting set smaller
+/// than the one known when it was learned: any such hitting set either misses
+/// one of the `included` nodes or contains one of the `excluded` nodes.
+#[derive(Debug, Clone)]
+struct Nogood {
+    included: Vec<NodeIdx>,
+    excluded: Vec<NodeIdx>,
+}
+
+/// Nogoods learned from subtrees pruned by the packing bound.
+///
+/// If the packing bound prunes a subproblem with partial hitting set `P`,
+/// every hitting set containing `P` needs a distinct node outside of `P` for
+/// each packed edge, unless it contains one of the deleted nodes of a packed
+/// edge. Together with the size of `P`, this reaches the upper bound, so the
+/// same combination can be pruned wherever it occurs again in the search.
+#[derive(Debug)]
+pub struct NogoodStore {
+    nogoods: VecDeque<Nogood>,
+    max_nogoods: usize,
+    max_nogood_size: usize,
+
+    /// Nodes of each edge at the start of the search
+    initial_edges: Vec<Vec<NodeIdx>>,
+    in_partial_hs: Vec<bool>,
+}
+
+impl NogoodStore {
+    pub fn new(instance: &Instance, max_nogoods: usize, max_nogood_size: Option<usize>) -> Self {
+        let mut initial_edges = vec![Vec::new(); instance.num_edges_total()];
+        for &edge in instance.edges() {
+            initial_edges[edge.idx()] = instance.edge(edge).collect();
+        }
+        Self {
+            nogoods: VecDeque::new(),
+            max_nogoods,
+            max_nogood_size: max_nogood_size.unwrap_or(usize::MAX),
+            initial_edges,
+            in_partial_hs: vec![false; instance.num_nodes_total()],
+        }
+    }
+
+    /// Learns a nogood from a subproblem pruned because the packing is at
+    /// least as large as `upper_bound` minus the size of the partial hitting
+    /// set. If the store is full, the oldest nogood is discarded.
+    pub fn learn(
+        &mut self,
+        instance: &Instance,
+        partial_hs: &[NodeIdx],
+        packing: &[EdgeIdx],
+        upper_bound: usize,
+        stats: &mut ReductionStats,
+    ) {
+        // Only as many nodes of the partial hitting set are needed as the
+        // packing falls short of the upper bound. The first ones are the most
+        // general as they stem from the constraints and the earliest
+        // branching decisions.
+        let num_included = upper_bound.saturating_sub(packing.len());
+        let included = partial_hs[..num_included.min(partial_hs.len())].to_vec();
+        let mut excluded = Vec::new();
+        for &edge in packing {
+            let initial_size = self.initial_edges[edge.idx()].len();
+            if initial_size == instance.edge_size(edge) {
+                continue;
+            }
+            excluded.extend(
+                self.initial_edges[edge.idx()]
+                    .iter()
+                    .copied()
+                    .filter(|&node| instance.is_node_deleted(node)),
+            );
+        }
+
+        if included.len() + excluded.len() > self.max_nogood_size {
+            stats.nogoods_too_large += 1;
+            return;
+        }
+        if self.nogoods.len() >= self.max_nogoods {
+            self.nogoods.pop_front();
+            stats.nogoods_discarded += 1;
+        }
+        stats.nogoods_learned += 1;
+        stats.nogood_nodes_learned += included.len() + excluded.len();
+        self.nogoods.push_back(Nogood { included, excluded });
+    }
+
+    /// Whether the current subproblem matches a learned nogood.
+    pub fn is_pruned(&mut self, instance: &Instance, partial_hs: &[NodeIdx]) -> bool {
+        for &node in partial_hs {
+            self.in_partial_hs[node.idx()] = true;
+        }
+        let in_partial_hs = &self.in_partial_hs;
+        let pruned = self.nogoods.iter().any(|nogood| {
+            nogood.included.iter().all(|node| in_partial_hs[node.idx()])
+                && nogood
+                    .excluded
+                    .iter()
+                    .all(|&node| instance.is_node_deleted(node) && !in_partial_hs[node.idx()])
+        });
+        for &node in partial_hs {
+            self.in_partial_hs[node.idx()] = false;
+        }
+        pruned
+    }
+}
@@ -0,0 +1,111 @@
+use crate::instance::{EdgeIdx, Instance, NodeIdx};
+use serde::Serialize;
+
+/// Rule responsible for removing or forcing a node or edge
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ReductionRule {
+    /// Listed in the `forced_nodes` setting
+    ForcedSetting,
+
+    /// Part of the partial hitting set to complete
+    PartialHittingSet,
+
+    /// Listed in the `forbidden_nodes` setting
+    ForbiddenSetting,
+
+    /// Conflicts with a forced node
+    Conflict,
+
+    /// Has the same incident edges as its representative
+    TwinMerge,
+
+    /// Only node of an edge
+    ForcedVertex,
+
+    /// Its edges can not be hit by the remaining budget of other nodes
+    DegreeForcing,
+
+    /// Together with the remaining budget of other nodes, it can not hit all
+    /// edges
+    DegreeDiscarding,
+
+    CostlyDiscardEfficiency,
+    CostlyDiscardPackingUpdate,
+    CostlyDiscardPackingFromScratch,
+
+    /// Discard bound of a custom lower bound passed to the solver
+    CostlyDiscardCustom,
+
+    /// Found by a custom reduction passed to the solver
+    Custom,
+    VertexDomination,
+    EdgeDomination,
+}
+
+/// Change made to the instance by a reduction rule
+#[derive(Debug, Clone, Copy, Serialize)]
+pub enum PreprocessingAction {
+    RemovedNode(NodeIdx),
+    RemovedEdge(EdgeIdx),
+
+    /// The node was added to the hitting set, removing its incident edges
+    ForcedNode(NodeIdx),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PreprocessingStep {
+    pub action: PreprocessingAction,
+    pub rule: ReductionRule,
+
+    /// Node a merged twin was merged into
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub representative: Option<NodeIdx>,
+}
+
+/// Changes made to the instance before the first branching step, in the order
+/// they were applied.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PreprocessingReport {
+    pub steps: Vec<PreprocessingStep>,
+
+    /// Size of the instance left for branching
+    pub remaining_nodes: usize,
+    pub remaining_edges: usize,
+
+    /// The reductions at the root solved the instance or pruned it against
+    /// the initial hitting set, so no branching was needed
+    pub solved_at_root: bool,
+
+    #[serde(skip)]
+    root_finished: bool,
+}
+
+impl PreprocessingReport {
+    pub fn record(&mut self, action: PreprocessingAction, rule: ReductionRule) {
+        if !self.root_finished {
+            self.steps.push(PreprocessingStep {
+                action,
+                rule,
+                representative: None,
+            });
+        }
+    }
+
+    pub fn record_twin(&mut self, twin: NodeIdx, representative: NodeIdx) {
+        self.steps.push(PreprocessingStep {
+            action: PreprocessingAction::RemovedNode(twin),
+            rule: ReductionRule::TwinMerge,
+            representative: Some(representative),
+        });
+    }
+
+    /// Ends the recording after the reductions at the root are done.
+    pub fn finish_root(&mut self, instance: &Instance, solved: bool) {
+        if !self.root_finished {
+            self.root_finished = true;
+            self.remaining_nodes = instance.nodes().len();
+            self.remaining_edges = instance.num_edges();
+            self.solved_at_root = solved;
+        }
+    }
+}
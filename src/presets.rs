@@ -0,0 +1,82 @@
+//! Named built-in settings, so that the solver can be used without writing a
+//! settings file first.
+use crate::report::Settings;
+use anyhow::{anyhow, Result};
+use serde_json::{json, Map, Value};
+
+/// Names of all built-in presets
+pub const PRESET_NAMES: &[&str] = &[
+    "default",
+    "aggressive-reductions",
+    "fast-bounds-only",
+    "enumeration",
+];
+
+fn default_entries() -> Map<String, Value> {
+    let Value::Object(entries) = json!({
+        "enable_local_search": false,
+        "enable_max_degree_bound": true,
+        "enable_sum_degree_bound": false,
+        "enable_efficiency_bound": true,
+        "enable_packing_bound": true,
+        "enable_sum_over_packing_bound": true,
+        "packing_from_scratch_limit": 3,
+        "greedy_mode": "Once",
+    }) else {
+        unreachable!()
+    };
+    entries
+}
+
+/// Settings entries of a preset. Entries not listed take their default values.
+pub fn preset_entries(name: &str) -> Result<Map<String, Value>> {
+    let mut entries = default_entries();
+    let overrides = match name {
+        "default" => json!({}),
+        // Spend more time per branching step to explore fewer of them
+        "aggressive-reductions" => json!({
+            "enable_local_search": true,
+            "local_search_three_opt_budget": 100_000,
+            "enable_flow_bound": true,
+            "enable_clique_cover_bound": true,
+            "packing_from_scratch_limit": 10,
+            "greedy_mode": "AlwaysBeforeExpensiveReductions",
+            "initial_greedy_strategies": ["MaxDegree", "Regret", "EdgeSizeWeighted", "LpRounding"],
+            "upper_bound_search_iterations": 1000,
+            "upper_bound_search_tabu_tenure": 10,
+            "merge_twin_nodes": true,
+            "enable_degree_forcing": true,
+            "enable_degree_discarding": true,
+        }),
+        // Only the bounds that are cheap to compute
+        "fast-bounds-only" => json!({
+            "enable_sum_degree_bound": true,
+            "enable_packing_bound": false,
+            "enable_sum_over_packing_bound": false,
+            "packing_from_scratch_limit": 0,
+        }),
+        // Explore all minimum hitting sets to classify the nodes by their membership
+        "enumeration" => json!({
+            "classify_nodes": true,
+        }),
+        _ => {
+            return Err(anyhow!(
+                "unknown preset {}, expected one of {}",
+                name,
+                PRESET_NAMES.join(", ")
+            ))
+        }
+    };
+    if let Value::Object(overrides) = overrides {
+        entries.extend(overrides);
+    }
+    Ok(entries)
+}
+
+/// Settings of a preset, optionally with some entries replaced, e.g., by the
+/// ones from a settings file.
+pub fn preset_settings(name: &str, overrides: Option<Map<String, Value>>) -> Result<Settings> {
+    let mut entries = preset_entries(name)?;
+    entries.extend(overrides.into_iter().flatten());
+    Ok(serde_json::from_value(Value::Object(entries))?)
+}
@@ -0,0 +1,267 @@
+use crate::report::{InstanceProfile, ReductionStats, Report, Settings};
+use std::collections::BTreeMap;
+
+/// Share of the prunes or found reductions from which a rule counts as hot
+const HOT_SHARE: f64 = 0.1;
+
+/// Number of prunes below which the profile is considered too small to base
+/// any recommendation on
+const MIN_PRUNES_FOR_RECOMMENDATION: usize = 10;
+
+/// Counts the prunes of each lower bound enabled in the settings, and of the
+/// nogoods if they are learned.
+fn prune_counts(stats: &ReductionStats, settings: &Settings) -> Vec<(&'static str, usize)> {
+    [
+        (
+            "max_degree_bound",
+            settings.enable_max_degree_bound,
+            stats.max_degree_bound_breaks,
+        ),
+        (
+            "sum_degree_bound",
+            settings.enable_sum_degree_bound,
+            stats.sum_degree_bound_breaks,
+        ),
+        (
+            "efficiency_bound",
+            settings.enable_efficiency_bound,
+            stats.efficiency_degree_bound_breaks,
+        ),
+        (
+            "packing_bound",
+            settings.enable_packing_bound,
+            stats.packing_bound_breaks,
+        ),
+        (
+            "sum_over_packing_bound",
+            settings.enable_packing_bound && settings.enable_sum_over_packing_bound,
+            stats.sum_over_packing_bound_breaks,
+        ),
+        (
+            "flow_bound",
+            settings.enable_flow_bound,
+            stats.flow_bound_breaks,
+        ),
+        (
+            "clique_cover_bound",
+            settings.enable_clique_cover_bound,
+            stats.clique_cover_bound_breaks,
+        ),
+        ("custom_bounds", true, stats.custom_bound_breaks),
+        ("nogoods", settings.nogood_limit > 0, stats.nogood_breaks),
+    ]
+    .into_iter()
+    .filter(|&(_, enabled, _)| enabled)
+    .map(|(name, _, count)| (name, count))
+    .collect()
+}
+
+/// Counts the nodes or edges found by each reduction rule, together with the
+/// number of times it ran.
+fn reduction_counts(stats: &ReductionStats) -> Vec<(&'static str, usize, usize)> {
+    let from_scratch_found = stats
+        .costly_discard_packing_from_scratch_steps_per_run
+        .split_last()
+        .map_or(0, |(_, found)| found.iter().sum());
+    vec![
+        (
+            "forced_vertex",
+            stats.forced_vertex_runs,
+            stats.forced_vertices_found,
+        ),
+        (
+            "degree_forcing",
+            stats.degree_forcing_runs,
+            stats.degree_forcing_vertices_found,
+        ),
+        (
+            "degree_discarding",
+            stats.degree_discarding_runs,
+            stats.degree_discarding_vertices_found,
+        ),
+        (
+            "costly_discard_efficiency",
+            stats.costly_discard_efficiency_runs,
+            stats.costly_discard_efficiency_vertices_found,
+        ),
+        (
+            "costly_discard_packing_update",
+            stats.costly_discard_packing_update_runs,
+            stats.costly_discard_packing_update_vertices_found,
+        ),
+        (
+            "costly_discard_packing_from_scratch",
+            stats.costly_discard_packing_from_scratch_runs,
+            from_scratch_found,
+        ),
+        (
+            "costly_discard_custom",
+            stats.costly_discard_custom_runs,
+            stats.costly_discard_custom_vertices_found,
+        ),
+        (
+            "custom_reductions",
+            stats.custom_reduction_runs,
+            stats.custom_reduction_items_found,
+        ),
+        (
+            "vertex_domination",
+            stats.vertex_dominations_runs,
+            stats.vertex_dominations_vertices_found,
+        ),
+        (
+            "edge_domination",
+            stats.edge_dominations_runs,
+            stats.edge_dominations_edges_found,
+        ),
+    ]
+}
+
+/// Appends the rules with at least `HOT_SHARE` of the total count to `hot`,
+/// most important first.
+#[allow(clippy::cast_precision_loss)]
+fn push_hot(hot: &mut Vec<String>, counts: &[(&'static str, usize)]) {
+    let total: usize = counts.iter().map(|&(_, count)| count).sum();
+    let mut hot_counts: Vec<_> = counts
+        .iter()
+        .filter(|&&(_, count)| count > 0 && count as f64 >= HOT_SHARE * total as f64)
+        .collect();
+    hot_counts.sort_by_key(|&&(name, count)| (std::cmp::Reverse(count), name));
+    hot.extend(hot_counts.into_iter().map(|&(name, _)| name.to_string()));
+}
+
+/// Summarizes which rules pruned subproblems or reduced the hypergraph during
+/// the search.
+#[allow(clippy::cast_precision_loss)]
+pub fn calc_profile(report: &Report) -> InstanceProfile {
+    let stats = &report.reductions;
+    let prunes = prune_counts(stats, &report.settings);
+    let reductions: Vec<_> = reduction_counts(stats)
+        .into_iter()
+        .filter(|&(_, runs, _)| runs > 0)
+        .collect();
+    let total_prunes = prunes.iter().map(|&(_, count)| count).sum();
+
+    let mut hot = Vec::new();
+    push_hot(&mut hot, &prunes);
+    let found: Vec<_> = reductions
+        .iter()
+        .map(|&(name, _, found)| (name, found))
+        .collect();
+    push_hot(&mut hot, &found);
+
+    // Custom bounds are only listed if some were passed to the solver
+    let cold = prunes
+        .iter()
+        .filter(|&&(name, count)| count == 0 && name != "custom_bounds")
+        .map(|&(name, _)| name)
+        .chain(
+            found
+                .iter()
+                .filter(|&&(_, count)| count == 0)
+                .map(|&(name, _)| name),
+        )
+        .map(str::to_string)
+        .collect();
+
+    InstanceProfile {
+        prunes: total_prunes,
+        prune_shares: prunes
+            .iter()
+            .filter(|&&(_, count)| count > 0)
+            .map(|&(name, count)| (name.to_string(), count as f64 / total_prunes as f64))
+            .collect(),
+        reductions_found: found
+            .iter()
+            .filter(|&&(_, count)| count > 0)
+            .map(|&(name, count)| (name.to_string(), count))
+            .collect::<BTreeMap<_, _>>(),
+        hot,
+        cold,
+    }
+}
+
+/// Adapts the settings to a profile, returning the changed settings together
+/// with a reason for each change.
+///
+/// Rules that never pruned or reduced anything are disabled if a setting
+/// controls them (including nogood learning), as computing them was wasted
+/// time. The efficiency bound is
+/// only disabled if its costly discards found nothing either. The packing
+/// bound is always kept, as several other rules build on its packing. Local
+/// search is enabled if the packing bound causes most prunes, as it
+/// strengthens exactly that bound. Profiles with fewer than 10 prunes do not
+/// lead to any changes.
+pub fn recommend_settings(
+    profile: &InstanceProfile,
+    mut settings: Settings,
+) -> (Settings, Vec<String>) {
+    let mut reasons = Vec::new();
+    if profile.prunes < MIN_PRUNES_FOR_RECOMMENDATION {
+        reasons.push(format!(
+            "Only {} prunes recorded, keeping the settings",
+            profile.prunes
+        ));
+        return (settings, reasons);
+    }
+
+    let is_cold = |name: &str| profile.cold.iter().any(|cold| cold == name);
+    let mut disable = |name: &str, setting: &mut bool| {
+        if *setting {
+            *setting = false;
+            reasons.push(format!("Disabled {} as it never helped", name));
+        }
+    };
+    if is_cold("max_degree_bound") {
+        disable("max_degree_bound", &mut settings.enable_max_degree_bound);
+    }
+    if is_cold("sum_degree_bound") {
+        disable("sum_degree_bound", &mut settings.enable_sum_degree_bound);
+    }
+    if is_cold("efficiency_bound")
+        && !profile
+            .reductions_found
+            .contains_key("costly_discard_efficiency")
+    {
+        disable("efficiency_bound", &mut settings.enable_efficiency_bound);
+    }
+    if is_cold("sum_over_packing_bound") {
+        disable(
+            "sum_over_packing_bound",
+            &mut settings.enable_sum_over_packing_bound,
+        );
+    }
+    if is_cold("flow_bound") {
+        disable("flow_bound", &mut settings.enable_flow_bound);
+    }
+    if is_cold("clique_cover_bound") {
+        disable(
+            "clique_cover_bound",
+            &mut settings.enable_clique_cover_bound,
+        );
+    }
+    if is_cold("degree_forcing") {
+        disable("degree_forcing", &mut settings.enable_degree_forcing);
+    }
+    if is_cold("degree_discarding") {
+        disable("degree_discarding", &mut settings.enable_degree_discarding);
+    }
+    if is_cold("nogoods") && settings.nogood_limit > 0 {
+        settings.nogood_limit = 0;
+        reasons.push("Disabled nogoods as they never helped".to_string());
+    }
+
+    let packing_share = profile
+        .prune_shares
+        .get("packing_bound")
+        .copied()
+        .unwrap_or(0.0);
+    if packing_share >= 0.5 && !settings.enable_local_search {
+        settings.enable_local_search = true;
+        reasons.push(format!(
+            "Enabled local search as the packing bound caused {:.0}% of the prunes",
+            packing_share * 100.0
+        ));
+    }
+    (settings, reasons)
+}
@@ -0,0 +1,153 @@
+use crate::{
+    clock::Instant,
+    report::{IncumbentEvent, ProgressEvent, ReportEvent, ReportStreamInfo},
+};
+use anyhow::Result;
+use std::{
+    fmt::{self, Debug, Formatter},
+    io::Write,
+    time::Duration,
+};
+
+/// Writes progress events as json lines, at most once per interval.
+pub struct ProgressWriter {
+    writer: Box<dyn Write>,
+    interval: Duration,
+    last_event_time: Option<Instant>,
+}
+
+impl Debug for ProgressWriter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProgressWriter")
+            .field("interval", &self.interval)
+            .field("last_event_time", &self.last_event_time)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ProgressWriter {
+    pub fn new(writer: Box<dyn Write>, interval: Duration) -> Self {
+        Self {
+            writer,
+            interval,
+            last_event_time: None,
+        }
+    }
+
+    pub fn is_due(&self, now: Instant) -> bool {
+        self.last_event_time
+            .is_none_or(|last_event_time| now - last_event_time >= self.interval)
+    }
+
+    pub fn write_event(&mut self, event: &ProgressEvent) -> Result<()> {
+        self.last_event_time = Some(Instant::now());
+        serde_json::to_writer(&mut self.writer, event)?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Writes events that would otherwise accumulate in the report, such as the
+/// upper bound improvements, as json lines, keeping the memory usage of long
+/// runs constant.
+pub struct ReportStream {
+    writer: Box<dyn Write>,
+    location: String,
+    num_events: usize,
+}
+
+impl Debug for ReportStream {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReportStream")
+            .field("location", &self.location)
+            .field("num_events", &self.num_events)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ReportStream {
+    /// The location (e.g., a file name) is recorded in the report to refer
+    /// to the stream.
+    pub fn new(writer: Box<dyn Write>, location: String) -> Self {
+        Self {
+            writer,
+            location,
+            num_events: 0,
+        }
+    }
+
+    pub fn write_event(&mut self, event: &ReportEvent) -> Result<()> {
+        serde_json::to_writer(&mut self.writer, event)?;
+        self.writer.write_all(b"\n")?;
+        self.num_events += 1;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    pub fn info(&self) -> ReportStreamInfo {
+        ReportStreamInfo {
+            location: self.location.clone(),
+            num_events: self.num_events,
+        }
+    }
+}
+
+/// Passes progress events to a callback, at most once per given number of
+/// branching steps.
+pub struct ProgressCallback {
+    callback: Box<dyn FnMut(&ProgressEvent)>,
+    interval: usize,
+    next_step: usize,
+}
+
+impl Debug for ProgressCallback {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProgressCallback")
+            .field("interval", &self.interval)
+            .field("next_step", &self.next_step)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ProgressCallback {
+    pub fn new(interval: usize, callback: impl FnMut(&ProgressEvent) + 'static) -> Self {
+        Self {
+            callback: Box::new(callback),
+            interval: interval.max(1),
+            next_step: 0,
+        }
+    }
+
+    pub fn is_due(&self, branching_steps: usize) -> bool {
+        branching_steps >= self.next_step
+    }
+
+    pub fn call(&mut self, event: &ProgressEvent) {
+        self.next_step = event.branching_steps + self.interval;
+        (self.callback)(event);
+    }
+}
+
+/// Passes each new best hitting set found during the search to a callback.
+pub struct IncumbentCallback(Box<dyn FnMut(&IncumbentEvent)>);
+
+impl Debug for IncumbentCallback {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IncumbentCallback").finish_non_exhaustive()
+    }
+}
+
+impl IncumbentCallback {
+    pub fn new(callback: impl FnMut(&IncumbentEvent) + 'static) -> Self {
+        Self(Box::new(callback))
+    }
+
+    pub fn call(&mut self, event: &IncumbentEvent) {
+        (self.0)(event);
+    }
+}
@@ -1,26 +1,44 @@
 use crate::{
-    data_structures::{subset_trie::SubsetTrie, superset_trie::SupersetTrie},
+    clock::Instant,
+    data_structures::{
+        subset_trie::{self, SubsetTrie, SubsetTrieArena},
+        superset_trie::{self, SupersetTrie, SupersetTrieArena},
+    },
     instance::{EdgeIdx, Instance, NodeIdx},
-    lower_bound::{self, EfficiencyBound, PackingBound},
-    report::{GreedyMode, Report, Settings, UpperBoundImprovement},
-    small_indices::{IdxHashSet, SmallIdx},
-    solve::State,
-};
-use log::info;
-use std::{
-    cmp::Reverse,
-    collections::BinaryHeap,
-    time::{Duration, Instant},
+    lower_bound::{self, BuiltinBound, EfficiencyBound, LowerBound, PackingBound},
+    preprocessing::{PreprocessingAction, ReductionRule},
+    report::{GreedyMode, GreedyStrategy, ReductionStats, Report, Settings},
+    small_indices::{IdxHashMap, IdxHashSet, SmallIdx},
+    solve::{SolverRng, State},
+    upper_bound,
 };
+use log::{info, trace};
+use rand::SeedableRng;
+use serde::Serialize;
+use std::{cmp::Reverse, fmt, mem, sync::Arc, thread, time::Duration};
 
-#[derive(Copy, Clone, Debug)]
-enum ReducedItem {
+/// Change made to the instance by a reduction rule
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ReducedItem {
+    /// The node can be left out of the hitting set
     RemovedNode(NodeIdx),
+
+    /// Every hitting set of the other edges also hits this edge
     RemovedEdge(EdgeIdx),
+
+    /// The node can be added to the hitting set, removing its incident edges
     ForcedNode(NodeIdx),
 }
 
 impl ReducedItem {
+    fn action(self) -> PreprocessingAction {
+        match self {
+            Self::RemovedNode(node) => PreprocessingAction::RemovedNode(node),
+            Self::RemovedEdge(edge) => PreprocessingAction::RemovedEdge(edge),
+            Self::ForcedNode(node) => PreprocessingAction::ForcedNode(node),
+        }
+    }
+
     fn apply(self, instance: &mut Instance, partial_hs: &mut Vec<NodeIdx>) {
         match self {
             Self::RemovedNode(node) => instance.delete_node(node),
@@ -31,6 +49,7 @@ impl ReducedItem {
                 partial_hs.push(node);
             }
         }
+        self.check_if_paranoid(instance, "applying");
     }
 
     fn restore(self, instance: &mut Instance, partial_hs: &mut Vec<NodeIdx>) {
@@ -44,6 +63,17 @@ impl ReducedItem {
                 partial_hs.pop();
             }
         }
+        self.check_if_paranoid(instance, "restoring");
+    }
+
+    /// Verifies the instance after this item was applied or restored if the
+    /// instance is paranoid, naming the item if it is inconsistent.
+    fn check_if_paranoid(self, instance: &Instance, step: &str) {
+        if instance.is_paranoid() {
+            if let Err(err) = instance.check_invariants() {
+                panic!("Instance inconsistent after {} {:?}: {:#}", step, self, err);
+            }
+        }
     }
 }
 
@@ -51,6 +81,13 @@ impl ReducedItem {
 pub struct Reduction(Vec<ReducedItem>);
 
 impl Reduction {
+    pub fn num_forced_nodes(&self) -> usize {
+        self.0
+            .iter()
+            .filter(|item| matches!(item, ReducedItem::ForcedNode(_)))
+            .count()
+    }
+
     pub fn restore(&self, instance: &mut Instance, partial_hs: &mut Vec<NodeIdx>) {
         for item in self.0.iter().rev() {
             item.restore(instance, partial_hs);
@@ -58,6 +95,34 @@ impl Reduction {
     }
 }
 
+/// Reason why a subproblem can not lead to a smaller hitting set
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum PruneReason {
+    /// The partial hitting set is not smaller than the minimum hitting set
+    Incumbent,
+
+    MaxDegreeBound,
+    SumDegreeBound,
+    EfficiencyBound,
+    PackingBound,
+    SumOverPackingBound,
+    FlowBound,
+    CliqueCoverBound,
+
+    /// One of the lower bounds passed in `SolveHooks::lower_bounds`
+    CustomBound,
+
+    /// Conflicts between nodes leave some edge without nodes
+    Conflicts,
+
+    /// Removed nodes leave some edge with fewer nodes than it needs to be hit
+    /// by
+    UncoverableEdge,
+
+    /// The subproblem matches a nogood learned earlier
+    Nogood,
+}
+
 #[derive(Debug, Clone)]
 pub enum ReductionResult {
     /// The instance was fully solved through reductions.
@@ -70,7 +135,7 @@ pub enum ReductionResult {
     ///
     /// This occurs when a lower bound meets or exceeds the size of the current
     /// minimum hitting set (the upper bound).
-    Unsolvable,
+    Unsolvable(PruneReason),
 
     /// A minimum hitting set smaller or equal to the stopping size was found.
     Stop,
@@ -79,49 +144,371 @@ pub enum ReductionResult {
     Finished,
 }
 
-fn find_dominated_nodes(instance: &Instance) -> impl Iterator<Item = ReducedItem> + '_ {
-    let mut nodes = instance.nodes().to_vec();
-    nodes.sort_unstable_by_key(|&node| Reverse(instance.node_degree(node)));
-    let mut trie = SupersetTrie::new(instance.num_edges_total());
-    nodes.into_iter().filter_map(move |node| {
+/// Word-parallel variant of `find_dominated_nodes` for small instances.
+///
+/// A node is dominated by any other node contained in all of its edges. Of
+/// multiple nodes with identical incidences, the one with the smallest index
+/// is kept.
+fn find_dominated_nodes_bitset(instance: &Instance, edge_bitsets: &[u128]) -> Vec<ReducedItem> {
+    let alive = instance
+        .nodes()
+        .iter()
+        .fold(0, |bits, node| bits | (1 << node.idx()));
+    instance
+        .nodes()
+        .iter()
+        .copied()
+        .filter(|&node| {
+            let dominating = instance
+                .node(node)
+                .fold(alive, |bits, edge| bits & edge_bitsets[edge.idx()])
+                & !(1 << node.idx());
+            let degree = instance.node_degree(node);
+            let mut candidates = dominating;
+            while candidates != 0 {
+                let other = NodeIdx::from(candidates.trailing_zeros() as usize);
+                candidates &= candidates - 1;
+                let other_degree = instance.node_degree(other);
+                if other_degree > degree || (other_degree == degree && other < node) {
+                    return true;
+                }
+            }
+            false
+        })
+        .map(ReducedItem::RemovedNode)
+        .collect()
+}
+
+/// Word-parallel variant of `find_dominated_edges` for small instances.
+fn find_dominated_edges_bitset(instance: &Instance, edge_bitsets: &[u128]) -> Vec<ReducedItem> {
+    let mut edges = instance.edges().to_vec();
+    edges.sort_unstable_by_key(|&edge| (instance.edge_size(edge), edge));
+    let mut kept = Vec::new();
+    edges
+        .into_iter()
+        .filter(|&edge| {
+            let bits = edge_bitsets[edge.idx()];
+            let dominated = instance.edge_requirement(edge) == 1
+                && kept.iter().any(|&kept_bits| kept_bits & !bits == 0);
+            if !dominated {
+                kept.push(bits);
+            }
+            dominated
+        })
+        .map(ReducedItem::RemovedEdge)
+        .collect()
+}
+
+/// Trie of the node domination rule, kept across the iterations of the
+/// `reduce` loop.
+///
+/// After a run, the trie stores the incident edges of every node kept by the
+/// run. Deletions applied afterwards are recorded, so that the next run only
+/// replaces and checks the nodes whose incident edges changed.
+#[derive(Debug, Default)]
+struct NodeDominationTrie {
+    arena: SupersetTrieArena<EdgeIdx>,
+    /// Trie node at which the stored incidences of each node end, invalid for
+    /// nodes not stored in the trie
+    stored: Vec<superset_trie::TrieNodeIdx>,
+    /// Whether the trie matches the instance, up to the recorded changes
+    valid: bool,
+    /// Nodes deleted or with edges deleted since the last run
+    changed: Vec<NodeIdx>,
+}
+
+/// Trie of the edge domination rule, kept like `NodeDominationTrie`.
+///
+/// The marker of each trie node counts the stored edges ending there, as edges
+/// needing to be hit more than once are stored even if the same edge is.
+#[derive(Debug, Default)]
+struct EdgeDominationTrie {
+    arena: SubsetTrieArena<NodeIdx, u32>,
+    stored: Vec<subset_trie::TrieNodeIdx>,
+    valid: bool,
+    /// Edges deleted or with nodes deleted since the last run
+    changed: Vec<EdgeIdx>,
+}
+
+/// Tries used by the domination rules, which are updated incrementally with
+/// the deletions made through `apply` and `delete_conflicting_nodes`.
+///
+/// Any other change of the instance, such as restoring a reduction, makes both
+/// rules rebuild their tries on their next run. The allocations of the tries
+/// are reused either way.
+#[derive(Debug, Default)]
+pub struct DominationTries {
+    nodes: NodeDominationTrie,
+    edges: EdgeDominationTrie,
+    /// `Instance::num_changes` after the last recorded change
+    instance_changes: usize,
+}
+
+impl DominationTries {
+    /// Makes both rules rebuild their tries on their next run.
+    pub fn invalidate(&mut self) {
+        self.nodes.valid = false;
+        self.edges.valid = false;
+    }
+
+    /// Invalidates the tries if the instance was changed without recording
+    /// the changes.
+    fn check_unrecorded_changes(&mut self, instance: &Instance) {
+        if self.instance_changes != instance.num_changes() {
+            self.invalidate();
+            self.instance_changes = instance.num_changes();
+        }
+    }
+
+    /// Runs a function that restores everything it deletes in reverse order,
+    /// which leaves the tries valid.
+    fn run_restoring<T>(
+        &mut self,
+        instance: &mut Instance,
+        func: impl FnOnce(&mut Instance) -> T,
+    ) -> T {
+        let up_to_date = self.instance_changes == instance.num_changes();
+        let result = func(instance);
+        if up_to_date {
+            self.instance_changes = instance.num_changes();
+        }
+        result
+    }
+
+    /// Applies a reduced item to the instance, recording its changes.
+    pub fn apply(
+        &mut self,
+        item: ReducedItem,
+        instance: &mut Instance,
+        partial_hs: &mut Vec<NodeIdx>,
+    ) {
+        let up_to_date = self.instance_changes == instance.num_changes();
+        self.record(item, instance);
+        item.apply(instance, partial_hs);
+        if up_to_date {
+            self.instance_changes = instance.num_changes();
+        }
+    }
+
+    /// Like `Instance::delete_conflicting_nodes`, but records the deletions.
+    pub fn delete_conflicting_nodes(
+        &mut self,
+        instance: &mut Instance,
+        node: NodeIdx,
+    ) -> (Vec<NodeIdx>, bool) {
+        let up_to_date = self.instance_changes == instance.num_changes();
+        let (conflicting, feasible) = instance.delete_conflicting_nodes(node);
+        for &other in &conflicting {
+            self.record_node_deletion(other, instance);
+        }
+        if up_to_date {
+            self.instance_changes = instance.num_changes();
+        }
+        (conflicting, feasible)
+    }
+
+    /// Records the changes an item is about to make to the instance.
+    fn record(&mut self, item: ReducedItem, instance: &Instance) {
+        match item {
+            ReducedItem::RemovedNode(node) => self.record_node_deletion(node, instance),
+            ReducedItem::RemovedEdge(edge) => self.record_edge_deletion(edge, instance),
+            ReducedItem::ForcedNode(node) => {
+                self.record_node_deletion(node, instance);
+                // Edges needing to be hit more than once are kept with one
+                // node less, which also changes them
+                for edge in instance.node(node) {
+                    self.record_edge_deletion(edge, instance);
+                }
+            }
+        }
+    }
+
+    /// Records the deletion of a node. As its incident edges are kept by the
+    /// instance, this can also be done right after deleting it.
+    fn record_node_deletion(&mut self, node: NodeIdx, instance: &Instance) {
+        if self.nodes.valid {
+            self.nodes.changed.push(node);
+        }
+        if self.edges.valid {
+            self.edges.changed.extend(instance.node(node));
+        }
+    }
+
+    fn record_edge_deletion(&mut self, edge: EdgeIdx, instance: &Instance) {
+        if self.nodes.valid {
+            self.nodes.changed.extend(instance.edge(edge));
+        }
+        if self.edges.valid {
+            self.edges.changed.push(edge);
+        }
+    }
+}
+
+/// Finds nodes whose edges all contain another node. Of multiple nodes with
+/// identical incidences, one is kept.
+///
+/// If the trie is valid, only the nodes whose incident edges changed since
+/// the last run are checked. Other nodes can not become dominated, as
+/// deletions only shrink the incidences of the nodes that could dominate
+/// them.
+fn find_dominated_nodes<'a>(
+    instance: &'a Instance,
+    trie: &'a mut NodeDominationTrie,
+) -> Box<dyn Iterator<Item = ReducedItem> + 'a> {
+    if let Some(edge_bitsets) = instance.edge_bitsets() {
+        trie.valid = false;
+        return Box::new(find_dominated_nodes_bitset(instance, &edge_bitsets).into_iter());
+    }
+
+    let NodeDominationTrie {
+        arena,
+        stored,
+        valid,
+        changed,
+    } = trie;
+    let (mut trie, mut nodes) = if *valid {
+        let mut trie = SupersetTrie::open(instance.num_edges_total(), arena);
+        changed.sort_unstable();
+        changed.dedup();
+        for &node in &*changed {
+            let trie_node =
+                mem::replace(&mut stored[node.idx()], superset_trie::TrieNodeIdx::INVALID);
+            if trie_node.valid() {
+                trie.remove(trie_node);
+            }
+        }
+        let nodes: Vec<_> = changed
+            .drain(..)
+            .filter(|&node| !instance.is_node_deleted(node))
+            .collect();
+        (trie, nodes)
+    } else {
+        *valid = true;
+        changed.clear();
+        stored.clear();
+        stored.resize(
+            instance.num_nodes_total(),
+            superset_trie::TrieNodeIdx::INVALID,
+        );
+        let trie = SupersetTrie::new(instance.num_edges_total(), arena);
+        (trie, instance.nodes().to_vec())
+    };
+
+    nodes.sort_unstable_by_key(|&node| (Reverse(instance.node_degree(node)), node));
+    Box::new(nodes.into_iter().filter_map(move |node| {
         if trie.contains_superset(instance.node(node)) {
             Some(ReducedItem::RemovedNode(node))
         } else {
-            trie.insert(instance.node(node));
+            stored[node.idx()] = trie.insert(instance.node(node));
             None
         }
-    })
+    }))
 }
 
-fn find_dominated_edges(instance: &Instance) -> impl Iterator<Item = ReducedItem> + '_ {
-    let mut edges = instance.edges().to_vec();
-    edges.sort_unstable_by_key(|&edge| instance.edge_size(edge));
-    let mut trie = SubsetTrie::new(instance.num_nodes_total());
-    edges.into_iter().filter_map(move |edge| {
-        if trie.find_subset(instance.edge(edge)) {
+fn subset_trie_chunked_max_nodes(settings: &Settings) -> usize {
+    settings
+        .subset_trie_chunked_max_nodes
+        .unwrap_or(subset_trie::DEFAULT_CHUNKED_MAX_VAL_RANGE)
+}
+
+/// Edges whose domination needs to be checked again after the recorded
+/// changes, or `None` if the trie needs to be rebuilt.
+///
+/// Besides the changed edges themselves, these are the edges containing a
+/// changed edge, which all contain its node of smallest degree. Other edges
+/// can not become dominated, as their potential subsets did not change.
+fn changed_edge_candidates(instance: &Instance, changed: &[EdgeIdx]) -> Option<Vec<EdgeIdx>> {
+    let mut candidates = Vec::new();
+    for &edge in changed {
+        if instance.is_edge_deleted(edge) {
+            continue;
+        }
+        // An empty edge is contained in every other edge
+        let node = instance
+            .edge(edge)
+            .min_by_key(|&node| instance.node_degree(node))?;
+        candidates.push(edge);
+        candidates.extend(instance.node(node));
+    }
+    candidates.sort_unstable();
+    candidates.dedup();
+    Some(candidates)
+}
+
+/// Finds edges containing another edge, which are hit by every hitting set of
+/// the other edge. Edges that need to be hit more than once are kept, as a
+/// single hit of the smaller edge does not suffice for them. Of multiple
+/// identical edges, one is kept.
+///
+/// If the trie is valid, only the candidates of `changed_edge_candidates` are
+/// checked.
+fn find_dominated_edges<'a>(
+    instance: &'a Instance,
+    chunked_max_nodes: usize,
+    trie: &'a mut EdgeDominationTrie,
+) -> Box<dyn Iterator<Item = ReducedItem> + 'a> {
+    if let Some(edge_bitsets) = instance.edge_bitsets() {
+        trie.valid = false;
+        return Box::new(find_dominated_edges_bitset(instance, &edge_bitsets).into_iter());
+    }
+
+    let EdgeDominationTrie {
+        arena,
+        stored,
+        valid,
+        changed,
+    } = trie;
+    changed.sort_unstable();
+    changed.dedup();
+    let candidates = if *valid {
+        changed_edge_candidates(instance, changed)
+    } else {
+        None
+    };
+    let (mut trie, mut edges) = if let Some(candidates) = candidates {
+        let mut trie = SubsetTrie::open(instance.num_nodes_total(), arena);
+        for &edge in changed.iter().chain(&candidates) {
+            let trie_node =
+                mem::replace(&mut stored[edge.idx()], subset_trie::TrieNodeIdx::INVALID);
+            if trie_node.valid() {
+                *trie.marker_mut(trie_node) -= 1;
+            }
+        }
+        (trie, candidates)
+    } else {
+        *valid = true;
+        stored.clear();
+        stored.resize(
+            instance.num_edges_total(),
+            subset_trie::TrieNodeIdx::INVALID,
+        );
+        let trie = SubsetTrie::new(instance.num_nodes_total(), chunked_max_nodes, arena);
+        (trie, instance.edges().to_vec())
+    };
+    changed.clear();
+
+    edges.sort_unstable_by_key(|&edge| (instance.edge_size(edge), edge));
+    Box::new(edges.into_iter().filter_map(move |edge| {
+        if instance.edge_requirement(edge) == 1 && trie.find_subset(instance.edge(edge)) > 0 {
             Some(ReducedItem::RemovedEdge(edge))
         } else {
-            trie.insert(true, instance.edge(edge));
+            let trie_node = trie.insert_path(instance.edge(edge));
+            *trie.marker_mut(trie_node) += 1;
+            stored[edge.idx()] = trie_node;
             None
         }
-    })
+    }))
 }
 
+/// Finds the nodes of unit edges, that is edges of size one or edges with as
+/// many nodes as they need to be hit by, which every hitting set contains. The
+/// instance keeps track of these edges, so this only takes time proportional
+/// to their number.
 fn find_forced_nodes(instance: &Instance) -> impl Iterator<Item = ReducedItem> {
     let forced: IdxHashSet<_> = instance
-        .edges()
+        .unit_edges()
         .iter()
-        .copied()
-        .filter_map(|edge| {
-            let mut edge_nodes_iter = instance.edge(edge);
-            edge_nodes_iter.next().and_then(|first_node| {
-                if edge_nodes_iter.next().is_some() {
-                    None
-                } else {
-                    Some(first_node)
-                }
-            })
-        })
+        .flat_map(|&edge| instance.edge(edge))
         .collect();
     forced.into_iter().map(ReducedItem::ForcedNode)
 }
@@ -193,81 +580,267 @@ fn find_costly_discard_using_packing_from_scratch(
         })
 }
 
-pub fn calc_greedy_approximation(instance: &Instance) -> Vec<NodeIdx> {
-    let mut hit = vec![true; instance.num_edges_total()];
-    for edge in instance.edges() {
-        hit[edge.idx()] = false;
+/// Greedily adds the node hitting the most unhit edges until all edges are hit
+/// as often as they need to be. Returns `None` if conflicts between nodes
+/// prevent finding a hitting set.
+pub fn calc_greedy_approximation(instance: &Instance) -> Option<Vec<NodeIdx>> {
+    // Number of hits each edge still needs
+    let mut needed = vec![0; instance.num_edges_total()];
+    for &edge in instance.edges() {
+        needed[edge.idx()] = instance.edge_requirement(edge);
     }
+
+    // Bucket queue indexed by degree. Since degrees only ever decrease, the
+    // maximum degree can be tracked by a pointer that only moves downwards.
+    // Outdated entries are skipped lazily when popped.
     let mut node_degrees = vec![0; instance.num_nodes_total()];
-    let mut node_queue = BinaryHeap::new();
+    let mut max_degree = 0;
     for &node in instance.nodes() {
         node_degrees[node.idx()] = instance.node_degree(node);
-        node_queue.push((node_degrees[node.idx()], node));
+        max_degree = max_degree.max(node_degrees[node.idx()]);
+    }
+    let mut buckets = vec![Vec::new(); max_degree + 1];
+    for &node in instance.nodes() {
+        buckets[node_degrees[node.idx()]].push(node);
     }
 
     let mut hs = Vec::new();
-    while let Some((degree, node)) = node_queue.pop() {
-        if degree == 0 {
-            break;
-        }
-        if degree > node_degrees[node.idx()] {
+    while max_degree > 0 {
+        let Some(node) = buckets[max_degree].pop() else {
+            max_degree -= 1;
+            continue;
+        };
+        if node_degrees[node.idx()] != max_degree {
             continue;
         }
 
         hs.push(node);
-        node_degrees[node.idx()] = 0; // Fewer elements in the heap
+        node_degrees[node.idx()] = 0;
+        for other in instance.conflicts(node) {
+            node_degrees[other.idx()] = 0;
+        }
         for edge in instance.node(node) {
-            if hit[edge.idx()] {
+            if needed[edge.idx()] == 0 {
                 continue;
             }
 
-            hit[edge.idx()] = true;
+            needed[edge.idx()] -= 1;
+            if needed[edge.idx()] > 0 {
+                continue;
+            }
             for edge_node in instance.edge(edge) {
                 if node_degrees[edge_node.idx()] > 0 {
                     node_degrees[edge_node.idx()] -= 1;
-                    node_queue.push((node_degrees[edge_node.idx()], edge_node));
+                    buckets[node_degrees[edge_node.idx()]].push(edge_node);
                 }
             }
         }
     }
 
-    hs
+    // With conflicts, the chosen nodes might exclude too many nodes of some
+    // edge
+    instance
+        .edges()
+        .iter()
+        .all(|edge| needed[edge.idx()] == 0)
+        .then_some(hs)
 }
 
 fn recalculate_greedy_upper_bound(instance: &Instance, state: &mut State, report: &mut Report) {
     report.reductions.greedy_runs += 1;
-    let improvements_list_ref = &mut report.upper_bound_improvements;
-    let branching_steps = report.branching_steps;
-    collect_time_info(&mut report.runtimes.greedy, || {
-        let greedy = calc_greedy_approximation(instance);
-        if state.partial_hs.len() + greedy.len() < state.minimum_hs.len() {
-            state.minimum_hs.clear();
-            state.minimum_hs.extend(state.partial_hs.iter().copied());
-            state.minimum_hs.extend(greedy.iter().copied());
-            improvements_list_ref.push(UpperBoundImprovement {
-                new_bound: state.minimum_hs.len(),
-                branching_steps,
-                runtime: state.solve_start_time.elapsed(),
-            });
-            info!(
-                "Found HS of size {} using greedy (partial {} + greedy {})",
-                state.minimum_hs.len(),
-                state.partial_hs.len(),
-                greedy.len()
-            );
+    let settings = &report.settings;
+    let improved = collect_time_info(&mut report.runtimes.greedy, || {
+        let greedy = if settings.greedy_strategy == GreedyStrategy::MaxDegree
+            || instance.has_conflicts()
+            || instance.has_requirements()
+        {
+            calc_greedy_approximation(instance)
+        } else {
+            let mut rng = SolverRng::seed_from_u64(settings.seed);
+            Some(upper_bound::calc_greedy_approximation_with_strategy(
+                instance,
+                settings.greedy_strategy,
+                &mut rng,
+            ))
+        };
+        let Some(greedy) = greedy else {
+            return false;
+        };
+        if state.partial_hs.len() + greedy.len() >= state.minimum_hs.len() {
+            return false;
         }
+        state.minimum_hs.clear();
+        state.minimum_hs.extend(state.partial_hs.iter().copied());
+        state.minimum_hs.extend(greedy.iter().copied());
+        info!(
+            event = "incumbent", size = state.minimum_hs.len(), method = "greedy";
+            "Found HS of size {} using greedy (partial {} + greedy {})",
+            state.minimum_hs.len(),
+            state.partial_hs.len(),
+            greedy.len()
+        );
+        true
     });
+    if improved {
+        state.record_improvement(report);
+    }
 }
 
-fn collect_time_info<T>(runtime: &mut Duration, func: impl FnOnce() -> T) -> T {
+pub fn collect_time_info<T>(runtime: &mut Duration, func: impl FnOnce() -> T) -> T {
     let before = Instant::now();
     let result = func();
     *runtime += before.elapsed();
     result
 }
 
+/// Finds nodes whose edges can not be hit by `budget` other nodes, counting
+/// only the degrees of the other nodes. As no smaller hitting set can add more
+/// than `budget` nodes, these nodes must be part of it.
+fn find_forced_nodes_by_degree(instance: &Instance, budget: usize) -> Vec<ReducedItem> {
+    let mut largest_degrees: Vec<_> = instance
+        .nodes()
+        .iter()
+        .map(|&node| instance.node_degree(node))
+        .collect();
+    largest_degrees.sort_unstable_by_key(|&degree| Reverse(degree));
+    largest_degrees.truncate(budget + 1);
+
+    instance
+        .nodes()
+        .iter()
+        .copied()
+        .filter(|&node| {
+            // Skipping one occurrence of the node's degree yields the largest
+            // degrees of the other nodes
+            let degree = instance.node_degree(node);
+            let mut skipped = false;
+            let max_hit_edges: usize = largest_degrees
+                .iter()
+                .filter(|&&other_degree| {
+                    let skip = !skipped && other_degree == degree;
+                    skipped |= skip;
+                    !skip
+                })
+                .take(budget)
+                .map(|&other_degree| other_degree.min(degree))
+                .sum();
+            max_hit_edges < degree
+        })
+        .map(ReducedItem::ForcedNode)
+        .collect()
+}
+
+/// Finds nodes that can not be part of a hitting set adding at most `budget`
+/// nodes: even together with the `budget - 1` other nodes of largest degree,
+/// they hit fewer than all edges. This generalizes discarding a node of degree
+/// one, which domination misses if the node's edge is not contained in the
+/// edges of another node.
+fn find_discarded_nodes_by_degree(instance: &Instance, budget: usize) -> Vec<ReducedItem> {
+    let num_edges = instance.num_edges();
+    let mut largest_degrees: Vec<_> = instance
+        .nodes()
+        .iter()
+        .map(|&node| instance.node_degree(node))
+        .collect();
+    largest_degrees.sort_unstable_by_key(|&degree| Reverse(degree));
+    let num_others = budget.saturating_sub(1);
+    largest_degrees.truncate(num_others + 1);
+    let top_sum: usize = largest_degrees.iter().take(num_others).sum();
+
+    instance
+        .nodes()
+        .iter()
+        .copied()
+        .filter(|&node| {
+            if budget == 0 {
+                return true;
+            }
+            // If the node's degree is among the largest ones, the next largest
+            // degree takes its place
+            let degree = instance.node_degree(node);
+            let among_largest = num_others.checked_sub(1).is_some_and(|last| {
+                largest_degrees
+                    .get(last)
+                    .is_none_or(|&other_degree| other_degree <= degree)
+            });
+            let others_sum = if among_largest {
+                top_sum - degree + largest_degrees.get(num_others).copied().unwrap_or(0)
+            } else {
+                top_sum
+            };
+            degree + others_sum < num_edges
+        })
+        .map(ReducedItem::RemovedNode)
+        .collect()
+}
+
+/// A reduction rule supplied by a library user.
+///
+/// Custom reductions are passed in `SolveHooks::reductions` and run in every
+/// reduction round after the built-in rules based on degrees and lower bounds,
+/// in the order given, until one of them finds something. The items are
+/// applied in order and must be valid for the instance at that point: an edge
+/// hit by a node forced earlier in the same batch must not be removed again.
+/// Items referring to nodes or edges that are already deleted are ignored,
+/// except for forced nodes, which make the subproblem infeasible (as they
+/// conflict with an earlier forced node).
+pub trait CustomReduction: Send + Sync {
+    /// Identifies the reduction in logs
+    fn name(&self) -> &'static str;
+
+    /// Finds changes that keep at least one hitting set of the instance with
+    /// fewer than `lower_bound_breakpoint` nodes, if any exists.
+    fn reduce(
+        &self,
+        instance: &Instance,
+        lower_bound_breakpoint: usize,
+        settings: &Settings,
+    ) -> Vec<ReducedItem>;
+}
+
+impl fmt::Debug for dyn CustomReduction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Runs the custom reductions until one of them finds something, dropping
+/// duplicate items and those removing deleted nodes or edges.
+fn find_custom_reductions(
+    instance: &Instance,
+    lower_bound_breakpoint: usize,
+    custom_reductions: &[Box<dyn CustomReduction>],
+    settings: &Settings,
+) -> Vec<ReducedItem> {
+    for reduction in custom_reductions {
+        let mut items = reduction.reduce(instance, lower_bound_breakpoint, settings);
+        let mut seen = IdxHashSet::default();
+        items.retain(|&item| {
+            let alive = match item {
+                ReducedItem::RemovedNode(node) => !instance.is_node_deleted(node),
+                ReducedItem::RemovedEdge(edge) => !instance.is_edge_deleted(edge),
+                ReducedItem::ForcedNode(_) => true,
+            };
+            alive && seen.insert(item)
+        });
+        if !items.is_empty() {
+            trace!(
+                "Custom reduction {} found {} items",
+                reduction.name(),
+                items.len()
+            );
+            return items;
+        }
+    }
+    Vec::new()
+}
+
+/// Runs a reduction rule, recording it in `batch_rule` if it found anything.
+#[allow(clippy::too_many_arguments)]
 fn run_reduction<I>(
     reduced_items: &mut Vec<ReducedItem>,
+    rule: ReductionRule,
+    batch_rule: &mut Option<ReductionRule>,
     runtime: &mut Duration,
     runs: &mut usize,
     item_counter: &mut usize,
@@ -281,6 +854,290 @@ fn run_reduction<I>(
         reduced_items.extend(func());
     });
     *item_counter += reduced_items.len() - len_before;
+    if reduced_items.len() > len_before {
+        *batch_rule = Some(rule);
+    }
+}
+
+/// Calculates the efficiency and packing bounds concurrently.
+///
+/// The runtimes of both calculations are recorded in the report, even though
+/// they overlap.
+fn calc_bounds_in_parallel(
+    instance: &Instance,
+    report: &mut Report,
+    packing_hint: Option<Vec<EdgeIdx>>,
+) -> ((EfficiencyBound, Vec<EfficiencyBound>), PackingBound) {
+    if packing_hint.is_some() {
+        report.reductions.packings_repaired += 1;
+    }
+    let settings = &report.settings;
+    let runtimes = &mut report.runtimes;
+    thread::scope(|scope| {
+        let efficiency_runtime = &mut runtimes.efficiency_bound;
+        let efficiency_handle = scope.spawn(move || {
+            collect_time_info(efficiency_runtime, || {
+                lower_bound::calc_efficiency_bound(instance)
+            })
+        });
+        let packing_bound = collect_time_info(&mut runtimes.packing_bound, || {
+            calc_packing_bound(instance, settings, packing_hint)
+        });
+        let efficiency_bound = efficiency_handle
+            .join()
+            .expect("Efficiency bound thread panicked");
+        (efficiency_bound, packing_bound)
+    })
+}
+
+/// Computes the packing bound from scratch, or by repairing a packing of the
+/// instance before some of its nodes or edges were deleted.
+fn calc_packing_bound(
+    instance: &Instance,
+    settings: &Settings,
+    packing_hint: Option<Vec<EdgeIdx>>,
+) -> PackingBound {
+    match packing_hint {
+        Some(packing) => PackingBound::repair(instance, settings, packing),
+        None => PackingBound::new(instance, settings),
+    }
+}
+
+/// Results of the lower bounds of one reduction round that are reused by
+/// later bounds and reduction rules
+#[derive(Default)]
+struct BoundResults {
+    /// Whether the efficiency and packing bounds are computed concurrently
+    parallel: bool,
+
+    /// Packing to repair instead of computing the packing bound from scratch
+    packing_hint: Option<Vec<EdgeIdx>>,
+    efficiency: Option<(EfficiencyBound, Vec<EfficiencyBound>)>,
+    packing: Option<PackingBound>,
+}
+
+/// Computes a built-in lower bound if it is enabled, or takes it from
+/// `results` if it was computed already. Returns why the subproblem can be
+/// pruned if the bound reaches the breakpoint.
+fn check_builtin_bound(
+    bound: BuiltinBound,
+    instance: &Instance,
+    state: &mut State,
+    report: &mut Report,
+    results: &mut BoundResults,
+    lower_bound_breakpoint: usize,
+) -> Option<PruneReason> {
+    if !bound.is_enabled(&report.settings) {
+        return None;
+    }
+    if let Some(selection) = &mut state.bound_selection {
+        if selection.skip(bound, state.depth) {
+            return None;
+        }
+    }
+    if bound == BuiltinBound::Efficiency && results.parallel && report.settings.enable_packing_bound
+    {
+        let (efficiency, packing) =
+            calc_bounds_in_parallel(instance, report, results.packing_hint.take());
+        results.efficiency = Some(efficiency);
+        results.packing = Some(packing);
+    }
+
+    let settings = &report.settings;
+    let runtimes = &mut report.runtimes;
+    let value = match bound {
+        BuiltinBound::MaxDegree => collect_time_info(&mut runtimes.max_degree_bound, || {
+            bound.compute(instance, settings)
+        }),
+        BuiltinBound::SumDegree => collect_time_info(&mut runtimes.sum_degree_bound, || {
+            bound.compute(instance, settings)
+        }),
+        BuiltinBound::Efficiency => {
+            let (efficiency_bound, _) = results.efficiency.get_or_insert_with(|| {
+                collect_time_info(&mut runtimes.efficiency_bound, || {
+                    lower_bound::calc_efficiency_bound(instance)
+                })
+            });
+            efficiency_bound.round().unwrap_or(usize::MAX)
+        }
+        BuiltinBound::Packing => {
+            if results.packing.is_none() {
+                let packing_hint = results.packing_hint.take();
+                if packing_hint.is_some() {
+                    report.reductions.packings_repaired += 1;
+                }
+                results.packing = Some(collect_time_info(&mut runtimes.packing_bound, || {
+                    calc_packing_bound(instance, settings, packing_hint)
+                }));
+            }
+            let packing_bound = results.packing.as_ref().unwrap();
+            let local_search_stats = packing_bound.local_search_stats();
+            report.reductions.local_search_two_opt_swaps += local_search_stats.two_opt_swaps;
+            report.reductions.local_search_three_opt_swaps += local_search_stats.three_opt_swaps;
+            packing_bound.bound()
+        }
+        BuiltinBound::SumOverPacking => {
+            let packing_bound = results
+                .packing
+                .as_ref()
+                .expect("Packing bound is computed before the sum over packing bound");
+            collect_time_info(&mut runtimes.sum_over_packing_bound, || {
+                packing_bound.calc_sum_over_packing_bound(instance)
+            })
+        }
+        BuiltinBound::Flow => collect_time_info(&mut runtimes.flow_bound, || {
+            bound.compute(instance, settings)
+        }),
+        BuiltinBound::CliqueCover => collect_time_info(&mut runtimes.clique_cover_bound, || {
+            bound.compute(instance, settings)
+        }),
+    };
+    state.record_lower_bound(value);
+    if let Some(selection) = &mut state.bound_selection {
+        selection.record(bound, state.depth, value >= lower_bound_breakpoint);
+    }
+    if value < lower_bound_breakpoint {
+        return None;
+    }
+    let packing = results.packing.as_ref().map(PackingBound::packing);
+    Some(record_bound_break(
+        bound,
+        instance,
+        state,
+        &mut report.reductions,
+        packing,
+    ))
+}
+
+/// Counts a prune by a built-in lower bound, and learns from the packing if
+/// the prune is due to it.
+fn record_bound_break(
+    bound: BuiltinBound,
+    instance: &Instance,
+    state: &mut State,
+    stats: &mut ReductionStats,
+    packing: Option<&[EdgeIdx]>,
+) -> PruneReason {
+    match bound {
+        BuiltinBound::MaxDegree => {
+            stats.max_degree_bound_breaks += 1;
+            PruneReason::MaxDegreeBound
+        }
+        BuiltinBound::SumDegree => {
+            stats.sum_degree_bound_breaks += 1;
+            PruneReason::SumDegreeBound
+        }
+        BuiltinBound::Efficiency => {
+            stats.efficiency_degree_bound_breaks += 1;
+            PruneReason::EfficiencyBound
+        }
+        BuiltinBound::Packing => {
+            stats.packing_bound_breaks += 1;
+            let packing = packing.unwrap_or_default();
+            if let Some(activity) = &mut state.activity {
+                activity.bump(packing);
+            }
+            if let Some(nogoods) = &mut state.nogoods {
+                nogoods.learn(
+                    instance,
+                    &state.partial_hs,
+                    packing,
+                    state.minimum_hs.len(),
+                    stats,
+                );
+            }
+            PruneReason::PackingBound
+        }
+        BuiltinBound::SumOverPacking => {
+            stats.sum_over_packing_bound_breaks += 1;
+            if let Some(activity) = &mut state.activity {
+                activity.bump(packing.unwrap_or_default());
+            }
+            PruneReason::SumOverPackingBound
+        }
+        BuiltinBound::Flow => {
+            stats.flow_bound_breaks += 1;
+            PruneReason::FlowBound
+        }
+        BuiltinBound::CliqueCover => {
+            stats.clique_cover_bound_breaks += 1;
+            PruneReason::CliqueCoverBound
+        }
+    }
+}
+
+/// Computes the enabled custom lower bounds until one of them reaches the
+/// breakpoint.
+fn check_custom_bounds(
+    instance: &Instance,
+    state: &mut State,
+    report: &mut Report,
+    lower_bound_breakpoint: usize,
+) -> Option<PruneReason> {
+    let custom_bounds = Arc::clone(&state.custom_bounds);
+    for bound in custom_bounds
+        .iter()
+        .filter(|bound| bound.is_enabled(&report.settings))
+    {
+        let value = collect_time_info(&mut report.runtimes.custom_bounds, || {
+            bound.compute(instance, &report.settings)
+        });
+        state.record_lower_bound(value);
+        if value >= lower_bound_breakpoint {
+            report.reductions.custom_bound_breaks += 1;
+            return Some(PruneReason::CustomBound);
+        }
+    }
+    None
+}
+
+/// Forces the nodes for which the discard bound of any enabled custom lower
+/// bound reaches the breakpoint.
+fn find_costly_discards_using_custom_bounds(
+    instance: &Instance,
+    lower_bound_breakpoint: usize,
+    custom_bounds: &[Box<dyn LowerBound>],
+    settings: &Settings,
+) -> Vec<ReducedItem> {
+    let mut forced: Vec<_> = custom_bounds
+        .iter()
+        .filter(|bound| bound.is_enabled(settings))
+        .flat_map(|bound| bound.discard_bounds(instance, settings))
+        .filter(|&(node, bound)| bound >= lower_bound_breakpoint && !instance.is_node_deleted(node))
+        .map(|(node, _)| node)
+        .collect();
+    forced.sort_unstable();
+    forced.dedup();
+    forced.into_iter().map(ReducedItem::ForcedNode).collect()
+}
+
+/// Finds dominated nodes and dominated edges concurrently.
+///
+/// Both results are relative to the current instance, so the dominated edges
+/// must be discarded if any dominated nodes are removed.
+fn find_dominated_in_parallel(
+    instance: &Instance,
+    tries: &mut DominationTries,
+    report: &mut Report,
+) -> (Vec<ReducedItem>, Vec<ReducedItem>) {
+    let chunked_max_nodes = subset_trie_chunked_max_nodes(&report.settings);
+    let runtimes = &mut report.runtimes;
+    let DominationTries { nodes, edges, .. } = tries;
+    thread::scope(|scope| {
+        let node_runtime = &mut runtimes.vertex_domination;
+        let nodes_handle = scope.spawn(move || {
+            collect_time_info(node_runtime, || {
+                find_dominated_nodes(instance, nodes).collect()
+            })
+        });
+        let dominated_edges = collect_time_info(&mut runtimes.edge_domination, || {
+            find_dominated_edges(instance, chunked_max_nodes, edges).collect()
+        });
+        let dominated_nodes = nodes_handle
+            .join()
+            .expect("Node domination thread panicked");
+        (dominated_nodes, dominated_edges)
+    })
 }
 
 #[allow(clippy::too_many_lines)]
@@ -289,17 +1146,60 @@ pub fn reduce(
     state: &mut State,
     report: &mut Report,
 ) -> (ReductionResult, Reduction) {
-    if report.settings.greedy_mode == GreedyMode::Once {
+    let greedy_now = match report.settings.greedy_mode {
+        GreedyMode::Once => true,
+        // `decisions` counts the reductions so far, including this one
+        GreedyMode::Every(n) => (report.decisions - 1).is_multiple_of(n),
+        _ => false,
+    };
+    if greedy_now {
         recalculate_greedy_upper_bound(instance, state, report);
-        if state.minimum_hs.len() <= report.settings.stop_at {
+        if report.settings.stop_at > 0 && state.minimum_hs.len() <= report.settings.stop_at {
             return (ReductionResult::Stop, Reduction(vec![]));
         }
     }
 
+    // Node domination can only find new dominated nodes after edges were
+    // deleted, and edge domination only after nodes were deleted. These flags
+    // track whether the respective rule has been run without any such change
+    // since, in which case it can be skipped. Otherwise the rule updates its
+    // trie with the deletions recorded since its last run, which may have
+    // been in the parent subproblem.
+    let mut node_domination_up_to_date = mem::take(&mut state.node_domination_up_to_date);
+    let mut edge_domination_up_to_date = false;
+    state.domination_tries.check_unrecorded_changes(instance);
+
+    // Changes at the root are recorded for the preprocessing report
+    let mut preprocessing = if state.depth == 0 {
+        state.preprocessing.take()
+    } else {
+        None
+    };
+
+    // A packing of the instance remains valid after deleting nodes or edges,
+    // so the packing of the previous round (or of the parent subproblem) only
+    // needs to be repaired
+    let mut packing_hint = if report.settings.reuse_packing {
+        state.packing_hint.take()
+    } else {
+        None
+    };
+
+    state.subtree_lower_bound = state.partial_hs.len();
     let mut reduced_items = Vec::new();
     let result = loop {
         if state.partial_hs.len() >= state.minimum_hs.len() {
-            break ReductionResult::Unsolvable;
+            break ReductionResult::Unsolvable(PruneReason::Incumbent);
+        }
+
+        if let Some(nogoods) = &mut state.nogoods {
+            let pruned = collect_time_info(&mut report.runtimes.nogoods, || {
+                nogoods.is_pruned(instance, &state.partial_hs)
+            });
+            if pruned {
+                report.reductions.nogood_breaks += 1;
+                break ReductionResult::Unsolvable(PruneReason::Nogood);
+            }
         }
 
         if instance.num_edges() == 0 {
@@ -308,83 +1208,89 @@ pub fn reduce(
 
         if report.settings.greedy_mode == GreedyMode::AlwaysBeforeBounds {
             recalculate_greedy_upper_bound(instance, state, report);
-            if state.minimum_hs.len() <= report.settings.stop_at {
+            if report.settings.stop_at > 0 && state.minimum_hs.len() <= report.settings.stop_at {
                 break ReductionResult::Stop;
             }
             if state.partial_hs.len() >= state.minimum_hs.len() {
-                break ReductionResult::Unsolvable;
+                break ReductionResult::Unsolvable(PruneReason::Incumbent);
             }
         }
 
         let mut lower_bound_breakpoint = state.minimum_hs.len() - state.partial_hs.len();
-        if report.settings.enable_max_degree_bound {
-            let max_degree_bound = collect_time_info(&mut report.runtimes.max_degree_bound, || {
-                lower_bound::calc_max_degree_bound(instance).unwrap_or(usize::MAX)
-            });
-            if max_degree_bound >= lower_bound_breakpoint {
-                report.reductions.max_degree_bound_breaks += 1;
-                break ReductionResult::Unsolvable;
-            }
-        }
 
-        if report.settings.enable_sum_degree_bound {
-            let sum_degree_bound = collect_time_info(&mut report.runtimes.sum_degree_bound, || {
-                lower_bound::calc_sum_degree_bound(instance)
-            });
-            if sum_degree_bound >= lower_bound_breakpoint {
-                report.reductions.sum_degree_bound_breaks += 1;
-                break ReductionResult::Unsolvable;
-            }
-        }
-
-        let discard_efficiency_bounds = if report.settings.enable_efficiency_bound {
-            let (efficiency_bound, discard_efficiency_bounds) =
-                collect_time_info(&mut report.runtimes.efficiency_bound, || {
-                    lower_bound::calc_efficiency_bound(instance)
-                });
-            if efficiency_bound.round().unwrap_or(usize::MAX) >= lower_bound_breakpoint {
-                report.reductions.efficiency_degree_bound_breaks += 1;
-                break ReductionResult::Unsolvable;
-            }
-            discard_efficiency_bounds
-        } else {
-            Vec::new()
-        };
-
-        let packing_bound = if report.settings.enable_packing_bound {
-            let settings_ref = &report.settings;
-            let packing_bound = collect_time_info(&mut report.runtimes.packing_bound, || {
-                PackingBound::new(instance, settings_ref)
-            });
-            if packing_bound.bound() >= lower_bound_breakpoint {
-                report.reductions.packing_bound_breaks += 1;
-                break ReductionResult::Unsolvable;
-            }
-            packing_bound
-        } else {
-            PackingBound::default()
+        // Threads are not available in the browser
+        let parallel = !cfg!(target_arch = "wasm32")
+            && report
+                .settings
+                .parallel_reductions_min_edges
+                .is_some_and(|min_edges| instance.num_edges() >= min_edges);
+        let mut bound_results = BoundResults {
+            parallel,
+            packing_hint: packing_hint.take(),
+            ..BoundResults::default()
         };
 
-        if report.settings.enable_packing_bound && report.settings.enable_sum_over_packing_bound {
-            let sum_over_packing_bound =
-                collect_time_info(&mut report.runtimes.sum_over_packing_bound, || {
-                    packing_bound.calc_sum_over_packing_bound(instance)
-                });
-            if sum_over_packing_bound >= lower_bound_breakpoint {
-                report.reductions.sum_over_packing_bound_breaks += 1;
-                break ReductionResult::Unsolvable;
-            }
+        let prune_reason = BuiltinBound::ALL
+            .into_iter()
+            .find_map(|bound| {
+                check_builtin_bound(
+                    bound,
+                    instance,
+                    state,
+                    report,
+                    &mut bound_results,
+                    lower_bound_breakpoint,
+                )
+            })
+            .or_else(|| check_custom_bounds(instance, state, report, lower_bound_breakpoint));
+        if let Some(prune_reason) = prune_reason {
+            break ReductionResult::Unsolvable(prune_reason);
+        }
+        let discard_efficiency_bounds = bound_results
+            .efficiency
+            .map(|(_, discard_bounds)| discard_bounds)
+            .unwrap_or_default();
+        let packing_bound = bound_results.packing.unwrap_or_default();
+        if report.settings.reuse_packing && report.settings.enable_packing_bound {
+            packing_hint = Some(packing_bound.packing().to_vec());
         }
 
         let unchanged_len = reduced_items.len();
+        let mut batch_rule = None;
         run_reduction(
             &mut reduced_items,
+            ReductionRule::ForcedVertex,
+            &mut batch_rule,
             &mut report.runtimes.forced_vertex,
             &mut report.reductions.forced_vertex_runs,
             &mut report.reductions.forced_vertices_found,
             || find_forced_nodes(instance),
         );
 
+        if reduced_items.len() == unchanged_len && report.settings.enable_degree_forcing {
+            run_reduction(
+                &mut reduced_items,
+                ReductionRule::DegreeForcing,
+                &mut batch_rule,
+                &mut report.runtimes.degree_forcing,
+                &mut report.reductions.degree_forcing_runs,
+                &mut report.reductions.degree_forcing_vertices_found,
+                || find_forced_nodes_by_degree(instance, lower_bound_breakpoint - 1),
+            );
+        }
+
+        if reduced_items.len() == unchanged_len && report.settings.enable_degree_discarding {
+            run_reduction(
+                &mut reduced_items,
+                ReductionRule::DegreeDiscarding,
+                &mut batch_rule,
+                &mut report.runtimes.degree_discarding,
+                &mut report.reductions.degree_discarding_runs,
+                &mut report.reductions.degree_discarding_vertices_found,
+                || find_discarded_nodes_by_degree(instance, lower_bound_breakpoint - 1),
+            );
+        }
+
         if reduced_items.len() == unchanged_len && report.settings.enable_efficiency_bound {
             // Do not time this step as all costly parts are integrated into the
             // calculation of the efficiency bound above. This steps just checks
@@ -392,6 +1298,8 @@ pub fn reduce(
             let mut dummy_duration = Duration::default();
             run_reduction(
                 &mut reduced_items,
+                ReductionRule::CostlyDiscardEfficiency,
+                &mut batch_rule,
                 &mut dummy_duration,
                 &mut report.reductions.costly_discard_efficiency_runs,
                 &mut report.reductions.costly_discard_efficiency_vertices_found,
@@ -408,6 +1316,8 @@ pub fn reduce(
         if reduced_items.len() == unchanged_len && report.settings.enable_packing_bound {
             run_reduction(
                 &mut reduced_items,
+                ReductionRule::CostlyDiscardPackingUpdate,
+                &mut batch_rule,
                 &mut report.runtimes.costly_discard_packing_update,
                 &mut report.reductions.costly_discard_packing_update_runs,
                 &mut report
@@ -423,15 +1333,57 @@ pub fn reduce(
             );
         }
 
+        if reduced_items.len() == unchanged_len && !state.custom_bounds.is_empty() {
+            let custom_bounds = &state.custom_bounds;
+            let settings_ref = &report.settings;
+            run_reduction(
+                &mut reduced_items,
+                ReductionRule::CostlyDiscardCustom,
+                &mut batch_rule,
+                &mut report.runtimes.custom_bounds,
+                &mut report.reductions.costly_discard_custom_runs,
+                &mut report.reductions.costly_discard_custom_vertices_found,
+                || {
+                    find_costly_discards_using_custom_bounds(
+                        instance,
+                        lower_bound_breakpoint,
+                        custom_bounds,
+                        settings_ref,
+                    )
+                },
+            );
+        }
+
+        if reduced_items.len() == unchanged_len && !state.custom_reductions.is_empty() {
+            let custom_reductions = &state.custom_reductions;
+            let settings_ref = &report.settings;
+            run_reduction(
+                &mut reduced_items,
+                ReductionRule::Custom,
+                &mut batch_rule,
+                &mut report.runtimes.custom_reductions,
+                &mut report.reductions.custom_reduction_runs,
+                &mut report.reductions.custom_reduction_items_found,
+                || {
+                    find_custom_reductions(
+                        instance,
+                        lower_bound_breakpoint,
+                        custom_reductions,
+                        settings_ref,
+                    )
+                },
+            );
+        }
+
         if reduced_items.len() == unchanged_len
             && report.settings.greedy_mode == GreedyMode::AlwaysBeforeExpensiveReductions
         {
             recalculate_greedy_upper_bound(instance, state, report);
-            if state.minimum_hs.len() <= report.settings.stop_at {
+            if report.settings.stop_at > 0 && state.minimum_hs.len() <= report.settings.stop_at {
                 break ReductionResult::Stop;
             }
             if state.partial_hs.len() >= state.minimum_hs.len() {
-                break ReductionResult::Unsolvable;
+                break ReductionResult::Unsolvable(PruneReason::Incumbent);
             }
             lower_bound_breakpoint = state.minimum_hs.len() - state.partial_hs.len();
         }
@@ -441,18 +1393,23 @@ pub fn reduce(
                 .reductions
                 .costly_discard_packing_from_scratch_steps_per_run;
             let settings_ref = &report.settings;
+            let tries = &mut state.domination_tries;
             let mut dummy_counter = 0;
             run_reduction(
                 &mut reduced_items,
+                ReductionRule::CostlyDiscardPackingFromScratch,
+                &mut batch_rule,
                 &mut report.runtimes.costly_discard_packing_from_scratch,
                 &mut report.reductions.costly_discard_packing_from_scratch_runs,
                 &mut dummy_counter,
                 || {
-                    let result = find_costly_discard_using_packing_from_scratch(
-                        instance,
-                        lower_bound_breakpoint,
-                        settings_ref,
-                    );
+                    let result = tries.run_restoring(instance, |instance| {
+                        find_costly_discard_using_packing_from_scratch(
+                            instance,
+                            lower_bound_breakpoint,
+                            settings_ref,
+                        )
+                    });
                     match result {
                         None => {
                             table_ref[settings_ref.packing_from_scratch_limit] += 1;
@@ -467,60 +1424,211 @@ pub fn reduce(
             );
         }
 
+        let (mut parallel_dominated_nodes, mut parallel_dominated_edges) = if parallel
+            && reduced_items.len() == unchanged_len
+            && !node_domination_up_to_date
+            && !instance.has_conflicts()
+            && !instance.has_requirements()
+            && !edge_domination_up_to_date
+        {
+            let (dominated_nodes, dominated_edges) =
+                find_dominated_in_parallel(instance, &mut state.domination_tries, report);
+            (Some(dominated_nodes), Some(dominated_edges))
+        } else {
+            (None, None)
+        };
+
         if reduced_items.len() == unchanged_len {
-            run_reduction(
-                &mut reduced_items,
-                &mut report.runtimes.vertex_domination,
-                &mut report.reductions.vertex_dominations_runs,
-                &mut report.reductions.vertex_dominations_vertices_found,
-                || find_dominated_nodes(instance),
-            );
+            // Vertex domination is unsound with conflicts, as the dominating
+            // node might conflict with nodes the dominated one does not, and
+            // with coverage requirements, as an edge might need both nodes
+            if node_domination_up_to_date || instance.has_conflicts() || instance.has_requirements()
+            {
+                report.reductions.vertex_dominations_skipped += 1;
+            } else {
+                run_reduction(
+                    &mut reduced_items,
+                    ReductionRule::VertexDomination,
+                    &mut batch_rule,
+                    &mut report.runtimes.vertex_domination,
+                    &mut report.reductions.vertex_dominations_runs,
+                    &mut report.reductions.vertex_dominations_vertices_found,
+                    || {
+                        parallel_dominated_nodes.take().map_or_else(
+                            || find_dominated_nodes(instance, &mut state.domination_tries.nodes),
+                            |nodes| Box::new(nodes.into_iter()),
+                        )
+                    },
+                );
+                node_domination_up_to_date = true;
+            }
         }
 
         if reduced_items.len() == unchanged_len {
-            run_reduction(
-                &mut reduced_items,
-                &mut report.runtimes.edge_domination,
-                &mut report.reductions.edge_dominations_runs,
-                &mut report.reductions.edge_dominations_edges_found,
-                || find_dominated_edges(instance),
-            );
+            if edge_domination_up_to_date {
+                report.reductions.edge_dominations_skipped += 1;
+            } else {
+                let chunked_max_nodes = subset_trie_chunked_max_nodes(&report.settings);
+                run_reduction(
+                    &mut reduced_items,
+                    ReductionRule::EdgeDomination,
+                    &mut batch_rule,
+                    &mut report.runtimes.edge_domination,
+                    &mut report.reductions.edge_dominations_runs,
+                    &mut report.reductions.edge_dominations_edges_found,
+                    || {
+                        parallel_dominated_edges.take().map_or_else(
+                            || {
+                                find_dominated_edges(
+                                    instance,
+                                    chunked_max_nodes,
+                                    &mut state.domination_tries.edges,
+                                )
+                            },
+                            |edges| Box::new(edges.into_iter()),
+                        )
+                    },
+                );
+                edge_domination_up_to_date = true;
+            }
+        }
+        if parallel_dominated_edges.is_some() {
+            // The edges found in parallel are discarded after finding dominated
+            // nodes, so the trie lacks them even though they are still alive
+            state.domination_tries.edges.valid = false;
         }
 
         if reduced_items.len() == unchanged_len {
+            // Deleting a node (as done when branching on it) does not change
+            // the incidences of other nodes
+            state.node_domination_up_to_date = node_domination_up_to_date;
+            state.packing_hint = packing_hint;
             break ReductionResult::Finished;
         }
 
-        collect_time_info(&mut report.runtimes.applying_reductions, || {
-            for reduced_item in &reduced_items[unchanged_len..] {
-                reduced_item.apply(instance, &mut state.partial_hs);
+        let infeasible = collect_time_info(&mut report.runtimes.applying_reductions, || {
+            // Forcing a node into the hitting set removes all nodes conflicting
+            // with it, which are recorded as additional items
+            let new_items: Vec<_> = reduced_items.drain(unchanged_len..).collect();
+            let rule = batch_rule.expect("Reduced items without a rule");
+            for reduced_item in new_items {
+                match reduced_item {
+                    ReducedItem::RemovedNode(_) => edge_domination_up_to_date = false,
+                    ReducedItem::RemovedEdge(_) => node_domination_up_to_date = false,
+                    ReducedItem::ForcedNode(node) => {
+                        if instance.is_node_deleted(node) {
+                            // Conflicts with a node forced earlier in this batch
+                            return Some(PruneReason::Conflicts);
+                        }
+                        node_domination_up_to_date = false;
+                        edge_domination_up_to_date = false;
+                    }
+                }
+                state
+                    .domination_tries
+                    .apply(reduced_item, instance, &mut state.partial_hs);
+                reduced_items.push(reduced_item);
+                if let Some(preprocessing) = &mut preprocessing {
+                    preprocessing.record(reduced_item.action(), rule);
+                }
+
+                if let ReducedItem::RemovedNode(node) = reduced_item {
+                    // Removing several nodes of an edge in one batch can leave
+                    // it with fewer nodes than it needs to be hit by
+                    if instance.has_requirements() && !instance.is_coverable_without(node) {
+                        return Some(PruneReason::UncoverableEdge);
+                    }
+                }
+
+                if let ReducedItem::ForcedNode(node) = reduced_item {
+                    let (conflicting, feasible) = state
+                        .domination_tries
+                        .delete_conflicting_nodes(instance, node);
+                    if let Some(preprocessing) = &mut preprocessing {
+                        for &other in &conflicting {
+                            preprocessing.record(
+                                PreprocessingAction::RemovedNode(other),
+                                ReductionRule::Conflict,
+                            );
+                        }
+                    }
+                    reduced_items.extend(conflicting.into_iter().map(ReducedItem::RemovedNode));
+                    if !feasible {
+                        return Some(PruneReason::Conflicts);
+                    }
+                }
             }
+            None
         });
+        if let Some(reason) = infeasible {
+            break ReductionResult::Unsolvable(reason);
+        }
     };
 
+    if preprocessing.is_some() {
+        state.preprocessing = preprocessing;
+    }
     (result, Reduction(reduced_items))
 }
 
+/// Forces the nodes of unit edges into the partial hitting set until no such
+/// edges are left, like unit propagation in SAT solvers. Stops early once
+/// the partial hitting set reaches `max_size`, leaving the pruning to `reduce`.
+///
+/// The second return value is `false` if conflicts with a forced node leave
+/// some edge without nodes. The changes must be restored in either case.
+pub fn propagate_unit_edges(
+    instance: &mut Instance,
+    partial_hs: &mut Vec<NodeIdx>,
+    max_size: usize,
+    tries: &mut DominationTries,
+) -> (Reduction, bool) {
+    let mut reduced_items = Vec::new();
+    while partial_hs.len() < max_size {
+        let Some(&edge) = instance.unit_edges().first() else {
+            break;
+        };
+        let node = instance.edge(edge).next().expect("Unit edge without nodes");
+        let item = ReducedItem::ForcedNode(node);
+        tries.apply(item, instance, partial_hs);
+        reduced_items.push(item);
+
+        let (conflicting, feasible) = tries.delete_conflicting_nodes(instance, node);
+        reduced_items.extend(conflicting.into_iter().map(ReducedItem::RemovedNode));
+        if !feasible {
+            return (Reduction(reduced_items), false);
+        }
+    }
+    (Reduction(reduced_items), true)
+}
+
 pub fn reduce_for_ilp(instance: &mut Instance) -> (usize, usize) {
     let mut reduced = Vec::new();
     let mut dummy_partial_hs = Vec::new();
     let mut reduced_nodes = 0;
     let mut reduced_edges = 0;
+    let mut tries = DominationTries::default();
     loop {
         let mut changed = false;
 
-        reduced.extend(find_dominated_nodes(instance));
+        if !instance.has_conflicts() && !instance.has_requirements() {
+            reduced.extend(find_dominated_nodes(instance, &mut tries.nodes));
+        }
         reduced_nodes += reduced.len();
         changed |= !reduced.is_empty();
         for item in reduced.drain(..) {
-            item.apply(instance, &mut dummy_partial_hs);
+            tries.apply(item, instance, &mut dummy_partial_hs);
         }
 
-        reduced.extend(find_dominated_edges(instance));
+        reduced.extend(find_dominated_edges(
+            instance,
+            subset_trie::DEFAULT_CHUNKED_MAX_VAL_RANGE,
+            &mut tries.edges,
+        ));
         reduced_edges += reduced.len();
         changed |= !reduced.is_empty();
         for item in reduced.drain(..) {
-            item.apply(instance, &mut dummy_partial_hs);
+            tries.apply(item, instance, &mut dummy_partial_hs);
         }
 
         if !changed {
@@ -530,3 +1638,183 @@ pub fn reduce_for_ilp(instance: &mut Instance) -> (usize, usize) {
 
     (reduced_nodes, reduced_edges)
 }
+
+/// A node with the same incident edges as its representative
+#[derive(Debug, Clone, Copy)]
+pub struct MergedTwin {
+    pub twin: NodeIdx,
+    pub representative: NodeIdx,
+}
+
+/// Merges nodes with identical incident edges into the one with the smallest
+/// index by deleting the others. At most one node of such a group is useful
+/// in a minimum hitting set, so this only loses alternative solutions. The
+/// merged twins are returned in order of deletion and need to be restored in
+/// reverse order.
+pub fn merge_twin_nodes(instance: &mut Instance) -> Vec<MergedTwin> {
+    let mut nodes = instance.nodes().to_vec();
+    nodes.sort_unstable();
+    let mut representatives: IdxHashMap<Vec<EdgeIdx>, NodeIdx> = IdxHashMap::default();
+    let mut merged = Vec::new();
+    for node in nodes {
+        let edges: Vec<_> = instance.node(node).collect();
+        if let Some(&representative) = representatives.get(&edges) {
+            merged.push(MergedTwin {
+                twin: node,
+                representative,
+            });
+        } else {
+            representatives.insert(edges, node);
+        }
+    }
+    for twin in &merged {
+        instance.delete_node(twin.twin);
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        find_dominated_edges, find_dominated_nodes, DominationTries, ReducedItem, Reduction,
+    };
+    use crate::{
+        data_structures::subset_trie::DEFAULT_CHUNKED_MAX_VAL_RANGE,
+        instance::{EdgeIdx, Instance, NodeIdx},
+        solve::SolverRng,
+        test_utils::load_text,
+    };
+    use rand::{seq::SliceRandom, Rng, SeedableRng};
+
+    /// Random hypergraph with too many nodes for the bitset variants, where
+    /// some edges are subsets of others
+    fn random_instance(rng: &mut SolverRng) -> Instance {
+        let num_nodes = 150;
+        let mut edges: Vec<Vec<usize>> = Vec::new();
+        while edges.len() < 600 {
+            let mut edge = if !edges.is_empty() && rng.gen_bool(0.1) {
+                let mut edge = edges.choose(rng).unwrap().clone();
+                edge.truncate(rng.gen_range(1..=edge.len()));
+                edge
+            } else {
+                let size = rng.gen_range(2..=6);
+                (0..size).map(|_| rng.gen_range(0..num_nodes)).collect()
+            };
+            edge.shuffle(rng);
+            edge.sort_unstable();
+            edge.dedup();
+            edges.push(edge);
+        }
+
+        let lines: Vec<_> = edges
+            .iter()
+            .map(|edge| {
+                let nodes: Vec<_> = edge.iter().map(ToString::to_string).collect();
+                format!("{} {}\n", edge.len(), nodes.join(" "))
+            })
+            .collect();
+        load_text(&format!(
+            "{} {}\n{}",
+            num_nodes,
+            edges.len(),
+            lines.concat()
+        ))
+    }
+
+    fn is_node_dominated(
+        instance: &Instance,
+        node: NodeIdx,
+        kept: impl Fn(NodeIdx) -> bool,
+    ) -> bool {
+        instance.nodes().iter().any(|&other| {
+            other != node
+                && kept(other)
+                && instance
+                    .node(node)
+                    .all(|edge| instance.edge(edge).any(|n| n == other))
+        })
+    }
+
+    fn is_edge_dominated(
+        instance: &Instance,
+        edge: EdgeIdx,
+        kept: impl Fn(EdgeIdx) -> bool,
+    ) -> bool {
+        instance.edges().iter().any(|&other| {
+            other != edge
+                && kept(other)
+                && instance
+                    .edge(other)
+                    .all(|node| instance.edge(edge).any(|n| n == node))
+        })
+    }
+
+    #[test]
+    fn incremental_domination_finds_all_dominated() {
+        let mut rng = SolverRng::seed_from_u64(0);
+        for _ in 0..3 {
+            let mut instance = random_instance(&mut rng);
+            let mut tries = DominationTries::default();
+            for round in 0..100 {
+                if instance.nodes().is_empty() || instance.edges().is_empty() {
+                    break;
+                }
+                let mut applied = Vec::new();
+                let mut partial_hs = Vec::new();
+                if round > 0 {
+                    // Deleting nodes lets edges become dominated, and deleting
+                    // edges lets nodes become dominated
+                    let node = *instance.nodes().choose(&mut rng).unwrap();
+                    let item = match rng.gen_range(0..5) {
+                        0 | 1 => ReducedItem::RemovedNode(node),
+                        2 | 3 => {
+                            ReducedItem::RemovedEdge(*instance.edges().choose(&mut rng).unwrap())
+                        }
+                        _ => ReducedItem::ForcedNode(node),
+                    };
+                    tries.apply(item, &mut instance, &mut partial_hs);
+                    applied.push(item);
+                }
+
+                let nodes: Vec<_> = find_dominated_nodes(&instance, &mut tries.nodes).collect();
+                let removed = |node| nodes.contains(&ReducedItem::RemovedNode(node));
+                for &item in &nodes {
+                    let ReducedItem::RemovedNode(node) = item else {
+                        panic!("Unexpected item {:?}", item);
+                    };
+                    assert!(is_node_dominated(&instance, node, |other| !removed(other)));
+                    tries.apply(item, &mut instance, &mut partial_hs);
+                }
+                for &node in instance.nodes() {
+                    assert!(!is_node_dominated(&instance, node, |_| true), "{}", node);
+                }
+                applied.extend(nodes);
+
+                let edges: Vec<_> = find_dominated_edges(
+                    &instance,
+                    DEFAULT_CHUNKED_MAX_VAL_RANGE,
+                    &mut tries.edges,
+                )
+                .collect();
+                let removed = |edge| edges.contains(&ReducedItem::RemovedEdge(edge));
+                for &item in &edges {
+                    let ReducedItem::RemovedEdge(edge) = item else {
+                        panic!("Unexpected item {:?}", item);
+                    };
+                    assert!(is_edge_dominated(&instance, edge, |other| !removed(other)));
+                    tries.apply(item, &mut instance, &mut partial_hs);
+                }
+                for &edge in instance.edges() {
+                    assert!(!is_edge_dominated(&instance, edge, |_| true), "{}", edge);
+                }
+                applied.extend(edges);
+
+                if round % 5 == 4 {
+                    // Restoring is not recorded, so the tries are rebuilt
+                    Reduction(applied).restore(&mut instance, &mut partial_hs);
+                    tries.check_unrecorded_changes(&instance);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,201 @@
+//! Renumbering of nodes and edges while solving.
+//!
+//! When loading with `LoadOptions::compact_nodes`, nodes not contained in any
+//! edge are dropped and the remaining ones are renumbered consecutively,
+//! keeping their relative order. With `deterministic` set, the nodes and edges
+//! are additionally renumbered canonically, see `canonical`. `solve::solve`
+//! translates the node indices of the settings to the relabeled ones and those
+//! of the solution and report back, so that callers only ever see the original
+//! indices.
+use crate::{
+    instance::{EdgeIdx, NodeIdx},
+    report::{InfeasibilityExplanation, NodeClass, Report, SecondaryObjective, Settings},
+    small_indices::SmallIdx,
+};
+use anyhow::{anyhow, ensure, Result};
+
+/// Mapping between the original and the relabeled node and edge indices
+#[derive(Debug, Clone)]
+pub struct NodeRelabeling {
+    /// Original index of each relabeled node
+    original_nodes: Vec<NodeIdx>,
+
+    /// Relabeled index of each original node. Empty if the original indices
+    /// are increasing, as for compacted nodes, which are found by binary
+    /// search instead.
+    relabeled_nodes: Vec<NodeIdx>,
+
+    /// Original index of each relabeled edge. Empty if the edges keep their
+    /// indices.
+    original_edges: Vec<EdgeIdx>,
+
+    num_original_nodes: usize,
+}
+
+impl NodeRelabeling {
+    /// Relabeling dropping nodes, given the original index of each kept node
+    /// in increasing order
+    pub(crate) fn new(original_nodes: Vec<NodeIdx>, num_original_nodes: usize) -> Self {
+        Self {
+            original_nodes,
+            relabeled_nodes: Vec::new(),
+            original_edges: Vec::new(),
+            num_original_nodes,
+        }
+    }
+
+    /// Relabeling permuting all nodes and edges, given the original index of
+    /// each relabeled node and edge
+    pub(crate) fn permutation(original_nodes: Vec<NodeIdx>, original_edges: Vec<EdgeIdx>) -> Self {
+        let mut relabeled_nodes = vec![NodeIdx::INVALID; original_nodes.len()];
+        for (idx, node) in original_nodes.iter().enumerate() {
+            relabeled_nodes[node.idx()] = NodeIdx::from(idx);
+        }
+        Self {
+            num_original_nodes: original_nodes.len(),
+            original_nodes,
+            relabeled_nodes,
+            original_edges,
+        }
+    }
+
+    /// Number of nodes declared by the input, including the dropped ones
+    pub fn num_original_nodes(&self) -> usize {
+        self.num_original_nodes
+    }
+
+    pub fn original(&self, node: NodeIdx) -> NodeIdx {
+        self.original_nodes[node.idx()]
+    }
+
+    /// Relabeled index of a node, or `None` if the node was dropped because it
+    /// is not part of any edge
+    pub fn relabeled(&self, original: NodeIdx) -> Option<NodeIdx> {
+        if self.relabeled_nodes.is_empty() {
+            self.original_nodes
+                .binary_search(&original)
+                .ok()
+                .map(NodeIdx::from)
+        } else {
+            self.relabeled_nodes.get(original.idx()).copied()
+        }
+    }
+
+    pub fn original_edge(&self, edge: EdgeIdx) -> EdgeIdx {
+        self.original_edges.get(edge.idx()).copied().unwrap_or(edge)
+    }
+
+    fn restore_nodes(&self, nodes: &mut [NodeIdx]) {
+        for node in nodes {
+            *node = self.original(*node);
+        }
+    }
+
+    fn restore_edges(&self, edges: &mut [EdgeIdx]) {
+        for edge in edges {
+            *edge = self.original_edge(*edge);
+        }
+    }
+
+    /// Translates the node indices of the settings to the relabeled indices.
+    /// Forbidden nodes and nodes of the initial hitting set that are not part
+    /// of any edge are dropped, as they do not affect the solution. Forced
+    /// nodes must be part of an edge.
+    pub fn relabel_settings(&self, mut settings: Settings) -> Result<Settings> {
+        for (nodes, name) in [
+            (&mut settings.forced_nodes, "forced node"),
+            (
+                &mut settings.partial_hitting_set,
+                "node of the partial hitting set",
+            ),
+        ] {
+            for node in nodes {
+                *node = self.relabeled(*node).ok_or_else(|| {
+                    anyhow!(
+                        "{} {} is not part of any edge and was dropped by compacting the nodes",
+                        name,
+                        node
+                    )
+                })?;
+            }
+        }
+        settings.forbidden_nodes = settings
+            .forbidden_nodes
+            .iter()
+            .filter_map(|&node| self.relabeled(node))
+            .collect();
+        if let Some(initial_hs) = &mut settings.initial_hitting_set {
+            *initial_hs = initial_hs
+                .iter()
+                .filter_map(|&node| self.relabeled(node))
+                .collect();
+        }
+        if let Some(SecondaryObjective::MinCostSum { costs }) = &mut settings.secondary_objective {
+            ensure!(
+                costs.len() == self.num_original_nodes,
+                "secondary objective has {} costs, but the hypergraph has {} nodes",
+                costs.len(),
+                self.num_original_nodes
+            );
+            *costs = self
+                .original_nodes
+                .iter()
+                .map(|node| costs[node.idx()])
+                .collect();
+        }
+        Ok(settings)
+    }
+
+    /// Translates the node and edge indices of the hitting set and the report
+    /// back to the original indices. Recorded search trees, branching
+    /// decisions, and preprocessing logs keep the relabeled indices.
+    pub fn restore_report(&self, hitting_set: &mut [NodeIdx], report: &mut Report) {
+        self.restore_nodes(hitting_set);
+        let certificate = &mut report.root_bounds.certificate;
+        self.restore_edges(&mut certificate.packing);
+        for component in &mut certificate.components {
+            self.restore_edges(&mut component.edges);
+        }
+        if let Some(completion) = &mut report.completion {
+            self.restore_nodes(completion);
+        }
+        if let Some(coverage) = &mut report.budget_coverage {
+            self.restore_nodes(&mut coverage.nodes);
+        }
+        if let Some(classification) = &mut report.node_classification {
+            // Nodes without edges are never needed
+            let mut classes = vec![NodeClass::Redundant; self.num_original_nodes];
+            for (idx, &class) in classification.classes.iter().enumerate() {
+                classes[self.original(NodeIdx::from(idx)).idx()] = class;
+            }
+            classification.classes = classes;
+        }
+        if let Some(explanation) = &mut report.infeasibility {
+            self.restore_explanation(explanation);
+        }
+    }
+
+    /// Translates the node and edge indices of an explanation back to the
+    /// original indices.
+    pub fn restore_explanation(&self, explanation: &mut InfeasibilityExplanation) {
+        match explanation {
+            InfeasibilityExplanation::ForcedAndForbidden { node } => {
+                *node = self.original(*node);
+            }
+            InfeasibilityExplanation::ConflictingForcedNodes { first, second } => {
+                *first = self.original(*first);
+                *second = self.original(*second);
+            }
+            InfeasibilityExplanation::EdgeForbidden { edge, nodes } => {
+                *edge = self.original_edge(*edge);
+                for forbidden in nodes {
+                    forbidden.node = self.original(forbidden.node);
+                    forbidden.conflicts_with = forbidden.conflicts_with.map(|n| self.original(n));
+                }
+            }
+            InfeasibilityExplanation::BudgetBelowForced { .. }
+            | InfeasibilityExplanation::ConflictsUnsatisfiable
+            | InfeasibilityExplanation::TargetUnreachable { .. } => {}
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use crate::instance::NodeIdx;
+use anyhow::{Context, Result};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Debug, Formatter},
+    io::{BufRead, Write},
+};
+
+/// Node branched on in a branching step
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BranchingDecision {
+    pub branching_step: usize,
+    pub node: NodeIdx,
+}
+
+/// Writes the branching decisions of a run as json lines.
+pub struct DecisionRecorder {
+    writer: Box<dyn Write>,
+}
+
+impl Debug for DecisionRecorder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DecisionRecorder").finish_non_exhaustive()
+    }
+}
+
+impl DecisionRecorder {
+    pub fn new(writer: Box<dyn Write>) -> Self {
+        Self { writer }
+    }
+
+    pub fn write_decision(&mut self, decision: BranchingDecision) -> Result<()> {
+        serde_json::to_writer(&mut self.writer, &decision)?;
+        self.writer.write_all(b"\n")?;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Replays recorded branching decisions instead of choosing the branching
+/// nodes by the usual heuristic. If the run diverges from the recording, the
+/// heuristic is used for the remainder of the run.
+#[derive(Debug)]
+pub struct Replay {
+    decisions: Vec<BranchingDecision>,
+    next: usize,
+
+    /// Stop the search before taking the branching step after this one
+    stop_after: Option<usize>,
+}
+
+impl Replay {
+    pub fn new(decisions: Vec<BranchingDecision>, stop_after: Option<usize>) -> Self {
+        Self {
+            decisions,
+            next: 0,
+            stop_after,
+        }
+    }
+
+    /// Reads decisions written by a `DecisionRecorder`.
+    pub fn read_decisions(reader: impl BufRead) -> Result<Vec<BranchingDecision>> {
+        let mut decisions = Vec::new();
+        for (line_idx, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let decision = serde_json::from_str(&line)
+                .with_context(|| format!("invalid branching decision in line {}", line_idx + 1))?;
+            decisions.push(decision);
+        }
+        Ok(decisions)
+    }
+
+    /// Whether the search should stop before taking the given branching step.
+    pub fn should_stop(&self, branching_step: usize) -> bool {
+        self.stop_after
+            .is_some_and(|stop_after| branching_step > stop_after)
+    }
+
+    /// Returns the recorded node to branch on in the given branching step, or
+    /// `None` if the recording is exhausted or diverged. `is_valid` checks
+    /// whether the node can be branched on in the current subproblem.
+    pub fn next_node(
+        &mut self,
+        branching_step: usize,
+        is_valid: impl FnOnce(NodeIdx) -> bool,
+    ) -> Option<NodeIdx> {
+        let decision = *self.decisions.get(self.next)?;
+        self.next += 1;
+        if decision.branching_step == branching_step && is_valid(decision.node) {
+            if self.next == self.decisions.len() {
+                info!(
+                    "Replayed all {} branching decisions, continuing with the branching heuristic",
+                    self.decisions.len()
+                );
+            }
+            return Some(decision.node);
+        }
+
+        warn!(
+            "Replay diverged in branching step {}: recorded decision was node {} in branching step \
+             {}, continuing with the branching heuristic",
+            branching_step, decision.node, decision.branching_step
+        );
+        self.next = self.decisions.len();
+        None
+    }
+}
@@ -1,6 +1,11 @@
-use crate::instance::NodeIdx;
+use crate::{
+    instance::{EdgeIdx, NodeIdx},
+    preprocessing::PreprocessingReport,
+    search_tree::SearchTree,
+};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize, Serializer};
-use std::time::Duration;
+use std::{collections::BTreeMap, fmt, time::Duration};
 
 fn serialize_duration_as_seconds<S>(duration: &Duration, ser: S) -> Result<S::Ok, S::Error>
 where
@@ -9,78 +14,214 @@ where
     ser.serialize_f64(duration.as_secs_f64())
 }
 
-#[derive(Debug, Clone, Default, Serialize)]
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
 pub struct RuntimeStats {
     #[serde(serialize_with = "serialize_duration_as_seconds")]
+    #[schemars(with = "f64")]
     pub total: Duration,
 
     #[serde(serialize_with = "serialize_duration_as_seconds")]
+    #[schemars(with = "f64")]
     pub greedy: Duration,
 
     #[serde(serialize_with = "serialize_duration_as_seconds")]
+    #[schemars(with = "f64")]
+    pub upper_bound_search: Duration,
+
+    #[serde(serialize_with = "serialize_duration_as_seconds")]
+    #[schemars(with = "f64")]
+    pub dive: Duration,
+
+    #[serde(serialize_with = "serialize_duration_as_seconds")]
+    #[schemars(with = "f64")]
     pub max_degree_bound: Duration,
 
     #[serde(serialize_with = "serialize_duration_as_seconds")]
+    #[schemars(with = "f64")]
     pub sum_degree_bound: Duration,
 
     #[serde(serialize_with = "serialize_duration_as_seconds")]
+    #[schemars(with = "f64")]
     pub efficiency_bound: Duration,
 
     #[serde(serialize_with = "serialize_duration_as_seconds")]
+    #[schemars(with = "f64")]
     pub packing_bound: Duration,
 
     #[serde(serialize_with = "serialize_duration_as_seconds")]
+    #[schemars(with = "f64")]
     pub sum_over_packing_bound: Duration,
 
     #[serde(serialize_with = "serialize_duration_as_seconds")]
+    #[schemars(with = "f64")]
+    pub flow_bound: Duration,
+
+    #[serde(serialize_with = "serialize_duration_as_seconds")]
+    #[schemars(with = "f64")]
+    pub clique_cover_bound: Duration,
+
+    /// Custom lower bounds and their discard bounds
+    #[serde(serialize_with = "serialize_duration_as_seconds")]
+    #[schemars(with = "f64")]
+    pub custom_bounds: Duration,
+
+    #[serde(serialize_with = "serialize_duration_as_seconds")]
+    #[schemars(with = "f64")]
+    pub custom_reductions: Duration,
+
+    #[serde(serialize_with = "serialize_duration_as_seconds")]
+    #[schemars(with = "f64")]
+    pub nogoods: Duration,
+
+    #[serde(serialize_with = "serialize_duration_as_seconds")]
+    #[schemars(with = "f64")]
+    pub symmetry: Duration,
+
+    #[serde(serialize_with = "serialize_duration_as_seconds")]
+    #[schemars(with = "f64")]
     pub forced_vertex: Duration,
 
     #[serde(serialize_with = "serialize_duration_as_seconds")]
+    #[schemars(with = "f64")]
+    pub degree_forcing: Duration,
+
+    #[serde(serialize_with = "serialize_duration_as_seconds")]
+    #[schemars(with = "f64")]
+    pub degree_discarding: Duration,
+
+    #[serde(serialize_with = "serialize_duration_as_seconds")]
+    #[schemars(with = "f64")]
     pub costly_discard_packing_update: Duration,
 
     #[serde(serialize_with = "serialize_duration_as_seconds")]
+    #[schemars(with = "f64")]
     pub costly_discard_packing_from_scratch: Duration,
 
     #[serde(serialize_with = "serialize_duration_as_seconds")]
+    #[schemars(with = "f64")]
     pub vertex_domination: Duration,
 
     #[serde(serialize_with = "serialize_duration_as_seconds")]
+    #[schemars(with = "f64")]
     pub edge_domination: Duration,
 
     #[serde(serialize_with = "serialize_duration_as_seconds")]
+    #[schemars(with = "f64")]
     pub applying_reductions: Duration,
+
+    #[serde(serialize_with = "serialize_duration_as_seconds")]
+    #[schemars(with = "f64")]
+    pub node_classification: Duration,
+
+    #[serde(serialize_with = "serialize_duration_as_seconds")]
+    #[schemars(with = "f64")]
+    pub canonical_solution: Duration,
+
+    #[serde(serialize_with = "serialize_duration_as_seconds")]
+    #[schemars(with = "f64")]
+    pub secondary_objective: Duration,
 }
 
-#[derive(Debug, Clone, Serialize)]
+/// Compact summary of the rules that pruned subproblems or reduced the hypergraph, used by
+/// `findminhs recommend-settings`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct InstanceProfile {
+    /// Number of subproblems pruned by a lower bound or a nogood
+    pub prunes: usize,
+
+    /// Fraction of the prunes caused by each rule that pruned at least once
+    pub prune_shares: BTreeMap<String, f64>,
+
+    /// Number of nodes or edges found by each reduction rule that found at least one
+    pub reductions_found: BTreeMap<String, usize>,
+
+    /// Rules causing at least a tenth of the prunes or found reductions, most important first
+    pub hot: Vec<String>,
+
+    /// Rules that ran but never pruned or reduced anything
+    pub cold: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct UpperBoundImprovement {
     pub new_bound: usize,
     pub branching_steps: usize,
 
     #[serde(serialize_with = "serialize_duration_as_seconds")]
+    #[schemars(with = "f64")]
     pub runtime: Duration,
 }
 
-#[derive(Debug, Clone, Default, Serialize)]
+/// Event written to a report stream instead of being kept in the report
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ReportEvent {
+    /// Written once before the search starts
+    Difficulty(DifficultyEstimate),
+    UpperBoundImprovement(UpperBoundImprovement),
+}
+
+/// Reference to the stream that received the report events
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ReportStreamInfo {
+    pub location: String,
+    pub num_events: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
 pub struct ReductionStats {
     pub max_degree_bound_breaks: usize,
     pub sum_degree_bound_breaks: usize,
     pub efficiency_degree_bound_breaks: usize,
     pub packing_bound_breaks: usize,
     pub sum_over_packing_bound_breaks: usize,
+    pub flow_bound_breaks: usize,
+    pub clique_cover_bound_breaks: usize,
+    pub custom_bound_breaks: usize,
+    pub nogood_breaks: usize,
+
+    pub local_search_two_opt_swaps: usize,
+    pub local_search_three_opt_swaps: usize,
+
+    /// Packings repaired from those of the previous reduction round or the parent subproblem,
+    /// see the `reuse_packing` setting
+    pub packings_repaired: usize,
 
     pub greedy_runs: usize,
+    pub dive_runs: usize,
+    pub dive_improvements: usize,
     pub forced_vertex_runs: usize,
     pub forced_vertices_found: usize,
+    pub unit_propagation_vertices_found: usize,
+    pub degree_forcing_runs: usize,
+    pub degree_forcing_vertices_found: usize,
+    pub degree_discarding_runs: usize,
+    pub degree_discarding_vertices_found: usize,
     pub costly_discard_efficiency_runs: usize,
     pub costly_discard_efficiency_vertices_found: usize,
     pub costly_discard_packing_update_runs: usize,
     pub costly_discard_packing_update_vertices_found: usize,
     pub costly_discard_packing_from_scratch_runs: usize,
     pub costly_discard_packing_from_scratch_steps_per_run: Vec<usize>,
+    pub costly_discard_custom_runs: usize,
+    pub costly_discard_custom_vertices_found: usize,
+    pub custom_reduction_runs: usize,
+    pub custom_reduction_items_found: usize,
     pub vertex_dominations_runs: usize,
     pub vertex_dominations_vertices_found: usize,
+    pub vertex_dominations_skipped: usize,
     pub edge_dominations_runs: usize,
     pub edge_dominations_edges_found: usize,
+    pub edge_dominations_skipped: usize,
+    pub nogoods_learned: usize,
+    pub nogood_nodes_learned: usize,
+    pub nogoods_discarded: usize,
+    pub nogoods_too_large: usize,
+    pub symmetric_nodes_discarded: usize,
+    pub twin_nodes_merged: usize,
+
+    /// Duplicate edges of the input merged into a single edge while loading
+    pub duplicate_edges_merged: usize,
 }
 
 impl ReductionStats {
@@ -95,30 +236,225 @@ impl ReductionStats {
     }
 }
 
-#[derive(Debug, Clone, Default, Serialize)]
+/// Witnesses for the degree and packing root bounds, allowing them to be
+/// validated without trusting the solver. The efficiency and sum-over-packing
+/// bounds can be recomputed directly from the instance (and the packing).
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+pub struct LowerBoundCertificate {
+    pub num_edges: usize,
+
+    /// The largest node degrees in decreasing order, as many as given by the
+    /// sum-degree bound. All but the last one sum to less than `num_edges`,
+    /// and the first one is the maximum degree.
+    pub largest_degrees: Vec<usize>,
+
+    /// Pairwise disjoint edges, each of which needs as many distinct nodes as
+    /// its coverage requirement
+    pub packing: Vec<EdgeIdx>,
+
+    /// Witnesses for the sum-degree bounds of the connected components, if the hypergraph has
+    /// more than one. Their sum is the sum-degree bound of the whole hypergraph.
+    pub components: Vec<ComponentCertificate>,
+}
+
+/// Witness for the sum-degree bound of a single connected component
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+pub struct ComponentCertificate {
+    pub edges: Vec<EdgeIdx>,
+
+    /// The largest node degrees within the component in decreasing order, as many as given by
+    /// the sum-degree bound of the component
+    pub largest_degrees: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
 pub struct RootBounds {
+    /// Number of connected components, the lower bounds are summed over them
+    pub components: usize,
     pub max_degree: usize,
     pub sum_degree: usize,
     pub efficiency: usize,
     pub packing: usize,
+    pub packing_order: PackingOrder,
     pub sum_over_packing: usize,
+    pub flow: Option<usize>,
+    pub clique_cover: Option<usize>,
     pub greedy_upper: usize,
+    pub greedy_strategies_upper: Option<usize>,
+    pub upper_bound_search: Option<usize>,
+    pub certificate: LowerBoundCertificate,
+}
+
+impl RootBounds {
+    /// Name and value of the largest lower bound
+    pub fn best_named_lower_bound(&self) -> (&'static str, usize) {
+        [
+            ("max_degree", self.max_degree),
+            ("sum_degree", self.sum_degree),
+            ("efficiency", self.efficiency),
+            ("packing", self.packing),
+            ("sum_over_packing", self.sum_over_packing),
+            ("flow", self.flow.unwrap_or(0)),
+            ("clique_cover", self.clique_cover.unwrap_or(0)),
+        ]
+        .into_iter()
+        .rev()
+        .max_by_key(|&(_, value)| value)
+        .expect("List of bounds is not empty")
+    }
+
+    pub fn best_lower_bound(&self) -> usize {
+        self.max_degree
+            .max(self.sum_degree)
+            .max(self.efficiency)
+            .max(self.packing)
+            .max(self.sum_over_packing)
+            .max(self.flow.unwrap_or(0))
+            .max(self.clique_cover.unwrap_or(0))
+    }
+}
+
+/// Estimate of how hard an instance is, computed before the search starts
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DifficultyEstimate {
+    /// Best lower bound computed at the root
+    pub root_lower_bound: usize,
+
+    /// Size of the greedy hitting set computed at the root
+    pub root_upper_bound: usize,
+
+    /// Difference between the root upper and lower bound
+    pub root_gap: usize,
+
+    /// Fraction of the node-edge pairs in which the node is part of the edge
+    pub density: f64,
+    pub average_edge_size: f64,
+
+    /// Number of connected components
+    pub components: usize,
+    pub largest_component_nodes: usize,
+    pub largest_component_edges: usize,
+
+    /// Maximum node degree divided by the average node degree
+    pub degree_skew: f64,
+
+    /// Estimated base-10 logarithm of the number of branching steps needed
+    pub score: f64,
+
+    /// Suggested time limit in seconds, generous enough for most instances
+    /// with this score
+    pub recommended_time_limit: f64,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub enum GreedyMode {
     Never,
     Once,
     AlwaysBeforeBounds,
     AlwaysBeforeExpensiveReductions,
+
+    /// Recompute the greedy upper bound at the start of every n-th reduction
+    Every(usize),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum GreedyStrategy {
+    /// Pick the node hitting the most unhit edges
+    #[default]
+    MaxDegree,
+
+    /// Like `MaxDegree`, but break ties randomly
+    RandomTieBreak,
+
+    /// Handle the smallest unhit edge first, picking its node hitting the most unhit edges
+    Regret,
+
+    /// Pick the node maximizing the sum of inverse sizes of the unhit edges it hits
+    EdgeSizeWeighted,
+
+    /// Round a fractional hitting set found by the primal-dual method, at most the maximum edge
+    /// size times the LP optimum
+    LpRounding,
+
+    /// Run `MaxDegree` on a smaller surrogate hypergraph with similar nodes contracted, and expand
+    /// its solution back. Much faster on huge hypergraphs, but usually worse
+    Coarsened,
+}
+
+/// Order in which edges are considered when greedily building a packing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum PackingOrder {
+    /// Prefer edges whose nodes have the smallest sum of degrees, breaking ties by their maximum
+    /// degree
+    #[default]
+    DegreeSum,
+
+    /// Shuffle the edges using the seed from the settings
+    Random,
+
+    /// Prefer the smallest edges, breaking ties like `DegreeSum`
+    EdgeSizeFirst,
+}
+
+/// Objective used to choose among the minimum hitting sets
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum SecondaryObjective {
+    /// Maximize the sum of the node degrees in the input hypergraph
+    MaxDegreeSum,
+
+    /// Minimize the sum of the given node costs, one per node
+    MinCostSum { costs: Vec<f64> },
+}
+
+/// When to restart the search from the root, measured in branching steps since
+/// the last restart
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum RestartStrategy {
+    /// Restart after `unit` times the next element of the Luby sequence (1, 1, 2, 1, 1, 2, 4, ...)
+    Luby { unit: usize },
+
+    /// Restart after `initial` steps, multiplying the limit by `factor` after each restart
+    Geometric { initial: usize, factor: f64 },
+}
+
+/// Skipping of lower bounds that never prune subproblems in a band of depths
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct AdaptiveBoundSettings {
+    /// Number of consecutive depths sharing their statistics
+    pub depth_band: usize,
+
+    /// Number of times a bound is computed in a band before it may be skipped there
+    pub min_evaluations: usize,
+
+    /// Compute a skipped bound anyway every this many times, so that it is used again if it
+    /// starts pruning
+    pub reprobe_interval: usize,
+}
+
+/// Branching step budget for the subtrees rooted at a fixed depth of the search tree
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SubtreeBudgetSchedule {
+    /// Number of branching decisions above the roots of the budgeted subtrees
+    pub depth: usize,
+
+    /// Branching steps allowed in each subtree in the first iteration
+    pub initial: usize,
+
+    /// Factor by which the budget grows after each iteration aborting a subtree
+    pub factor: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[allow(clippy::struct_excessive_bools)]
 pub struct Settings {
     /// Use local search to improve the packing bound
     pub enable_local_search: bool,
 
+    /// Number of steps spent searching for 3-for-2 exchanges during each packing local search,
+    /// counting each edge scanned and each disjointness check between two edges (0 disables them)
+    #[serde(default)]
+    pub local_search_three_opt_budget: usize,
+
     /// Enable the max-degree bound
     pub enable_max_degree_bound: bool,
 
@@ -134,30 +470,581 @@ pub struct Settings {
     /// Enable the sum-over-packing bound (requires packing bound to be enabled)
     pub enable_sum_over_packing_bound: bool,
 
+    /// Enable the bound from a maximum fractional matching of the edges of size two
+    #[serde(default)]
+    pub enable_flow_bound: bool,
+
+    /// Enable the bound from covering the graph formed by the edges of size two with disjoint
+    /// cliques
+    #[serde(default)]
+    pub enable_clique_cover_bound: bool,
+
     /// Number of nodes to check in the costly discard with from-scratch packing step
     pub packing_from_scratch_limit: usize,
 
+    /// Order in which edges are added to the greedy packing of the packing bounds
+    #[serde(default)]
+    pub packing_order: PackingOrder,
+
+    /// Repair the packing of the previous reduction round or the parent subproblem instead of
+    /// computing a new one, which is faster but may give weaker bounds
+    #[serde(default)]
+    pub reuse_packing: bool,
+
     /// When to update the greedy upper bound during reductions
     pub greedy_mode: GreedyMode,
 
+    /// Strategy of the greedy upper bound updated during reductions. Instances with conflicts
+    /// always use `MaxDegree`.
+    #[serde(default)]
+    pub greedy_strategy: GreedyStrategy,
+
+    /// Every this many branching steps, follow the branching heuristic from the current
+    /// subproblem down to a leaf to improve the best hitting set
+    #[serde(default)]
+    pub dive_interval: Option<usize>,
+
+    /// Greedy strategies to run before branching, the smallest hitting set found is used as the
+    /// initial upper bound
+    #[serde(default)]
+    pub initial_greedy_strategies: Vec<GreedyStrategy>,
+
+    /// Nodes that must be part of the hitting set
+    #[serde(default)]
+    pub forced_nodes: Vec<NodeIdx>,
+
+    /// Partial hitting set whose nodes are committed to, the solver finds an optimal completion
+    #[serde(default)]
+    pub partial_hitting_set: Vec<NodeIdx>,
+
+    /// Nodes that must not be part of the hitting set
+    #[serde(default)]
+    pub forbidden_nodes: Vec<NodeIdx>,
+
     /// Hitting set to initialize the solver with
     pub initial_hitting_set: Option<Vec<NodeIdx>>,
 
+    /// Number of tabu search iterations used to improve the initial hitting set before branching
+    /// (0 disables the search)
+    #[serde(default)]
+    pub upper_bound_search_iterations: usize,
+
+    /// Number of iterations a node may not be swapped again after being moved during the tabu
+    /// search
+    #[serde(default)]
+    pub upper_bound_search_tabu_tenure: usize,
+
+    /// Seed for the random number generator used by all randomized components
+    #[serde(default)]
+    pub seed: u64,
+
+    /// After solving, determine for each node whether it is contained in all, some, or none of
+    /// the minimum hitting sets
+    #[serde(default)]
+    pub classify_nodes: bool,
+
+    /// After solving, replace the hitting set by the lexicographically smallest minimum hitting
+    /// set (comparing the sorted node indices)
+    #[serde(default)]
+    pub canonical_solution: bool,
+
+    /// After solving, replace the hitting set by a minimum hitting set that is optimal under this
+    /// objective
+    #[serde(default)]
+    pub secondary_objective: Option<SecondaryObjective>,
+
+    /// Omit all wall-clock measurements from the report, so that identical inputs and settings
+    /// produce identical reports. Also solves the instance numbered canonically, so that
+    /// renumbering the input does not change the search
+    #[serde(default)]
+    pub deterministic: bool,
+
+    /// Maximum number of nodes to select. If there is no hitting set of this size, the nodes
+    /// hitting the maximum number of edges are selected instead
+    #[serde(default)]
+    pub budget: Option<usize>,
+
     /// Stop solving once a hitting set this size or smaller is found
     #[serde(default)]
     pub stop_at: usize,
+
+    /// Stop solving after this many seconds, keeping the best hitting set found so far
+    #[serde(default)]
+    pub time_limit: Option<f64>,
+
+    /// Stop solving after this many branching steps, keeping the best hitting set found so far
+    #[serde(default)]
+    pub max_branching_steps: Option<usize>,
+
+    /// Stop solving after this many subproblems, counting the root and each subproblem created by
+    /// a branching decision, keeping the best hitting set found so far
+    #[serde(default)]
+    pub max_decisions: Option<usize>,
+
+    /// Stop solving once the best hitting set is within this many percent of the lower bound,
+    /// relative to the size of the hitting set
+    #[serde(default)]
+    pub gap: Option<f64>,
+
+    /// Compute independent bounds and reductions in parallel on instances with at least this
+    /// many edges
+    #[serde(default)]
+    pub parallel_reductions_min_edges: Option<usize>,
+
+    /// Largest number of nodes for which the trie of the edge domination rule stores the
+    /// children of each trie node in chunked arrays rather than a hash map (defaults to 256)
+    #[serde(default)]
+    pub subset_trie_chunked_max_nodes: Option<usize>,
+
+    /// Record the branch-and-bound tree up to this many search nodes
+    #[serde(default)]
+    pub search_tree_max_nodes: Option<usize>,
+
+    /// Maximum number of nogoods learned from subtrees pruned by the packing bound that are kept
+    /// at once, discarding the oldest ones first (0 disables learning)
+    #[serde(default)]
+    pub nogood_limit: usize,
+
+    /// Maximum number of nodes in a learned nogood, larger ones are not kept
+    #[serde(default)]
+    pub max_nogood_size: Option<usize>,
+
+    /// Branch on the node with the largest activity-weighted degree, where edges gain activity
+    /// by pruning subtrees through the packing bounds
+    #[serde(default)]
+    pub activity_branching: bool,
+
+    /// On hypergraphs whose edges have at most three nodes, branch on the nodes of a smallest edge
+    /// instead of the node of maximum degree
+    #[serde(default)]
+    pub three_hs_branching: bool,
+
+    /// Periodically restart the search from the root, keeping the best hitting set, activities,
+    /// and nogoods
+    #[serde(default)]
+    pub restarts: Option<RestartStrategy>,
+
+    /// Explore the search tree in iterations of increasing discrepancy limits, where each
+    /// iteration only follows paths excluding the branching node at most that many times
+    #[serde(default)]
+    pub limited_discrepancy_search: bool,
+
+    /// Abort subtrees exceeding a budget of branching steps, and revisit them in another
+    /// iteration with a larger budget, limiting the effect of bad early branching decisions
+    #[serde(default)]
+    pub subtree_budgets: Option<SubtreeBudgetSchedule>,
+
+    /// Stop computing the lower bounds that never prune a subproblem in a band of depths
+    #[serde(default)]
+    pub adaptive_bounds: Option<AdaptiveBoundSettings>,
+
+    /// After branching on a node, discard all nodes interchangeable with it in the branch
+    /// excluding it
+    #[serde(default)]
+    pub symmetry_breaking: bool,
+
+    /// Before solving, merge nodes with identical incident edges into a single representative
+    #[serde(default)]
+    pub merge_twin_nodes: bool,
+
+    /// Force nodes into the hitting set whose edges can not be hit by the remaining budget of
+    /// other nodes, judging only by their degrees
+    #[serde(default)]
+    pub enable_degree_forcing: bool,
+
+    /// Discard nodes that can not be part of a smaller hitting set since, judging only by the
+    /// degrees, the remaining budget of other nodes can not hit the edges they miss
+    #[serde(default)]
+    pub enable_degree_discarding: bool,
+
+    /// Record the nodes and edges removed or forced before the first branching step
+    #[serde(default)]
+    pub record_preprocessing: bool,
+
+    /// Check every new best hitting set against the edges of the input instance as soon as it is
+    /// found, panicking if it misses one. Useful when developing custom reductions, but slow.
+    #[serde(default)]
+    pub validate_incumbents: bool,
+
+    /// Solve instances with at most 20 nodes left after applying the forced and forbidden nodes by
+    /// a branch-and-bound search over bitmasks instead of the regular search
+    #[serde(default)]
+    pub tiny_instance_fast_path: bool,
 }
 
-#[derive(Debug, Clone, Serialize)]
+/// Version of the json report format. Adding fields or enum variants keeps the
+/// version, while removing or renaming fields, or changing their meaning, bumps
+/// it. Consumers should ignore unknown fields.
+pub const REPORT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct Report {
+    /// Version of the report format, see [`REPORT_VERSION`]
+    pub report_version: u32,
+
     pub file_name: String,
+
+    /// Difficulty of the instance estimated before solving
+    pub difficulty: DifficultyEstimate,
+    pub status: SolveStatus,
     pub opt: usize,
+
+    /// Best lower bound on the size of a hitting set proven by the search, equal to `opt` if the
+    /// hitting set is minimum
+    pub lower_bound: usize,
+
+    /// Difference between the hitting set size and the best lower bound, in percent of the
+    /// hitting set size
+    pub gap: f64,
+
     pub branching_steps: usize,
+
+    /// Number of subproblems solved, counting the root and each subproblem created by a branching
+    /// decision
+    pub decisions: usize,
+    pub branching_engine: BranchingEngine,
+    pub restarts: usize,
+
+    /// Discrepancy limit of the last iteration, if using limited discrepancy search
+    pub discrepancy_limit: Option<usize>,
+
+    /// Iterations and final budget, if using subtree budgets
+    pub subtree_budgets: Option<SubtreeBudgetStats>,
+
+    /// Evaluations, prunes, and skips of each lower bound per band of depths, if using adaptive
+    /// bounds
+    pub adaptive_bounds: Option<Vec<BoundBandStats>>,
     pub upper_bound_improvements: Vec<UpperBoundImprovement>,
+
+    /// Stream that received the upper bound improvements instead of `upper_bound_improvements`,
+    /// if any
+    pub report_stream: Option<ReportStreamInfo>,
     pub settings: Settings,
     pub root_bounds: RootBounds,
     pub runtimes: RuntimeStats,
     pub reductions: ReductionStats,
+    pub profile: InstanceProfile,
+    pub node_classification: Option<NodeClassification>,
+
+    /// Additional solver runs used to find the canonical hitting set, if requested
+    pub canonical_solution: Option<CanonicalSolution>,
+
+    /// Values of the secondary objective, if one was given
+    pub secondary_objective: Option<SecondaryObjectiveResult>,
+
+    /// Nodes added to the partial hitting set from the settings, if one was given
+    pub completion: Option<Vec<NodeIdx>>,
+
+    /// Best selection of nodes within the budget, if no hitting set fits the budget
+    pub budget_coverage: Option<BudgetCoverage>,
+
+    /// Why no hitting set is as small as requested by `stop_at` (or the budget)
+    pub infeasibility: Option<InfeasibilityExplanation>,
+
+    /// Recorded search tree, written to a separate file
+    #[serde(skip)]
+    pub search_tree: Option<SearchTree>,
+
+    /// Changes made before the first branching step, written to a separate file
+    #[serde(skip)]
+    pub preprocessing: Option<PreprocessingReport>,
+}
+
+/// Rule used to select the node to branch on
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, JsonSchema)]
+pub enum BranchingEngine {
+    /// Node of maximum degree
+    #[default]
+    MaxDegree,
+
+    /// Node of maximum activity-weighted degree
+    Activity,
+
+    /// Node of maximum degree in a smallest edge, preferring edges containing nodes of large
+    /// degree, used if no edge has more than three nodes. Branching on an edge of size three
+    /// creates at most three subproblems that each add a node to the hitting set, and edges of
+    /// size two are preferred as they only create two.
+    ThreeHittingSet,
+}
+
+/// Reason why the solver stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+pub enum SolveStatus {
+    /// The hitting set is minimum
+    Optimal,
+
+    /// A hitting set no larger than `stop_at` (or the budget) was found
+    StoppedAt,
+
+    /// The time limit was reached before the hitting set was proven minimum
+    TimeLimit,
+
+    /// The limit on branching steps or decisions was reached before the hitting set was proven
+    /// minimum
+    StepLimit,
+
+    /// The hitting set is within the requested gap of the lower bound
+    GapReached,
+
+    /// The replay of recorded branching decisions stopped at the chosen branching step
+    ReplayStopped,
+
+    /// The search was cancelled through the library API before the hitting set was proven minimum
+    Cancelled,
+
+    /// The solver panicked, the hitting set is the best one found before and might be invalid
+    Panicked,
+
+    /// No hitting set fits within the budget, the solution is the selection of at most `budget`
+    /// nodes hitting the most edges, which misses some edges
+    BudgetExceeded,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, JsonSchema)]
+pub struct SubtreeBudgetStats {
+    pub iterations: usize,
+
+    /// Budget per subtree in the last iteration
+    pub budget: usize,
+
+    /// Number of subproblems skipped because their subtree exceeded its budget
+    pub aborted_subproblems: usize,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct BoundBandStats {
+    pub bound: String,
+
+    /// Smallest depth of the band
+    pub min_depth: usize,
+    pub evaluations: usize,
+    pub breaks: usize,
+    pub skips: usize,
+}
+
+/// Membership of a node in the minimum hitting sets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+pub enum NodeClass {
+    /// Contained in every minimum hitting set
+    Essential,
+
+    /// Contained in some, but not all minimum hitting sets
+    Optional,
+
+    /// Not contained in any minimum hitting set
+    Redundant,
+}
+
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+pub struct NodeClassification {
+    /// Class of each node, indexed by node
+    pub classes: Vec<NodeClass>,
+
+    /// Number of additional solver runs with a node forced in or out
+    pub solves: usize,
+    pub branching_steps: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+pub struct CanonicalSolution {
+    pub solves: usize,
+    pub branching_steps: usize,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct SecondaryObjectiveResult {
+    /// Value of the minimum hitting set found before optimizing the secondary objective
+    pub initial_value: f64,
+    pub value: f64,
+
+    /// Whether `value` is proven to be optimal among all minimum hitting sets
+    pub optimal: bool,
+    pub branching_steps: usize,
+}
+
+/// Node that may not be part of the hitting set
+#[derive(Debug, Clone, Copy, Serialize, JsonSchema)]
+pub struct ForbiddenNode {
+    pub node: NodeIdx,
+
+    /// Forced node the node conflicts with, or `None` if it is forbidden by the settings
+    pub conflicts_with: Option<NodeIdx>,
+}
+
+/// Evidence that no hitting set of the requested size exists
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LowerBoundWitness {
+    /// Lower bound computed at the root, see the certificate in the root bounds
+    RootBound { bound: String, value: usize },
+
+    /// The branch-and-bound search proved the minimum size
+    Search { branching_steps: usize },
+}
+
+/// Explanation why no hitting set satisfies the constraints or the requested size
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum InfeasibilityExplanation {
+    /// The node is both forced and forbidden
+    ForcedAndForbidden { node: NodeIdx },
+
+    /// Two forced nodes conflict with each other
+    ConflictingForcedNodes { first: NodeIdx, second: NodeIdx },
+
+    /// All nodes of the edge are forbidden, directly or by conflicting with forced nodes
+    EdgeForbidden {
+        edge: EdgeIdx,
+        nodes: Vec<ForbiddenNode>,
+    },
+
+    /// More nodes are forced than the budget allows
+    BudgetBelowForced { budget: usize, forced: usize },
+
+    /// No hitting set avoids selecting conflicting nodes together
+    ConflictsUnsatisfiable,
+
+    /// No hitting set is as small as `stop_at` (or the budget)
+    TargetUnreachable {
+        target: usize,
+        minimum: usize,
+
+        /// Whether `minimum` is the size of a minimum hitting set. With a budget, the search only
+        /// proves that no hitting set fits the budget, and `minimum` is the size of the best one
+        /// found before the search
+        minimum_proven: bool,
+
+        /// Number of forced nodes, which count towards the minimum
+        forced: usize,
+        witness: LowerBoundWitness,
+    },
+}
+
+impl fmt::Display for InfeasibilityExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ForcedAndForbidden { node } => {
+                write!(f, "node {} is both forced and forbidden", node)
+            }
+            Self::ConflictingForcedNodes { first, second } => {
+                write!(f, "forced nodes {} and {} conflict", first, second)
+            }
+            Self::EdgeForbidden { edge, nodes } => {
+                write!(f, "all nodes of edge {} are forbidden (", edge)?;
+                for (idx, forbidden) in nodes.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ", ")?;
+                    }
+                    match forbidden.conflicts_with {
+                        Some(forced) => write!(
+                            f,
+                            "{} conflicts with forced node {}",
+                            forbidden.node, forced
+                        )?,
+                        None => write!(f, "{} is forbidden", forbidden.node)?,
+                    }
+                }
+                write!(f, ")")
+            }
+            Self::BudgetBelowForced { budget, forced } => write!(
+                f,
+                "budget {} is smaller than the number of forced nodes ({})",
+                budget, forced
+            ),
+            Self::ConflictsUnsatisfiable => {
+                write!(f, "no hitting set satisfies the conflict constraints")
+            }
+            Self::TargetUnreachable {
+                target,
+                minimum,
+                minimum_proven,
+                forced,
+                witness,
+            } => {
+                write!(f, "no hitting set of size {} or smaller exists", target)?;
+                if *minimum_proven {
+                    write!(f, ", the minimum is {}", minimum)?;
+                } else {
+                    write!(f, ", the best one found has size {}", minimum)?;
+                }
+                match witness {
+                    LowerBoundWitness::RootBound { bound, value } => write!(
+                        f,
+                        " ({} forced nodes plus a {} bound of {} on the remaining instance)",
+                        forced, bound, value
+                    ),
+                    LowerBoundWitness::Search { branching_steps } => write!(
+                        f,
+                        " (proven by a search of {} branching steps)",
+                        branching_steps
+                    ),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct BudgetCoverage {
+    pub nodes: Vec<NodeIdx>,
+    pub hit_edges: usize,
+    pub total_edges: usize,
+
+    /// Whether no selection within the budget hits more edges, false if the time limit was
+    /// reached first
+    pub optimal: bool,
+}
+
+/// Snapshot of the solver state, emitted periodically as a json line
+#[derive(Debug, Clone, Serialize)]
+pub struct ProgressEvent {
+    #[serde(serialize_with = "serialize_duration_as_seconds")]
+    pub elapsed: Duration,
+
+    pub branching_steps: usize,
+    pub incumbent: usize,
+    pub lower_bound: usize,
+    pub depth: usize,
+}
+
+/// New best hitting set found during the search
+#[derive(Debug, Clone, Serialize)]
+pub struct IncumbentEvent<'a> {
+    #[serde(serialize_with = "serialize_duration_as_seconds")]
+    pub elapsed: Duration,
+
+    pub branching_steps: usize,
+    pub size: usize,
+    pub hitting_set: &'a [NodeIdx],
+}
+
+/// Difference between an upper and a lower bound on the size of a minimum
+/// hitting set, in percent of the upper bound
+#[allow(clippy::cast_precision_loss)]
+pub fn relative_gap(upper: usize, lower: usize) -> f64 {
+    if upper == 0 {
+        0.0
+    } else {
+        upper.saturating_sub(lower) as f64 / upper as f64 * 100.0
+    }
+}
+
+impl Report {
+    /// Number of nodes in the written solution, which is the budget selection instead of the
+    /// hitting set if no hitting set fits within the budget
+    pub fn solution_size(&self) -> usize {
+        self.budget_coverage
+            .as_ref()
+            .map_or(self.opt, |coverage| coverage.nodes.len())
+    }
+
+    /// Resets all wall-clock measurements to zero
+    pub fn clear_runtimes(&mut self) {
+        self.runtimes = RuntimeStats::default();
+        for improvement in &mut self.upper_bound_improvements {
+            improvement.runtime = Duration::ZERO;
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -169,3 +1056,160 @@ pub struct IlpReductionReport {
     pub reduced_vertices: usize,
     pub reduced_edges: usize,
 }
+
+/// Statistics of a quantity measured over several benchmark runs
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct SampleStats {
+    pub mean: f64,
+    pub median: f64,
+
+    /// Sample standard deviation (zero for a single run)
+    pub stddev: f64,
+
+    pub min: f64,
+    pub max: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchReport {
+    pub file_name: String,
+    pub runs: usize,
+
+    /// Sizes of the hitting sets found, which only differ between runs with
+    /// different seeds if the solver stopped early
+    pub opts: Vec<usize>,
+
+    /// Total solving time in seconds
+    pub runtime: SampleStats,
+
+    pub branching_steps: SampleStats,
+    pub settings: Settings,
+}
+
+/// Summary of solving a hypergraph given by an oracle
+#[derive(Debug, Clone, Serialize)]
+pub struct ImplicitReport {
+    /// Number of hitting sets passed to the oracle
+    pub iterations: usize,
+
+    /// Number of edges returned by the oracle
+    pub num_edges: usize,
+
+    pub opt: usize,
+
+    /// Status of the last solve, the final hitting set is minimum if it is `Optimal`
+    pub status: SolveStatus,
+
+    /// Whether the oracle confirmed that the final hitting set hits all edges
+    pub finished: bool,
+
+    /// Total number of branching steps over all solves
+    pub branching_steps: usize,
+
+    #[serde(serialize_with = "serialize_duration_as_seconds")]
+    pub solve_runtime: Duration,
+
+    #[serde(serialize_with = "serialize_duration_as_seconds")]
+    pub oracle_runtime: Duration,
+
+    pub settings: Settings,
+}
+
+/// Results of one settings configuration during tuning
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigurationResult {
+    /// Values of the tuned settings
+    pub parameters: serde_json::Map<String, serde_json::Value>,
+
+    /// Number of hypergraphs solved to optimality
+    pub solved: usize,
+
+    /// Number of hypergraphs on which the solver failed
+    pub failed: usize,
+
+    /// Sum of the solving times of all hypergraphs in seconds
+    pub total_runtime: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TuneReport {
+    pub num_hypergraphs: usize,
+
+    /// All evaluated configurations, best first
+    pub configurations: Vec<ConfigurationResult>,
+
+    /// Base settings with the parameters of the best configuration applied
+    pub best_settings: Option<Settings>,
+}
+
+/// Recorded result of solving one hypergraph for a baseline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    /// File name of the hypergraph
+    pub hypergraph: String,
+    pub status: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<usize>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branching_steps: Option<usize>,
+
+    /// Solving time in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runtime: Option<f64>,
+}
+
+/// Results of solving a set of hypergraphs, which later builds are checked against
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Baseline {
+    /// Version of the solver that recorded the baseline
+    pub version: String,
+    pub settings: Settings,
+    pub hypergraphs: Vec<BaselineEntry>,
+}
+
+/// Difference between a baseline entry and a new run that exceeds the tolerances
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BaselineDeviation {
+    /// The hypergraph of the baseline was not solved again
+    Missing,
+    StatusChanged {
+        old: String,
+        new: String,
+    },
+    SizeChanged {
+        old: usize,
+        new: usize,
+    },
+    MoreBranchingSteps {
+        old: usize,
+        new: usize,
+    },
+    SlowerRuntime {
+        old: f64,
+        new: f64,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BaselineMismatch {
+    pub hypergraph: String,
+
+    #[serde(flatten)]
+    pub deviation: BaselineDeviation,
+}
+
+/// Outcome of checking new runs against a baseline
+#[derive(Debug, Clone, Serialize)]
+pub struct BaselineCheck {
+    /// Number of hypergraphs compared against the baseline
+    pub checked: usize,
+
+    /// Number of hypergraphs solved with fewer branching steps than in the baseline
+    pub fewer_branching_steps: usize,
+
+    /// All deviations exceeding the tolerances
+    pub mismatches: Vec<BaselineMismatch>,
+}
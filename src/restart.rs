@@ -0,0 +1,142 @@
+use crate::report::{RestartStrategy, SubtreeBudgetSchedule, SubtreeBudgetStats};
+
+/// Element `idx` (starting at 0) of the Luby sequence 1, 1, 2, 1, 1, 2, 4, ...
+fn luby(mut idx: usize) -> usize {
+    loop {
+        // Find the finished subsequence of length 2^k - 1 containing idx
+        let mut len = 1;
+        while len < idx + 1 {
+            len = 2 * len + 1;
+        }
+        if len == idx + 1 {
+            return len.div_ceil(2);
+        }
+        idx -= len / 2;
+    }
+}
+
+/// Decides when the search restarts from the root, based on the number of
+/// branching steps since the last restart.
+#[derive(Debug, Clone)]
+pub struct RestartSchedule {
+    strategy: RestartStrategy,
+    num_restarts: usize,
+    limit: usize,
+    steps_at_last_restart: usize,
+}
+
+impl RestartSchedule {
+    pub fn new(strategy: RestartStrategy) -> Self {
+        let mut schedule = Self {
+            strategy,
+            num_restarts: 0,
+            limit: 0,
+            steps_at_last_restart: 0,
+        };
+        schedule.limit = schedule.calc_limit();
+        schedule
+    }
+
+    #[allow(
+        clippy::cast_possible_wrap,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    fn calc_limit(&self) -> usize {
+        match self.strategy {
+            RestartStrategy::Luby { unit } => unit.saturating_mul(luby(self.num_restarts)),
+            RestartStrategy::Geometric { initial, factor } => {
+                (initial as f64 * factor.powi(self.num_restarts as i32)).min(usize::MAX as f64)
+                    as usize
+            }
+        }
+    }
+
+    pub fn is_due(&self, branching_steps: usize) -> bool {
+        branching_steps - self.steps_at_last_restart >= self.limit
+    }
+
+    pub fn restart(&mut self, branching_steps: usize) {
+        self.num_restarts += 1;
+        self.steps_at_last_restart = branching_steps;
+        self.limit = self.calc_limit();
+    }
+
+    pub fn num_restarts(&self) -> usize {
+        self.num_restarts
+    }
+}
+
+/// Limits the branching steps spent in each subtree rooted at the depth of
+/// the schedule. Aborted subtrees are revisited by repeating the search with
+/// a larger budget, until an iteration completes without aborting any.
+#[derive(Debug, Clone)]
+pub struct SubtreeBudgets {
+    schedule: SubtreeBudgetSchedule,
+    stats: SubtreeBudgetStats,
+
+    /// Branching steps when the current budgeted subtree was entered
+    subtree_start: usize,
+
+    /// Smallest lower bound of a subproblem aborted in the current iteration
+    pub skipped_lower_bound: Option<usize>,
+}
+
+impl SubtreeBudgets {
+    pub fn new(schedule: SubtreeBudgetSchedule) -> Self {
+        Self {
+            schedule,
+            stats: SubtreeBudgetStats {
+                iterations: 1,
+                budget: schedule.initial,
+                aborted_subproblems: 0,
+            },
+            subtree_start: 0,
+            skipped_lower_bound: None,
+        }
+    }
+
+    /// Called before solving a subproblem at the given depth. Returns whether
+    /// it is part of a subtree that exceeded its budget.
+    pub fn exceeded(&mut self, depth: usize, branching_steps: usize) -> bool {
+        if depth == self.schedule.depth {
+            self.subtree_start = branching_steps;
+        }
+        depth > self.schedule.depth && branching_steps - self.subtree_start >= self.stats.budget
+    }
+
+    /// Records that a subproblem with the given lower bound was aborted.
+    pub fn abort(&mut self, lower_bound: usize) {
+        self.stats.aborted_subproblems += 1;
+        self.skipped_lower_bound = Some(
+            self.skipped_lower_bound
+                .map_or(lower_bound, |skipped| skipped.min(lower_bound)),
+        );
+    }
+
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_possible_wrap,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    pub fn next_iteration(&mut self) {
+        // Computed from the initial budget, so that rounding down does not
+        // prevent small budgets from growing
+        let SubtreeBudgetSchedule {
+            initial, factor, ..
+        } = self.schedule;
+        self.stats.budget = (initial as f64 * factor.powi(self.stats.iterations as i32))
+            .min(usize::MAX as f64) as usize;
+        self.stats.iterations += 1;
+        self.skipped_lower_bound = None;
+    }
+
+    pub fn budget(&self) -> usize {
+        self.stats.budget
+    }
+
+    pub fn stats(&self) -> SubtreeBudgetStats {
+        self.stats
+    }
+}
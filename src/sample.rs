@@ -0,0 +1,387 @@
+use crate::{
+    instance::{EdgeIdx, Instance, NodeIdx},
+    small_indices::SmallIdx,
+    solve::SolverRng,
+};
+use anyhow::{ensure, Result};
+use rand::{
+    seq::{index, SliceRandom},
+    SeedableRng,
+};
+use serde::Serialize;
+use std::{cmp::Reverse, collections::BinaryHeap, io::Write};
+
+/// How the edges of a sub-instance are chosen
+#[derive(Debug, Clone, Copy)]
+pub enum SampleMethod {
+    /// The given number of edges, chosen uniformly at random
+    RandomEdges { count: usize, seed: u64 },
+
+    /// All edges within the given distance of a node. The edges containing
+    /// the node have distance one, and edges sharing a node with an edge of
+    /// distance `d` have distance at most `d + 1`.
+    Ball { center: NodeIdx, radius: usize },
+
+    /// The edges contained in a dense part with the given number of nodes,
+    /// found by repeatedly removing the node of minimum degree
+    Densest { num_nodes: usize },
+}
+
+/// Part of an instance with renumbered nodes and edges
+#[derive(Debug, Clone, Serialize)]
+pub struct SubInstance {
+    /// Original index of each node of the sub-instance
+    pub nodes: Vec<NodeIdx>,
+
+    /// Original index of each edge of the sub-instance
+    pub edges: Vec<EdgeIdx>,
+
+    /// Write the nodes of each edge ordered by their new index instead of in
+    /// their original order, which would reveal the order of their original
+    /// indices
+    #[serde(skip)]
+    pub sort_edge_nodes: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonSubInstance {
+    num_nodes: usize,
+    edges: Vec<Vec<usize>>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    requirements: Vec<usize>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    conflicts: Vec<Vec<usize>>,
+}
+
+fn random_edges(instance: &Instance, count: usize, seed: u64) -> Vec<EdgeIdx> {
+    let mut rng = SolverRng::seed_from_u64(seed);
+    let edges = instance.edges();
+    let mut sampled: Vec<_> = index::sample(&mut rng, edges.len(), count.min(edges.len()))
+        .into_iter()
+        .map(|idx| edges[idx])
+        .collect();
+    sampled.sort_unstable();
+    sampled
+}
+
+fn ball(instance: &Instance, center: NodeIdx, radius: usize) -> Vec<EdgeIdx> {
+    let mut node_seen = vec![false; instance.num_nodes_total()];
+    let mut edge_seen = vec![false; instance.num_edges_total()];
+    let mut frontier = vec![center];
+    node_seen[center.idx()] = true;
+    let mut edges = Vec::new();
+    for _ in 0..radius {
+        let mut next_frontier = Vec::new();
+        for node in frontier {
+            for edge in instance.node(node) {
+                if edge_seen[edge.idx()] {
+                    continue;
+                }
+                edge_seen[edge.idx()] = true;
+                edges.push(edge);
+                for other in instance.edge(edge) {
+                    if !node_seen[other.idx()] {
+                        node_seen[other.idx()] = true;
+                        next_frontier.push(other);
+                    }
+                }
+            }
+        }
+        frontier = next_frontier;
+    }
+    edges.sort_unstable();
+    edges
+}
+
+fn densest(instance: &Instance, num_nodes: usize) -> Vec<EdgeIdx> {
+    let mut removed = vec![false; instance.num_nodes_total()];
+    let mut edge_removed = vec![false; instance.num_edges_total()];
+    let mut degrees = vec![0; instance.num_nodes_total()];
+    let mut heap = BinaryHeap::new();
+    for &node in instance.nodes() {
+        degrees[node.idx()] = instance.node_degree(node);
+        heap.push((Reverse(degrees[node.idx()]), node));
+    }
+
+    // Outdated heap entries are skipped lazily, as degrees only decrease
+    let mut remaining = instance.nodes().len();
+    while remaining > num_nodes {
+        let Some((Reverse(degree), node)) = heap.pop() else {
+            break;
+        };
+        if removed[node.idx()] || degree != degrees[node.idx()] {
+            continue;
+        }
+        removed[node.idx()] = true;
+        remaining -= 1;
+        for edge in instance.node(node) {
+            if edge_removed[edge.idx()] {
+                continue;
+            }
+            edge_removed[edge.idx()] = true;
+            for other in instance.edge(edge) {
+                if !removed[other.idx()] {
+                    degrees[other.idx()] -= 1;
+                    heap.push((Reverse(degrees[other.idx()]), other));
+                }
+            }
+        }
+    }
+
+    instance
+        .edges()
+        .iter()
+        .copied()
+        .filter(|edge| !edge_removed[edge.idx()])
+        .collect()
+}
+
+impl SubInstance {
+    /// Chooses the edges of a sub-instance. Its nodes are the nodes of these
+    /// edges, numbered in order of their first appearance.
+    pub fn sample(instance: &Instance, method: SampleMethod) -> Result<Self> {
+        let edges = match method {
+            SampleMethod::RandomEdges { count, seed } => random_edges(instance, count, seed),
+            SampleMethod::Ball { center, radius } => {
+                ensure!(
+                    center.idx() < instance.num_nodes_total(),
+                    "node index {} out of bounds",
+                    center
+                );
+                ball(instance, center, radius)
+            }
+            SampleMethod::Densest { num_nodes } => densest(instance, num_nodes),
+        };
+
+        let mut new_index = vec![usize::MAX; instance.num_nodes_total()];
+        let mut nodes = Vec::new();
+        for &edge in &edges {
+            for node in instance.edge(edge) {
+                if new_index[node.idx()] == usize::MAX {
+                    new_index[node.idx()] = nodes.len();
+                    nodes.push(node);
+                }
+            }
+        }
+        Ok(Self {
+            nodes,
+            edges,
+            sort_edge_nodes: false,
+        })
+    }
+
+    /// Renumbers all nodes and edges of an instance using random permutations
+    /// chosen by the seed, so that the numbering reveals nothing about the
+    /// original one.
+    ///
+    /// The result only depends on the instance and the seed, and describes an
+    /// equivalent instance: node `i` and edge `j` of the written instance are
+    /// `nodes[i]` and `edges[j]` of the original one, so hitting sets map to
+    /// hitting sets of the same size in both directions. Duplicate edges are
+    /// only written as often as they are contained in `instance`, so it should
+    /// be loaded with `keep_duplicate_edges` to keep them. With `deterministic`
+    /// set, the solver takes the same path on the result, up to the limits of
+    /// `canonical`.
+    ///
+    /// With `keep_numbering`, nodes and edges keep their indices instead, and
+    /// only deleted ones are dropped. The result is then not anonymized, but
+    /// the solver behaves identically on it.
+    pub fn anonymize(instance: &Instance, seed: u64, keep_numbering: bool) -> Self {
+        let mut nodes = instance.nodes().to_vec();
+        let mut edges = instance.edges().to_vec();
+        nodes.sort_unstable();
+        edges.sort_unstable();
+        if !keep_numbering {
+            let mut rng = SolverRng::seed_from_u64(seed);
+            nodes.shuffle(&mut rng);
+            edges.shuffle(&mut rng);
+        }
+        Self {
+            nodes,
+            edges,
+            sort_edge_nodes: !keep_numbering,
+        }
+    }
+
+    fn new_indices(&self, instance: &Instance) -> Vec<usize> {
+        let mut new_index = vec![usize::MAX; instance.num_nodes_total()];
+        for (idx, node) in self.nodes.iter().enumerate() {
+            new_index[node.idx()] = idx;
+        }
+        new_index
+    }
+
+    fn edge_nodes(&self, instance: &Instance, new_index: &[usize], edge: EdgeIdx) -> Vec<usize> {
+        let mut nodes: Vec<_> = instance
+            .edge(edge)
+            .map(|node| new_index[node.idx()])
+            .collect();
+        if self.sort_edge_nodes {
+            nodes.sort_unstable();
+        }
+        nodes
+    }
+
+    /// Writes the sub-instance in the text-based hypergraph format.
+    pub fn write_text(&self, instance: &Instance, mut writer: impl Write) -> Result<()> {
+        let new_index = self.new_indices(instance);
+        writeln!(writer, "{} {}", self.nodes.len(), self.edges.len())?;
+        for &edge in &self.edges {
+            write!(writer, "{}", instance.edge_size(edge))?;
+            for node in self.edge_nodes(instance, &new_index, edge) {
+                write!(writer, " {}", node)?;
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the sub-instance in the json hypergraph format, including the
+    /// coverage requirements of its edges and the conflicts between its nodes.
+    pub fn write_json(&self, instance: &Instance, writer: impl Write) -> Result<()> {
+        let new_index = self.new_indices(instance);
+        let edges = self
+            .edges
+            .iter()
+            .map(|&edge| self.edge_nodes(instance, &new_index, edge))
+            .collect();
+        let requirements = if instance.has_requirements() {
+            self.edges
+                .iter()
+                .map(|&edge| instance.edge_requirement(edge))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let mut conflicts = Vec::new();
+        for &node in &self.nodes {
+            for &other in instance.conflicts(node) {
+                if other > node && new_index[other.idx()] != usize::MAX {
+                    let mut pair = vec![new_index[node.idx()], new_index[other.idx()]];
+                    if self.sort_edge_nodes {
+                        pair.sort_unstable();
+                    }
+                    conflicts.push(pair);
+                }
+            }
+        }
+        if self.sort_edge_nodes {
+            conflicts.sort_unstable();
+        }
+        let json = JsonSubInstance {
+            num_nodes: self.nodes.len(),
+            edges,
+            requirements,
+            conflicts,
+        };
+        serde_json::to_writer(writer, &json)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SubInstance;
+    use crate::{
+        instance::Instance,
+        report::Settings,
+        small_indices::SmallIdx,
+        solve::{solve, SolveHooks},
+        test_utils::{load_text, write_to_string, BRANCHING_HYPERGRAPH},
+    };
+
+    /// Contains a duplicate edge and a node without edges
+    const HYPERGRAPH: &str = "7 6\n3 0 1 2\n2 2 3\n3 1 4 5\n2 2 3\n1 5\n2 0 4\n";
+
+    fn write(instance: &Instance, sub_instance: &SubInstance) -> String {
+        write_to_string(|writer| sub_instance.write_text(instance, writer))
+    }
+
+    #[test]
+    fn anonymize_is_deterministic() {
+        let instance = load_text(HYPERGRAPH);
+        let outputs: Vec<_> = (0..8)
+            .map(|seed| write(&instance, &SubInstance::anonymize(&instance, seed, false)))
+            .collect();
+        for (seed, output) in outputs.iter().enumerate() {
+            let again = SubInstance::anonymize(&instance, seed as u64, false);
+            assert_eq!(&write(&instance, &again), output);
+        }
+        assert!(outputs.iter().any(|output| output != &outputs[0]));
+    }
+
+    #[test]
+    fn anonymize_writes_equivalent_instance() {
+        let instance = load_text(HYPERGRAPH);
+        for seed in 0..8 {
+            let anonymized = SubInstance::anonymize(&instance, seed, false);
+            let output = load_text(&write(&instance, &anonymized));
+            assert_eq!(output.num_nodes_total(), instance.num_nodes_total());
+            assert_eq!(output.num_edges_total(), instance.num_edges_total());
+
+            let mut nodes = anonymized.nodes.clone();
+            nodes.sort_unstable();
+            assert_eq!(nodes, instance.nodes());
+            let mut edges = anonymized.edges.clone();
+            edges.sort_unstable();
+            assert_eq!(edges, instance.edges());
+
+            for (&new_edge, &edge) in output.edges().iter().zip(&anonymized.edges) {
+                let mut nodes: Vec<_> = output
+                    .edge(new_edge)
+                    .map(|node| anonymized.nodes[node.idx()])
+                    .collect();
+                nodes.sort_unstable();
+                assert!(nodes.iter().copied().eq(instance.edge(edge)));
+            }
+        }
+    }
+
+    #[test]
+    fn anonymize_keeps_numbering() {
+        let instance = load_text(HYPERGRAPH);
+        let kept = SubInstance::anonymize(&instance, 1, true);
+        assert_eq!(write(&instance, &kept), HYPERGRAPH);
+    }
+
+    #[test]
+    fn deterministic_solve_ignores_anonymization() {
+        let instance = load_text(BRANCHING_HYPERGRAPH);
+        let mut settings: Settings =
+            serde_json::from_str(include_str!("../settings.json")).unwrap();
+        settings.deterministic = true;
+        settings.tiny_instance_fast_path = false;
+        let solve_deterministic = |instance: Instance| {
+            solve(
+                instance,
+                String::new(),
+                settings.clone(),
+                SolveHooks::default(),
+            )
+            .unwrap()
+        };
+
+        let written = write(&instance, &SubInstance::anonymize(&instance, 0, true));
+        let (mut hitting_set, report) = solve_deterministic(load_text(&written));
+        hitting_set.sort_unstable();
+        for seed in 0..8 {
+            let anonymized = SubInstance::anonymize(&instance, seed, false);
+            let output = load_text(&write(&instance, &anonymized));
+            let (anonymized_hs, anonymized_report) = solve_deterministic(output);
+            let mut translated: Vec<_> = anonymized_hs
+                .iter()
+                .map(|node| anonymized.nodes[node.idx()])
+                .collect();
+            translated.sort_unstable();
+            assert_eq!(translated, hitting_set);
+            assert_eq!(anonymized_report.branching_steps, report.branching_steps);
+            assert_eq!(anonymized_report.decisions, report.decisions);
+            assert_eq!(
+                serde_json::to_value(&anonymized_report.reductions).unwrap(),
+                serde_json::to_value(&report.reductions).unwrap()
+            );
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use crate::{instance::NodeIdx, reductions::PruneReason};
+use anyhow::Result;
+use serde::Serialize;
+use std::io::Write;
+
+/// Branching decision leading to a search node
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Decision {
+    pub node: NodeIdx,
+
+    /// Whether the node was added to the hitting set or discarded
+    pub included: bool,
+}
+
+/// What happened in a search node after applying the reductions
+#[derive(Debug, Clone, Copy, Serialize)]
+pub enum SearchNodeOutcome {
+    /// All edges were hit
+    Solved,
+
+    /// The subtree was pruned
+    Pruned(PruneReason),
+
+    /// A hitting set within the stopping size was found
+    Stopped,
+
+    /// The search branched on the given node
+    Branched(NodeIdx),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchNode {
+    pub id: usize,
+    pub parent: Option<usize>,
+
+    /// Decision leading to this node, `None` for the root
+    pub decision: Option<Decision>,
+
+    pub depth: usize,
+
+    /// Size of the partial hitting set after applying the reductions
+    pub partial_size: usize,
+
+    /// Size of the minimum hitting set known after applying the reductions
+    pub incumbent: usize,
+
+    /// Largest lower bound computed for this subtree
+    pub lower_bound: usize,
+
+    pub remaining_nodes: usize,
+    pub remaining_edges: usize,
+    pub outcome: SearchNodeOutcome,
+}
+
+/// Records the branch-and-bound tree up to a maximum number of nodes.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SearchTree {
+    #[serde(skip)]
+    max_nodes: usize,
+
+    /// Set if nodes were omitted after the maximum number of nodes was reached
+    pub truncated: bool,
+
+    pub nodes: Vec<SearchNode>,
+
+    #[serde(skip)]
+    parents: Vec<usize>,
+
+    #[serde(skip)]
+    next_decision: Option<Decision>,
+}
+
+impl SearchTree {
+    pub fn new(max_nodes: usize) -> Self {
+        Self {
+            max_nodes,
+            ..Self::default()
+        }
+    }
+
+    /// Sets the decision leading to the next recorded node.
+    pub fn set_decision(&mut self, node: NodeIdx, included: bool) {
+        self.next_decision = Some(Decision { node, included });
+    }
+
+    /// Records a search node as a child of the node entered last. Returns
+    /// `false` if the node was omitted since the tree is full.
+    pub fn record(&mut self, mut node: SearchNode) -> bool {
+        let decision = self.next_decision.take();
+        if self.nodes.len() >= self.max_nodes {
+            self.truncated = true;
+            return false;
+        }
+
+        node.id = self.nodes.len();
+        node.parent = self.parents.last().copied();
+        node.decision = decision;
+        self.nodes.push(node);
+        true
+    }
+
+    /// Makes the node recorded last the parent of subsequently recorded nodes.
+    pub fn enter(&mut self) {
+        self.parents.push(self.nodes.len() - 1);
+    }
+
+    /// Undoes the last `enter` call.
+    pub fn leave(&mut self) {
+        self.parents.pop();
+    }
+
+    pub fn write_json(&self, writer: impl Write) -> Result<()> {
+        serde_json::to_writer(writer, self)?;
+        Ok(())
+    }
+
+    pub fn write_dot(&self, mut writer: impl Write) -> Result<()> {
+        writeln!(writer, "digraph search_tree {{")?;
+        writeln!(writer, "  node [shape=box, fontname=monospace];")?;
+        for node in &self.nodes {
+            let (outcome, color) = match node.outcome {
+                SearchNodeOutcome::Solved => ("solved".to_string(), "palegreen"),
+                SearchNodeOutcome::Pruned(reason) => (format!("pruned: {:?}", reason), "lightpink"),
+                SearchNodeOutcome::Stopped => ("stopped".to_string(), "gold"),
+                SearchNodeOutcome::Branched(branch_node) => {
+                    (format!("branch on {}", branch_node), "white")
+                }
+            };
+            writeln!(
+                writer,
+                "  n{} [label=\"#{}\\npartial {}, lb {}, ub {}\\n{} nodes, {} edges\\n{}\", \
+                 style=filled, fillcolor={}];",
+                node.id,
+                node.id,
+                node.partial_size,
+                node.lower_bound,
+                node.incumbent,
+                node.remaining_nodes,
+                node.remaining_edges,
+                outcome,
+                color
+            )?;
+            if let Some(parent) = node.parent {
+                let label = node.decision.map_or(String::new(), |decision| {
+                    format!(
+                        "{}{}",
+                        if decision.included { "+" } else { "-" },
+                        decision.node
+                    )
+                });
+                writeln!(
+                    writer,
+                    "  n{} -> n{} [label=\"{}\"];",
+                    parent, node.id, label
+                )?;
+            }
+        }
+        if self.truncated {
+            writeln!(
+                writer,
+                "  truncated [label=\"truncated after {} nodes\", shape=plaintext];",
+                self.nodes.len()
+            )?;
+        }
+        writeln!(writer, "}}")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,260 @@
+use crate::{
+    clock::Instant,
+    instance::{EdgeIdx, Instance, NodeIdx},
+    report::SecondaryObjective,
+    small_indices::SmallIdx,
+};
+use anyhow::{ensure, Result};
+
+/// Value of each node under a secondary objective, together with whether the
+/// sum of the values is maximized or minimized.
+#[derive(Debug, Clone)]
+pub struct NodeValues {
+    values: Vec<f64>,
+    maximize: bool,
+}
+
+impl NodeValues {
+    /// Computes the node values on the input hypergraph, before any nodes or
+    /// edges are deleted.
+    pub fn new(objective: &SecondaryObjective, instance: &Instance) -> Result<Self> {
+        let num_nodes = instance.num_nodes_total();
+        match objective {
+            SecondaryObjective::MaxDegreeSum => Ok(Self {
+                #[allow(clippy::cast_precision_loss)]
+                values: (0..num_nodes)
+                    .map(|node| {
+                        // Merged duplicate edges count as the separate edges they were
+                        instance
+                            .node(NodeIdx::from(node))
+                            .map(|edge| instance.edge_multiplicity(edge))
+                            .sum::<usize>() as f64
+                    })
+                    .collect(),
+                maximize: true,
+            }),
+            SecondaryObjective::MinCostSum { costs } => {
+                ensure!(
+                    costs.len() == num_nodes,
+                    "secondary objective has {} costs, but the hypergraph has {} nodes",
+                    costs.len(),
+                    num_nodes
+                );
+                ensure!(
+                    costs.iter().all(|cost| cost.is_finite()),
+                    "secondary objective costs must be finite"
+                );
+                Ok(Self {
+                    values: costs.clone(),
+                    maximize: false,
+                })
+            }
+        }
+    }
+
+    pub fn sum(&self, nodes: &[NodeIdx]) -> f64 {
+        nodes.iter().map(|node| self.values[node.idx()]).sum()
+    }
+
+    /// Non-negative costs to minimize. As all minimum hitting sets have the
+    /// same size, shifting the values does not change the optimum.
+    fn costs(&self) -> Vec<f64> {
+        if self.maximize {
+            let max = self.values.iter().copied().fold(0.0, f64::max);
+            self.values.iter().map(|value| max - value).collect()
+        } else {
+            let min = self.values.iter().copied().fold(0.0, f64::min);
+            self.values.iter().map(|value| value - min).collect()
+        }
+    }
+}
+
+/// Branch and bound search for a hitting set of at most `size` nodes
+/// minimizing the sum of the node costs.
+///
+/// Branches on the unhit edge with the fewest selectable nodes beyond the
+/// number of hits it still needs, trying its nodes in order of increasing cost
+/// and excluding each one from the later branches. A subproblem is pruned
+/// using a greedy packing of unhit edges: each packed edge needs as many
+/// distinct nodes as hits, each costing at least as much as its cheapest
+/// selectable node.
+struct SecondarySearch<'a> {
+    instance: &'a Instance,
+    costs: Vec<f64>,
+    size: usize,
+    deadline: Option<Instant>,
+    hit_count: Vec<usize>,
+    /// Number of reasons a node can not be chosen: being chosen or excluded
+    /// already, or conflicting with a chosen node
+    num_blockers: Vec<usize>,
+    packed: Vec<bool>,
+    chosen: Vec<NodeIdx>,
+    cost: f64,
+    best: Vec<NodeIdx>,
+    best_cost: f64,
+    branching_steps: usize,
+    timed_out: bool,
+}
+
+impl SecondarySearch<'_> {
+    fn selectable(&self, edge: EdgeIdx) -> impl Iterator<Item = NodeIdx> + '_ {
+        self.instance
+            .edge(edge)
+            .filter(|node| self.num_blockers[node.idx()] == 0)
+    }
+
+    /// Number of hits the edge needs beyond those by the chosen nodes
+    fn needed(&self, edge: EdgeIdx) -> usize {
+        self.instance
+            .edge_requirement(edge)
+            .saturating_sub(self.hit_count[edge.idx()])
+    }
+
+    fn add(&mut self, node: NodeIdx) {
+        self.chosen.push(node);
+        self.cost += self.costs[node.idx()];
+        self.num_blockers[node.idx()] += 1;
+        for other in self.instance.conflicts(node) {
+            self.num_blockers[other.idx()] += 1;
+        }
+        for edge in self.instance.node(node) {
+            self.hit_count[edge.idx()] += 1;
+        }
+    }
+
+    fn remove(&mut self, node: NodeIdx) {
+        debug_assert_eq!(self.chosen.last().copied(), Some(node));
+        self.chosen.pop();
+        self.cost -= self.costs[node.idx()];
+        self.num_blockers[node.idx()] -= 1;
+        for other in self.instance.conflicts(node) {
+            self.num_blockers[other.idx()] -= 1;
+        }
+        for edge in self.instance.node(node) {
+            self.hit_count[edge.idx()] -= 1;
+        }
+    }
+
+    /// Returns the number of nodes needed by a greedy packing of the unhit
+    /// edges and a lower bound on the cost of hitting them.
+    fn packing_bound(&mut self) -> (usize, f64) {
+        let mut num_packed = 0;
+        let mut cost = 0.0;
+        for &edge in self.instance.edges() {
+            let needed = self.needed(edge);
+            if needed == 0 || self.selectable(edge).any(|node| self.packed[node.idx()]) {
+                continue;
+            }
+            num_packed += needed;
+            #[allow(clippy::cast_precision_loss)]
+            let needed_cost = needed as f64;
+            cost += needed_cost
+                * self
+                    .selectable(edge)
+                    .map(|node| self.costs[node.idx()])
+                    .fold(f64::INFINITY, f64::min);
+            for node in self.instance.edge(edge) {
+                self.packed[node.idx()] = true;
+            }
+        }
+        for node in self.instance.nodes() {
+            self.packed[node.idx()] = false;
+        }
+        (num_packed, cost)
+    }
+
+    fn search(&mut self) {
+        if self.timed_out {
+            return;
+        }
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.timed_out = true;
+            return;
+        }
+
+        let branching_edge = self
+            .instance
+            .edges()
+            .iter()
+            .copied()
+            .filter(|&edge| self.needed(edge) > 0)
+            // Edges with too few selectable nodes come first, as `None`
+            .min_by_key(|&edge| self.selectable(edge).count().checked_sub(self.needed(edge)));
+        let Some(branching_edge) = branching_edge else {
+            if self.cost < self.best_cost {
+                self.best_cost = self.cost;
+                self.best.clone_from(&self.chosen);
+            }
+            return;
+        };
+        if self.selectable(branching_edge).count() < self.needed(branching_edge) {
+            return;
+        }
+
+        let (num_packed, cost_bound) = self.packing_bound();
+        if self.chosen.len() + num_packed > self.size || self.cost + cost_bound >= self.best_cost {
+            return;
+        }
+
+        self.branching_steps += 1;
+        let mut nodes: Vec<_> = self.selectable(branching_edge).collect();
+        nodes.sort_by(|a, b| self.costs[a.idx()].total_cmp(&self.costs[b.idx()]));
+        for &node in &nodes {
+            self.add(node);
+            self.search();
+            self.remove(node);
+            self.num_blockers[node.idx()] += 1;
+        }
+        for &node in &nodes {
+            self.num_blockers[node.idx()] -= 1;
+        }
+    }
+}
+
+/// Result of optimizing the secondary objective among the minimum hitting sets
+#[derive(Debug, Clone)]
+pub struct SecondaryResult {
+    pub hitting_set: Vec<NodeIdx>,
+    pub branching_steps: usize,
+
+    /// Whether the search completed before the deadline
+    pub complete: bool,
+}
+
+/// Finds a hitting set of the remaining instance optimizing the secondary
+/// objective among those of at most `size` nodes. Returns `initial` if no
+/// better hitting set exists.
+pub fn optimize(
+    instance: &Instance,
+    values: &NodeValues,
+    size: usize,
+    initial: &[NodeIdx],
+    deadline: Option<Instant>,
+) -> SecondaryResult {
+    let costs = values.costs();
+    let initial_cost = initial.iter().map(|node| costs[node.idx()]).sum();
+    let mut search = SecondarySearch {
+        instance,
+        costs,
+        size,
+        deadline,
+        hit_count: vec![0; instance.num_edges_total()],
+        num_blockers: vec![0; instance.num_nodes_total()],
+        packed: vec![false; instance.num_nodes_total()],
+        chosen: Vec::new(),
+        cost: 0.0,
+        best: initial.to_vec(),
+        best_cost: initial_cost,
+        branching_steps: 0,
+        timed_out: false,
+    };
+    search.search();
+    SecondaryResult {
+        hitting_set: search.best,
+        branching_steps: search.branching_steps,
+        complete: !search.timed_out,
+    }
+}
@@ -0,0 +1,137 @@
+use crate::{
+    instance::{Instance, LoadOptions, NodeIdx},
+    progress::ProgressWriter,
+    report::{Report, Settings},
+    solve::{self, SolveHooks},
+};
+use anyhow::{anyhow, Context, Result};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    io::{BufRead, BufReader, Cursor, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    sync::Arc,
+    thread,
+    time::Duration,
+};
+
+/// File name reported for requests without a name
+const DEFAULT_NAME: &str = "<serve>";
+
+/// Options shared by all connections of the server.
+#[derive(Debug, Clone)]
+pub struct ServeConfig {
+    /// Settings used for requests that don't contain settings
+    pub settings: Option<Settings>,
+    pub load_options: LoadOptions,
+}
+
+/// Request to solve a hypergraph, sent as a single json line.
+#[derive(Debug, Deserialize)]
+struct SolveRequest {
+    /// Hypergraph in the json format
+    instance: Value,
+
+    #[serde(default)]
+    settings: Option<Settings>,
+
+    /// Reported as the file name of the hypergraph
+    #[serde(default)]
+    name: Option<String>,
+
+    /// Stream progress events at this interval in seconds while solving
+    #[serde(default)]
+    progress_interval: Option<f64>,
+}
+
+/// Final line written in response to a request
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum SolveResponse {
+    Result {
+        hitting_set: Vec<NodeIdx>,
+        report: Box<Report>,
+    },
+    Error(String),
+}
+
+/// Accepts connections on `addr` and solves the requests sent over them until
+/// the process is terminated. Each connection is handled on its own thread,
+/// answering its requests in order.
+pub fn serve(addr: impl ToSocketAddrs, config: ServeConfig) -> Result<()> {
+    let listener = TcpListener::bind(addr).context("failed to bind server socket")?;
+    info!("Listening on {}", listener.local_addr()?);
+    let config = Arc::new(config);
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                warn!("Failed to accept connection: {}", err);
+                continue;
+            }
+        };
+        let config = Arc::clone(&config);
+        thread::spawn(move || {
+            let peer = stream
+                .peer_addr()
+                .map_or_else(|_| "unknown peer".to_string(), |addr| addr.to_string());
+            debug!("Accepted connection from {}", peer);
+            if let Err(err) = handle_connection(stream, &config) {
+                warn!("Connection to {} failed: {}", peer, err);
+            }
+        });
+    }
+    Ok(())
+}
+
+fn handle_connection(stream: TcpStream, config: &ServeConfig) -> Result<()> {
+    let reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match handle_request(&line, &writer, config) {
+            Ok((hitting_set, report)) => SolveResponse::Result {
+                hitting_set,
+                report: Box::new(report),
+            },
+            Err(err) => SolveResponse::Error(format!("{:#}", err)),
+        };
+        serde_json::to_writer(&mut writer, &response)?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+fn handle_request(
+    line: &str,
+    stream: &TcpStream,
+    config: &ServeConfig,
+) -> Result<(Vec<NodeIdx>, Report)> {
+    let request: SolveRequest = serde_json::from_str(line).context("invalid request")?;
+    let settings = request
+        .settings
+        .or_else(|| config.settings.clone())
+        .ok_or_else(|| anyhow!("request contains no settings and the server has no default"))?;
+    let instance_json = serde_json::to_vec(&request.instance)?;
+    let instance = Instance::load_from_json(Cursor::new(instance_json), config.load_options)
+        .context("failed to load hypergraph")?;
+    let progress = match request.progress_interval {
+        Some(interval) => {
+            let interval = Duration::try_from_secs_f64(interval)
+                .map_err(|_| anyhow!("invalid progress interval"))?;
+            Some(ProgressWriter::new(Box::new(stream.try_clone()?), interval))
+        }
+        None => None,
+    };
+    let hooks = SolveHooks {
+        progress,
+        ..SolveHooks::default()
+    };
+    let name = request.name.unwrap_or_else(|| DEFAULT_NAME.to_string());
+    solve::solve(instance, name, settings, hooks)
+}
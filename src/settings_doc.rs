@@ -0,0 +1,399 @@
+//! Description of all settings for `findminhs settings --describe`. Types,
+//! defaults, allowed values, and descriptions are taken from the json schema
+//! of [`Settings`], so the description can not diverge from the settings
+//! format. Only the pipeline stage of each field is annotated here.
+use crate::{presets, report::Settings};
+use serde_json::{Map, Value};
+use std::fmt::Write;
+
+/// Part of the solving process a setting acts on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PipelineStage {
+    Preprocessing,
+    GreedyUpperBound,
+    LowerBounds,
+    Reductions,
+    Branching,
+    Termination,
+    Postprocessing,
+    General,
+}
+
+impl PipelineStage {
+    pub const ALL: [Self; 8] = [
+        Self::Preprocessing,
+        Self::GreedyUpperBound,
+        Self::LowerBounds,
+        Self::Reductions,
+        Self::Branching,
+        Self::Termination,
+        Self::Postprocessing,
+        Self::General,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Preprocessing => "Preprocessing",
+            Self::GreedyUpperBound => "Greedy upper bound",
+            Self::LowerBounds => "Lower bounds",
+            Self::Reductions => "Reductions",
+            Self::Branching => "Branching",
+            Self::Termination => "Termination",
+            Self::Postprocessing => "Postprocessing",
+            Self::General => "General",
+        }
+    }
+
+    /// How the settings of this stage affect `reduce()`, which runs on every
+    /// subproblem in rounds until the instance no longer changes
+    pub fn effect(self) -> &'static str {
+        match self {
+            Self::Preprocessing => {
+                "Applied once before the search. Every reduce() round works on the changed \
+                 instance and starts out with the initial upper bound."
+            }
+            Self::GreedyUpperBound => {
+                "Step 1 of a reduce() round: recomputes the greedy hitting set, which may lower \
+                 the breakpoint that the bounds and reductions are checked against."
+            }
+            Self::LowerBounds => {
+                "Step 2 of a reduce() round: the subproblem is pruned as soon as a lower bound \
+                 reaches the breakpoint. The efficiency and packing bounds are reused by the \
+                 costly discards of step 3."
+            }
+            Self::Reductions => {
+                "Step 3 of a reduce() round: rules forcing or discarding nodes and removing \
+                 edges. Another round starts whenever one of them changes the instance."
+            }
+            Self::Branching => {
+                "Not part of reduce(): decides how the search branches on the reduced \
+                 subproblems and which subtrees it visits."
+            }
+            Self::Termination => {
+                "Ends the search early, keeping the best hitting set found. reduce() stops as \
+                 soon as a hitting set of size stop_at is known."
+            }
+            Self::Postprocessing => {
+                "Not part of reduce(): runs after the search on the minimum hitting set."
+            }
+            Self::General => {
+                "Not part of reduce(): affects randomness, validation, and the recorded data."
+            }
+        }
+    }
+
+    /// Stage of the settings field with the given name
+    pub fn of_setting(field: &str) -> Option<Self> {
+        let stage = match field {
+            "forced_nodes"
+            | "forbidden_nodes"
+            | "partial_hitting_set"
+            | "initial_hitting_set"
+            | "initial_greedy_strategies"
+            | "upper_bound_search_iterations"
+            | "upper_bound_search_tabu_tenure"
+            | "merge_twin_nodes" => Self::Preprocessing,
+            "greedy_mode" | "greedy_strategy" => Self::GreedyUpperBound,
+            "enable_local_search"
+            | "local_search_three_opt_budget"
+            | "enable_max_degree_bound"
+            | "enable_sum_degree_bound"
+            | "enable_efficiency_bound"
+            | "enable_packing_bound"
+            | "enable_sum_over_packing_bound"
+            | "enable_flow_bound"
+            | "enable_clique_cover_bound"
+            | "packing_order"
+            | "reuse_packing"
+            | "adaptive_bounds"
+            | "parallel_reductions_min_edges" => Self::LowerBounds,
+            "packing_from_scratch_limit"
+            | "enable_degree_forcing"
+            | "enable_degree_discarding"
+            | "subset_trie_chunked_max_nodes" => Self::Reductions,
+            "dive_interval"
+            | "nogood_limit"
+            | "max_nogood_size"
+            | "activity_branching"
+            | "three_hs_branching"
+            | "restarts"
+            | "limited_discrepancy_search"
+            | "subtree_budgets"
+            | "symmetry_breaking"
+            | "tiny_instance_fast_path" => Self::Branching,
+            "budget"
+            | "stop_at"
+            | "time_limit"
+            | "max_branching_steps"
+            | "max_decisions"
+            | "gap" => Self::Termination,
+            "classify_nodes" | "canonical_solution" | "secondary_objective" => Self::Postprocessing,
+            "seed"
+            | "deterministic"
+            | "record_preprocessing"
+            | "search_tree_max_nodes"
+            | "validate_incumbents" => Self::General,
+            _ => return None,
+        };
+        Some(stage)
+    }
+}
+
+const LINE_WIDTH: usize = 100;
+
+/// Writes the text wrapped at `LINE_WIDTH`, indenting all lines after the
+/// first one by `hanging_indent` more
+fn write_wrapped(out: &mut String, indent: usize, hanging_indent: usize, text: &str) {
+    let mut line_indent = indent;
+    let mut line_len = 0;
+    for word in text.split_whitespace() {
+        if line_len > 0 && line_len + 1 + word.len() > LINE_WIDTH {
+            out.push('\n');
+            line_indent = indent + hanging_indent;
+            line_len = 0;
+        }
+        if line_len == 0 {
+            out.extend(std::iter::repeat_n(' ', line_indent));
+            line_len = line_indent;
+        } else {
+            out.push(' ');
+            line_len += 1;
+        }
+        out.push_str(word);
+        line_len += word.len();
+    }
+    out.push('\n');
+}
+
+fn definition_name(schema: &Value) -> Option<&str> {
+    schema.get("$ref")?.as_str()?.strip_prefix("#/definitions/")
+}
+
+fn primitive_type_name(kind: &str, schema: &Value) -> String {
+    match kind {
+        "integer" if schema.get("minimum").and_then(Value::as_f64) == Some(0.0) => {
+            "non-negative integer".to_string()
+        }
+        "array" => match schema.get("items") {
+            Some(items) => format!("array of {}", type_name(items)),
+            None => "array".to_string(),
+        },
+        "object" => match schema.get("properties").and_then(Value::as_object) {
+            Some(properties) => {
+                let entries: Vec<_> = properties
+                    .iter()
+                    .map(|(name, property)| format!("{}: {}", name, type_name(property)))
+                    .collect();
+                format!("{{{}}}", entries.join(", "))
+            }
+            None => "object".to_string(),
+        },
+        kind => kind.to_string(),
+    }
+}
+
+fn type_name(schema: &Value) -> String {
+    if let Some(name) = definition_name(schema) {
+        return name.to_string();
+    }
+    for combinator in ["allOf", "anyOf", "oneOf"] {
+        if let Some(schemas) = schema.get(combinator).and_then(Value::as_array) {
+            let names: Vec<_> = schemas.iter().map(type_name).collect();
+            return names.join(" or ");
+        }
+    }
+    match schema.get("type") {
+        Some(Value::String(kind)) => primitive_type_name(kind, schema),
+        Some(Value::Array(kinds)) => {
+            let names: Vec<_> = kinds
+                .iter()
+                .filter_map(Value::as_str)
+                .map(|kind| primitive_type_name(kind, schema))
+                .collect();
+            names.join(" or ")
+        }
+        _ => "any".to_string(),
+    }
+}
+
+/// Definitions referenced by a field, directly or as the type of its elements
+fn referenced_definitions<'a>(schema: &'a Value, names: &mut Vec<&'a str>) {
+    if let Some(name) = definition_name(schema) {
+        names.push(name);
+    }
+    for combinator in ["allOf", "anyOf", "oneOf"] {
+        if let Some(schemas) = schema.get(combinator).and_then(Value::as_array) {
+            for schema in schemas {
+                referenced_definitions(schema, names);
+            }
+        }
+    }
+    if let Some(items) = schema.get("items") {
+        referenced_definitions(items, names);
+    }
+}
+
+fn description(schema: &Value) -> Option<&str> {
+    schema.get("description").and_then(Value::as_str)
+}
+
+/// Allowed values of a definition, each with its description
+fn allowed_values(definition: &Value) -> Vec<(String, Option<&str>)> {
+    let Some(variants) = definition.get("oneOf").and_then(Value::as_array) else {
+        if let Some(values) = definition.get("enum").and_then(Value::as_array) {
+            return values
+                .iter()
+                .map(|value| (value.to_string(), None))
+                .collect();
+        }
+        // A struct, whose entries are listed instead
+        let Some(properties) = definition.get("properties").and_then(Value::as_object) else {
+            return Vec::new();
+        };
+        return properties
+            .iter()
+            .map(|(name, property)| {
+                (
+                    format!("{}: {}", name, type_name(property)),
+                    description(property),
+                )
+            })
+            .collect();
+    };
+
+    let mut values = Vec::new();
+    for variant in variants {
+        if let Some(names) = variant.get("enum").and_then(Value::as_array) {
+            values.extend(
+                names
+                    .iter()
+                    .map(|name| (name.to_string(), description(variant))),
+            );
+        } else if let Some(properties) = variant.get("properties").and_then(Value::as_object) {
+            for (name, property) in properties {
+                values.push((
+                    format!("{{\"{}\": {}}}", name, type_name(property)),
+                    description(variant),
+                ));
+            }
+        }
+    }
+    values
+}
+
+fn describe_field(
+    out: &mut String,
+    name: &str,
+    property: &Value,
+    required: bool,
+    definitions: &Map<String, Value>,
+    values: Option<&Map<String, Value>>,
+) {
+    let _ = writeln!(out, "{}", name);
+    if let Some(description) = description(property) {
+        write_wrapped(out, 4, 0, description);
+    }
+    let _ = writeln!(out, "    type:    {}", type_name(property));
+    let default = match property.get("default") {
+        Some(default) => default.to_string(),
+        None if required => match presets::preset_entries("default")
+            .ok()
+            .and_then(|mut entries| entries.remove(name))
+        {
+            Some(value) => format!("required ({} in the default preset)", value),
+            None => "required".to_string(),
+        },
+        None => Value::Null.to_string(),
+    };
+    let _ = writeln!(out, "    default: {}", default);
+    if let Some(value) = values.and_then(|values| values.get(name)) {
+        let _ = writeln!(out, "    value:   {}", value);
+    }
+
+    let mut names = Vec::new();
+    referenced_definitions(property, &mut names);
+    for name in names {
+        let Some(definition) = definitions.get(name) else {
+            continue;
+        };
+        let allowed = allowed_values(definition);
+        if allowed.is_empty() {
+            continue;
+        }
+        let listed = if definition.get("properties").is_some() {
+            "entries"
+        } else {
+            "values"
+        };
+        let _ = writeln!(out, "    {} {}:", name, listed);
+        for (value, description) in allowed {
+            let line = match description {
+                Some(description) => format!("{}: {}", value, description),
+                None => value,
+            };
+            write_wrapped(out, 8, 4, &line);
+        }
+    }
+}
+
+/// Describes every settings field, grouped by the stage of the solver it acts
+/// on. If settings are given, their value of each field is included.
+pub fn describe_settings(settings: Option<&Settings>) -> String {
+    let schema = serde_json::to_value(schemars::schema_for!(Settings))
+        .expect("Failed to serialize the settings schema");
+    let empty = Map::new();
+    let properties = schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .unwrap_or(&empty);
+    let definitions = schema
+        .get("definitions")
+        .and_then(Value::as_object)
+        .unwrap_or(&empty);
+    let required: Vec<_> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|names| names.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    let values = settings.map(|settings| match serde_json::to_value(settings) {
+        Ok(Value::Object(values)) => values,
+        _ => panic!("Settings did not serialize to an object"),
+    });
+
+    let mut stages: Vec<_> = PipelineStage::ALL.into_iter().map(Some).collect();
+    stages.push(None);
+    let mut out = String::new();
+    for stage in stages {
+        let fields: Vec<_> = properties
+            .iter()
+            .filter(|(name, _)| PipelineStage::of_setting(name) == stage)
+            .collect();
+        if fields.is_empty() {
+            continue;
+        }
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        match stage {
+            Some(stage) => {
+                let _ = writeln!(out, "## {}", stage.name());
+                write_wrapped(&mut out, 0, 0, stage.effect());
+            }
+            None => {
+                let _ = writeln!(out, "## Other");
+            }
+        }
+        for (name, property) in fields {
+            out.push('\n');
+            describe_field(
+                &mut out,
+                name,
+                property,
+                required.contains(&name.as_str()),
+                definitions,
+                values.as_ref(),
+            );
+        }
+    }
+    out
+}
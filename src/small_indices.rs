@@ -5,6 +5,20 @@ use std::{
     hash::Hash,
 };
 
+/// Integer type backing all index structs.
+///
+/// This is `u32` by default to conserve space, the `wide-indices` feature
+/// switches it to `u64` for instances exceeding the limits of a `u32`.
+#[cfg(not(feature = "wide-indices"))]
+pub type RawIdx = u32;
+
+/// Integer type backing all index structs.
+///
+/// This is `u32` by default to conserve space, the `wide-indices` feature
+/// switches it to `u64` for instances exceeding the limits of a `u32`.
+#[cfg(feature = "wide-indices")]
+pub type RawIdx = u64;
+
 pub trait SmallIdx:
     Sized
     + Copy
@@ -14,9 +28,9 @@ pub trait SmallIdx:
     + Hash
     + Ord
     + Into<usize>
-    + Into<u32>
+    + Into<RawIdx>
     + From<usize>
-    + From<u32>
+    + From<RawIdx>
     + Serialize
     + DeserializeOwned
 {
@@ -39,7 +53,7 @@ pub trait SmallIdx:
     }
 }
 
-/// Creates an index struct that uses a `u32` to store the index.
+/// Creates an index struct that uses a `RawIdx` to store the index.
 #[macro_export]
 macro_rules! create_idx_struct {
     ($vis:vis $name:ident) => {
@@ -54,13 +68,14 @@ macro_rules! create_idx_struct {
             PartialOrd,
             ::serde::Serialize,
             ::serde::Deserialize,
+            ::schemars::JsonSchema,
         )]
         #[serde(transparent)]
-        $vis struct $name(u32);
+        $vis struct $name($crate::small_indices::RawIdx);
 
         impl $crate::small_indices::SmallIdx for $name {
             #[allow(dead_code)]
-            const INVALID: Self = Self(u32::max_value());
+            const INVALID: Self = Self($crate::small_indices::RawIdx::MAX);
 
             fn idx(&self) -> usize {
                 self.0 as usize
@@ -69,13 +84,16 @@ macro_rules! create_idx_struct {
 
         impl ::std::convert::From<usize> for $name {
             fn from(idx: usize) -> Self {
-                debug_assert!(<u32 as ::std::convert::TryFrom<usize>>::try_from(idx).is_ok());
-                Self(idx as u32)
+                debug_assert!(
+                    <$crate::small_indices::RawIdx as ::std::convert::TryFrom<usize>>::try_from(idx)
+                        .is_ok()
+                );
+                Self(idx as $crate::small_indices::RawIdx)
             }
         }
 
-        impl ::std::convert::From<u32> for $name {
-            fn from(idx: u32) -> Self {
+        impl ::std::convert::From<$crate::small_indices::RawIdx> for $name {
+            fn from(idx: $crate::small_indices::RawIdx) -> Self {
                 Self(idx)
             }
         }
@@ -87,7 +105,7 @@ macro_rules! create_idx_struct {
             }
         }
 
-        impl ::std::convert::From<$name> for u32 {
+        impl ::std::convert::From<$name> for $crate::small_indices::RawIdx {
             fn from(i: $name) -> Self {
                 i.0
             }
@@ -108,6 +126,11 @@ macro_rules! create_idx_struct {
     };
 }
 
+/// Checks whether an index or count can be represented by the index structs.
+pub fn fits_raw_idx(val: usize) -> bool {
+    RawIdx::try_from(val).is_ok_and(|raw| raw != RawIdx::MAX)
+}
+
 /// Hash map with optimized hash function for small indices.
 pub type IdxHashMap<I, V> = FxHashMap<I, V>;
 
@@ -0,0 +1,153 @@
+use crate::{
+    instance::NodeIdx,
+    report::{Report, SolveStatus},
+    small_indices::fits_raw_idx,
+};
+use anyhow::{anyhow, ensure, Context, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{BufRead, Write},
+    str::FromStr,
+};
+
+/// Format of written solution files
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SolutionFormat {
+    /// Json array of node indices, or a json object if metadata is included
+    #[default]
+    Json,
+
+    /// One node index per line, preceded by metadata in `#` comment lines
+    Text,
+}
+
+impl FromStr for SolutionFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "json" => Ok(Self::Json),
+            "text" => Ok(Self::Text),
+            _ => Err(anyhow!("Unknown solution format {}", s)),
+        }
+    }
+}
+
+/// Information identifying the run that produced a solution
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SolutionMetadata {
+    pub file_name: String,
+    pub size: usize,
+
+    /// Whether the hitting set is proven to be minimum
+    pub optimal: bool,
+    pub solver_version: String,
+
+    /// 64-bit FNV-1a hash of the settings serialized as json, in hexadecimal
+    pub settings_hash: String,
+}
+
+impl SolutionMetadata {
+    pub fn new(report: &Report) -> Result<Self> {
+        let settings = serde_json::to_vec(&report.settings)?;
+        Ok(Self {
+            file_name: report.file_name.clone(),
+            size: report.solution_size(),
+            optimal: report.status == SolveStatus::Optimal,
+            solver_version: env!("CARGO_PKG_VERSION").to_string(),
+            settings_hash: format!("{:016x}", fnv1a(&settings)),
+        })
+    }
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SolutionWithMetadata {
+    #[serde(flatten)]
+    pub metadata: SolutionMetadata,
+    pub hitting_set: Vec<NodeIdx>,
+}
+
+/// Contents of a json solution file
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum SolutionFile {
+    Nodes(Vec<NodeIdx>),
+    WithMetadata(SolutionWithMetadata),
+}
+
+impl SolutionFile {
+    pub fn into_nodes(self) -> Vec<NodeIdx> {
+        match self {
+            Self::Nodes(nodes) => nodes,
+            Self::WithMetadata(solution) => solution.hitting_set,
+        }
+    }
+}
+
+pub fn write_solution(
+    mut writer: impl Write,
+    hitting_set: &[NodeIdx],
+    format: SolutionFormat,
+    metadata: Option<SolutionMetadata>,
+) -> Result<()> {
+    match (format, metadata) {
+        (SolutionFormat::Json, None) => serde_json::to_writer(&mut writer, hitting_set)?,
+        (SolutionFormat::Json, Some(metadata)) => {
+            let solution = SolutionFile::WithMetadata(SolutionWithMetadata {
+                metadata,
+                hitting_set: hitting_set.to_vec(),
+            });
+            serde_json::to_writer(&mut writer, &solution)?;
+        }
+        (SolutionFormat::Text, metadata) => {
+            if let Some(metadata) = metadata {
+                writeln!(writer, "# file_name: {}", metadata.file_name)?;
+                writeln!(writer, "# size: {}", metadata.size)?;
+                writeln!(writer, "# optimal: {}", metadata.optimal)?;
+                writeln!(writer, "# solver_version: {}", metadata.solver_version)?;
+                writeln!(writer, "# settings_hash: {}", metadata.settings_hash)?;
+            }
+            for node in hitting_set {
+                writeln!(writer, "{}", node)?;
+            }
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads a solution in any of the formats written by `write_solution`.
+pub fn read_solution(mut reader: impl BufRead) -> Result<Vec<NodeIdx>> {
+    let mut content = String::new();
+    reader.read_to_string(&mut content)?;
+    if content.trim_start().starts_with(['[', '{']) {
+        let solution: SolutionFile = serde_json::from_str(&content)?;
+        return Ok(solution.into_nodes());
+    }
+
+    let mut nodes = Vec::new();
+    for (line_idx, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let node: usize = line
+            .parse()
+            .with_context(|| format!("invalid node index in line {}", line_idx + 1))?;
+        ensure!(
+            fits_raw_idx(node),
+            "node index {} in line {} is too large",
+            node,
+            line_idx + 1
+        );
+        nodes.push(NodeIdx::from(node));
+    }
+    Ok(nodes)
+}
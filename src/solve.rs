@@ -1,24 +1,395 @@
 use crate::{
-    instance::{Instance, NodeIdx},
-    lower_bound::{self, PackingBound},
-    reductions::{self, ReductionResult},
-    report::{ReductionStats, Report, RootBounds, RuntimeStats, Settings, UpperBoundImprovement},
+    activity::EdgeActivity,
+    bound_selection::BoundSelection,
+    canonical,
+    clock::Instant,
+    coverage, difficulty,
+    instance::{EdgeIdx, Instance, NodeIdx},
+    lower_bound::{self, LowerBound, PackingBound},
+    nogoods::NogoodStore,
+    preprocessing::{PreprocessingAction, PreprocessingReport, ReductionRule},
+    profile,
+    progress::{IncumbentCallback, ProgressCallback, ProgressWriter, ReportStream},
+    reductions::{
+        self, CustomReduction, DominationTries, MergedTwin, ReducedItem, ReductionResult,
+    },
+    relabel::NodeRelabeling,
+    replay::{BranchingDecision, DecisionRecorder, Replay},
+    report::{
+        self, BranchingEngine, BudgetCoverage, CanonicalSolution, ComponentCertificate,
+        DifficultyEstimate, ForbiddenNode, GreedyMode, IncumbentEvent, InfeasibilityExplanation,
+        InstanceProfile, LowerBoundCertificate, LowerBoundWitness, NodeClass, NodeClassification,
+        ProgressEvent, ReductionStats, Report, ReportEvent, RestartStrategy, RootBounds,
+        RuntimeStats, SecondaryObjectiveResult, Settings, SolveStatus, UpperBoundImprovement,
+        REPORT_VERSION,
+    },
+    restart::{RestartSchedule, SubtreeBudgets},
+    search_tree::{SearchNode, SearchNodeOutcome, SearchTree},
+    secondary::{self, NodeValues},
     small_indices::{IdxHashSet, SmallIdx},
+    symmetry, tiny, upper_bound,
 };
-use anyhow::{ensure, Result};
+use anyhow::{anyhow, bail, ensure, Result};
 use log::{debug, info, trace, warn};
-use std::time::Instant;
+use rand::SeedableRng;
+use rand_pcg::Pcg64Mcg;
+use std::{
+    any::Any,
+    cmp::Reverse,
+    error::Error,
+    fmt, mem,
+    num::NonZeroUsize,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
 
 const ITERATION_LOG_INTERVAL_SECS: u64 = 60;
 
-#[derive(Debug, Clone)]
+/// Optional outputs and inputs of a solver run, besides the instance and
+/// settings
+#[derive(Debug, Default)]
+pub struct SolveHooks {
+    pub progress: Option<ProgressWriter>,
+
+    /// Records the branching decisions
+    pub decision_recorder: Option<DecisionRecorder>,
+
+    /// Replays previously recorded branching decisions
+    pub replay: Option<Replay>,
+
+    /// Aborts the search once set, keeping the best hitting set found so far
+    pub cancel: Option<Arc<AtomicBool>>,
+
+    pub progress_callback: Option<ProgressCallback>,
+
+    /// Called whenever the search finds a smaller hitting set
+    pub incumbent_callback: Option<IncumbentCallback>,
+
+    /// Receives the upper bound improvements instead of the report
+    pub report_stream: Option<ReportStream>,
+
+    /// Lower bounds computed after the built-in ones in every reduction
+    /// round
+    pub lower_bounds: Vec<Box<dyn LowerBound>>,
+
+    /// Reductions run after the built-in rules based on degrees and lower
+    /// bounds in every reduction round
+    pub reductions: Vec<Box<dyn CustomReduction>>,
+}
+
+#[derive(Debug)]
 pub struct State {
     pub partial_hs: Vec<NodeIdx>,
     pub minimum_hs: Vec<NodeIdx>,
+
+    /// Best hitting set found before the search, if it does not fit the
+    /// budget. `minimum_hs` is then a placeholder one node larger than the
+    /// budget until the search finds a hitting set within the budget, so that
+    /// all larger ones are pruned.
+    pub budget_fallback_hs: Option<Vec<NodeIdx>>,
     pub solve_start_time: Instant,
     pub last_log_time: Instant,
+
+    /// Node domination is known to find nothing on the instance passed to the
+    /// next `reduce` call
+    pub node_domination_up_to_date: bool,
+    pub domination_tries: DominationTries,
+
+    /// Number of branching decisions leading to the current subproblem
+    pub depth: usize,
+
+    pub branching_engine: BranchingEngine,
+
+    pub progress: Option<ProgressWriter>,
+    pub progress_callback: Option<ProgressCallback>,
+    pub incumbent_callback: Option<IncumbentCallback>,
+    pub report_stream: Option<ReportStream>,
+
+    /// Point in time at which the search is aborted
+    pub deadline: Option<Instant>,
+    pub cancel: Option<Arc<AtomicBool>>,
+
+    /// Largest lower bound on the size of any hitting set in the current
+    /// subtree, as computed by the last `reduce` call
+    pub subtree_lower_bound: usize,
+
+    /// Lower bound on the size of any hitting set of the whole instance
+    pub global_lower_bound: usize,
+
+    /// Lower bounds of the subproblems branched on along the current search
+    /// path, and whether the branch excluding the node is still pending
+    pub open_branches: Vec<(usize, bool)>,
+
+    pub search_tree: Option<SearchTree>,
+    pub decision_recorder: Option<DecisionRecorder>,
+    pub replay: Option<Replay>,
+    pub nogoods: Option<NogoodStore>,
+    pub activity: Option<EdgeActivity>,
+    pub restarts: Option<RestartSchedule>,
+
+    /// Limit on the branches excluding the branching node, if using limited
+    /// discrepancy search
+    pub discrepancies: Option<DiscrepancyLimit>,
+    pub subtree_budgets: Option<SubtreeBudgets>,
+    pub bound_selection: Option<BoundSelection>,
+
+    /// Records the changes made before the first branching step
+    pub preprocessing: Option<PreprocessingReport>,
+    pub custom_bounds: Arc<Vec<Box<dyn LowerBound>>>,
+    pub custom_reductions: Arc<Vec<Box<dyn CustomReduction>>>,
+
+    /// Packing of the instance as left by the last `reduce` call, to be
+    /// repaired by the subproblems branched on next
+    pub packing_hint: Option<Vec<EdgeIdx>>,
+
+    /// Edges of the input instance with their coverage requirements, if every
+    /// incumbent is validated against them (`validate_incumbents`)
+    pub validation_edges: Option<Vec<(Vec<NodeIdx>, usize)>>,
+}
+
+/// Limits the number of branches excluding the branching node along each
+/// search path. Excluding the node deviates from the branching heuristic,
+/// which prefers the node to be part of the hitting set.
+#[derive(Debug, Default)]
+pub struct DiscrepancyLimit {
+    pub limit: usize,
+
+    /// Number of branches excluding the branching node along the current
+    /// search path
+    pub current: usize,
+
+    /// Smallest lower bound of a branch skipped in the current iteration
+    pub skipped_lower_bound: Option<usize>,
+}
+
+impl State {
+    /// Records that `minimum_hs` was improved by the search, passing it to
+    /// the incumbent callback.
+    pub fn record_improvement(&mut self, report: &mut Report) {
+        self.validate_incumbent(report.branching_steps);
+        let elapsed = self.solve_start_time.elapsed();
+        self.push_improvement(
+            report,
+            UpperBoundImprovement {
+                new_bound: self.minimum_hs.len(),
+                branching_steps: report.branching_steps,
+                runtime: elapsed,
+            },
+        );
+        if let Some(callback) = &mut self.incumbent_callback {
+            callback.call(&IncumbentEvent {
+                elapsed,
+                branching_steps: report.branching_steps,
+                size: self.minimum_hs.len(),
+                hitting_set: &self.minimum_hs,
+            });
+        }
+    }
+
+    /// Writes an upper bound improvement to the report stream, or adds it to
+    /// the report if there is none.
+    fn push_improvement(&mut self, report: &mut Report, mut improvement: UpperBoundImprovement) {
+        let Some(stream) = &mut self.report_stream else {
+            report.upper_bound_improvements.push(improvement);
+            return;
+        };
+        if report.settings.deterministic {
+            improvement.runtime = Duration::ZERO;
+        }
+        if let Err(err) = stream.write_event(&ReportEvent::UpperBoundImprovement(improvement)) {
+            warn!(
+                "Failed to write report event, keeping events in the report: {}",
+                err
+            );
+            self.report_stream = None;
+        }
+    }
+
+    /// Writes the difficulty estimate as the first event of the report stream
+    /// and flushes it, so that it can be read before the search finishes.
+    fn push_difficulty(&mut self, report: &Report) {
+        let Some(stream) = &mut self.report_stream else {
+            return;
+        };
+        let event = ReportEvent::Difficulty(report.difficulty.clone());
+        if let Err(err) = stream.write_event(&event).and_then(|()| stream.flush()) {
+            warn!(
+                "Failed to write report event, keeping events in the report: {}",
+                err
+            );
+            self.report_stream = None;
+        }
+    }
+
+    /// Records the report stream in the report and flushes it.
+    fn finish_report_stream(&mut self, report: &mut Report) {
+        let Some(stream) = &mut self.report_stream else {
+            return;
+        };
+        report.report_stream = Some(stream.info());
+        if let Err(err) = stream.flush() {
+            warn!("Failed to flush report stream: {}", err);
+        }
+    }
+
+    /// Panics if `validate_incumbents` is set and `minimum_hs` misses an edge
+    /// of the input instance, to catch invalid incumbents as soon as they are
+    /// found.
+    fn validate_incumbent(&self, branching_steps: usize) {
+        let Some(edges) = &self.validation_edges else {
+            return;
+        };
+        let hs_set: IdxHashSet<_> = self.minimum_hs.iter().copied().collect();
+        if let Some(edge) = edges.iter().position(|(nodes, requirement)| {
+            nodes.iter().filter(|node| hs_set.contains(node)).count() < *requirement
+        }) {
+            panic!(
+                "Incumbent of size {} found after {} branching steps does not hit edge {} {:?}",
+                self.minimum_hs.len(),
+                branching_steps,
+                edge,
+                edges[edge].0
+            );
+        }
+    }
+
+    /// Whether `minimum_hs` is the placeholder used instead of
+    /// `budget_fallback_hs`
+    fn has_placeholder_incumbent(&self) -> bool {
+        self.budget_fallback_hs.is_some() && self.minimum_hs.first() == Some(&NodeIdx::INVALID)
+    }
+
+    /// Replaces the placeholder incumbent by `budget_fallback_hs`, returning
+    /// the size of the placeholder if it was still in use.
+    fn restore_budget_fallback(&mut self) -> Option<usize> {
+        if !self.has_placeholder_incumbent() {
+            return None;
+        }
+        let fallback = self.budget_fallback_hs.take()?;
+        Some(mem::replace(&mut self.minimum_hs, fallback).len())
+    }
+
+    /// Records a lower bound for the remaining instance of the current
+    /// subproblem
+    pub fn record_lower_bound(&mut self, bound: usize) {
+        let bound = self.partial_hs.len().saturating_add(bound);
+        self.subtree_lower_bound = self.subtree_lower_bound.max(bound);
+    }
+
+    /// Raises the global lower bound to the smallest bound of any subproblem
+    /// that is not fully explored yet, unless the best hitting set is smaller.
+    ///
+    /// The open subproblems are the pending branches excluding a node along
+    /// the current search path, and the subproblem currently being solved.
+    /// Their bounds are at least those of their parents.
+    fn update_global_lower_bound(&mut self) {
+        let Some(&(current, _)) = self.open_branches.last() else {
+            return;
+        };
+        let open = self
+            .open_branches
+            .iter()
+            .filter(|&&(_, exclude_pending)| exclude_pending)
+            .fold(current, |min, &(bound, _)| min.min(bound));
+        let skipped = self
+            .discrepancies
+            .as_ref()
+            .and_then(|discrepancies| discrepancies.skipped_lower_bound)
+            .into_iter()
+            .chain(
+                self.subtree_budgets
+                    .as_ref()
+                    .and_then(|budgets| budgets.skipped_lower_bound),
+            )
+            .min()
+            .unwrap_or(usize::MAX);
+        let bound = open.min(skipped).min(self.minimum_hs.len());
+        self.global_lower_bound = self.global_lower_bound.max(bound);
+    }
+}
+
+/// Error returned if no hitting set satisfies the constraints of an instance
+#[derive(Debug)]
+pub struct Infeasible(pub InfeasibilityExplanation);
+
+impl fmt::Display for Infeasible {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "instance is unsolvable: {}", self.0)
+    }
+}
+
+impl Error for Infeasible {}
+
+/// Error returned if the solver panicked, e.g., because the final hitting set
+/// failed validation. The incumbent is kept so that it can still be written,
+/// but it is not guaranteed to be a hitting set.
+#[derive(Debug)]
+pub struct Panicked {
+    pub message: String,
+
+    /// Best hitting set found before the panic
+    pub hitting_set: Vec<NodeIdx>,
+
+    /// Report up to the panic, with status `Panicked`
+    pub report: Box<Report>,
+}
+
+impl Panicked {
+    fn new(payload: &(dyn Any + Send), mut state: State, mut report: Report) -> Self {
+        let message = if let Some(message) = payload.downcast_ref::<&str>() {
+            (*message).to_string()
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message.clone()
+        } else {
+            "unknown panic payload".to_string()
+        };
+        state.restore_budget_fallback();
+        warn!(
+            event = "panicked", size = state.minimum_hs.len();
+            "Solver panicked, keeping best hitting set of size {}",
+            state.minimum_hs.len()
+        );
+
+        // The instance and most of the state may be inconsistent, so only
+        // values that are updated atomically are reported
+        report.status = SolveStatus::Panicked;
+        report.opt = state.minimum_hs.len();
+        report.lower_bound = state.global_lower_bound.min(report.opt);
+        report.gap = report::relative_gap(report.opt, report.lower_bound);
+        report.runtimes.total = state.solve_start_time.elapsed();
+        report.search_tree = state.search_tree.take();
+        report.preprocessing = state.preprocessing.take();
+        if let Some(recorder) = &mut state.decision_recorder {
+            if let Err(err) = recorder.flush() {
+                warn!("Failed to flush recorded branching decisions: {}", err);
+            }
+        }
+        state.finish_report_stream(&mut report);
+        Self {
+            message,
+            hitting_set: state.minimum_hs,
+            report: Box::new(report),
+        }
+    }
 }
 
+impl fmt::Display for Panicked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "solver panicked: {}", self.message)
+    }
+}
+
+impl Error for Panicked {}
+
+/// Random number generator used by all randomized components. It is seeded
+/// from the settings so that runs are reproducible.
+pub type SolverRng = Pcg64Mcg;
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 enum Status {
     /// Continue solving to search for smaller hitting sets
@@ -26,6 +397,24 @@ enum Status {
 
     /// A hitting set smaller or equal to the stopping size has been found
     Stop,
+
+    /// The time limit has been reached
+    TimeLimit,
+
+    /// The limit on branching steps or decisions has been reached
+    StepLimit,
+
+    /// The best hitting set is within the requested gap of the lower bound
+    GapReached,
+
+    /// The replay reached the branching step to stop at
+    ReplayStopped,
+
+    /// The search was cancelled from outside
+    Cancelled,
+
+    /// The search should restart from the root
+    Restart,
 }
 
 fn branch_on(
@@ -34,29 +423,386 @@ fn branch_on(
     state: &mut State,
     report: &mut Report,
 ) -> Status {
-    trace!("Branching on {}", node);
+    trace!(event = "branch", node = node.idx(); "Branching on {}", node);
     report.branching_steps += 1;
-    instance.delete_node(node);
-
-    instance.delete_incident_edges(node);
-    state.partial_hs.push(node);
-    let status_without = solve_recursive(instance, state, report);
+    if let Some(recorder) = &mut state.decision_recorder {
+        let decision = BranchingDecision {
+            branching_step: report.branching_steps,
+            node,
+        };
+        if let Err(err) = recorder.write_decision(decision) {
+            warn!(
+                "Failed to record branching decision, disabling recording: {}",
+                err
+            );
+            state.decision_recorder = None;
+        }
+    }
+    state.depth += 1;
+    state.open_branches.push((state.subtree_lower_bound, true));
+    let interchangeable = if report.settings.symmetry_breaking
+        && !instance.has_conflicts()
+        && !instance.has_requirements()
+    {
+        reductions::collect_time_info(&mut report.runtimes.symmetry, || {
+            symmetry::find_interchangeable_nodes(instance, node)
+        })
+    } else {
+        Vec::new()
+    };
+    let node_domination_up_to_date = mem::take(&mut state.node_domination_up_to_date);
+    // The packing stays valid in both branches, which only delete nodes and
+    // edges, but not after restoring them
+    let packing_hint = state.packing_hint.take();
+    state.packing_hint.clone_from(&packing_hint);
+    // The domination tries are updated with the changes of this branch, so
+    // that they can be reused in the subproblem
+    state.domination_tries.apply(
+        ReducedItem::ForcedNode(node),
+        instance,
+        &mut state.partial_hs,
+    );
+    if let Some(search_tree) = &mut state.search_tree {
+        search_tree.set_decision(node, true);
+    }
+    let (conflicting, feasible) = state
+        .domination_tries
+        .delete_conflicting_nodes(instance, node);
+    let status_without = if feasible {
+        propagate_unit_edges_and_solve(instance, state, report)
+    } else {
+        Status::Continue
+    };
+    state.packing_hint = None;
+    for &other in conflicting.iter().rev() {
+        instance.restore_node(other);
+    }
     debug_assert_eq!(state.partial_hs.last().copied(), Some(node));
     state.partial_hs.pop();
     instance.restore_incident_edges(node);
 
-    if status_without == Status::Stop {
+    if status_without != Status::Continue {
         instance.restore_node(node);
-        return Status::Stop;
+        state.open_branches.pop();
+        state.depth -= 1;
+        return status_without;
     }
+    state.open_branches.last_mut().unwrap().1 = false;
 
-    let status_with = solve_recursive(instance, state, report);
+    if let Some(discrepancies) = &mut state.discrepancies {
+        if discrepancies.current >= discrepancies.limit {
+            let (bound, _) = *state.open_branches.last().unwrap();
+            discrepancies.skipped_lower_bound = Some(
+                discrepancies
+                    .skipped_lower_bound
+                    .map_or(bound, |skipped| skipped.min(bound)),
+            );
+            instance.restore_node(node);
+            state.open_branches.pop();
+            state.depth -= 1;
+            return Status::Continue;
+        }
+        discrepancies.current += 1;
+    }
+    state.node_domination_up_to_date = node_domination_up_to_date;
+    if let Some(search_tree) = &mut state.search_tree {
+        search_tree.set_decision(node, false);
+    }
+    // Hitting sets containing any of the interchangeable nodes are covered by
+    // the branch including the node
+    let (num_deleted, feasible) =
+        symmetry::delete_interchangeable_nodes(instance, &interchangeable);
+    report.reductions.symmetric_nodes_discarded += num_deleted;
+    state.packing_hint = packing_hint;
+    let status_with = if feasible {
+        propagate_unit_edges_and_solve(instance, state, report)
+    } else {
+        Status::Continue
+    };
+    state.packing_hint = None;
+    for &other in interchangeable[..num_deleted].iter().rev() {
+        instance.restore_node(other);
+    }
+    if let Some(discrepancies) = &mut state.discrepancies {
+        discrepancies.current -= 1;
+    }
     instance.restore_node(node);
+    state.open_branches.pop();
+    state.depth -= 1;
     status_with
 }
 
+/// Forces the nodes of edges that were reduced to a single node by the last
+/// branching decision before solving the subproblem, saving a `reduce` pass.
+fn propagate_unit_edges_and_solve(
+    instance: &mut Instance,
+    state: &mut State,
+    report: &mut Report,
+) -> Status {
+    let (propagation, feasible) = reductions::propagate_unit_edges(
+        instance,
+        &mut state.partial_hs,
+        state.minimum_hs.len(),
+        &mut state.domination_tries,
+    );
+    report.reductions.unit_propagation_vertices_found += propagation.num_forced_nodes();
+    if propagation.num_forced_nodes() > 0 {
+        // Forcing nodes deletes their edges, which node domination has not seen
+        state.node_domination_up_to_date = false;
+    }
+    let status = if feasible {
+        solve_recursive(instance, state, report)
+    } else {
+        Status::Continue
+    };
+    propagation.restore(instance, &mut state.partial_hs);
+    status
+}
+
+/// Records the current subproblem in the search tree, if one is recorded.
+/// Returns whether the node was recorded.
+fn record_search_node(instance: &Instance, state: &mut State, outcome: SearchNodeOutcome) -> bool {
+    let Some(search_tree) = &mut state.search_tree else {
+        return false;
+    };
+    search_tree.record(SearchNode {
+        // Set by the search tree
+        id: 0,
+        parent: None,
+        decision: None,
+
+        depth: state.depth,
+        partial_size: state.partial_hs.len(),
+        incumbent: state.minimum_hs.len(),
+        lower_bound: state.subtree_lower_bound,
+        remaining_nodes: instance.nodes().len(),
+        remaining_edges: instance.num_edges(),
+        outcome,
+    })
+}
+
+fn progress_event(state: &State, report: &Report) -> ProgressEvent {
+    ProgressEvent {
+        elapsed: state.solve_start_time.elapsed(),
+        branching_steps: report.branching_steps,
+        incumbent: state.minimum_hs.len(),
+        lower_bound: state.global_lower_bound,
+        depth: state.depth,
+    }
+}
+
+fn write_progress_event(state: &mut State, event: &ProgressEvent) {
+    let Some(progress) = &mut state.progress else {
+        return;
+    };
+    if let Err(err) = progress.write_event(event) {
+        warn!(
+            "Failed to write progress event, disabling progress output: {}",
+            err
+        );
+        state.progress = None;
+    }
+}
+
+/// Writes a progress event and passes it to the callback if either is due.
+fn emit_due_progress_events(state: &mut State, report: &Report, now: Instant) {
+    let progress_due = state
+        .progress
+        .as_ref()
+        .is_some_and(|progress| progress.is_due(now));
+    let callback_due = state
+        .progress_callback
+        .as_ref()
+        .is_some_and(|callback| callback.is_due(report.branching_steps));
+    if !progress_due && !callback_due {
+        return;
+    }
+
+    let event = progress_event(state, report);
+    if progress_due {
+        write_progress_event(state, &event);
+    }
+    if let (true, Some(callback)) = (callback_due, &mut state.progress_callback) {
+        callback.call(&event);
+    }
+}
+
+/// Selects the node to branch on, either from the replayed decisions or
+/// according to the branching engine.
+fn select_branching_node(instance: &Instance, state: &mut State, branching_step: usize) -> NodeIdx {
+    let replayed_node = state.replay.as_mut().and_then(|replay| {
+        replay.next_node(branching_step, |node| {
+            node.idx() < instance.num_nodes_total()
+                && !instance.is_node_deleted(node)
+                && instance.node_degree(node) > 0
+        })
+    });
+    replayed_node.unwrap_or_else(|| heuristic_branching_node(instance, state))
+}
+
+/// Selects the node to branch on using the branching engine
+fn heuristic_branching_node(instance: &Instance, state: &State) -> NodeIdx {
+    match (state.branching_engine, &state.activity) {
+        (BranchingEngine::Activity, Some(activity)) => {
+            instance
+                .nodes()
+                .iter()
+                .copied()
+                .map(|node| (activity.node_score(instance, node), node))
+                .max_by(|(score1, _), (score2, _)| score1.total_cmp(score2))
+                .expect("Branching on an empty instance")
+                .1
+        }
+        (BranchingEngine::ThreeHittingSet, _) => {
+            let edge = instance
+                .edges()
+                .iter()
+                .copied()
+                .min_by_key(|&edge| {
+                    let max_degree = instance
+                        .edge(edge)
+                        .map(|node| instance.node_degree(node))
+                        .max();
+                    // Edges needing all but the fewest of their nodes leave
+                    // the fewest choices
+                    (
+                        instance.edge_size(edge) - instance.edge_requirement(edge),
+                        Reverse(max_degree),
+                    )
+                })
+                .expect("Branching on an empty instance");
+            instance
+                .edge(edge)
+                .max_by_key(|&node| instance.node_degree(node))
+                .expect("Branching on an empty edge")
+        }
+        _ => instance
+            .nodes()
+            .iter()
+            .copied()
+            .max_by_key(|&node| instance.node_degree(node))
+            .expect("Branching on an empty instance"),
+    }
+}
+
+/// Follows the branching heuristic from the current subproblem down to a
+/// leaf, always adding the branching node to the hitting set, and keeps the
+/// hitting set found if it is smaller than the best one.
+fn dive(instance: &mut Instance, state: &mut State, report: &mut Report) {
+    let start = Instant::now();
+    report.reductions.dive_runs += 1;
+    let mut path = Vec::new();
+    loop {
+        if instance.num_edges() == 0 {
+            if state.partial_hs.len() < state.minimum_hs.len() {
+                debug!(
+                    event = "incumbent", size = state.partial_hs.len(), method = "dive";
+                    "Found HS of size {} by diving",
+                    state.partial_hs.len()
+                );
+                state.minimum_hs.clone_from(&state.partial_hs);
+                report.reductions.dive_improvements += 1;
+                state.record_improvement(report);
+            }
+            break;
+        }
+        if state.partial_hs.len() + 1 >= state.minimum_hs.len() {
+            break;
+        }
+
+        let node = heuristic_branching_node(instance, state);
+        instance.delete_node(node);
+        instance.delete_incident_edges(node);
+        state.partial_hs.push(node);
+        let (conflicting, mut feasible) = instance.delete_conflicting_nodes(node);
+        let propagation = feasible.then(|| {
+            let (propagation, propagation_feasible) = reductions::propagate_unit_edges(
+                instance,
+                &mut state.partial_hs,
+                state.minimum_hs.len(),
+                &mut state.domination_tries,
+            );
+            feasible = propagation_feasible;
+            propagation
+        });
+        path.push((node, conflicting, propagation));
+        if !feasible {
+            break;
+        }
+    }
+
+    for (node, conflicting, propagation) in path.into_iter().rev() {
+        if let Some(propagation) = propagation {
+            propagation.restore(instance, &mut state.partial_hs);
+        }
+        for &other in conflicting.iter().rev() {
+            instance.restore_node(other);
+        }
+        debug_assert_eq!(state.partial_hs.last().copied(), Some(node));
+        state.partial_hs.pop();
+        instance.restore_incident_edges(node);
+        instance.restore_node(node);
+    }
+    report.runtimes.dive += start.elapsed();
+}
+
+/// Returns the reason for stopping the search before solving the next
+/// subproblem, if any.
+fn check_stop(state: &State, report: &Report, now: Instant) -> Option<Status> {
+    if state.deadline.is_some_and(|deadline| now >= deadline) {
+        return Some(Status::TimeLimit);
+    }
+    if report
+        .settings
+        .max_branching_steps
+        .is_some_and(|max| report.branching_steps >= max)
+        || report
+            .settings
+            .max_decisions
+            .is_some_and(|max| report.decisions >= max)
+    {
+        return Some(Status::StepLimit);
+    }
+    if state
+        .cancel
+        .as_ref()
+        .is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+    {
+        return Some(Status::Cancelled);
+    }
+    // The placeholder incumbent is no hitting set, so its gap means nothing
+    if !state.has_placeholder_incumbent()
+        && report.settings.gap.is_some_and(|gap| {
+            report::relative_gap(state.minimum_hs.len(), state.global_lower_bound) <= gap
+        })
+    {
+        return Some(Status::GapReached);
+    }
+    if state
+        .restarts
+        .as_ref()
+        .is_some_and(|restarts| restarts.is_due(report.branching_steps))
+    {
+        return Some(Status::Restart);
+    }
+    None
+}
+
+#[allow(clippy::too_many_lines)]
 fn solve_recursive(instance: &mut Instance, state: &mut State, report: &mut Report) -> Status {
     let now = Instant::now();
+    state.update_global_lower_bound();
+    if let Some(status) = check_stop(state, report, now) {
+        return status;
+    }
+    if let Some(budgets) = &mut state.subtree_budgets {
+        if budgets.exceeded(state.depth, report.branching_steps) {
+            let (bound, _) = *state.open_branches.last().unwrap();
+            budgets.abort(bound);
+            return Status::Continue;
+        }
+    }
+    report.decisions += 1;
     if (now - state.last_log_time).as_secs() >= ITERATION_LOG_INTERVAL_SECS {
         info!(
             "Running on {} for {} branching steps",
@@ -64,19 +810,27 @@ fn solve_recursive(instance: &mut Instance, state: &mut State, report: &mut Repo
         );
         state.last_log_time = now;
     }
+    emit_due_progress_events(state, report, now);
 
     let (reduction_result, reduction) = reductions::reduce(instance, state, report);
+    if state.depth == 0 {
+        if let Some(preprocessing) = &mut state.preprocessing {
+            let solved = !matches!(reduction_result, ReductionResult::Finished);
+            preprocessing.finish_root(instance, solved);
+        }
+    }
     let status = match reduction_result {
         ReductionResult::Solved => {
+            record_search_node(instance, state, SearchNodeOutcome::Solved);
             if state.partial_hs.len() < state.minimum_hs.len() {
-                info!("Found HS of size {} by branching", state.partial_hs.len());
+                info!(
+                    event = "incumbent", size = state.partial_hs.len(), method = "branching";
+                    "Found HS of size {} by branching",
+                    state.partial_hs.len()
+                );
                 state.minimum_hs.clear();
                 state.minimum_hs.extend(state.partial_hs.iter().copied());
-                report.upper_bound_improvements.push(UpperBoundImprovement {
-                    new_bound: state.minimum_hs.len(),
-                    branching_steps: report.branching_steps,
-                    runtime: state.solve_start_time.elapsed(),
-                });
+                state.record_improvement(report);
             } else {
                 warn!(
                     "Found HS is not smaller than best known ({} vs. {}), should have been pruned",
@@ -85,40 +839,67 @@ fn solve_recursive(instance: &mut Instance, state: &mut State, report: &mut Repo
                 );
             }
 
-            if state.minimum_hs.len() <= report.settings.stop_at {
+            if report.settings.stop_at > 0 && state.minimum_hs.len() <= report.settings.stop_at {
                 Status::Stop
             } else {
                 Status::Continue
             }
         }
-        ReductionResult::Unsolvable => Status::Continue,
-        ReductionResult::Stop => Status::Stop,
+        ReductionResult::Unsolvable(reason) => {
+            record_search_node(instance, state, SearchNodeOutcome::Pruned(reason));
+            Status::Continue
+        }
+        ReductionResult::Stop => {
+            record_search_node(instance, state, SearchNodeOutcome::Stopped);
+            Status::Stop
+        }
+        ReductionResult::Finished
+            if state
+                .replay
+                .as_ref()
+                .is_some_and(|replay| replay.should_stop(report.branching_steps + 1)) =>
+        {
+            Status::ReplayStopped
+        }
         ReductionResult::Finished => {
-            let node = instance
-                .nodes()
-                .iter()
-                .copied()
-                .max_by_key(|&node| instance.node_degree(node))
-                .expect("Branching on an empty instance");
-            branch_on(node, instance, state, report)
+            let dive_due = report
+                .settings
+                .dive_interval
+                .is_some_and(|interval| (report.branching_steps + 1).is_multiple_of(interval));
+            if dive_due {
+                dive(instance, state, report);
+            }
+            if dive_due
+                && report.settings.stop_at > 0
+                && state.minimum_hs.len() <= report.settings.stop_at
+            {
+                record_search_node(instance, state, SearchNodeOutcome::Stopped);
+                Status::Stop
+            } else {
+                let node = select_branching_node(instance, state, report.branching_steps + 1);
+                let recorded =
+                    record_search_node(instance, state, SearchNodeOutcome::Branched(node));
+                if recorded {
+                    state.search_tree.as_mut().unwrap().enter();
+                }
+                let status = branch_on(node, instance, state, report);
+                if recorded {
+                    state.search_tree.as_mut().unwrap().leave();
+                }
+                status
+            }
         }
     };
 
     reduction.restore(instance, &mut state.partial_hs);
+    // Node domination may find something again after restoring edges, e.g.,
+    // on the root visited again by a restart
+    state.node_domination_up_to_date = false;
     status
 }
 
-fn is_hitting_set(hs: &[NodeIdx], instance: &Instance) -> bool {
-    let hs_set: IdxHashSet<_> = hs.iter().copied().collect();
-    instance
-        .edges()
-        .iter()
-        .all(|&edge| instance.edge(edge).any(|node| hs_set.contains(&node)))
-}
-
-fn get_initial_hitting_set(instance: &Instance, settings: &Settings) -> Result<Vec<NodeIdx>> {
+fn validate_initial_hitting_set(instance: &Instance, settings: &Settings) -> Result<()> {
     if let Some(initial_hs) = &settings.initial_hitting_set {
-        info!("Using initial hitting set from settings");
         debug!("Validating initial hitting set from settings");
         for &node in initial_hs {
             ensure!(
@@ -128,78 +909,1862 @@ fn get_initial_hitting_set(instance: &Instance, settings: &Settings) -> Result<V
             );
         }
         ensure!(
-            is_hitting_set(initial_hs, instance),
+            instance.is_hitting_set(initial_hs),
             "initial hitting set is not valid"
         );
+        ensure!(
+            instance.is_conflict_free(initial_hs),
+            "initial hitting set contains conflicting nodes"
+        );
+    }
 
-        Ok(initial_hs.clone())
-    } else {
-        Ok(instance.nodes().to_vec())
+    Ok(())
+}
+
+fn validate_restart_strategy(settings: &Settings) -> Result<()> {
+    match settings.restarts {
+        Some(RestartStrategy::Luby { unit }) => {
+            ensure!(unit > 0, "Luby restart unit must be positive");
+        }
+        Some(RestartStrategy::Geometric { initial, factor }) => {
+            ensure!(initial > 0, "initial restart limit must be positive");
+            // Otherwise, the search might never complete
+            ensure!(
+                factor > 1.0,
+                "geometric restart factor must be larger than 1"
+            );
+        }
+        None => {}
     }
+    Ok(())
 }
 
-fn calculate_root_bounds(instance: &Instance, settings: &Settings) -> RootBounds {
-    let num_nodes = instance.num_nodes_total();
-    let root_packing = PackingBound::new(instance, settings);
-    RootBounds {
-        max_degree: lower_bound::calc_max_degree_bound(instance).unwrap_or(num_nodes),
-        sum_degree: lower_bound::calc_sum_degree_bound(instance),
-        efficiency: lower_bound::calc_efficiency_bound(instance)
-            .0
-            .round()
-            .unwrap_or(num_nodes),
-        packing: root_packing.bound(),
-        sum_over_packing: root_packing.calc_sum_over_packing_bound(instance),
-        greedy_upper: reductions::calc_greedy_approximation(instance).len(),
+fn validate_subtree_budgets(settings: &Settings) -> Result<()> {
+    if let Some(schedule) = settings.subtree_budgets {
+        ensure!(
+            schedule.initial > 0,
+            "initial subtree budget must be positive"
+        );
+        // Otherwise, the search might never complete
+        ensure!(
+            schedule.factor > 1.0,
+            "subtree budget factor must be larger than 1"
+        );
+        ensure!(
+            !settings.limited_discrepancy_search && settings.restarts.is_none(),
+            "subtree budgets can not be combined with limited discrepancy search or restarts"
+        );
     }
+    Ok(())
 }
 
-pub fn solve(
-    mut instance: Instance,
-    file_name: String,
-    settings: Settings,
-) -> Result<(Vec<NodeIdx>, Report)> {
-    let initial_hs = get_initial_hitting_set(&instance, &settings)?;
-    let root_bounds = calculate_root_bounds(&instance, &settings);
-    let packing_from_scratch_limit = settings.packing_from_scratch_limit;
-    let mut report = Report {
-        file_name,
-        opt: initial_hs.len(),
-        branching_steps: 0,
-        settings,
-        root_bounds,
-        runtimes: RuntimeStats::default(),
-        reductions: ReductionStats::new(packing_from_scratch_limit),
-        upper_bound_improvements: Vec::new(),
+/// Explains why no hitting set as small as `stop_at` exists, using the root
+/// lower bounds if they suffice and the completed search otherwise.
+fn explain_unreachable_target(
+    report: &Report,
+    num_forced: usize,
+    minimum_proven: bool,
+) -> InfeasibilityExplanation {
+    let target = report.settings.stop_at;
+    let (bound, value) = report.root_bounds.best_named_lower_bound();
+    let witness = if num_forced + value > target {
+        LowerBoundWitness::RootBound {
+            bound: bound.to_string(),
+            value,
+        }
+    } else {
+        LowerBoundWitness::Search {
+            branching_steps: report.branching_steps,
+        }
     };
+    InfeasibilityExplanation::TargetUnreachable {
+        target,
+        minimum: report.opt,
+        minimum_proven,
+        forced: num_forced,
+        witness,
+    }
+}
 
-    let mut state = State {
-        partial_hs: Vec::new(),
-        minimum_hs: initial_hs,
-        last_log_time: Instant::now(),
-        solve_start_time: Instant::now(),
-    };
-    let status = solve_recursive(&mut instance, &mut state, &mut report);
-    report.runtimes.total = state.solve_start_time.elapsed();
-    report.opt = state.minimum_hs.len();
+fn get_initial_hitting_set(
+    instance: &Instance,
+    settings: &Settings,
+    constraints: &NodeConstraints,
+) -> Vec<NodeIdx> {
+    if let Some(initial_hs) = &settings.initial_hitting_set {
+        let hs_set: IdxHashSet<_> = initial_hs.iter().copied().collect();
+        if constraints.forced.iter().all(|node| hs_set.contains(node))
+            && !constraints
+                .forbidden
+                .iter()
+                .any(|node| hs_set.contains(node))
+        {
+            info!("Using initial hitting set from settings");
+            return initial_hs.clone();
+        }
+        warn!(
+            "Ignoring initial hitting set from settings, it violates the forced or forbidden nodes"
+        );
+    }
 
-    info!("Validating found hitting set");
-    assert_eq!(instance.num_nodes_total(), instance.nodes().len());
-    assert_eq!(instance.num_edges_total(), instance.edges().len());
-    assert!(is_hitting_set(&state.minimum_hs, &instance));
+    let mut hs = constraints.forced.clone();
+    hs.extend(instance.nodes().iter().copied());
+    hs
+}
 
-    if status == Status::Continue {
+/// Nodes that were fixed to be in or out of the hitting set before solving
+#[derive(Debug, Clone, Default)]
+struct NodeConstraints {
+    forced: Vec<NodeIdx>,
+    forbidden: Vec<NodeIdx>,
+}
+
+/// Applies the forced and forbidden nodes from the settings as permanent
+/// reductions. They must be undone using `undo_node_constraints`.
+fn apply_node_constraints(instance: &mut Instance, settings: &Settings) -> Result<NodeConstraints> {
+    let mut is_forced = vec![false; instance.num_nodes_total()];
+    let mut is_forbidden = vec![false; instance.num_nodes_total()];
+    for (nodes, forbidden, name) in [
+        (&settings.forced_nodes, false, "forced nodes"),
+        (&settings.partial_hitting_set, false, "partial hitting set"),
+        (&settings.forbidden_nodes, true, "forbidden nodes"),
+    ] {
+        for &node in nodes {
+            ensure!(
+                node.idx() < instance.num_nodes_total(),
+                "node index {} out of bounds in {}",
+                node,
+                name
+            );
+            if forbidden {
+                is_forbidden[node.idx()] = true;
+            } else {
+                is_forced[node.idx()] = true;
+            }
+        }
+    }
+    for &node in instance.nodes() {
+        ensure!(
+            !is_forced[node.idx()] || !is_forbidden[node.idx()],
+            Infeasible(InfeasibilityExplanation::ForcedAndForbidden { node })
+        );
+    }
+
+    // Nodes conflicting with forced nodes can not be part of the hitting set
+    let mut conflicting = Vec::new();
+    let mut conflicts_with = vec![None; instance.num_nodes_total()];
+    for &node in instance.nodes() {
+        if !is_forced[node.idx()] {
+            continue;
+        }
+        for &other in instance.conflicts(node) {
+            ensure!(
+                !is_forced[other.idx()],
+                Infeasible(InfeasibilityExplanation::ConflictingForcedNodes {
+                    first: node,
+                    second: other,
+                })
+            );
+            if !is_forbidden[other.idx()] {
+                is_forbidden[other.idx()] = true;
+                conflicting.push(other);
+                conflicts_with[other.idx()] = Some(node);
+            }
+        }
+    }
+    for &edge in instance.edges() {
+        let hittable = instance
+            .edge(edge)
+            .filter(|node| is_forced[node.idx()] || !is_forbidden[node.idx()])
+            .count()
+            >= instance.edge_requirement(edge);
+        ensure!(
+            hittable,
+            Infeasible(InfeasibilityExplanation::EdgeForbidden {
+                edge,
+                nodes: instance
+                    .edge(edge)
+                    .map(|node| ForbiddenNode {
+                        node,
+                        conflicts_with: conflicts_with[node.idx()],
+                    })
+                    .collect(),
+            })
+        );
+    }
+
+    let mut constraints = NodeConstraints::default();
+    for &node in settings
+        .forced_nodes
+        .iter()
+        .chain(&settings.partial_hitting_set)
+    {
+        if !mem::take(&mut is_forced[node.idx()]) {
+            continue;
+        }
+        instance.delete_node(node);
+        instance.delete_incident_edges(node);
+        constraints.forced.push(node);
+    }
+    for &node in settings.forbidden_nodes.iter().chain(&conflicting) {
+        if !mem::take(&mut is_forbidden[node.idx()]) {
+            continue;
+        }
+        instance.delete_node(node);
+        constraints.forbidden.push(node);
+    }
+    if !constraints.forced.is_empty() || !constraints.forbidden.is_empty() {
         info!(
-            "Found minimum hitting set in {:.2?} and {} branching steps",
-            report.runtimes.total, report.branching_steps
+            "Applied {} forced and {} forbidden nodes",
+            constraints.forced.len(),
+            constraints.forbidden.len()
+        );
+    }
+
+    Ok(constraints)
+}
+
+fn record_node_constraints(
+    preprocessing: &mut PreprocessingReport,
+    settings: &Settings,
+    constraints: &NodeConstraints,
+) {
+    let forced_by_setting: IdxHashSet<_> = settings.forced_nodes.iter().copied().collect();
+    for &node in &constraints.forced {
+        let rule = if forced_by_setting.contains(&node) {
+            ReductionRule::ForcedSetting
+        } else {
+            ReductionRule::PartialHittingSet
+        };
+        preprocessing.record(PreprocessingAction::ForcedNode(node), rule);
+    }
+    let forbidden_by_setting: IdxHashSet<_> = settings.forbidden_nodes.iter().copied().collect();
+    for &node in &constraints.forbidden {
+        let rule = if forbidden_by_setting.contains(&node) {
+            ReductionRule::ForbiddenSetting
+        } else {
+            ReductionRule::Conflict
+        };
+        preprocessing.record(PreprocessingAction::RemovedNode(node), rule);
+    }
+}
+
+fn undo_node_constraints(instance: &mut Instance, constraints: &NodeConstraints) {
+    for &node in constraints.forbidden.iter().rev() {
+        instance.restore_node(node);
+    }
+    for &node in constraints.forced.iter().rev() {
+        instance.restore_incident_edges(node);
+        instance.restore_node(node);
+    }
+}
+
+/// Activity branching takes precedence over the branching rule for hypergraphs
+/// with edges of size at most three, which is only used if enabled and all
+/// edges are small enough. Edges only shrink during the search.
+fn select_branching_engine(instance: &Instance, settings: &Settings) -> BranchingEngine {
+    if settings.activity_branching {
+        return BranchingEngine::Activity;
+    }
+    if !settings.three_hs_branching {
+        return BranchingEngine::MaxDegree;
+    }
+    let max_edge_size = instance.max_edge_size();
+    if max_edge_size <= 3 {
+        info!("All edges have at most 3 nodes, branching on smallest edges");
+        BranchingEngine::ThreeHittingSet
+    } else {
+        info!(
+            "Largest edge has {} nodes, using max-degree branching",
+            max_edge_size
+        );
+        BranchingEngine::MaxDegree
+    }
+}
+
+/// Computes the lower bounds on an instance that is not split into
+/// components, leaving the upper bounds unset.
+fn calculate_lower_bounds(instance: &Instance, settings: &Settings) -> RootBounds {
+    let num_nodes = instance.num_nodes_total();
+    let root_packing = PackingBound::new(instance, settings);
+    let sum_degree = lower_bound::calc_sum_degree_bound(instance);
+    let mut degrees: Vec<_> = instance
+        .nodes()
+        .iter()
+        .map(|&node| instance.node_degree(node))
+        .collect();
+    degrees.sort_unstable_by(|a, b| b.cmp(a));
+    degrees.truncate(sum_degree);
+
+    RootBounds {
+        max_degree: lower_bound::calc_max_degree_bound(instance).unwrap_or(num_nodes),
+        sum_degree,
+        efficiency: lower_bound::calc_efficiency_bound(instance)
+            .0
+            .round()
+            .unwrap_or(num_nodes),
+        packing: root_packing.bound(),
+        packing_order: settings.packing_order,
+        sum_over_packing: root_packing.calc_sum_over_packing_bound(instance),
+        flow: settings
+            .enable_flow_bound
+            .then(|| lower_bound::calc_flow_bound(instance)),
+        clique_cover: settings
+            .enable_clique_cover_bound
+            .then(|| lower_bound::calc_clique_cover_bound(instance)),
+        certificate: LowerBoundCertificate {
+            num_edges: instance.num_edges(),
+            largest_degrees: degrees,
+            packing: root_packing.packing().to_vec(),
+            components: Vec::new(),
+        },
+        ..RootBounds::default()
+    }
+}
+
+/// Computes the lower bounds of each component, with the edges of the
+/// component instances translated back to those of the whole instance.
+fn calculate_component_bounds(
+    instance: &Instance,
+    components: &[Vec<EdgeIdx>],
+    settings: &Settings,
+) -> Option<Vec<RootBounds>> {
+    components
+        .iter()
+        .map(|edges| {
+            let component = instance.restrict_to_edges(edges).ok()?;
+            let mut bounds = calculate_lower_bounds(&component, settings);
+            for edge in &mut bounds.certificate.packing {
+                *edge = edges[edge.idx()];
+            }
+            Some(bounds)
+        })
+        .collect()
+}
+
+/// Computes the lower bounds of each connected component separately and sums
+/// them up, which is at least as strong as computing them on the whole
+/// instance. The components are distributed among threads if reductions are
+/// computed in parallel on an instance of this size.
+fn calculate_summed_component_bounds(
+    instance: &Instance,
+    components: &[Vec<EdgeIdx>],
+    settings: &Settings,
+) -> Option<RootBounds> {
+    let parallel = !cfg!(target_arch = "wasm32")
+        && settings
+            .parallel_reductions_min_edges
+            .is_some_and(|min_edges| instance.num_edges() >= min_edges);
+    let num_threads = if parallel {
+        thread::available_parallelism().map_or(1, NonZeroUsize::get)
+    } else {
+        1
+    };
+    let component_bounds = if num_threads > 1 {
+        let chunk_size = components.len().div_ceil(num_threads);
+        thread::scope(|scope| {
+            let handles: Vec<_> = components
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || calculate_component_bounds(instance, chunk, settings))
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("Root bound thread panicked"))
+                .collect::<Option<Vec<_>>>()
+        })?
+        .into_iter()
+        .flatten()
+        .collect()
+    } else {
+        calculate_component_bounds(instance, components, settings)?
+    };
+
+    let mut bounds = RootBounds {
+        packing_order: settings.packing_order,
+        flow: settings.enable_flow_bound.then_some(0),
+        clique_cover: settings.enable_clique_cover_bound.then_some(0),
+        certificate: LowerBoundCertificate {
+            num_edges: instance.num_edges(),
+            ..LowerBoundCertificate::default()
+        },
+        ..RootBounds::default()
+    };
+    for (component, edges) in component_bounds.into_iter().zip(components) {
+        bounds.max_degree += component.max_degree;
+        bounds.sum_degree += component.sum_degree;
+        bounds.efficiency += component.efficiency;
+        bounds.packing += component.packing;
+        bounds.sum_over_packing += component.sum_over_packing;
+        bounds.flow = bounds.flow.zip(component.flow).map(|(a, b)| a + b);
+        bounds.clique_cover = bounds
+            .clique_cover
+            .zip(component.clique_cover)
+            .map(|(a, b)| a + b);
+        bounds
+            .certificate
+            .packing
+            .extend_from_slice(&component.certificate.packing);
+        bounds.certificate.components.push(ComponentCertificate {
+            edges: edges.clone(),
+            largest_degrees: component.certificate.largest_degrees,
+        });
+    }
+    Some(bounds)
+}
+
+fn calculate_root_bounds(instance: &Instance, settings: &Settings) -> RootBounds {
+    let components = instance.edge_components();
+    let mut bounds = if components.len() > 1 {
+        calculate_summed_component_bounds(instance, &components, settings)
+            .unwrap_or_else(|| calculate_lower_bounds(instance, settings))
+    } else {
+        calculate_lower_bounds(instance, settings)
+    };
+    bounds.components = components.len();
+    bounds.greedy_upper = reductions::calc_greedy_approximation(instance)
+        .map_or(instance.num_nodes_total(), |greedy| greedy.len());
+    bounds
+}
+
+/// Runs all configured greedy strategies and returns the smallest hitting set
+/// found if it is smaller than the given one.
+fn run_initial_greedy_strategies(
+    instance: &Instance,
+    forced: &[NodeIdx],
+    initial_hs: Vec<NodeIdx>,
+    report: &mut Report,
+    rng: &mut SolverRng,
+) -> Vec<NodeIdx> {
+    let start = Instant::now();
+    let mut best_hs: Option<Vec<NodeIdx>> = None;
+    for &strategy in &report.settings.initial_greedy_strategies {
+        let mut hs = forced.to_vec();
+        hs.extend(upper_bound::calc_greedy_approximation_with_strategy(
+            instance, strategy, rng,
+        ));
+        debug!(
+            "Greedy strategy {:?} found HS of size {}",
+            strategy,
+            hs.len()
+        );
+        if best_hs
+            .as_ref()
+            .is_none_or(|best_hs| hs.len() < best_hs.len())
+        {
+            best_hs = Some(hs);
+        }
+    }
+    report.runtimes.greedy += start.elapsed();
+
+    let Some(best_hs) = best_hs else {
+        return initial_hs;
+    };
+    report.root_bounds.greedy_strategies_upper = Some(best_hs.len());
+    if best_hs.len() < initial_hs.len() {
+        info!(
+            event = "incumbent", size = best_hs.len(), method = "greedy_strategies";
+            "Found HS of size {} using greedy strategies",
+            best_hs.len()
+        );
+        report.opt = best_hs.len();
+        report.upper_bound_improvements.push(UpperBoundImprovement {
+            new_bound: best_hs.len(),
+            branching_steps: 0,
+            runtime: Duration::ZERO,
+        });
+        best_hs
+    } else {
+        initial_hs
+    }
+}
+
+/// Runs the tabu search on the better of the given hitting set and the greedy
+/// approximation. Returns the improved hitting set if it is smaller.
+fn improve_initial_hitting_set(
+    instance: &Instance,
+    forced: &[NodeIdx],
+    initial_hs: Vec<NodeIdx>,
+    report: &mut Report,
+    rng: &mut SolverRng,
+) -> Vec<NodeIdx> {
+    let start = Instant::now();
+    let greedy = reductions::calc_greedy_approximation(instance)
+        .expect("Tabu search does not support conflicts");
+    let start_hs = if forced.len() + greedy.len() < initial_hs.len() {
+        greedy
+    } else {
+        // The search runs on the instance without the forced nodes
+        let mut alive = vec![false; instance.num_nodes_total()];
+        for &node in instance.nodes() {
+            alive[node.idx()] = true;
+        }
+        initial_hs
+            .iter()
+            .copied()
+            .filter(|node| alive[node.idx()])
+            .collect()
+    };
+    let mut improved_hs = forced.to_vec();
+    improved_hs.extend(upper_bound::improve_hitting_set_by_tabu_search(
+        instance,
+        &start_hs,
+        report.settings.upper_bound_search_iterations,
+        report.settings.upper_bound_search_tabu_tenure,
+        rng,
+    ));
+    report.runtimes.upper_bound_search = start.elapsed();
+    report.root_bounds.upper_bound_search = Some(improved_hs.len());
+
+    if improved_hs.len() < initial_hs.len() {
+        info!(
+            event = "incumbent", size = improved_hs.len(), method = "tabu_search";
+            "Found HS of size {} using tabu search in {:.2?}",
+            improved_hs.len(),
+            report.runtimes.upper_bound_search
+        );
+        report.opt = improved_hs.len();
+        report.upper_bound_improvements.push(UpperBoundImprovement {
+            new_bound: improved_hs.len(),
+            branching_steps: 0,
+            runtime: Duration::ZERO,
+        });
+        improved_hs
+    } else {
+        initial_hs
+    }
+}
+
+/// Searches for a hitting set with at most `size` nodes on the remaining
+/// instance. Nodes deleted from the instance are excluded from the search.
+/// The run and its branching steps are added to the given counters.
+fn find_hitting_set_of_size(
+    instance: &mut Instance,
+    forced: &[NodeIdx],
+    size: usize,
+    report: &Report,
+    solves: &mut usize,
+    branching_steps: &mut usize,
+) -> Option<Vec<NodeIdx>> {
+    let mut sub_report = Report {
+        report_version: REPORT_VERSION,
+        file_name: report.file_name.clone(),
+        difficulty: report.difficulty.clone(),
+        status: SolveStatus::Optimal,
+        opt: size + 1,
+        lower_bound: 0,
+        gap: 0.0,
+        branching_steps: 0,
+        decisions: 0,
+        branching_engine: report.branching_engine,
+        restarts: 0,
+        discrepancy_limit: None,
+        subtree_budgets: None,
+        adaptive_bounds: None,
+        settings: Settings {
+            stop_at: size,
+            gap: None,
+            max_branching_steps: None,
+            max_decisions: None,
+            ..report.settings.clone()
+        },
+        root_bounds: RootBounds::default(),
+        runtimes: RuntimeStats::default(),
+        reductions: ReductionStats::new(report.settings.packing_from_scratch_limit),
+        profile: InstanceProfile::default(),
+        upper_bound_improvements: Vec::new(),
+        report_stream: None,
+        node_classification: None,
+        canonical_solution: None,
+        secondary_objective: None,
+        completion: None,
+        budget_coverage: None,
+        infeasibility: None,
+        search_tree: None,
+        preprocessing: None,
+    };
+    let mut state = State {
+        partial_hs: forced.to_vec(),
+        // Placeholder that is never returned, only its size is used for pruning
+        minimum_hs: vec![NodeIdx::INVALID; size + 1],
+        budget_fallback_hs: None,
+        last_log_time: Instant::now(),
+        solve_start_time: Instant::now(),
+        node_domination_up_to_date: false,
+        domination_tries: DominationTries::default(),
+        depth: 0,
+        branching_engine: report.branching_engine,
+        progress: None,
+        progress_callback: None,
+        incumbent_callback: None,
+        report_stream: None,
+        deadline: None,
+        cancel: None,
+        subtree_lower_bound: 0,
+        global_lower_bound: 0,
+        open_branches: Vec::new(),
+        search_tree: None,
+        decision_recorder: None,
+        replay: None,
+        nogoods: None,
+        activity: None,
+        restarts: None,
+        discrepancies: None,
+        subtree_budgets: None,
+        bound_selection: None,
+        preprocessing: None,
+        custom_bounds: Arc::default(),
+        custom_reductions: Arc::default(),
+        packing_hint: None,
+        validation_edges: None,
+    };
+    solve_recursive(instance, &mut state, &mut sub_report);
+    *solves += 1;
+    *branching_steps += sub_report.branching_steps;
+
+    if state.minimum_hs.len() <= size {
+        Some(state.minimum_hs)
+    } else {
+        None
+    }
+}
+
+/// Transfers the classes of the representatives to their merged twins.
+fn classify_merged_twins(classification: &mut NodeClassification, merged_twins: &[MergedTwin]) {
+    let classes = &mut classification.classes;
+    for merged in merged_twins {
+        // A representative contained in every minimum hitting set of the
+        // merged instance can be swapped for any of its twins, so the whole
+        // group is only contained in some of them
+        let class = &mut classes[merged.representative.idx()];
+        if *class == NodeClass::Essential {
+            *class = NodeClass::Optional;
+        }
+        classes[merged.twin.idx()] = *class;
+    }
+}
+
+/// Determines for each node whether it is contained in all, some, or none of
+/// the minimum hitting sets, by re-solving with the node forced in or out.
+fn classify_nodes(
+    instance: &mut Instance,
+    forced: &[NodeIdx],
+    minimum_hs: &[NodeIdx],
+    report: &Report,
+) -> NodeClassification {
+    let mut classification = NodeClassification::default();
+    let num_nodes = instance.num_nodes_total();
+    let mut in_some = vec![false; num_nodes];
+    let mut not_in_all = vec![true; num_nodes];
+    let record_minimum_hs = |hs: &[NodeIdx], in_some: &mut [bool], not_in_all: &mut [bool]| {
+        let mut contained = vec![false; num_nodes];
+        for &node in hs {
+            contained[node.idx()] = true;
+            in_some[node.idx()] = true;
+        }
+        for (not_in_all, contained) in not_in_all.iter_mut().zip(contained) {
+            *not_in_all |= !contained;
+        }
+    };
+    for &node in minimum_hs {
+        in_some[node.idx()] = true;
+        not_in_all[node.idx()] = false;
+    }
+
+    // Forced and forbidden nodes are deleted and already classified correctly
+    let mut alive = vec![false; num_nodes];
+    for &node in instance.nodes() {
+        alive[node.idx()] = true;
+    }
+
+    let opt = minimum_hs.len();
+    for node_idx in 0..num_nodes {
+        let node = NodeIdx::from(node_idx);
+        if !alive[node.idx()] {
+            continue;
+        }
+        if !in_some[node.idx()] && opt > 0 {
+            debug!(
+                event = "classify_node", node = node.idx();
+                "Classifying node {} by forcing it into the hitting set",
+                node
+            );
+            instance.delete_node(node);
+            instance.delete_incident_edges(node);
+            let (conflicting, feasible) = instance.delete_conflicting_nodes(node);
+            if feasible {
+                if let Some(mut hs) = find_hitting_set_of_size(
+                    instance,
+                    forced,
+                    opt - 1,
+                    report,
+                    &mut classification.solves,
+                    &mut classification.branching_steps,
+                ) {
+                    hs.push(node);
+                    record_minimum_hs(&hs, &mut in_some, &mut not_in_all);
+                }
+            }
+            for &other in conflicting.iter().rev() {
+                instance.restore_node(other);
+            }
+            instance.restore_incident_edges(node);
+            instance.restore_node(node);
+        }
+
+        // Nodes of edges needing all of their nodes are in every hitting set
+        let has_unit_edge = instance
+            .node(node)
+            .any(|edge| instance.edge_size(edge) == instance.edge_requirement(edge));
+        if in_some[node.idx()] && !not_in_all[node.idx()] && !has_unit_edge {
+            debug!(
+                event = "classify_node", node = node.idx();
+                "Classifying node {} by removing it from the hitting set",
+                node
+            );
+            instance.delete_node(node);
+            if let Some(hs) = find_hitting_set_of_size(
+                instance,
+                forced,
+                opt,
+                report,
+                &mut classification.solves,
+                &mut classification.branching_steps,
+            ) {
+                record_minimum_hs(&hs, &mut in_some, &mut not_in_all);
+            }
+            instance.restore_node(node);
+        }
+    }
+
+    classification.classes = in_some
+        .into_iter()
+        .zip(not_in_all)
+        .map(|(in_some, not_in_all)| match (in_some, not_in_all) {
+            (false, _) => NodeClass::Redundant,
+            (true, false) => NodeClass::Essential,
+            (true, true) => NodeClass::Optional,
+        })
+        .collect();
+    classification
+}
+
+/// Changes made to the instance while fixing the canonical hitting set
+enum CanonicalChoice {
+    Included {
+        node: NodeIdx,
+        conflicting: Vec<NodeIdx>,
+    },
+    Excluded(NodeIdx),
+}
+
+/// Finds the lexicographically smallest minimum hitting set by deciding for
+/// each node in increasing order whether it can be added to the nodes chosen
+/// so far. The last hitting set found serves as a witness, so only nodes not
+/// contained in it require an additional solver run.
+fn find_canonical_hitting_set(
+    instance: &mut Instance,
+    forced: &[NodeIdx],
+    minimum_hs: &[NodeIdx],
+    report: &Report,
+) -> (Vec<NodeIdx>, CanonicalSolution) {
+    let mut stats = CanonicalSolution::default();
+    let num_nodes = instance.num_nodes_total();
+    let opt = minimum_hs.len();
+    let mut in_witness = vec![false; num_nodes];
+    for &node in minimum_hs {
+        in_witness[node.idx()] = true;
+    }
+
+    let mut nodes = instance.nodes().to_vec();
+    nodes.sort_unstable();
+    let mut chosen = forced.to_vec();
+    let mut choices = Vec::new();
+    for node in nodes {
+        if chosen.len() == opt {
+            break;
+        }
+        // Conflicts of nodes chosen earlier are already excluded
+        if instance.is_node_deleted(node) {
+            continue;
+        }
+        instance.delete_node(node);
+        instance.delete_incident_edges(node);
+        let (conflicting, feasible) = instance.delete_conflicting_nodes(node);
+        chosen.push(node);
+        let included = in_witness[node.idx()]
+            || feasible && {
+                debug!(
+                    event = "canonical_node", node = node.idx();
+                    "Checking whether node {} can be added to the canonical hitting set",
+                    node
+                );
+                let witness = find_hitting_set_of_size(
+                    instance,
+                    &chosen,
+                    opt,
+                    report,
+                    &mut stats.solves,
+                    &mut stats.branching_steps,
+                );
+                if let Some(witness) = &witness {
+                    in_witness.fill(false);
+                    for &node in witness {
+                        in_witness[node.idx()] = true;
+                    }
+                }
+                witness.is_some()
+            };
+        if included {
+            choices.push(CanonicalChoice::Included { node, conflicting });
+        } else {
+            chosen.pop();
+            for &other in conflicting.iter().rev() {
+                instance.restore_node(other);
+            }
+            instance.restore_incident_edges(node);
+            choices.push(CanonicalChoice::Excluded(node));
+        }
+    }
+    debug_assert_eq!(instance.num_edges(), 0);
+
+    for choice in choices.into_iter().rev() {
+        match choice {
+            CanonicalChoice::Included { node, conflicting } => {
+                for &other in conflicting.iter().rev() {
+                    instance.restore_node(other);
+                }
+                instance.restore_incident_edges(node);
+                instance.restore_node(node);
+            }
+            CanonicalChoice::Excluded(node) => instance.restore_node(node),
+        }
+    }
+    chosen.sort_unstable();
+    (chosen, stats)
+}
+
+/// Selects at most `budget` nodes (including the forced ones) hitting as many
+/// edges as possible, or the best selection found before the deadline. Must be
+/// called while the node constraints are applied.
+fn maximize_coverage_within_budget(
+    instance: &Instance,
+    forced: &[NodeIdx],
+    budget: usize,
+    deadline: Option<Instant>,
+) -> BudgetCoverage {
+    let start = Instant::now();
+    let result = coverage::maximize_coverage(instance, budget - forced.len(), deadline);
+    let mut nodes = forced.to_vec();
+    nodes.extend_from_slice(&result.nodes);
+
+    // All edges deleted at this point are hit by forced nodes. Merged
+    // duplicates are counted as the separate input edges they were.
+    let total_edges = instance.num_edges_total() + instance.num_merged_duplicate_edges();
+    let remaining_edges: usize = instance
+        .edges()
+        .iter()
+        .map(|&edge| instance.edge_multiplicity(edge))
+        .sum();
+    let hit_edges = total_edges - remaining_edges + result.num_hit;
+    info!(
+        "No hitting set within budget {}, best selection hits {} of {} edges (found in {:.2?})",
+        budget,
+        hit_edges,
+        total_edges,
+        start.elapsed()
+    );
+    if !result.complete {
+        warn!("Reached time limit while maximizing the number of hit edges within the budget");
+    }
+    BudgetCoverage {
+        nodes,
+        hit_edges,
+        total_edges,
+        optimal: result.complete,
+    }
+}
+
+fn record_completion(report: &mut Report, minimum_hs: &[NodeIdx]) {
+    let partial_hs: IdxHashSet<_> = report
+        .settings
+        .partial_hitting_set
+        .iter()
+        .copied()
+        .collect();
+    let completion: Vec<_> = minimum_hs
+        .iter()
+        .copied()
+        .filter(|node| !partial_hs.contains(node))
+        .collect();
+    info!(
+        "Completed partial hitting set of size {} with {} nodes",
+        partial_hs.len(),
+        completion.len()
+    );
+    report.completion = Some(completion);
+}
+
+/// Signature of the functions solving an instance after relabeling it
+type SolveFn =
+    fn(Instance, String, Settings, SolveHooks, &mut SolveBuffers) -> Result<(Vec<NodeIdx>, Report)>;
+
+/// Solves a relabeled instance using `solve_inner`, translating the node
+/// indices of the settings, hitting sets and report.
+fn solve_relabeled(
+    instance: Instance,
+    file_name: String,
+    settings: &Settings,
+    mut hooks: SolveHooks,
+    relabeling: &NodeRelabeling,
+    buffers: &mut SolveBuffers,
+    solve_inner: SolveFn,
+) -> Result<(Vec<NodeIdx>, Report)> {
+    let relabeled_settings = relabeling.relabel_settings(settings.clone())?;
+    if let Some(mut callback) = hooks.incumbent_callback.take() {
+        let relabeling = relabeling.clone();
+        hooks.incumbent_callback = Some(IncumbentCallback::new(move |event| {
+            let hitting_set: Vec<_> = event
+                .hitting_set
+                .iter()
+                .map(|&node| relabeling.original(node))
+                .collect();
+            callback.call(&IncumbentEvent {
+                hitting_set: &hitting_set,
+                ..*event
+            });
+        }));
+    }
+
+    let restore = |hitting_set: &mut [NodeIdx], report: &mut Report| {
+        relabeling.restore_report(hitting_set, report);
+        let restored = &mut report.settings;
+        restored.forced_nodes.clone_from(&settings.forced_nodes);
+        restored
+            .forbidden_nodes
+            .clone_from(&settings.forbidden_nodes);
+        restored
+            .partial_hitting_set
+            .clone_from(&settings.partial_hitting_set);
+        restored
+            .initial_hitting_set
+            .clone_from(&settings.initial_hitting_set);
+        restored
+            .secondary_objective
+            .clone_from(&settings.secondary_objective);
+    };
+    match solve_inner(instance, file_name, relabeled_settings, hooks, buffers) {
+        Ok((mut hitting_set, mut report)) => {
+            restore(&mut hitting_set, &mut report);
+            Ok((hitting_set, report))
+        }
+        Err(mut err) => {
+            if let Some(Infeasible(explanation)) = err.downcast_mut::<Infeasible>() {
+                relabeling.restore_explanation(explanation);
+            } else if let Some(panicked) = err.downcast_mut::<Panicked>() {
+                restore(&mut panicked.hitting_set, &mut panicked.report);
+            }
+            Err(err)
+        }
+    }
+}
+
+pub fn solve(
+    instance: Instance,
+    file_name: String,
+    settings: Settings,
+    hooks: SolveHooks,
+) -> Result<(Vec<NodeIdx>, Report)> {
+    solve_reusing(
+        instance,
+        file_name,
+        settings,
+        hooks,
+        &mut SolveBuffers::default(),
+    )
+}
+
+/// Allocations of the search kept between the solves of a `Solver`
+#[derive(Debug, Default)]
+struct SolveBuffers {
+    domination_tries: DominationTries,
+    activities: Vec<f64>,
+    open_branches: Vec<(usize, bool)>,
+}
+
+/// Solves many instances with the same settings, reusing the allocations of
+/// the domination tries, edge activities and search path between solves.
+/// Cheaper than calling `solve` repeatedly when solving many small instances.
+#[derive(Debug)]
+pub struct Solver {
+    settings: Settings,
+    buffers: SolveBuffers,
+}
+
+impl Solver {
+    pub fn new(settings: Settings) -> Self {
+        Self {
+            settings,
+            buffers: SolveBuffers::default(),
+        }
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    pub fn solve(
+        &mut self,
+        instance: Instance,
+        file_name: String,
+        hooks: SolveHooks,
+    ) -> Result<(Vec<NodeIdx>, Report)> {
+        solve_reusing(
+            instance,
+            file_name,
+            self.settings.clone(),
+            hooks,
+            &mut self.buffers,
+        )
+    }
+}
+
+/// Estimates how hard the instance is to solve with the given settings, like
+/// the `difficulty` entry of the report, but without solving it.
+pub fn estimate_difficulty(instance: &Instance, settings: &Settings) -> Result<DifficultyEstimate> {
+    let mut instance = instance.clone();
+    let settings = match instance.node_relabeling() {
+        Some(relabeling) => relabeling.relabel_settings(settings.clone())?,
+        None => settings.clone(),
+    };
+    apply_node_constraints(&mut instance, &settings)?;
+    if settings.merge_twin_nodes && !instance.has_conflicts() && !instance.has_requirements() {
+        reductions::merge_twin_nodes(&mut instance);
+    }
+    let root_bounds = calculate_root_bounds(&instance, &settings);
+    Ok(difficulty::estimate_difficulty(&instance, &root_bounds))
+}
+
+fn solve_reusing(
+    instance: Instance,
+    file_name: String,
+    settings: Settings,
+    hooks: SolveHooks,
+    buffers: &mut SolveBuffers,
+) -> Result<(Vec<NodeIdx>, Report)> {
+    match instance.node_relabeling().cloned() {
+        Some(relabeling) => solve_relabeled(
+            instance,
+            file_name,
+            &settings,
+            hooks,
+            &relabeling,
+            buffers,
+            solve_canonically,
+        ),
+        None => solve_canonically(instance, file_name, settings, hooks, buffers),
+    }
+}
+
+/// With `deterministic` set, solves a copy of the instance numbered by
+/// `canonical::canonical_numbering`, so that the search does not depend on
+/// the numbering of the input. A canonical solution is defined by the
+/// numbering, so it is found on the instance as given.
+fn solve_canonically(
+    instance: Instance,
+    file_name: String,
+    settings: Settings,
+    hooks: SolveHooks,
+    buffers: &mut SolveBuffers,
+) -> Result<(Vec<NodeIdx>, Report)> {
+    let num_nodes = instance.num_nodes_total();
+    let settings_valid = [
+        &settings.forced_nodes,
+        &settings.partial_hitting_set,
+        &settings.forbidden_nodes,
+    ]
+    .into_iter()
+    .chain(&settings.initial_hitting_set)
+    .flatten()
+    .all(|node| node.idx() < num_nodes);
+    let has_deletions =
+        instance.nodes().len() < num_nodes || instance.num_edges() < instance.num_edges_total();
+    // Invalid settings are reported by `solve_instance`
+    if !settings.deterministic || settings.canonical_solution || !settings_valid || has_deletions {
+        return solve_instance(instance, file_name, settings, hooks, buffers);
+    }
+
+    let (nodes, edges) = canonical::canonical_numbering(&instance, &settings);
+    let renumbered = instance.renumbered(&nodes, &edges);
+    drop(instance);
+    let relabeling = NodeRelabeling::permutation(nodes, edges);
+    solve_relabeled(
+        renumbered,
+        file_name,
+        &settings,
+        hooks,
+        &relabeling,
+        buffers,
+        solve_instance,
+    )
+}
+
+#[allow(clippy::too_many_lines)]
+fn solve_instance(
+    mut instance: Instance,
+    file_name: String,
+    settings: Settings,
+    hooks: SolveHooks,
+    buffers: &mut SolveBuffers,
+) -> Result<(Vec<NodeIdx>, Report)> {
+    let mut settings = settings;
+    if let Some(budget) = settings.budget {
+        // Any hitting set within the budget is optimal for the budgeted problem
+        settings.stop_at = settings.stop_at.max(budget);
+    }
+    validate_initial_hitting_set(&instance, &settings)?;
+    validate_restart_strategy(&settings)?;
+    if let GreedyMode::Every(n) = settings.greedy_mode {
+        ensure!(n > 0, "greedy mode interval must be positive");
+    }
+    ensure!(
+        settings.dive_interval != Some(0),
+        "dive interval must be positive"
+    );
+    ensure!(
+        !settings.limited_discrepancy_search || settings.restarts.is_none(),
+        "limited discrepancy search can not be combined with restarts"
+    );
+    validate_subtree_budgets(&settings)?;
+    if let Some(adaptive) = settings.adaptive_bounds {
+        ensure!(adaptive.depth_band > 0, "depth band must be positive");
+        ensure!(
+            adaptive.reprobe_interval > 0,
+            "reprobe interval must be positive"
         );
+    }
+    if let Some(gap) = settings.gap {
+        ensure!(gap.is_finite() && gap >= 0.0, "invalid gap {}", gap);
+    }
+    ensure!(
+        !settings.canonical_solution || settings.secondary_objective.is_none(),
+        "a canonical hitting set can not be combined with a secondary objective"
+    );
+    let secondary_values = settings
+        .secondary_objective
+        .as_ref()
+        .map(|objective| NodeValues::new(objective, &instance))
+        .transpose()?;
+    // Taken before any reductions are applied, so that later bugs are caught
+    let validation_edges = settings.validate_incumbents.then(|| {
+        instance
+            .edges()
+            .iter()
+            .map(|&edge| {
+                (
+                    instance.edge(edge).collect(),
+                    instance.edge_requirement(edge),
+                )
+            })
+            .collect()
+    });
+    let constraints = apply_node_constraints(&mut instance, &settings)?;
+    if let Some(budget) = settings.budget {
+        ensure!(
+            constraints.forced.len() <= budget,
+            Infeasible(InfeasibilityExplanation::BudgetBelowForced {
+                budget,
+                forced: constraints.forced.len(),
+            })
+        );
+    }
+    let merged_twins = if !settings.merge_twin_nodes {
+        Vec::new()
+    } else if instance.has_conflicts() {
+        warn!("Skipping twin node merging, it does not support conflicts");
+        Vec::new()
+    } else if instance.has_requirements() {
+        warn!("Skipping twin node merging, it does not support coverage requirements");
+        Vec::new()
     } else {
+        let merged_twins = reductions::merge_twin_nodes(&mut instance);
+        info!("Merged {} twin nodes", merged_twins.len());
+        merged_twins
+    };
+    let preprocessing = settings.record_preprocessing.then(|| {
+        let mut preprocessing = PreprocessingReport::default();
+        record_node_constraints(&mut preprocessing, &settings, &constraints);
+        for merged in &merged_twins {
+            preprocessing.record_twin(merged.twin, merged.representative);
+        }
+        preprocessing
+    });
+    let mut initial_hs = get_initial_hitting_set(&instance, &settings, &constraints);
+    let root_bounds = calculate_root_bounds(&instance, &settings);
+    let difficulty = difficulty::estimate_difficulty(&instance, &root_bounds);
+    info!(
+        "Estimated difficulty score {:.1}, recommended time limit {:.0}s",
+        difficulty.score, difficulty.recommended_time_limit
+    );
+    let branching_engine = select_branching_engine(&instance, &settings);
+    let packing_from_scratch_limit = settings.packing_from_scratch_limit;
+    let mut report = Report {
+        report_version: REPORT_VERSION,
+        file_name,
+        difficulty,
+        status: SolveStatus::Optimal,
+        opt: initial_hs.len(),
+        lower_bound: 0,
+        gap: 0.0,
+        branching_steps: 0,
+        decisions: 0,
+        branching_engine,
+        restarts: 0,
+        discrepancy_limit: None,
+        subtree_budgets: None,
+        adaptive_bounds: None,
+        settings,
+        root_bounds,
+        runtimes: RuntimeStats::default(),
+        reductions: ReductionStats::new(packing_from_scratch_limit),
+        profile: InstanceProfile::default(),
+        upper_bound_improvements: Vec::new(),
+        report_stream: None,
+        node_classification: None,
+        canonical_solution: None,
+        secondary_objective: None,
+        completion: None,
+        budget_coverage: None,
+        infeasibility: None,
+        search_tree: None,
+        preprocessing: None,
+    };
+    report.reductions.twin_nodes_merged = merged_twins.len();
+    report.reductions.duplicate_edges_merged = instance.num_merged_duplicate_edges();
+
+    let mut rng = SolverRng::seed_from_u64(report.settings.seed);
+    let heuristics_enabled = !report.settings.initial_greedy_strategies.is_empty()
+        || report.settings.upper_bound_search_iterations > 0;
+    if heuristics_enabled && instance.has_conflicts() {
+        warn!("Skipping greedy strategies and tabu search, they do not support conflicts");
+    } else if heuristics_enabled && instance.has_requirements() {
+        warn!(
+            "Skipping greedy strategies and tabu search, they do not support coverage requirements"
+        );
+    } else {
+        if !report.settings.initial_greedy_strategies.is_empty() {
+            initial_hs = run_initial_greedy_strategies(
+                &instance,
+                &constraints.forced,
+                initial_hs,
+                &mut report,
+                &mut rng,
+            );
+        }
+        if report.settings.upper_bound_search_iterations > 0 {
+            initial_hs = improve_initial_hitting_set(
+                &instance,
+                &constraints.forced,
+                initial_hs,
+                &mut report,
+                &mut rng,
+            );
+        }
+    }
+
+    // Nogoods are learned from packings hitting each edge once
+    let learn_nogoods = report.settings.nogood_limit > 0;
+    if learn_nogoods && instance.has_requirements() {
+        warn!("Skipping nogood learning, it does not support coverage requirements");
+    }
+
+    let solve_start_time = Instant::now();
+    let deadline = match report.settings.time_limit {
+        Some(secs) => {
+            let limit = Duration::try_from_secs_f64(secs)
+                .map_err(|_| anyhow!("invalid time limit {}", secs))?;
+            solve_start_time.checked_add(limit)
+        }
+        None => None,
+    };
+    let mut state = State {
+        partial_hs: constraints.forced.clone(),
+        minimum_hs: initial_hs,
+        budget_fallback_hs: None,
+        last_log_time: solve_start_time,
+        solve_start_time,
+        node_domination_up_to_date: false,
+        domination_tries: mem::take(&mut buffers.domination_tries),
+        depth: 0,
+        branching_engine: report.branching_engine,
+        progress: hooks.progress,
+        progress_callback: hooks.progress_callback,
+        incumbent_callback: hooks.incumbent_callback,
+        report_stream: hooks.report_stream,
+        deadline,
+        cancel: hooks.cancel,
+        subtree_lower_bound: 0,
+        global_lower_bound: constraints.forced.len() + report.root_bounds.best_lower_bound(),
+        open_branches: mem::take(&mut buffers.open_branches),
+        search_tree: report.settings.search_tree_max_nodes.map(SearchTree::new),
+        decision_recorder: hooks.decision_recorder,
+        replay: hooks.replay,
+        nogoods: (learn_nogoods && !instance.has_requirements()).then(|| {
+            NogoodStore::new(
+                &instance,
+                report.settings.nogood_limit,
+                report.settings.max_nogood_size,
+            )
+        }),
+        activity: report
+            .settings
+            .activity_branching
+            .then(|| EdgeActivity::reusing(&instance, mem::take(&mut buffers.activities))),
+        restarts: report.settings.restarts.map(RestartSchedule::new),
+        discrepancies: report
+            .settings
+            .limited_discrepancy_search
+            .then(DiscrepancyLimit::default),
+        subtree_budgets: report.settings.subtree_budgets.map(SubtreeBudgets::new),
+        bound_selection: report.settings.adaptive_bounds.map(BoundSelection::new),
+        preprocessing,
+        custom_bounds: Arc::new(hooks.lower_bounds),
+        custom_reductions: Arc::new(hooks.reductions),
+        packing_hint: None,
+        validation_edges,
+    };
+    state.validate_incumbent(0);
+    if let (Some(callback), Some(improvement)) = (
+        &mut state.incumbent_callback,
+        report.upper_bound_improvements.last(),
+    ) {
+        // The heuristics run before the search already improved the initial
+        // hitting set
+        callback.call(&IncumbentEvent {
+            elapsed: improvement.runtime,
+            branching_steps: 0,
+            size: state.minimum_hs.len(),
+            hitting_set: &state.minimum_hs,
+        });
+    }
+    state.push_difficulty(&report);
+    // The improvements by the heuristics run before the search were recorded
+    // before the stream was available
+    if state.report_stream.is_some() {
+        for improvement in mem::take(&mut report.upper_bound_improvements) {
+            state.push_improvement(&mut report, improvement);
+        }
+    }
+    if let Some(budget) = report.settings.budget {
+        if instance.has_requirements() {
+            warn!("Skipping budget coverage, it does not support coverage requirements");
+        } else if state.minimum_hs.len() > budget + 1 {
+            // Only hitting sets within the budget matter, so the search does
+            // not need to improve the initial one beyond proving that none fits
+            let placeholder = vec![NodeIdx::INVALID; budget + 1];
+            state.budget_fallback_hs = Some(mem::replace(&mut state.minimum_hs, placeholder));
+        }
+    }
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        search(
+            &mut instance,
+            &mut state,
+            &mut report,
+            &constraints,
+            &merged_twins,
+            secondary_values.as_ref(),
+        )
+    }));
+    // The next instance may have made as many changes as this one when the
+    // tries are used again, so they need to be rebuilt. This also keeps them
+    // usable if the search panicked.
+    buffers.domination_tries = mem::take(&mut state.domination_tries);
+    buffers.domination_tries.invalidate();
+    buffers.open_branches = mem::take(&mut state.open_branches);
+    buffers.open_branches.clear();
+    if let Some(activity) = state.activity.take() {
+        buffers.activities = activity.into_activities();
+    }
+    match result {
+        Ok(final_nodes) => Ok((final_nodes?, report)),
+        Err(payload) => Err(Panicked::new(payload.as_ref(), state, report).into()),
+    }
+}
+
+/// Solves the instance by a bitmask branch-and-bound if the tiny instance
+/// fast path is enabled and applies. Returns `None` if it does not apply,
+/// e.g., because of conflicts, coverage requirements or a search tree that has
+/// to be recorded.
+fn solve_tiny(instance: &Instance, state: &mut State, report: &mut Report) -> Option<Status> {
+    if !report.settings.tiny_instance_fast_path
+        || instance.nodes().len() > tiny::MAX_NODES
+        || instance.has_conflicts()
+        || instance.has_requirements()
+        || state.replay.is_some()
+        || state.search_tree.is_some()
+    {
+        return None;
+    }
+
+    let limit = state
+        .minimum_hs
+        .len()
+        .saturating_sub(state.partial_hs.len());
+    if let Some(nodes) = tiny::find_minimum_hitting_set(instance, limit) {
+        state.minimum_hs.clone_from(&state.partial_hs);
+        state.minimum_hs.extend(nodes);
         info!(
+            event = "incumbent", size = state.minimum_hs.len(), method = "bitmask_branch_and_bound";
+            "Found HS of size {} by bitmask branch-and-bound",
+            state.minimum_hs.len()
+        );
+        state.record_improvement(report);
+    }
+    // Unlike the search, the bitmask search always proves optimality, even if
+    // `stop_at` is reached
+    Some(Status::Continue)
+}
+
+/// Runs the search and the steps after it, returning the final hitting set.
+#[allow(clippy::too_many_lines)]
+fn search(
+    instance: &mut Instance,
+    state: &mut State,
+    report: &mut Report,
+    constraints: &NodeConstraints,
+    merged_twins: &[MergedTwin],
+    secondary_values: Option<&NodeValues>,
+) -> Result<Vec<NodeIdx>> {
+    let mut status = match solve_tiny(instance, state, report) {
+        Some(status) => status,
+        None => solve_recursive(instance, state, report),
+    };
+    loop {
+        if status == Status::Restart {
+            let restarts = state
+                .restarts
+                .as_mut()
+                .expect("Restarting without a restart schedule");
+            restarts.restart(report.branching_steps);
+            debug!(
+                event = "restart", size = state.minimum_hs.len();
+                "Restart {} after {} branching steps, best hitting set has size {}",
+                restarts.num_restarts(),
+                report.branching_steps,
+                state.minimum_hs.len()
+            );
+        } else if let Some(discrepancies) = state
+            .discrepancies
+            .as_mut()
+            .filter(|discrepancies| discrepancies.skipped_lower_bound.is_some())
+            .filter(|_| status == Status::Continue)
+        {
+            // Branches skipped in the last iteration have to be explored
+            discrepancies.limit += 1;
+            discrepancies.skipped_lower_bound = None;
+            debug!(
+                "Increasing discrepancy limit to {} after {} branching steps, best hitting set has size {}",
+                discrepancies.limit,
+                report.branching_steps,
+                state.minimum_hs.len()
+            );
+        } else if let Some(budgets) = state
+            .subtree_budgets
+            .as_mut()
+            .filter(|budgets| budgets.skipped_lower_bound.is_some())
+            .filter(|_| status == Status::Continue)
+        {
+            // Aborted subtrees have to be explored
+            budgets.next_iteration();
+            debug!(
+                "Increasing subtree budget to {} after {} branching steps, best hitting set has size {}",
+                budgets.budget(),
+                report.branching_steps,
+                state.minimum_hs.len()
+            );
+        } else {
+            break;
+        }
+        status = solve_recursive(instance, state, report);
+    }
+    report.discrepancy_limit = state
+        .discrepancies
+        .as_ref()
+        .map(|discrepancies| discrepancies.limit);
+    // The search only proves that no hitting set smaller than the placeholder
+    // exists, so the initial hitting set is not known to be minimum
+    let placeholder_size = state.restore_budget_fallback();
+    report.subtree_budgets = state.subtree_budgets.as_ref().map(SubtreeBudgets::stats);
+    report.adaptive_bounds = state.bound_selection.as_ref().map(BoundSelection::stats);
+    report.restarts = state
+        .restarts
+        .as_ref()
+        .map_or(0, RestartSchedule::num_restarts);
+    report.runtimes.total = state.solve_start_time.elapsed();
+    report.profile = profile::calc_profile(report);
+    report.search_tree = state.search_tree.take();
+    report.preprocessing = state.preprocessing.take();
+    if let Some(recorder) = &mut state.decision_recorder {
+        if let Err(err) = recorder.flush() {
+            warn!("Failed to flush recorded branching decisions: {}", err);
+        }
+    }
+    state.finish_report_stream(report);
+    if !instance.is_conflict_free(&state.minimum_hs) {
+        // The initial hitting set of all nodes is only kept if no hitting set
+        // satisfies the conflicts, as any such hitting set is smaller
+        ensure!(
+            matches!(
+                status,
+                Status::TimeLimit
+                    | Status::StepLimit
+                    | Status::GapReached
+                    | Status::ReplayStopped
+                    | Status::Cancelled
+            ),
+            Infeasible(InfeasibilityExplanation::ConflictsUnsatisfiable)
+        );
+        bail!("search stopped before finding a hitting set satisfying the conflict constraints");
+    }
+    report.status = match status {
+        Status::Continue => SolveStatus::Optimal,
+        Status::Stop => SolveStatus::StoppedAt,
+        Status::TimeLimit => SolveStatus::TimeLimit,
+        Status::StepLimit => SolveStatus::StepLimit,
+        Status::GapReached => SolveStatus::GapReached,
+        Status::ReplayStopped => SolveStatus::ReplayStopped,
+        Status::Cancelled => SolveStatus::Cancelled,
+        Status::Restart => unreachable!("Restarts are handled above"),
+    };
+    report.opt = state.minimum_hs.len();
+    if status == Status::Continue {
+        state.global_lower_bound =
+            placeholder_size.map_or(report.opt, |size| state.global_lower_bound.max(size));
+    }
+    report.lower_bound = state.global_lower_bound;
+    report.gap = report::relative_gap(report.opt, report.lower_bound);
+    let event = progress_event(state, report);
+    write_progress_event(state, &event);
+    if let Some(callback) = &mut state.progress_callback {
+        callback.call(&event);
+    }
+
+    let message = match status {
+        Status::Continue => format!(
+            "Found minimum hitting set in {:.2?} and {} branching steps",
+            report.runtimes.total, report.branching_steps
+        ),
+        Status::Stop => format!(
             "Found hitting set <= {} in {:.2?} and {} branching steps",
             report.settings.stop_at, report.runtimes.total, report.branching_steps
+        ),
+        Status::TimeLimit => format!(
+            "Reached time limit after {:.2?} and {} branching steps, best hitting set has size {} (lower bound {})",
+            report.runtimes.total, report.branching_steps, report.opt, report.lower_bound
+        ),
+        Status::StepLimit => format!(
+            "Reached step limit after {} branching steps and {} decisions, best hitting set has size {} (lower bound {})",
+            report.branching_steps, report.decisions, report.opt, report.lower_bound
+        ),
+        Status::GapReached => format!(
+            "Reached gap of {:.2}% after {:.2?} and {} branching steps, best hitting set has size {}",
+            report.gap, report.runtimes.total, report.branching_steps, report.opt
+        ),
+        Status::ReplayStopped => format!(
+            "Stopped replay after {:.2?} and {} branching steps, best hitting set has size {}",
+            report.runtimes.total, report.branching_steps, report.opt
+        ),
+        Status::Cancelled => format!(
+            "Cancelled after {:.2?} and {} branching steps, best hitting set has size {}",
+            report.runtimes.total, report.branching_steps, report.opt
+        ),
+        Status::Restart => unreachable!("Restarts are handled above"),
+    };
+    info!(
+        event = "finished",
+        status:? = report.status,
+        size = report.opt,
+        lower_bound = report.lower_bound,
+        branching_steps = report.branching_steps;
+        "{}",
+        message
+    );
+    // A `stop_at` of zero is the default and means that no size was requested
+    if status == Status::Continue
+        && report.settings.stop_at > 0
+        && report.opt > report.settings.stop_at
+    {
+        let explanation = explain_unreachable_target(
+            report,
+            constraints.forced.len(),
+            placeholder_size.is_none(),
         );
+        info!("Requested size is unreachable: {}", explanation);
+        report.infeasibility = Some(explanation);
     }
     debug!("Final HS (size {}): {:?}", report.opt, &state.minimum_hs);
 
-    Ok((state.minimum_hs, report))
+    if !report.settings.partial_hitting_set.is_empty() {
+        record_completion(report, &state.minimum_hs);
+    }
+
+    if report.settings.classify_nodes {
+        if status == Status::Continue && placeholder_size.is_none() {
+            let start = Instant::now();
+            let mut classification =
+                classify_nodes(instance, &constraints.forced, &state.minimum_hs, report);
+            classify_merged_twins(&mut classification, merged_twins);
+            info!(
+                "Classified nodes using {} additional solver runs in {:.2?}",
+                classification.solves,
+                start.elapsed()
+            );
+            report.node_classification = Some(classification);
+            report.runtimes.node_classification = start.elapsed();
+        } else {
+            warn!("Skipping node classification since the hitting set is not known to be minimum");
+        }
+    }
+
+    // After a time limit, the coverage search only completes its first greedy
+    // selection
+    let budget_coverage = report
+        .settings
+        .budget
+        .filter(|&budget| report.opt > budget && status != Status::Cancelled)
+        .filter(|_| !instance.has_requirements())
+        .map(|budget| {
+            maximize_coverage_within_budget(instance, &constraints.forced, budget, state.deadline)
+        });
+
+    for merged in merged_twins.iter().rev() {
+        instance.restore_node(merged.twin);
+    }
+    if report.settings.canonical_solution {
+        if status == Status::Continue && budget_coverage.is_none() {
+            let start = Instant::now();
+            let (canonical_hs, canonical) = find_canonical_hitting_set(
+                instance,
+                &constraints.forced,
+                &state.minimum_hs,
+                report,
+            );
+            info!(
+                "Found canonical hitting set using {} additional solver runs in {:.2?}",
+                canonical.solves,
+                start.elapsed()
+            );
+            state.minimum_hs = canonical_hs;
+            report.canonical_solution = Some(canonical);
+            report.runtimes.canonical_solution = start.elapsed();
+        } else {
+            warn!(
+                "Skipping canonical hitting set since the hitting set is not known to be minimum"
+            );
+        }
+    }
+    if let Some(values) = secondary_values {
+        if status == Status::Continue && budget_coverage.is_none() {
+            let start = Instant::now();
+            let initial_value = values.sum(&state.minimum_hs);
+            let free_nodes: Vec<_> = state
+                .minimum_hs
+                .iter()
+                .copied()
+                .filter(|&node| !instance.is_node_deleted(node))
+                .collect();
+            let result = secondary::optimize(
+                instance,
+                values,
+                free_nodes.len(),
+                &free_nodes,
+                state.deadline,
+            );
+            state.minimum_hs.clone_from(&constraints.forced);
+            state.minimum_hs.extend(result.hitting_set);
+            let value = values.sum(&state.minimum_hs);
+            info!(
+                "Optimized secondary objective from {} to {} in {:.2?} and {} branching steps",
+                initial_value,
+                value,
+                start.elapsed(),
+                result.branching_steps
+            );
+            if !result.complete {
+                warn!("Reached time limit while optimizing the secondary objective");
+            }
+            report.secondary_objective = Some(SecondaryObjectiveResult {
+                initial_value,
+                value,
+                optimal: result.complete,
+                branching_steps: result.branching_steps,
+            });
+            report.runtimes.secondary_objective = start.elapsed();
+        } else {
+            warn!("Skipping secondary objective since the hitting set is not known to be minimum");
+        }
+    }
+    undo_node_constraints(instance, constraints);
+    info!("Validating found hitting set");
+    assert_eq!(instance.num_nodes_total(), instance.nodes().len());
+    assert_eq!(instance.num_edges_total(), instance.edges().len());
+    assert!(instance.is_hitting_set(&state.minimum_hs));
+
+    let final_nodes = if let Some(budget_coverage) = budget_coverage {
+        if budget_coverage.hit_edges < budget_coverage.total_edges {
+            // The selection is not a hitting set, so whatever stopped the
+            // search, the run did not find what was asked for
+            report.status = SolveStatus::BudgetExceeded;
+        } else if !budget_coverage.optimal && report.status == SolveStatus::Optimal {
+            report.status = SolveStatus::TimeLimit;
+        }
+        let nodes = budget_coverage.nodes.clone();
+        report.budget_coverage = Some(budget_coverage);
+        nodes
+    } else {
+        mem::take(&mut state.minimum_hs)
+    };
+
+    if report.settings.deterministic {
+        report.clear_runtimes();
+    }
+
+    Ok(final_nodes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{solve, SolveHooks};
+    use crate::{
+        instance::{Instance, LoadOptions},
+        report::{RestartStrategy, Settings, SolveStatus, SubtreeBudgetSchedule},
+        solution::SolutionMetadata,
+        summary::SolveSummary,
+        test_utils::{load_text, BRANCHING_HYPERGRAPH},
+    };
+    use std::{io::Cursor, path::Path};
+
+    /// Remaining nodes of each recorded root of the search tree, which is
+    /// visited again by restarts and by repeated iterations of the search
+    fn root_sizes(settings: Settings) -> Vec<usize> {
+        let instance = load_text(BRANCHING_HYPERGRAPH);
+        let (_, report) = solve(instance, String::new(), settings, SolveHooks::default()).unwrap();
+        report
+            .search_tree
+            .unwrap()
+            .nodes
+            .iter()
+            .filter(|node| node.parent.is_none())
+            .map(|node| node.remaining_nodes)
+            .collect()
+    }
+
+    #[test]
+    fn node_domination_reruns_at_root_after_restart() {
+        let mut settings: Settings =
+            serde_json::from_str(include_str!("../settings.json")).unwrap();
+        // Without bounds, the reduced root does not depend on the incumbent
+        settings.enable_max_degree_bound = false;
+        settings.enable_efficiency_bound = false;
+        settings.enable_packing_bound = false;
+        settings.enable_sum_over_packing_bound = false;
+        settings.packing_from_scratch_limit = 0;
+        settings.tiny_instance_fast_path = false;
+        settings.search_tree_max_nodes = Some(1000);
+
+        let restarts = Settings {
+            restarts: Some(RestartStrategy::Luby { unit: 1 }),
+            ..settings.clone()
+        };
+        let discrepancies = Settings {
+            limited_discrepancy_search: true,
+            ..settings.clone()
+        };
+        let budgets = Settings {
+            subtree_budgets: Some(SubtreeBudgetSchedule {
+                depth: 0,
+                initial: 1,
+                factor: 2.0,
+            }),
+            ..settings
+        };
+        for settings in [restarts, discrepancies, budgets] {
+            let roots = root_sizes(settings);
+            assert!(roots.len() > 1);
+            assert!(roots.iter().all(|&size| size == roots[0]), "{:?}", roots);
+        }
+    }
+
+    #[test]
+    fn budget_only_proves_that_no_hitting_set_fits() {
+        let mut settings: Settings =
+            serde_json::from_str(include_str!("../settings.json")).unwrap();
+        settings.tiny_instance_fast_path = false;
+        let solve_with = |budget| {
+            let settings = Settings {
+                budget,
+                ..settings.clone()
+            };
+            let instance = load_text(BRANCHING_HYPERGRAPH);
+            solve(instance, String::new(), settings, SolveHooks::default()).unwrap()
+        };
+
+        let (minimum_hs, _) = solve_with(None);
+        let budget = minimum_hs.len() - 1;
+        let (selection, report) = solve_with(Some(budget));
+        assert!(selection.len() <= budget);
+        assert!(report.budget_coverage.as_ref().unwrap().optimal);
+        assert!(report.lower_bound > budget);
+
+        // The selection misses some edges, so neither the summary nor the
+        // solution metadata may present it as a minimum hitting set
+        assert_eq!(report.status, SolveStatus::BudgetExceeded);
+        let metadata = SolutionMetadata::new(&report).unwrap();
+        assert_eq!(metadata.size, selection.len());
+        assert!(!metadata.optimal);
+        let summary = SolveSummary::new(Path::new("budget"), &Ok(report));
+        assert_eq!(summary.status, "budget_exceeded");
+        assert_eq!(summary.exit_code, 16);
+        assert_eq!(summary.size, Some(selection.len()));
+    }
+
+    #[test]
+    fn coverage_requirements_are_solved_without_expanding_edges() {
+        let mut settings: Settings =
+            serde_json::from_str(include_str!("../settings.json")).unwrap();
+        settings.validate_incumbents = true;
+        let solve_json = |json: serde_json::Value| {
+            let text = json.to_string();
+            let instance =
+                Instance::load_from_json(Cursor::new(&text), LoadOptions::default()).unwrap();
+            let (hs, _) = solve(
+                instance,
+                String::new(),
+                settings.clone(),
+                SolveHooks::default(),
+            )
+            .unwrap();
+            let instance =
+                Instance::load_from_json(Cursor::new(&text), LoadOptions::default()).unwrap();
+            assert!(instance.is_hitting_set(&hs));
+            hs.len()
+        };
+
+        // Expanding the first edge would take all of its subsets of 21 nodes
+        let all_nodes: Vec<_> = (0..30).collect();
+        let large_edge = serde_json::json!({
+            "num_nodes": 30,
+            "edges": [all_nodes, [0, 1], [2, 3, 4], [4, 5, 6]],
+            "requirements": [10, 2, 2, 1],
+        });
+        assert_eq!(solve_json(large_edge), 10);
+
+        let triangle = serde_json::json!({
+            "num_nodes": 6,
+            "edges": [[0, 1, 2], [2, 3, 4], [4, 5, 0]],
+            "requirements": [2, 2, 2],
+        });
+        assert_eq!(solve_json(triangle), 3);
+    }
 }
@@ -0,0 +1,145 @@
+use crate::{
+    report::{InfeasibilityExplanation, Report, SolveStatus},
+    solve::Infeasible,
+};
+use anyhow::Result;
+use serde::Serialize;
+use std::{fmt, path::Path};
+
+/// Context attached to errors caused by invalid input files
+#[derive(Debug)]
+pub struct InputError(pub String);
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Outcome of solving a hypergraph, determining the process exit code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolveOutcome {
+    Optimal,
+    StoppedAt,
+    TimeLimit,
+    StepLimit,
+    GapReached,
+    ReplayStopped,
+    Cancelled,
+    BudgetExceeded,
+    Error,
+    InvalidInput,
+    Infeasible,
+}
+
+impl SolveOutcome {
+    fn from_status(status: SolveStatus) -> Self {
+        match status {
+            SolveStatus::Optimal => Self::Optimal,
+            SolveStatus::StoppedAt => Self::StoppedAt,
+            SolveStatus::TimeLimit => Self::TimeLimit,
+            SolveStatus::StepLimit => Self::StepLimit,
+            SolveStatus::GapReached => Self::GapReached,
+            SolveStatus::ReplayStopped => Self::ReplayStopped,
+            SolveStatus::Cancelled => Self::Cancelled,
+            SolveStatus::Panicked => Self::Error,
+            SolveStatus::BudgetExceeded => Self::BudgetExceeded,
+        }
+    }
+
+    fn from_error(err: &anyhow::Error) -> Self {
+        if err.downcast_ref::<InputError>().is_some() {
+            Self::InvalidInput
+        } else if err.downcast_ref::<Infeasible>().is_some() {
+            Self::Infeasible
+        } else {
+            Self::Error
+        }
+    }
+
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Self::Optimal => 0,
+            Self::Error => 1,
+            Self::InvalidInput => 2,
+            Self::Infeasible => 3,
+            Self::StoppedAt => 10,
+            Self::TimeLimit => 11,
+            Self::ReplayStopped => 12,
+            Self::Cancelled => 13,
+            Self::GapReached => 14,
+            Self::StepLimit => 15,
+            Self::BudgetExceeded => 16,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Optimal => "optimal",
+            Self::StoppedAt => "stopped_at",
+            Self::TimeLimit => "time_limit",
+            Self::ReplayStopped => "replay_stopped",
+            Self::Cancelled => "cancelled",
+            Self::GapReached => "gap_reached",
+            Self::StepLimit => "step_limit",
+            Self::BudgetExceeded => "budget_exceeded",
+            Self::Error => "error",
+            Self::InvalidInput => "invalid_input",
+            Self::Infeasible => "infeasible",
+        }
+    }
+}
+
+/// Machine-readable summary of solving a single hypergraph
+#[derive(Debug, Clone, Serialize)]
+pub struct SolveSummary {
+    pub hypergraph: String,
+    pub status: &'static str,
+    pub exit_code: i32,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<usize>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branching_steps: Option<usize>,
+
+    /// Solving time in seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runtime: Option<f64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+
+    /// Why the constraints or the requested size can not be satisfied
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub infeasibility: Option<InfeasibilityExplanation>,
+}
+
+impl SolveSummary {
+    pub fn new(hypergraph: &Path, result: &Result<Report>) -> Self {
+        let outcome = match result {
+            Ok(report) => SolveOutcome::from_status(report.status),
+            Err(err) => SolveOutcome::from_error(err),
+        };
+        let report = result.as_ref().ok();
+        Self {
+            hypergraph: hypergraph.display().to_string(),
+            status: outcome.name(),
+            exit_code: outcome.exit_code(),
+            size: report.map(Report::solution_size),
+            branching_steps: report.map(|report| report.branching_steps),
+            runtime: report.map(|report| report.runtimes.total.as_secs_f64()),
+            error: result.as_ref().err().map(|err| format!("{:#}", err)),
+            infeasibility: match result {
+                Ok(report) => report.infeasibility.clone(),
+                Err(err) => err
+                    .downcast_ref::<Infeasible>()
+                    .map(|infeasible| infeasible.0.clone()),
+            },
+        }
+    }
+
+    pub fn is_optimal(&self) -> bool {
+        self.exit_code == SolveOutcome::Optimal.exit_code()
+    }
+}
@@ -0,0 +1,70 @@
+use crate::instance::{Instance, NodeIdx};
+
+/// Edges incident to `node` but not to `other`, each with `node` removed,
+/// sorted so that they can be compared as a multiset.
+fn edges_without(instance: &Instance, node: NodeIdx, other: NodeIdx) -> Vec<Vec<NodeIdx>> {
+    let mut edges: Vec<Vec<NodeIdx>> = instance
+        .node(node)
+        .filter_map(|edge| {
+            let mut nodes = Vec::with_capacity(instance.edge_size(edge) - 1);
+            for edge_node in instance.edge(edge) {
+                if edge_node == other {
+                    return None;
+                }
+                if edge_node != node {
+                    nodes.push(edge_node);
+                }
+            }
+            Some(nodes)
+        })
+        .collect();
+    edges.sort_unstable();
+    edges
+}
+
+/// Finds all nodes `other` for which swapping `node` and `other` maps the
+/// edges of the instance onto themselves.
+///
+/// This is the case if replacing `node` by `other` in the edges containing
+/// only `node` yields exactly the edges containing only `other`. For any
+/// hitting set containing `other` but not `node`, swapping both yields a
+/// hitting set of the same size containing `node`. Hence, after all hitting
+/// sets containing `node` were considered, all of these nodes can be
+/// discarded together with `node`.
+pub fn find_interchangeable_nodes(instance: &Instance, node: NodeIdx) -> Vec<NodeIdx> {
+    let degree = instance.node_degree(node);
+    let incidence_size = |node| -> usize {
+        instance
+            .node(node)
+            .map(|edge| instance.edge_size(edge))
+            .sum()
+    };
+    let node_incidence_size = incidence_size(node);
+    instance
+        .nodes()
+        .iter()
+        .copied()
+        .filter(|&other| {
+            other != node
+                && instance.node_degree(other) == degree
+                && incidence_size(other) == node_incidence_size
+                && edges_without(instance, node, other) == edges_without(instance, other, node)
+        })
+        .collect()
+}
+
+/// Deletes the given nodes, returning `false` if some edge has no nodes left
+/// afterwards. Nodes are deleted up to the first one emptying an edge, and the
+/// number of deleted nodes is returned as well.
+pub fn delete_interchangeable_nodes(instance: &mut Instance, nodes: &[NodeIdx]) -> (usize, bool) {
+    for (idx, &node) in nodes.iter().enumerate() {
+        instance.delete_node(node);
+        if instance
+            .node(node)
+            .any(|edge| instance.edge_size(edge) == 0)
+        {
+            return (idx + 1, false);
+        }
+    }
+    (nodes.len(), true)
+}
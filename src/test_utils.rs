@@ -0,0 +1,28 @@
+//! Helpers shared by the unit tests of several modules.
+
+use crate::instance::{Instance, LoadOptions};
+use anyhow::Result;
+use std::io::Cursor;
+
+/// Hypergraph on which the solver branches, and node domination finds nodes at
+/// the root
+pub const BRANCHING_HYPERGRAPH: &str =
+    "13 14\n4 7 8 10 12\n4 0 4 5 9\n3 2 6 8\n3 2 5 6\n3 0 3 10\n2 2 7\n3 4 6 10\n\
+     3 7 9 10\n3 8 11 12\n3 9 11 12\n2 2 9\n3 0 8 10\n3 5 7 12\n2 3 8\n";
+
+/// Loads a hypergraph in the text format. Duplicate edges are kept, so that
+/// the edge indices match the lines of the text.
+pub fn load_text(text: &str) -> Instance {
+    let options = LoadOptions {
+        keep_duplicate_edges: true,
+        ..LoadOptions::default()
+    };
+    Instance::load_from_text(Cursor::new(text), options).unwrap()
+}
+
+/// Returns everything `write` writes as a string.
+pub fn write_to_string(write: impl FnOnce(&mut Vec<u8>) -> Result<()>) -> String {
+    let mut output = Vec::new();
+    write(&mut output).unwrap();
+    String::from_utf8(output).unwrap()
+}
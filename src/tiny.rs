@@ -0,0 +1,104 @@
+//! Fast path for tiny instances. Nodes and edges are represented as bitmasks,
+//! and a small branch-and-bound search over these replaces the reductions,
+//! bounds and bookkeeping of the regular search.
+use crate::{
+    instance::{Instance, NodeIdx},
+    small_indices::SmallIdx,
+};
+
+/// Largest number of nodes for which the fast path is used
+pub const MAX_NODES: usize = 20;
+
+#[derive(Debug)]
+struct BitmaskSearch {
+    edges: Vec<u32>,
+    best: Option<u32>,
+    best_size: usize,
+}
+
+impl BitmaskSearch {
+    /// Extends the chosen nodes to a hitting set smaller than the best one,
+    /// never choosing excluded nodes.
+    fn branch(&mut self, chosen: u32, excluded: u32, size: usize) {
+        // Branch on the edge with the fewest choosable nodes, and bound using
+        // a greedy packing of disjoint edges
+        let mut branching_edge: Option<u32> = None;
+        let mut packed = 0;
+        let mut bound = size;
+        for &edge in &self.edges {
+            if edge & chosen != 0 {
+                continue;
+            }
+            let choosable = edge & !excluded;
+            if choosable == 0 {
+                return;
+            }
+            if branching_edge.is_none_or(|best| choosable.count_ones() < best.count_ones()) {
+                branching_edge = Some(choosable);
+            }
+            if choosable & packed == 0 {
+                packed |= choosable;
+                bound += 1;
+            }
+        }
+
+        let Some(mut remaining) = branching_edge else {
+            self.best = Some(chosen);
+            self.best_size = size;
+            return;
+        };
+        if bound >= self.best_size {
+            return;
+        }
+        // Nodes of the edge tried in earlier branches are excluded in later
+        // ones, which then cover hitting sets without them
+        let mut excluded = excluded;
+        while remaining != 0 {
+            let node = remaining & remaining.wrapping_neg();
+            remaining ^= node;
+            self.branch(chosen | node, excluded, size + 1);
+            excluded |= node;
+        }
+    }
+}
+
+/// Finds a minimum hitting set of the alive nodes and edges if it has fewer
+/// than `limit` nodes.
+pub fn find_minimum_hitting_set(instance: &Instance, limit: usize) -> Option<Vec<NodeIdx>> {
+    let nodes = instance.nodes();
+    assert!(
+        nodes.len() <= MAX_NODES,
+        "Instance is too large for the tiny fast path"
+    );
+    let mut bits = vec![0_u32; instance.num_nodes_total()];
+    for (pos, node) in nodes.iter().enumerate() {
+        bits[node.idx()] = 1 << pos;
+    }
+    let mut edges: Vec<u32> = instance
+        .edges()
+        .iter()
+        .map(|&edge| {
+            instance
+                .edge(edge)
+                .fold(0, |mask, node| mask | bits[node.idx()])
+        })
+        .collect();
+    // Packing small edges first gives better bounds
+    edges.sort_unstable_by_key(|&mask| (mask.count_ones(), mask));
+    edges.dedup();
+
+    let mut search = BitmaskSearch {
+        edges,
+        best: None,
+        best_size: limit,
+    };
+    search.branch(0, 0, 0);
+    let best = search.best?;
+    let hs = nodes
+        .iter()
+        .enumerate()
+        .filter(|&(pos, _)| best >> pos & 1 == 1)
+        .map(|(_, &node)| node)
+        .collect();
+    Some(hs)
+}
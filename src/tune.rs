@@ -0,0 +1,159 @@
+use crate::{
+    batch,
+    report::{ConfigurationResult, Settings, TuneReport},
+    solve::SolverRng,
+    summary::SolveSummary,
+};
+use anyhow::{anyhow, ensure, Context, Result};
+use log::info;
+use rand::{seq::index, SeedableRng};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub enum SearchStrategy {
+    /// Evaluate every combination of parameter values
+    #[default]
+    Grid,
+
+    /// Evaluate the given number of distinct combinations, chosen uniformly at
+    /// random
+    Random { samples: usize },
+}
+
+/// Describes which settings to tune and how
+#[derive(Debug, Clone, Deserialize)]
+pub struct TuneSpec {
+    /// Values to try for each tuned setting
+    pub parameters: Map<String, Value>,
+
+    #[serde(default)]
+    pub search: SearchStrategy,
+
+    /// Time limit in seconds for each solver run
+    #[serde(default)]
+    pub time_limit: Option<f64>,
+
+    /// Seed for choosing the configurations of the random search
+    #[serde(default)]
+    pub seed: u64,
+}
+
+/// A combination of parameter values together with the resulting settings
+type Configuration = (Map<String, Value>, Settings);
+
+fn expand_configurations(base: &Settings, spec: &TuneSpec) -> Result<Vec<Configuration>> {
+    let base_value = serde_json::to_value(base)?;
+    let Value::Object(base_map) = base_value else {
+        unreachable!("Settings are not serialized as an object");
+    };
+
+    let mut parameters = Vec::with_capacity(spec.parameters.len());
+    for (name, values) in &spec.parameters {
+        ensure!(base_map.contains_key(name), "unknown setting {}", name);
+        let values = values
+            .as_array()
+            .filter(|values| !values.is_empty())
+            .ok_or_else(|| anyhow!("values of {} must be a non-empty array", name))?;
+        parameters.push((name, values));
+    }
+
+    let num_combinations = parameters
+        .iter()
+        .try_fold(1_usize, |product, (_, values)| {
+            product.checked_mul(values.len())
+        })
+        .ok_or_else(|| anyhow!("too many combinations of parameter values"))?;
+    let combinations: Vec<usize> = match spec.search {
+        SearchStrategy::Grid => (0..num_combinations).collect(),
+        SearchStrategy::Random { samples } => {
+            let mut rng = SolverRng::seed_from_u64(spec.seed);
+            let mut combinations =
+                index::sample(&mut rng, num_combinations, samples.min(num_combinations)).into_vec();
+            combinations.sort_unstable();
+            combinations
+        }
+    };
+
+    combinations
+        .into_iter()
+        .map(|mut combination| {
+            let mut chosen = Map::new();
+            let mut settings_map = base_map.clone();
+            for &(name, values) in &parameters {
+                let value = &values[combination % values.len()];
+                combination /= values.len();
+                chosen.insert(name.clone(), value.clone());
+                settings_map.insert(name.clone(), value.clone());
+            }
+            let settings =
+                serde_json::from_value(Value::Object(settings_map)).with_context(|| {
+                    format!("invalid parameter values {}", Value::from(chosen.clone()))
+                })?;
+            Ok((chosen, settings))
+        })
+        .collect()
+}
+
+/// Solves all hypergraphs with each configuration described by the spec and
+/// ranks the configurations by the number of hypergraphs solved optimally,
+/// breaking ties by the total runtime.
+pub fn run_tuning(
+    instances: &[PathBuf],
+    base: &Settings,
+    spec: &TuneSpec,
+    jobs: usize,
+    solve_instance: impl Fn(&Path, Settings) -> SolveSummary + Sync,
+) -> Result<TuneReport> {
+    let mut base = base.clone();
+    if spec.time_limit.is_some() {
+        base.time_limit = spec.time_limit;
+    }
+    let configurations = expand_configurations(&base, spec)?;
+    info!(
+        "Tuning {} configurations on {} hypergraphs",
+        configurations.len(),
+        instances.len()
+    );
+
+    let mut results = Vec::with_capacity(configurations.len());
+    for (idx, (parameters, settings)) in configurations.iter().enumerate() {
+        let summaries = batch::run_batch(instances, jobs, |hypergraph| {
+            solve_instance(hypergraph, settings.clone())
+        });
+        let result = ConfigurationResult {
+            parameters: parameters.clone(),
+            solved: summaries
+                .iter()
+                .filter(|summary| summary.is_optimal())
+                .count(),
+            failed: summaries
+                .iter()
+                .filter(|summary| summary.size.is_none())
+                .count(),
+            total_runtime: summaries.iter().filter_map(|summary| summary.runtime).sum(),
+        };
+        info!(
+            "Configuration {}/{} {}: solved {}, total runtime {:.2}s",
+            idx + 1,
+            configurations.len(),
+            Value::from(parameters.clone()),
+            result.solved,
+            result.total_runtime
+        );
+        results.push((result, settings));
+    }
+
+    results.sort_by(|(a, _), (b, _)| {
+        b.solved
+            .cmp(&a.solved)
+            .then(a.total_runtime.total_cmp(&b.total_runtime))
+    });
+    let best_settings = results.first().map(|&(_, settings)| settings.clone());
+    Ok(TuneReport {
+        num_hypergraphs: instances.len(),
+        configurations: results.into_iter().map(|(result, _)| result).collect(),
+        best_settings,
+    })
+}
@@ -0,0 +1,381 @@
+use crate::{
+    coarsening,
+    instance::{EdgeIdx, Instance, NodeIdx},
+    reductions,
+    report::GreedyStrategy,
+    small_indices::SmallIdx,
+};
+use log::trace;
+use rand::Rng;
+use std::collections::BinaryHeap;
+
+/// Current solution of the tabu search: a set of nodes that may leave some
+/// edges unhit, together with the counters needed to evaluate moves.
+struct SearchState<'a> {
+    instance: &'a Instance,
+    in_hs: Vec<bool>,
+    hs: Vec<NodeIdx>,
+    hs_pos: Vec<usize>,
+    hit_count: Vec<usize>,
+    unhit: Vec<EdgeIdx>,
+    unhit_pos: Vec<usize>,
+
+    /// For nodes in the set, the number of edges that are hit by this node only
+    loss: Vec<usize>,
+}
+
+impl<'a> SearchState<'a> {
+    fn new(instance: &'a Instance, hs: &[NodeIdx]) -> Self {
+        let mut state = Self {
+            instance,
+            in_hs: vec![false; instance.num_nodes_total()],
+            hs: Vec::with_capacity(hs.len()),
+            hs_pos: vec![usize::MAX; instance.num_nodes_total()],
+            hit_count: vec![0; instance.num_edges_total()],
+            unhit: Vec::new(),
+            unhit_pos: vec![usize::MAX; instance.num_edges_total()],
+            loss: vec![0; instance.num_nodes_total()],
+        };
+        for &edge in instance.edges() {
+            state.unhit_pos[edge.idx()] = state.unhit.len();
+            state.unhit.push(edge);
+        }
+        for &node in hs {
+            state.add(node);
+        }
+        state
+    }
+
+    fn sole_hitter(&self, edge: EdgeIdx) -> NodeIdx {
+        self.instance
+            .edge(edge)
+            .find(|node| self.in_hs[node.idx()])
+            .expect("Edge hit once has no node in the set")
+    }
+
+    fn add(&mut self, node: NodeIdx) {
+        debug_assert!(!self.in_hs[node.idx()]);
+        self.in_hs[node.idx()] = true;
+        self.hs_pos[node.idx()] = self.hs.len();
+        self.hs.push(node);
+
+        for edge in self.instance.node(node) {
+            self.hit_count[edge.idx()] += 1;
+            match self.hit_count[edge.idx()] {
+                1 => {
+                    let pos = self.unhit_pos[edge.idx()];
+                    self.unhit.swap_remove(pos);
+                    if let Some(&moved) = self.unhit.get(pos) {
+                        self.unhit_pos[moved.idx()] = pos;
+                    }
+                    self.loss[node.idx()] += 1;
+                }
+                2 => {
+                    let other = self
+                        .instance
+                        .edge(edge)
+                        .find(|&other| other != node && self.in_hs[other.idx()])
+                        .expect("Edge hit twice has no other node in the set");
+                    self.loss[other.idx()] -= 1;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn remove(&mut self, node: NodeIdx) {
+        debug_assert!(self.in_hs[node.idx()]);
+        self.in_hs[node.idx()] = false;
+        let pos = self.hs_pos[node.idx()];
+        self.hs.swap_remove(pos);
+        if let Some(&moved) = self.hs.get(pos) {
+            self.hs_pos[moved.idx()] = pos;
+        }
+
+        for edge in self.instance.node(node) {
+            self.hit_count[edge.idx()] -= 1;
+            match self.hit_count[edge.idx()] {
+                0 => {
+                    self.unhit_pos[edge.idx()] = self.unhit.len();
+                    self.unhit.push(edge);
+                }
+                1 => {
+                    let other = self.sole_hitter(edge);
+                    self.loss[other.idx()] += 1;
+                }
+                _ => {}
+            }
+        }
+        self.loss[node.idx()] = 0;
+    }
+
+    /// Number of unhit edges that adding the node would hit
+    fn gain(&self, node: NodeIdx) -> usize {
+        self.instance
+            .node(node)
+            .filter(|edge| self.hit_count[edge.idx()] == 0)
+            .count()
+    }
+}
+
+/// Picks the element with the maximum key, breaking ties uniformly at random.
+fn random_max_by_key<T: Copy>(
+    items: impl Iterator<Item = T>,
+    mut key: impl FnMut(T) -> usize,
+    rng: &mut impl Rng,
+) -> Option<T> {
+    let mut best = None;
+    let mut best_key = 0;
+    let mut ties = 0;
+    for item in items {
+        let item_key = key(item);
+        if best.is_none() || item_key > best_key {
+            best = Some(item);
+            best_key = item_key;
+            ties = 1;
+        } else if item_key == best_key {
+            ties += 1;
+            if rng.gen_range(0..ties) == 0 {
+                best = Some(item);
+            }
+        }
+    }
+    best
+}
+
+/// Tries to shrink a hitting set using a tabu search over swap moves.
+///
+/// Whenever the current set hits all edges, the node whose removal leaves the
+/// fewest edges unhit is dropped. Otherwise, one node of the set is swapped for
+/// a node of a random unhit edge, preferring moves that leave few edges unhit.
+/// Nodes involved in a swap may not be moved again for `tabu_tenure`
+/// iterations. Returns the smallest hitting set encountered.
+pub fn improve_hitting_set_by_tabu_search(
+    instance: &Instance,
+    hs: &[NodeIdx],
+    iterations: usize,
+    tabu_tenure: usize,
+    rng: &mut impl Rng,
+) -> Vec<NodeIdx> {
+    let mut best = hs.to_vec();
+    let mut state = SearchState::new(instance, hs);
+    let mut tabu_until = vec![0; instance.num_nodes_total()];
+
+    for iteration in 0..iterations {
+        if state.unhit.is_empty() {
+            if state.hs.len() < best.len() {
+                best.clear();
+                best.extend(state.hs.iter().copied());
+            }
+            let Some(node) = random_max_by_key(
+                state.hs.iter().copied(),
+                |node| usize::MAX - state.loss[node.idx()],
+                rng,
+            ) else {
+                break;
+            };
+            state.remove(node);
+            continue;
+        }
+
+        let removed = random_max_by_key(
+            state
+                .hs
+                .iter()
+                .copied()
+                .filter(|node| tabu_until[node.idx()] <= iteration),
+            |node| usize::MAX - state.loss[node.idx()],
+            rng,
+        );
+        if let Some(node) = removed {
+            state.remove(node);
+            tabu_until[node.idx()] = iteration + tabu_tenure + 1;
+        }
+
+        let edge = state.unhit[rng.gen_range(0..state.unhit.len())];
+        let added = random_max_by_key(
+            instance
+                .edge(edge)
+                .filter(|node| tabu_until[node.idx()] <= iteration),
+            |node| state.gain(node),
+            rng,
+        )
+        .or_else(|| random_max_by_key(instance.edge(edge), |node| state.gain(node), rng));
+        let Some(node) = added else {
+            // Empty edges cannot be hit at all
+            break;
+        };
+        state.add(node);
+        tabu_until[node.idx()] = iteration + tabu_tenure + 1;
+    }
+
+    if state.unhit.is_empty() && state.hs.len() < best.len() {
+        best.clear();
+        best.extend(state.hs.iter().copied());
+    }
+    best
+}
+
+/// Greedy approximation that repeatedly picks the node with the highest score,
+/// breaking ties randomly.
+///
+/// The score of a node may only decrease as more edges are hit, which allows
+/// outdated heap entries to be updated lazily when popped. Scores are
+/// non-negative, so comparing their bit patterns orders them correctly.
+fn lazy_greedy(
+    instance: &Instance,
+    rng: &mut impl Rng,
+    score: impl Fn(&[bool], NodeIdx) -> f64,
+) -> Vec<NodeIdx> {
+    let mut hit = vec![true; instance.num_edges_total()];
+    for edge in instance.edges() {
+        hit[edge.idx()] = false;
+    }
+
+    let mut heap: BinaryHeap<_> = instance
+        .nodes()
+        .iter()
+        .map(|&node| (score(&hit, node).to_bits(), rng.gen::<u64>(), node))
+        .collect();
+    let mut hs = Vec::new();
+    while let Some((score_bits, tie_breaker, node)) = heap.pop() {
+        let current_score = score(&hit, node);
+        if current_score <= 0.0 {
+            continue;
+        }
+        if current_score.to_bits() != score_bits {
+            heap.push((current_score.to_bits(), tie_breaker, node));
+            continue;
+        }
+
+        hs.push(node);
+        for edge in instance.node(node) {
+            hit[edge.idx()] = true;
+        }
+    }
+
+    hs
+}
+
+/// Greedy approximation that handles the unhit edge with the fewest nodes
+/// first, since it offers the fewest alternatives. Among its nodes, the one
+/// hitting the most unhit edges is picked.
+fn calc_regret_greedy(instance: &Instance, rng: &mut impl Rng) -> Vec<NodeIdx> {
+    let mut hit = vec![true; instance.num_edges_total()];
+    for edge in instance.edges() {
+        hit[edge.idx()] = false;
+    }
+
+    let mut edges = instance.edges().to_vec();
+    edges.sort_by_cached_key(|&edge| (instance.edge_size(edge), rng.gen::<u64>()));
+    let mut hs = Vec::new();
+    for edge in edges {
+        if hit[edge.idx()] {
+            continue;
+        }
+
+        let node = random_max_by_key(
+            instance.edge(edge),
+            |node| instance.node(node).filter(|e| !hit[e.idx()]).count(),
+            rng,
+        )
+        .expect("Empty edge cannot be hit");
+        hs.push(node);
+        for edge in instance.node(node) {
+            hit[edge.idx()] = true;
+        }
+    }
+
+    hs
+}
+
+/// LP-based approximation using the primal-dual method.
+///
+/// Unhit edges are handled from smallest to largest. The dual variable of
+/// each one is raised until the constraint of one of its nodes becomes tight,
+/// and all tight nodes are added to the hitting set. The duals form a
+/// fractional packing, so the result is at most `f` times the optimum of the
+/// LP relaxation, where `f` is the size of the largest edge. Afterwards, nodes
+/// are dropped in reverse order of addition if all their edges are also hit by
+/// other nodes.
+#[allow(clippy::cast_precision_loss)]
+fn calc_lp_rounding(instance: &Instance, rng: &mut impl Rng) -> Vec<NodeIdx> {
+    const EPSILON: f64 = 1e-9;
+    let mut hit_count = vec![usize::MAX; instance.num_edges_total()];
+    for edge in instance.edges() {
+        hit_count[edge.idx()] = 0;
+    }
+    let mut slack = vec![1.0; instance.num_nodes_total()];
+    let mut in_hs = vec![false; instance.num_nodes_total()];
+
+    let mut edges = instance.edges().to_vec();
+    edges.sort_by_cached_key(|&edge| (instance.edge_size(edge), rng.gen::<u64>()));
+    let mut hs = Vec::new();
+    let mut dual_sum = 0.0;
+    for edge in edges {
+        if hit_count[edge.idx()] > 0 {
+            continue;
+        }
+
+        let increase = instance
+            .edge(edge)
+            .map(|node| slack[node.idx()])
+            .fold(f64::INFINITY, f64::min);
+        dual_sum += increase;
+        for node in instance.edge(edge) {
+            slack[node.idx()] -= increase;
+            if slack[node.idx()] <= EPSILON && !in_hs[node.idx()] {
+                in_hs[node.idx()] = true;
+                hs.push(node);
+                for edge in instance.node(node) {
+                    hit_count[edge.idx()] += 1;
+                }
+            }
+        }
+    }
+    trace!(
+        "Primal-dual LP rounding with dual objective {:.2}",
+        dual_sum
+    );
+
+    let mut idx = hs.len();
+    while idx > 0 {
+        idx -= 1;
+        let node = hs[idx];
+        if instance.node(node).all(|edge| hit_count[edge.idx()] > 1) {
+            for edge in instance.node(node) {
+                hit_count[edge.idx()] -= 1;
+            }
+            hs.swap_remove(idx);
+        }
+    }
+
+    hs
+}
+
+/// Calculates a hitting set using the given greedy strategy. Conflicts between
+/// nodes are not supported.
+#[allow(clippy::cast_precision_loss)]
+pub fn calc_greedy_approximation_with_strategy(
+    instance: &Instance,
+    strategy: GreedyStrategy,
+    rng: &mut impl Rng,
+) -> Vec<NodeIdx> {
+    match strategy {
+        GreedyStrategy::MaxDegree => reductions::calc_greedy_approximation(instance)
+            .expect("Greedy strategies do not support conflicts"),
+        GreedyStrategy::RandomTieBreak => lazy_greedy(instance, rng, |hit, node| {
+            instance.node(node).filter(|edge| !hit[edge.idx()]).count() as f64
+        }),
+        GreedyStrategy::Regret => calc_regret_greedy(instance, rng),
+        GreedyStrategy::LpRounding => calc_lp_rounding(instance, rng),
+        GreedyStrategy::Coarsened => coarsening::calc_coarsened_greedy(instance),
+        GreedyStrategy::EdgeSizeWeighted => lazy_greedy(instance, rng, |hit, node| {
+            instance
+                .node(node)
+                .filter(|edge| !hit[edge.idx()])
+                .map(|edge| 1.0 / instance.edge_size(edge) as f64)
+                .sum()
+        }),
+    }
+}
@@ -0,0 +1,64 @@
+//! JavaScript API for running the solver in the browser.
+//!
+//! Built with `wasm-bindgen`, taking hypergraphs in the json format and
+//! settings as json, just like the settings file of the `solve` subcommand.
+
+use crate::{
+    instance::{Instance, LoadOptions},
+    report::Settings,
+    small_indices::SmallIdx,
+    solve::SolveHooks,
+};
+use anyhow::{Context, Result};
+use std::io::Cursor;
+use wasm_bindgen::prelude::*;
+
+/// File name reported for instances passed from JavaScript
+const WASM_FILE_NAME: &str = "<wasm>";
+
+/// Minimum hitting set and solver report returned by `solve`
+#[wasm_bindgen]
+#[derive(Debug)]
+pub struct Solution {
+    hitting_set: Vec<usize>,
+    report: String,
+}
+
+#[wasm_bindgen]
+impl Solution {
+    /// Nodes of the minimum hitting set
+    #[wasm_bindgen(getter, js_name = hittingSet)]
+    pub fn hitting_set(&self) -> Vec<usize> {
+        self.hitting_set.clone()
+    }
+
+    /// Solver report formatted as json
+    #[wasm_bindgen(getter)]
+    pub fn report(&self) -> String {
+        self.report.clone()
+    }
+}
+
+fn solve_json(instance_json: &str, settings_json: &str) -> Result<Solution> {
+    let instance = Instance::load_from_json(Cursor::new(instance_json), LoadOptions::default())
+        .context("failed to load hypergraph")?;
+    let settings: Settings =
+        serde_json::from_str(settings_json).context("failed to parse settings")?;
+    let (final_hs, report) = crate::solve::solve(
+        instance,
+        WASM_FILE_NAME.to_string(),
+        settings,
+        SolveHooks::default(),
+    )?;
+    Ok(Solution {
+        hitting_set: final_hs.iter().map(SmallIdx::idx).collect(),
+        report: serde_json::to_string(&report)?,
+    })
+}
+
+/// Computes a minimum hitting set of a hypergraph in the json format, using
+/// settings given as json. Throws an error describing the problem on failure.
+#[wasm_bindgen]
+pub fn solve(instance_json: &str, settings_json: &str) -> Result<Solution, JsError> {
+    solve_json(instance_json, settings_json).map_err(|err| JsError::new(&format!("{:#}", err)))
+}